@@ -0,0 +1,366 @@
+//! AI CLI backends that turn a prompt into a text response.
+//!
+//! Generation commands build their prompt and hand it to an [`AiBackend`]
+//! picked by [`AiBackendKind`], so they aren't tied to `claude -p`.
+//! CodeRabbit is deliberately absent: its CLI reviews the working tree
+//! itself and can't answer free-form prompts, so it stays a reviewer
+//! (`ReviewerId::CoderabbitCli`) rather than a backend.
+
+use crate::error::{AppError, Result};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Output, Stdio};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+
+/// A CLI that can answer a single non-interactive prompt
+pub trait AiBackend: Send + Sync {
+    /// Display name used in error messages
+    fn name(&self) -> &'static str;
+
+    /// Run `prompt` and return the trimmed response. Empty responses are errors.
+    fn run_prompt(&self, prompt: &str) -> Result<String>;
+
+    /// Like `run_prompt`, but the process is registered in `processes` under
+    /// `request_id` so `cancel_ai_request` can kill it
+    fn run_prompt_tracked(
+        &self,
+        prompt: &str,
+        processes: &CancellationState,
+        request_id: Option<&str>,
+    ) -> Result<String> {
+        let _ = (processes, request_id);
+        self.run_prompt(prompt)
+    }
+}
+
+/// Backend selector passed from the frontend; Claude is the default
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum AiBackendKind {
+    #[default]
+    Claude,
+    Codex,
+}
+
+impl AiBackendKind {
+    /// Provider id reported to the frontend and used in review cache keys
+    pub fn provider_id(self) -> &'static str {
+        match self {
+            AiBackendKind::Claude => "claude-cli",
+            AiBackendKind::Codex => "codex-cli",
+        }
+    }
+
+    pub fn backend(self) -> Box<dyn AiBackend> {
+        match self {
+            AiBackendKind::Claude => Box::new(ClaudeCli),
+            AiBackendKind::Codex => Box::new(CodexCli),
+        }
+    }
+}
+
+/// Anthropic's Claude CLI, run as `claude -p <prompt>`
+pub struct ClaudeCli;
+
+impl AiBackend for ClaudeCli {
+    fn name(&self) -> &'static str {
+        "Claude"
+    }
+
+    fn run_prompt(&self, prompt: &str) -> Result<String> {
+        self.run_prompt_tracked(prompt, &CancellationState::new(), None)
+    }
+
+    fn run_prompt_tracked(
+        &self,
+        prompt: &str,
+        processes: &CancellationState,
+        request_id: Option<&str>,
+    ) -> Result<String> {
+        let claude_path = find_claude_binary()?;
+        let mut cmd = Command::new(&claude_path);
+        cmd.args(["-p", prompt]);
+        run_cli(self.name(), &claude_path, processes.output(request_id, cmd))
+    }
+}
+
+impl ClaudeCli {
+    /// Like `run_prompt`, but hands stdout to `on_chunk` as it arrives
+    pub async fn stream_prompt(&self, prompt: &str, on_chunk: impl FnMut(&str)) -> Result<String> {
+        let claude_path = find_claude_binary()?;
+        let mut cmd = tokio::process::Command::new(&claude_path);
+        cmd.args(["-p", prompt]);
+        stream_cli(self.name(), &claude_path, cmd, on_chunk).await
+    }
+}
+
+/// OpenAI's Codex CLI, run as `codex exec <prompt>`
+pub struct CodexCli;
+
+impl AiBackend for CodexCli {
+    fn name(&self) -> &'static str {
+        "Codex"
+    }
+
+    fn run_prompt(&self, prompt: &str) -> Result<String> {
+        self.run_prompt_tracked(prompt, &CancellationState::new(), None)
+    }
+
+    fn run_prompt_tracked(
+        &self,
+        prompt: &str,
+        processes: &CancellationState,
+        request_id: Option<&str>,
+    ) -> Result<String> {
+        let codex_path = find_codex_binary()?;
+        let mut cmd = Command::new(&codex_path);
+        // Prompts carry their own context, so don't require a git checkout as cwd
+        cmd.args(["exec", "--skip-git-repo-check", prompt]);
+        run_cli(self.name(), &codex_path, processes.output(request_id, cmd))
+    }
+}
+
+fn run_cli(name: &str, path: &Path, output: std::io::Result<Option<Output>>) -> Result<String> {
+    let output = output
+        .map_err(|e| AppError::ai(format!("Failed to run {} at {:?}: {}", name.to_lowercase(), path, e)))?
+        .ok_or_else(|| AppError::cancelled(format!("{} request was cancelled", name)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(AppError::ai(format!("{} failed: {}", name, stderr)));
+    }
+
+    let response = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    if response.is_empty() {
+        return Err(AppError::ai(format!("{} returned an empty response", name)));
+    }
+
+    Ok(response)
+}
+
+/// How often a tracked process is checked for exit
+const PROCESS_POLL_INTERVAL: Duration = Duration::from_millis(25);
+
+/// Running AI CLI processes keyed by a frontend-supplied request id, so a
+/// request can be cancelled when the user navigates away
+#[derive(Debug, Default)]
+pub struct CancellationState {
+    children: Mutex<HashMap<String, Child>>,
+}
+
+impl CancellationState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn children(&self) -> MutexGuard<'_, HashMap<String, Child>> {
+        self.children.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Run `cmd` to completion like `Command::output`. With a `request_id` the
+    /// child is tracked until it exits; `Ok(None)` means it was cancelled. An id
+    /// that is already running is rejected, since both runs would share one entry.
+    pub fn output(&self, request_id: Option<&str>, mut cmd: Command) -> std::io::Result<Option<Output>> {
+        let Some(id) = request_id else {
+            return cmd.output().map(Some);
+        };
+
+        let (stdout, stderr) = {
+            // Hold the lock across the check and insert so two runs can't both claim the id
+            let mut children = self.children();
+            if children.contains_key(id) {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::AlreadyExists,
+                    format!("request {} is already running", id),
+                ));
+            }
+            let mut child = cmd.stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+            // Drain pipes on threads so the child never blocks on a full pipe
+            let pipes = (drain_pipe(child.stdout.take()), drain_pipe(child.stderr.take()));
+            children.insert(id.to_string(), child);
+            pipes
+        };
+
+        let status = loop {
+            {
+                let mut children = self.children();
+                // `cancel` removes (and reaps) the child
+                let Some(child) = children.get_mut(id) else {
+                    return Ok(None);
+                };
+                match child.try_wait() {
+                    Ok(Some(status)) => {
+                        children.remove(id);
+                        break status;
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        if let Some(mut child) = children.remove(id) {
+                            let _ = child.kill();
+                            let _ = child.wait();
+                        }
+                        return Err(e);
+                    }
+                }
+            }
+            std::thread::sleep(PROCESS_POLL_INTERVAL);
+        };
+
+        Ok(Some(Output {
+            status,
+            stdout: stdout.join().unwrap_or_default(),
+            stderr: stderr.join().unwrap_or_default(),
+        }))
+    }
+
+    /// Kill and reap the process running for `request_id`.
+    /// Returns false if it already finished or was never registered.
+    pub fn cancel(&self, request_id: &str) -> bool {
+        let child = self.children().remove(request_id);
+        match child {
+            Some(mut child) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+fn drain_pipe(pipe: Option<impl Read + Send + 'static>) -> std::thread::JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    })
+}
+
+/// Async counterpart of `run_cli` that reads stdout incrementally. Chunks are
+/// split on UTF-8 boundaries; the full trimmed response is returned at the end.
+pub(super) async fn stream_cli(
+    name: &str,
+    path: &Path,
+    mut cmd: tokio::process::Command,
+    mut on_chunk: impl FnMut(&str),
+) -> Result<String> {
+    let mut child = cmd
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| AppError::ai(format!("Failed to run {} at {:?}: {}", name.to_lowercase(), path, e)))?;
+
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+    let mut stderr = child.stderr.take().expect("stderr is piped");
+    // Drain stderr concurrently so a chatty CLI can't stall on a full pipe
+    let stderr_task = tokio::spawn(async move {
+        let mut buf = Vec::new();
+        let _ = stderr.read_to_end(&mut buf).await;
+        buf
+    });
+
+    let mut response = String::new();
+    let mut pending = Vec::new();
+    let mut buf = [0u8; 4096];
+    loop {
+        let n = stdout
+            .read(&mut buf)
+            .await
+            .map_err(|e| AppError::ai(format!("Failed to read {} output: {}", name.to_lowercase(), e)))?;
+        if n == 0 {
+            break;
+        }
+        pending.extend_from_slice(&buf[..n]);
+        let text = take_utf8_prefix(&mut pending);
+        if !text.is_empty() {
+            on_chunk(&text);
+            response.push_str(&text);
+        }
+    }
+    if !pending.is_empty() {
+        let text = String::from_utf8_lossy(&pending).into_owned();
+        on_chunk(&text);
+        response.push_str(&text);
+    }
+
+    let status = child
+        .wait()
+        .await
+        .map_err(|e| AppError::ai(format!("Failed to wait for {}: {}", name.to_lowercase(), e)))?;
+    let stderr = stderr_task.await.unwrap_or_default();
+
+    if !status.success() {
+        return Err(AppError::ai(format!("{} failed: {}", name, String::from_utf8_lossy(&stderr))));
+    }
+
+    let response = response.trim().to_string();
+    if response.is_empty() {
+        return Err(AppError::ai(format!("{} returned an empty response", name)));
+    }
+
+    Ok(response)
+}
+
+/// Take the longest decodable prefix of `pending`, leaving a split trailing
+/// character for the next read. Invalid bytes are decoded lossily.
+pub(super) fn take_utf8_prefix(pending: &mut Vec<u8>) -> String {
+    let valid = match std::str::from_utf8(pending) {
+        Err(e) if e.error_len().is_none() => e.valid_up_to(),
+        _ => pending.len(),
+    };
+    let rest = pending.split_off(valid);
+    let text = String::from_utf8_lossy(pending).into_owned();
+    *pending = rest;
+    text
+}
+
+/// Common install locations for the claude CLI
+pub(super) fn claude_candidates() -> Vec<String> {
+    let home = std::env::var("HOME").unwrap_or_default();
+    vec![
+        format!("{}/.claude/local/claude", home),
+        format!("{}/.local/bin/claude", home),
+        format!("{}/.bun/bin/claude", home),
+        format!("{}/.npm-global/bin/claude", home),
+        "/usr/local/bin/claude".to_string(),
+        "/opt/homebrew/bin/claude".to_string(),
+    ]
+}
+
+/// Common install locations for the codex CLI
+pub(super) fn codex_candidates() -> Vec<String> {
+    let home = std::env::var("HOME").unwrap_or_default();
+    vec![
+        format!("{}/.local/bin/codex", home),
+        format!("{}/.bun/bin/codex", home),
+        format!("{}/.npm-global/bin/codex", home),
+        "/usr/local/bin/codex".to_string(),
+        "/opt/homebrew/bin/codex".to_string(),
+    ]
+}
+
+/// First existing candidate, else the bare name for a PATH lookup (works in dev mode)
+fn find_binary(name: &str, candidates: Vec<String>) -> PathBuf {
+    candidates
+        .into_iter()
+        .map(PathBuf::from)
+        .find(|p| p.exists())
+        .unwrap_or_else(|| PathBuf::from(name))
+}
+
+/// Find the claude CLI binary by checking common installation paths
+pub(super) fn find_claude_binary() -> Result<PathBuf> {
+    Ok(find_binary("claude", claude_candidates()))
+}
+
+/// Find the codex CLI binary by checking common installation paths
+fn find_codex_binary() -> Result<PathBuf> {
+    Ok(find_binary("codex", codex_candidates()))
+}
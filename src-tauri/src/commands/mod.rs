@@ -1,11 +1,16 @@
-use crate::error::{AppError, Result};
-use crate::git::{self, BranchInfo, CommitActivity, CommitGraph, CommitInfo, FileDiff, RepositoryInfo, StatusInfo, UnifiedDiff, WorktreeInfo, WorktreeCreateOptions, MergeStatus, FileConflictInfo, StashEntry, AheadBehind, ChangelogCommit, ReflogEntry, RebaseStatus, InteractiveRebaseCommit, InteractiveRebasePlanEntry, InteractiveRebaseState};
+use crate::error::{AppError, ErrorKind, Result};
+use crate::git::{self, CancellationRegistry, BranchInfo, CommitActivity, CommitGraph, CommitInfo, DiffFile, FileDiff, RepositoryInfo, StatusInfo, UnifiedDiff, WorktreeInfo, WorktreeCreateOptions, MergeStatus, FileConflictInfo, StashEntry, StashOptions, AheadBehind, ChangelogCommit, ReflogEntry, RemoteInfo, SeenMarker, ChangesSinceMarker, RebaseStatus, InteractiveRebaseCommit, InteractiveRebasePlanEntry, InteractiveRebaseState};
 use std::process::Command;
 use std::path::PathBuf;
 use std::fs;
-use tauri::Manager;
+use tauri::{Emitter, Manager};
 use tracing::instrument;
 
+mod ai;
+
+pub use ai::{AiBackend, AiBackendKind, CancellationState, ClaudeCli, CodexCli};
+use ai::find_claude_binary;
+
 // Skills-related types
 #[derive(serde::Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -39,60 +44,88 @@ struct RemoteSkillsCache {
 static REMOTE_SKILLS_CACHE: OnceLock<Mutex<Option<RemoteSkillsCache>>> = OnceLock::new();
 const CACHE_TTL: Duration = Duration::from_secs(10 * 60); // 10 minutes
 
-/// Parse skills.sh HTML and extract skill links
+/// Parse skills.sh HTML and extract skill links.
+/// Anchors pointing at /owner/repo/skill (exactly 3 segments, absolute or
+/// relative) are skills; docs/trending/agents pages are skipped.
 fn parse_skills_html(html: &str) -> Vec<RemoteSkill> {
-    let mut skills = Vec::new();
-    let mut seen = std::collections::HashSet::new();
+    let document = scraper::Html::parse_document(html);
+    let anchors = scraper::Selector::parse("a[href]").expect("valid selector");
 
-    // Look for links matching the pattern /owner/repo/skill (exactly 3 segments)
-    // The HTML contains links like: href="https://skills.sh/vercel-labs/agent-skills/vercel-react-best-practices"
-    // or relative: href="/vercel-labs/agent-skills/vercel-react-best-practices"
-    
-    // Simple regex-like pattern matching for href attributes
-    let mut pos = 0;
-    while let Some(href_start) = html[pos..].find("href=\"") {
-        let abs_pos = pos + href_start + 6; // skip href="
-        if let Some(href_end) = html[abs_pos..].find('"') {
-            let href = &html[abs_pos..abs_pos + href_end];
-            
-            // Extract path from URL
-            let path = if href.starts_with("https://skills.sh/") {
-                href.strip_prefix("https://skills.sh/").unwrap_or("")
-            } else if href.starts_with('/') && !href.starts_with("//") {
-                href.strip_prefix('/').unwrap_or("")
-            } else {
-                ""
-            };
-            
-            // Check if path has exactly 3 segments (owner/repo/skill)
-            let segments: Vec<&str> = path.trim_end_matches('/').split('/').collect();
-            if segments.len() == 3 && !segments[0].is_empty() && !segments[1].is_empty() && !segments[2].is_empty() {
-                // Skip docs and other non-skill paths
-                if segments[0] != "docs" && segments[0] != "trending" && segments[0] != "agents" {
-                    let key = format!("{}/{}/{}", segments[0], segments[1], segments[2]);
-                    if !seen.contains(&key) {
-                        seen.insert(key);
-                        
-                        skills.push(RemoteSkill {
-                            owner: segments[0].to_string(),
-                            repo: segments[1].to_string(),
-                            skill: segments[2].to_string(),
-                            url: format!("https://skills.sh/{}/{}/{}", segments[0], segments[1], segments[2]),
-                            installs: None,
-                        });
-                    }
-                }
-            }
-            
-            pos = abs_pos + href_end;
+    let mut skills: Vec<RemoteSkill> = Vec::new();
+    // Skill key -> index in `skills`, so repeated links to a skill are merged
+    let mut seen = std::collections::HashMap::new();
+
+    for anchor in document.select(&anchors) {
+        let Some(href) = anchor.value().attr("href") else {
+            continue;
+        };
+
+        // Extract path from URL
+        let path = if let Some(path) = href.strip_prefix("https://skills.sh/") {
+            path
+        } else if href.starts_with('/') && !href.starts_with("//") {
+            &href[1..]
         } else {
-            break;
+            continue;
+        };
+        let path = path.split(['?', '#']).next().unwrap_or("");
+
+        // Check if path has exactly 3 segments (owner/repo/skill)
+        let segments: Vec<&str> = path.trim_end_matches('/').split('/').collect();
+        if segments.len() != 3 || segments.iter().any(|s| s.is_empty()) {
+            continue;
+        }
+        // Skip docs and other non-skill paths
+        if matches!(segments[0], "docs" | "trending" | "agents") {
+            continue;
+        }
+
+        let key = segments.join("/");
+        let idx = *seen.entry(key.clone()).or_insert_with(|| {
+            skills.push(RemoteSkill {
+                owner: segments[0].to_string(),
+                repo: segments[1].to_string(),
+                skill: segments[2].to_string(),
+                url: format!("https://skills.sh/{}", key),
+                installs: None,
+            });
+            skills.len() - 1
+        });
+
+        // Fill in the install count from whichever link to the skill shows one
+        if skills[idx].installs.is_none() {
+            skills[idx].installs = extract_install_count(anchor);
         }
     }
-    
+
     skills
 }
 
+/// Install count shown inside a skill's anchor, either as an element whose class
+/// mentions "install" or as text like "12.3K installs"
+fn extract_install_count(anchor: scraper::ElementRef) -> Option<String> {
+    let install_el = scraper::Selector::parse("[class*=install]").expect("valid selector");
+    let is_count = |s: &str| {
+        !s.is_empty()
+            && s.chars().next().is_some_and(|c| c.is_ascii_digit())
+            && s.chars().all(|c| c.is_ascii_digit() || matches!(c, '.' | ',' | 'k' | 'K' | 'm' | 'M'))
+    };
+
+    for el in anchor.select(&install_el) {
+        let text = el.text().collect::<String>();
+        if let Some(count) = text.split_whitespace().find(|t| is_count(t)) {
+            return Some(count.to_string());
+        }
+    }
+
+    let text = anchor.text().collect::<Vec<_>>().join(" ");
+    let words: Vec<&str> = text.split_whitespace().collect();
+    words
+        .windows(2)
+        .find(|w| is_count(w[0]) && w[1].to_lowercase().starts_with("install"))
+        .map(|w| w[0].to_string())
+}
+
 /// Fetch remote skills from skills.sh with caching
 async fn fetch_remote_skills_cached() -> Result<Vec<RemoteSkill>> {
     let cache = REMOTE_SKILLS_CACHE.get_or_init(|| Mutex::new(None));
@@ -170,32 +203,82 @@ fn get_skills_dir_path(app: &tauri::AppHandle) -> Result<PathBuf> {
     Ok(app_data_dir.join("skills"))
 }
 
-/// Parse YAML frontmatter from skill markdown content
-fn parse_skill_frontmatter(content: &str) -> (String, String, String) {
-    // Default values
-    let mut name = String::from("Unnamed Skill");
-    let mut description = String::new();
-    let mut body = content.to_string();
-
-    // Check for YAML frontmatter (starts with ---)
-    if content.starts_with("---") {
-        if let Some(end_idx) = content[3..].find("---") {
-            let frontmatter = &content[3..3 + end_idx];
-            body = content[3 + end_idx + 3..].trim_start().to_string();
-
-            // Simple YAML parsing for name and description
-            for line in frontmatter.lines() {
-                let line = line.trim();
-                if let Some(value) = line.strip_prefix("name:") {
-                    name = value.trim().trim_matches('"').trim_matches('\'').to_string();
-                } else if let Some(value) = line.strip_prefix("description:") {
-                    description = value.trim().trim_matches('"').trim_matches('\'').to_string();
-                }
-            }
+/// Get the AI review cache directory path
+fn get_review_cache_dir_path(app: &tauri::AppHandle) -> Result<PathBuf> {
+    let app_data_dir = app.path()
+        .app_data_dir()
+        .map_err(|e| AppError::io(format!("Failed to get app data dir: {}", e)))?;
+    Ok(app_data_dir.join("review-cache"))
+}
+
+fn get_fix_backup_dir_path(app: &tauri::AppHandle) -> Result<PathBuf> {
+    let app_data_dir = app.path()
+        .app_data_dir()
+        .map_err(|e| AppError::io(format!("Failed to get app data dir: {}", e)))?;
+    Ok(app_data_dir.join("fix-backups"))
+}
+
+/// Known keys of a skill's YAML frontmatter (others are ignored)
+#[derive(serde::Deserialize)]
+struct SkillFrontmatter {
+    name: Option<String>,
+    description: Option<String>,
+}
+
+/// Split skill markdown into its YAML frontmatter and body. The frontmatter is the
+/// block between an opening `---` line and the next line that is exactly `---`, so
+/// `---` inside values or the body doesn't end it early.
+fn split_skill_frontmatter(content: &str) -> Option<(&str, &str)> {
+    let rest = content.trim_start().strip_prefix("---")?;
+    let rest = rest.strip_prefix("\r\n").or_else(|| rest.strip_prefix('\n'))?;
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return Some((&rest[..offset], rest[offset + line.len()..].trim_start()));
         }
+        offset += line.len();
     }
+    None
+}
+
+/// Parse YAML frontmatter from skill markdown content, falling back to defaults
+/// when it's missing or malformed. Returns (name, description, body).
+fn parse_skill_frontmatter(content: &str) -> (String, String, String) {
+    let Some((frontmatter, body)) = split_skill_frontmatter(content) else {
+        return (String::from("Unnamed Skill"), String::new(), content.to_string());
+    };
+
+    let parsed = serde_yaml::from_str::<SkillFrontmatter>(frontmatter).ok();
+    let name = parsed
+        .as_ref()
+        .and_then(|f| f.name.clone())
+        .unwrap_or_else(|| String::from("Unnamed Skill"));
+    let description = parsed.and_then(|f| f.description).unwrap_or_default();
+
+    (name, description, body.to_string())
+}
+
+/// Like `parse_skill_frontmatter`, but rejects missing or malformed frontmatter
+/// and requires non-empty `name` and `description` keys
+fn parse_skill_frontmatter_strict(content: &str) -> Result<(String, String, String)> {
+    let (frontmatter, body) = split_skill_frontmatter(content).ok_or_else(|| {
+        AppError::skill("Invalid skill file: missing YAML frontmatter between '---' lines")
+    })?;
+
+    let parsed: SkillFrontmatter = serde_yaml::from_str(frontmatter)
+        .map_err(|e| AppError::skill(format!("Invalid skill frontmatter: {}", e)))?;
 
-    (name, description, body)
+    let required = |value: Option<String>, key: &str| {
+        value
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .ok_or_else(|| AppError::skill(format!("Invalid skill frontmatter: missing required '{}' field", key)))
+    };
+    let name = required(parsed.name, "name")?;
+    let description = required(parsed.description, "description")?;
+
+    Ok((name, description, body.to_string()))
 }
 
 /// Generate a skill ID from a name
@@ -210,40 +293,65 @@ fn generate_skill_id(name: &str) -> String {
         .join("-")
 }
 
-/// Find the claude CLI binary by checking common installation paths
-fn find_claude_binary() -> Result<PathBuf> {
-    let home = std::env::var("HOME").unwrap_or_default();
+/// Extract a JSON value from model output that may wrap it in prose or markdown
+/// fences. Fenced blocks are tried first, then the whole text, then each balanced
+/// `{...}`/`[...]` span outside of fences (so braces in fenced code examples
+/// don't confuse the matching) until one parses.
+fn extract_json_value(text: &str) -> Option<serde_json::Value> {
+    let (fenced, prose) = split_code_fences(text);
 
-    let candidates = [
-        format!("{}/.claude/local/claude", home),
-        format!("{}/.local/bin/claude", home),
-        format!("{}/.bun/bin/claude", home),
-        format!("{}/.npm-global/bin/claude", home),
-        "/usr/local/bin/claude".to_string(),
-        "/opt/homebrew/bin/claude".to_string(),
-    ];
+    if let Some(value) = fenced.iter().find_map(|block| serde_json::from_str(block.trim()).ok()) {
+        return Some(value);
+    }
+    if let Ok(value) = serde_json::from_str(text.trim()) {
+        return Some(value);
+    }
 
-    for path in candidates {
-        let p = PathBuf::from(&path);
-        if p.exists() {
-            return Ok(p);
+    prose
+        .char_indices()
+        .filter(|(_, ch)| matches!(ch, '{' | '['))
+        .filter_map(|(start, _)| balanced_json_span(&prose[start..]))
+        .find_map(|span| serde_json::from_str(span).ok())
+}
+
+/// Split markdown into the contents of its ``` fenced blocks and the text outside
+/// them. An unterminated fence runs to the end of the text.
+fn split_code_fences(text: &str) -> (Vec<&str>, String) {
+    let mut blocks = Vec::new();
+    let mut prose = String::new();
+    let mut rest = text;
+
+    while let Some(open) = rest.find("```") {
+        prose.push_str(&rest[..open]);
+        // Skip the info string (e.g. "json") on the opening line
+        let after_open = &rest[open + 3..];
+        let body_start = after_open.find('\n').map(|i| i + 1).unwrap_or(after_open.len());
+        let body = &after_open[body_start..];
+        match body.find("```") {
+            Some(close) => {
+                blocks.push(&body[..close]);
+                rest = &body[close + 3..];
+            }
+            None => {
+                blocks.push(body);
+                rest = "";
+            }
         }
+        prose.push('\n');
     }
+    prose.push_str(rest);
 
-    // Fall back to PATH lookup (works in dev mode)
-    Ok(PathBuf::from("claude"))
+    (blocks, prose)
 }
 
-
-/// Extract a JSON object from text that may contain additional content.
-/// Looks for the outermost { } pair and returns the content between them.
-fn extract_json_object(text: &str) -> Option<&str> {
-    let start = text.find('{')?;
+/// The balanced `{...}` or `[...]` span at the start of `text`, skipping
+/// brackets inside JSON strings
+fn balanced_json_span(text: &str) -> Option<&str> {
     let mut depth = 0;
     let mut in_string = false;
     let mut escape_next = false;
 
-    for (i, ch) in text[start..].char_indices() {
+    for (i, ch) in text.char_indices() {
         if escape_next {
             escape_next = false;
             continue;
@@ -252,11 +360,11 @@ fn extract_json_object(text: &str) -> Option<&str> {
         match ch {
             '\\' if in_string => escape_next = true,
             '"' => in_string = !in_string,
-            '{' if !in_string => depth += 1,
-            '}' if !in_string => {
+            '{' | '[' if !in_string => depth += 1,
+            '}' | ']' if !in_string => {
                 depth -= 1;
                 if depth == 0 {
-                    return Some(&text[start..start + i + 1]);
+                    return Some(&text[..i + 1]);
                 }
             }
             _ => {}
@@ -276,6 +384,8 @@ fn extract_json_object(text: &str) -> Option<&str> {
 pub struct CLIAvailability {
     pub available: bool,
     pub path: Option<String>,
+    /// First line of `--version` output, when the CLI reports one
+    pub version: Option<String>,
     pub install_instructions: String,
 }
 
@@ -289,24 +399,36 @@ pub struct CLIStatus {
 }
 
 /// Check if a binary exists and is executable (actually verify it works)
-fn check_binary_available(name: &str, candidates: &[String]) -> (bool, Option<String>) {
-    // First check explicit paths
-    for path in candidates {
-        let p = PathBuf::from(path);
-        if p.exists() {
-            return (true, Some(path.clone()));
-        }
-    }
+fn check_binary_available(name: &str, candidates: &[String], install_instructions: &str) -> CLIAvailability {
+    // First check explicit paths; the version is best-effort there
+    let found = candidates.iter().find(|path| PathBuf::from(path).exists());
+    let (available, path, version) = match found {
+        Some(path) => (true, Some(path.clone()), binary_version(path)),
+        // Try PATH lookup by running --version
+        None => match binary_version(name) {
+            Some(version) => (true, Some(name.to_string()), Some(version)),
+            None => (false, None, None),
+        },
+    };
+    let version = version.filter(|v| !v.is_empty());
 
-    // Try PATH lookup by running --version
-    let output = std::process::Command::new(name)
-        .arg("--version")
-        .output();
+    CLIAvailability {
+        available,
+        path,
+        version,
+        install_instructions: install_instructions.to_string(),
+    }
+}
 
-    match output {
-        Ok(o) if o.status.success() => (true, Some(name.to_string())),
-        _ => (false, None),
+/// Run `<program> --version` and return the first non-empty line of stdout
+/// (empty if it printed nothing). None if it fails to run or exits unsuccessfully.
+fn binary_version(program: &str) -> Option<String> {
+    let output = Command::new(program).arg("--version").output().ok()?;
+    if !output.status.success() {
+        return None;
     }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Some(stdout.lines().map(str::trim).find(|l| !l.is_empty()).unwrap_or("").to_string())
 }
 
 #[tauri::command]
@@ -314,16 +436,11 @@ pub async fn check_cli_availability() -> Result<CLIStatus> {
     let home = std::env::var("HOME").unwrap_or_default();
 
     // Check Claude CLI
-    let claude_candidates = vec![
-        format!("{}/.claude/local/claude", home),
-        format!("{}/.local/bin/claude", home),
-        format!("{}/.bun/bin/claude", home),
-        format!("{}/.npm-global/bin/claude", home),
-        "/usr/local/bin/claude".to_string(),
-        "/opt/homebrew/bin/claude".to_string(),
-    ];
-
-    let (claude_available, claude_path) = check_binary_available("claude", &claude_candidates);
+    let claude = check_binary_available(
+        "claude",
+        &ai::claude_candidates(),
+        "npm install -g @anthropic-ai/claude-cli",
+    );
 
     // Check CodeRabbit CLI
     let coderabbit_candidates = vec![
@@ -333,36 +450,363 @@ pub async fn check_cli_availability() -> Result<CLIStatus> {
         "/usr/local/bin/coderabbit".to_string(),
         "/opt/homebrew/bin/coderabbit".to_string(),
     ];
-
-    let (coderabbit_available, coderabbit_path) = check_binary_available("coderabbit", &coderabbit_candidates);
+    let coderabbit = check_binary_available("coderabbit", &coderabbit_candidates, "npm install -g coderabbit");
 
     // Check Codex CLI (OpenAI)
-    let codex_candidates = vec![
-        format!("{}/.local/bin/codex", home),
-        format!("{}/.bun/bin/codex", home),
-        format!("{}/.npm-global/bin/codex", home),
-        "/usr/local/bin/codex".to_string(),
-        "/opt/homebrew/bin/codex".to_string(),
-    ];
-
-    let (codex_available, codex_path) = check_binary_available("codex", &codex_candidates);
+    let codex = check_binary_available("codex", &ai::codex_candidates(), "npm install -g @openai/codex");
 
     Ok(CLIStatus {
-        claude: CLIAvailability {
-            available: claude_available,
-            path: claude_path,
-            install_instructions: "npm install -g @anthropic-ai/claude-cli".to_string(),
-        },
-        coderabbit: CLIAvailability {
-            available: coderabbit_available,
-            path: coderabbit_path,
-            install_instructions: "npm install -g coderabbit".to_string(),
-        },
-        codex: CLIAvailability {
-            available: codex_available,
-            path: codex_path,
-            install_instructions: "npm install -g @openai/codex".to_string(),
-        },
+        claude,
+        coderabbit,
+        codex,
+    })
+}
+
+// =============================================================================
+// App Settings & Prompt Templates
+// =============================================================================
+
+/// Persisted app settings, stored as settings.json in the app data dir
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AppSettings {
+    pub prompts: PromptTemplates,
+}
+
+/// User-customizable AI prompt templates. `None` means use the built-in default.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PromptTemplates {
+    /// Placeholders: {diff} (required), {skills}
+    pub review: Option<String>,
+    /// Placeholders: {diff} (required)
+    pub commit_message: Option<String>,
+    /// Placeholders: {diff} (required)
+    pub diagram: Option<String>,
+    /// Placeholders: {file_path}, {ours}, {theirs} (required), {base}, {instructions}.
+    /// Without {base}, a supplied common ancestor is appended to the end.
+    pub conflict: Option<String>,
+}
+
+const DEFAULT_REVIEW_PROMPT: &str = r#"You are an expert code reviewer. Your goal is to provide actionable feedback.{skills}
+
+Analyze this git diff and provide a PR-style review with specific, actionable issues.
+
+For each issue you find, explain:
+- **problem**: What is wrong or concerning
+- **why**: Why it matters (impact on correctness, security, performance, maintainability)
+- **suggestion**: A concrete fix or improvement
+
+Categories: logic_bugs, edge_cases, security, performance, accidental_code, other
+Severities: low, medium, high, critical
+
+Respond ONLY with valid JSON (no markdown, no code blocks):
+{
+  "overview": "1-2 sentence summary of the changes",
+  "issues": [
+    {
+      "id": "issue-1",
+      "category": "logic_bugs",
+      "severity": "high",
+      "title": "brief title",
+      "problem": "what is wrong",
+      "why": "why it matters",
+      "suggestion": "how to fix it",
+      "filePath": "path/to/file.ts"
+    }
+  ]
+}
+
+If there are no issues, use an empty array for "issues".
+
+Diff to review:
+{diff}"#;
+
+const DEFAULT_COMMIT_MESSAGE_PROMPT: &str = "Generate a concise git commit message (max 72 chars for title) for these changes. \
+Use conventional commit format (feat:, fix:, refactor:, etc) if appropriate. \
+Only output the commit message, nothing else:\n\n{diff}";
+
+const DEFAULT_DIAGRAM_PROMPT: &str = r#"Analyze this git diff and generate a Mermaid sequence diagram showing the key interactions and data flow in the changed code. Focus on:
+1. Which components/modules/functions are involved
+2. How they communicate or pass data
+3. The order of operations
+
+Return ONLY valid Mermaid code starting with "sequenceDiagram" - no markdown fences, no explanation, just the diagram code.
+
+If the changes are primarily configuration, styling, or don't have meaningful interactions to diagram, generate a simple flowchart instead starting with "flowchart TB" showing what was changed and why.
+
+Git diff:
+```
+{diff}
+```"#;
+
+const DEFAULT_CONFLICT_PROMPT: &str = r#"You are resolving a Git merge conflict.
+
+File: {file_path}
+
+## Current Branch (Ours)
+```
+{ours}
+```
+
+{base}
+## Incoming Branch (Theirs)
+```
+{theirs}
+```
+
+{instructions}
+
+Analyze both versions (and the common ancestor, when given, to see what each side changed) and produce a merged result that:
+1. Preserves all intended functionality from both branches
+2. Resolves any conflicts logically
+3. Maintains code style consistency
+
+Respond ONLY with valid JSON in this exact format (no markdown, no code blocks, just raw JSON):
+{"resolved": "the merged code here", "explanation": "brief explanation of how you resolved the conflict"}"#;
+
+/// Which prompt a template is for; determines its default and required placeholders
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PromptKind {
+    Review,
+    CommitMessage,
+    Diagram,
+    Conflict,
+}
+
+impl PromptKind {
+    fn label(self) -> &'static str {
+        match self {
+            PromptKind::Review => "review",
+            PromptKind::CommitMessage => "commit message",
+            PromptKind::Diagram => "diagram",
+            PromptKind::Conflict => "conflict",
+        }
+    }
+
+    fn default_template(self) -> &'static str {
+        match self {
+            PromptKind::Review => DEFAULT_REVIEW_PROMPT,
+            PromptKind::CommitMessage => DEFAULT_COMMIT_MESSAGE_PROMPT,
+            PromptKind::Diagram => DEFAULT_DIAGRAM_PROMPT,
+            PromptKind::Conflict => DEFAULT_CONFLICT_PROMPT,
+        }
+    }
+
+    fn required_placeholders(self) -> &'static [&'static str] {
+        match self {
+            PromptKind::Review | PromptKind::CommitMessage | PromptKind::Diagram => &["{diff}"],
+            PromptKind::Conflict => &["{file_path}", "{ours}", "{theirs}"],
+        }
+    }
+}
+
+impl PromptTemplates {
+    fn get(&self, kind: PromptKind) -> Option<&str> {
+        let template = match kind {
+            PromptKind::Review => &self.review,
+            PromptKind::CommitMessage => &self.commit_message,
+            PromptKind::Diagram => &self.diagram,
+            PromptKind::Conflict => &self.conflict,
+        };
+        template.as_deref().filter(|t| !t.trim().is_empty())
+    }
+
+    /// Check that every custom template contains its required placeholders
+    fn validate(&self) -> Result<()> {
+        for kind in [PromptKind::Review, PromptKind::CommitMessage, PromptKind::Diagram, PromptKind::Conflict] {
+            if let Some(template) = self.get(kind) {
+                validate_prompt_template(kind, template)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The user's template for `kind` if set and valid, otherwise the built-in default
+    fn resolve(&self, kind: PromptKind) -> String {
+        match self.get(kind) {
+            Some(template) if validate_prompt_template(kind, template).is_ok() => template.to_string(),
+            Some(_) => {
+                tracing::warn!("Custom {} prompt template is missing required placeholders, using default", kind.label());
+                kind.default_template().to_string()
+            }
+            None => kind.default_template().to_string(),
+        }
+    }
+}
+
+fn validate_prompt_template(kind: PromptKind, template: &str) -> Result<()> {
+    let missing: Vec<&str> = kind
+        .required_placeholders()
+        .iter()
+        .copied()
+        .filter(|p| !template.contains(p))
+        .collect();
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(AppError::validation(format!(
+            "The {} prompt template must contain {}",
+            kind.label(),
+            missing.join(", ")
+        )))
+    }
+}
+
+/// Substitute `{name}` placeholders in a single pass, so placeholder-like text inside
+/// substituted values (e.g. a diff containing "{skills}") is left untouched
+fn render_prompt_template(template: &str, vars: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let replacement = after.find('}').and_then(|end| {
+            let name = &after[..end];
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| (*value, end))
+        });
+        match replacement {
+            Some((value, end)) => {
+                out.push_str(value);
+                rest = &after[end + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Max diff bytes sent to Claude for reviews
+const REVIEW_MAX_DIFF_LEN: usize = 12000;
+/// Max diff bytes sent to Claude for diagrams
+const DIAGRAM_MAX_DIFF_LEN: usize = 50000;
+
+/// Appended to each file patch that `truncate_diff` cut short
+const FILE_TRUNCATED_MARKER: &str = "...\n[file diff truncated]\n";
+
+/// Shrink a patch to about `max_bytes` without starving later files. The budget is
+/// split per file: files smaller than an even share are kept whole, and what's left
+/// is divided evenly among the larger ones, which are cut at a line (or at least char)
+/// boundary. Returns the patch and whether anything was cut.
+fn truncate_diff(patch: &str, max_bytes: usize) -> (String, bool) {
+    if patch.len() <= max_bytes {
+        return (patch.to_string(), false);
+    }
+
+    let files = split_patch_files(patch);
+
+    // Hand out budgets smallest file first, so unused share flows to larger files
+    let mut budgets = vec![0; files.len()];
+    let mut by_size: Vec<usize> = (0..files.len()).collect();
+    by_size.sort_by_key(|&i| files[i].len());
+    let mut remaining = max_bytes;
+    for (handed_out, &i) in by_size.iter().enumerate() {
+        let share = remaining / (files.len() - handed_out);
+        budgets[i] = files[i].len().min(share);
+        remaining -= budgets[i];
+    }
+
+    let mut out = String::with_capacity(max_bytes + files.len() * FILE_TRUNCATED_MARKER.len());
+    for (file, budget) in files.iter().zip(budgets) {
+        if file.len() <= budget {
+            out.push_str(file);
+            continue;
+        }
+        let mut cut = budget;
+        while !file.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        // Prefer ending on a whole line
+        if let Some(newline) = file[..cut].rfind('\n') {
+            cut = newline + 1;
+        }
+        out.push_str(&file[..cut]);
+        out.push_str(FILE_TRUNCATED_MARKER);
+    }
+
+    (out, true)
+}
+
+/// Split a patch at each `diff --git` header. Text before the first header (e.g. a
+/// section title) is its own piece.
+fn split_patch_files(patch: &str) -> Vec<&str> {
+    let mut files = Vec::new();
+    let mut start = 0;
+    let mut offset = 0;
+    for line in patch.split_inclusive('\n') {
+        if line.starts_with("diff --git ") && offset > start {
+            files.push(&patch[start..offset]);
+            start = offset;
+        }
+        offset += line.len();
+    }
+    if offset > start {
+        files.push(&patch[start..offset]);
+    }
+    files
+}
+
+/// `truncate_diff` for prompts: tells the model when files were shortened
+fn diff_for_prompt(patch: &str, max_bytes: usize) -> String {
+    match truncate_diff(patch, max_bytes) {
+        (diff, false) => diff,
+        (diff, true) => format!("{}\n[diff truncated: long files were shortened to fit]", diff),
+    }
+}
+
+fn get_settings_path(app: &tauri::AppHandle) -> Result<PathBuf> {
+    let app_data_dir = app.path()
+        .app_data_dir()
+        .map_err(|e| AppError::io(format!("Failed to get app data dir: {}", e)))?;
+    Ok(app_data_dir.join("settings.json"))
+}
+
+/// Load app settings, falling back to defaults if the file is missing or unreadable
+fn load_app_settings(app: &tauri::AppHandle) -> AppSettings {
+    get_settings_path(app)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+#[tauri::command]
+pub async fn get_app_settings(app: tauri::AppHandle) -> Result<AppSettings> {
+    Ok(load_app_settings(&app))
+}
+
+#[tauri::command]
+#[instrument(skip_all, err(Debug))]
+pub async fn save_app_settings(app: tauri::AppHandle, settings: AppSettings) -> Result<()> {
+    settings.prompts.validate()?;
+
+    let path = get_settings_path(&app)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| AppError::io(format!("Failed to create settings directory: {}", e)))?;
+    }
+    let json = serde_json::to_string_pretty(&settings)
+        .map_err(|e| AppError::parse(format!("Failed to serialize settings: {}", e)))?;
+    fs::write(&path, json)
+        .map_err(|e| AppError::io(format!("Failed to write settings: {}", e)))?;
+    Ok(())
+}
+
+/// Built-in prompt templates, so the settings UI can show them as a starting point
+#[tauri::command]
+pub async fn get_default_prompt_templates() -> Result<PromptTemplates> {
+    Ok(PromptTemplates {
+        review: Some(DEFAULT_REVIEW_PROMPT.to_string()),
+        commit_message: Some(DEFAULT_COMMIT_MESSAGE_PROMPT.to_string()),
+        diagram: Some(DEFAULT_DIAGRAM_PROMPT.to_string()),
+        conflict: Some(DEFAULT_CONFLICT_PROMPT.to_string()),
     })
 }
 
@@ -370,6 +814,28 @@ pub async fn check_cli_availability() -> Result<CLIStatus> {
 // Repository Commands
 // =============================================================================
 
+/// Cancel an in-flight operation started with the given `request_id`.
+/// Returns false if the operation already finished or was never registered.
+#[tauri::command]
+#[instrument(skip_all, fields(request_id = %request_id))]
+pub async fn cancel_operation(
+    operations: tauri::State<'_, CancellationRegistry>,
+    request_id: String,
+) -> Result<bool> {
+    Ok(operations.cancel(&request_id))
+}
+
+/// Kill the AI CLI process started under `request_id`.
+/// Returns false if the request already finished or was never registered.
+#[tauri::command]
+#[instrument(skip_all, fields(request_id = %request_id))]
+pub async fn cancel_ai_request(
+    processes: tauri::State<'_, CancellationState>,
+    request_id: String,
+) -> Result<bool> {
+    Ok(processes.cancel(&request_id))
+}
+
 #[tauri::command]
 #[instrument(skip_all, fields(path = %path), err(Debug))]
 pub async fn open_repository(path: String) -> Result<RepositoryInfo> {
@@ -396,9 +862,13 @@ pub async fn list_branches(repo_path: String) -> Result<Vec<BranchInfo>> {
 }
 
 #[tauri::command]
-pub async fn checkout_branch(repo_path: String, branch_name: String) -> Result<()> {
+pub async fn checkout_branch(
+    repo_path: String,
+    branch_name: String,
+    options: Option<git::BranchCheckoutOptions>,
+) -> Result<()> {
     let repo = git::open_repo(&repo_path)?;
-    Ok(git::checkout_branch(&repo, &branch_name)?)
+    Ok(git::checkout_branch_with(&repo, &branch_name, &options.unwrap_or_default())?)
 }
 
 #[tauri::command]
@@ -408,22 +878,123 @@ pub async fn create_branch(repo_path: String, branch_name: String, checkout: boo
 }
 
 #[tauri::command]
-#[instrument(skip_all, fields(branch = ?branch, limit, offset), err(Debug))]
-pub async fn get_commit_history(
-    repo_path: String,
+#[instrument(skip_all, fields(branch_name = %branch_name, force), err(Debug))]
+pub async fn delete_branch(repo_path: String, branch_name: String, force: bool) -> Result<()> {
+    let repo = git::open_repo(&repo_path)?;
+    Ok(git::delete_branch(&repo, &branch_name, force)?)
+}
+
+#[tauri::command]
+#[instrument(skip_all, fields(old_name = %old_name, new_name = %new_name), err(Debug))]
+pub async fn rename_branch(repo_path: String, old_name: String, new_name: String) -> Result<()> {
+    let repo = git::open_repo(&repo_path)?;
+    Ok(git::rename_branch(&repo, &old_name, &new_name)?)
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+#[instrument(
+    skip_all,
+    fields(branch = ?branch, limit, offset, since = ?since, until = ?until, verify_signatures = ?verify_signatures),
+    err(Debug)
+)]
+pub async fn get_commit_history(
+    operations: tauri::State<'_, CancellationRegistry>,
+    repo_path: String,
     branch: Option<String>,
     limit: usize,
     offset: usize,
+    since: Option<i64>,
+    until: Option<i64>,
+    verify_signatures: Option<bool>,
+    request_id: Option<String>,
 ) -> Result<Vec<CommitInfo>> {
+    let cancel = operations.register(request_id.as_deref());
+
+    // Run blocking git operation on dedicated thread pool
+    let result = tokio::task::spawn_blocking(move || {
+        let repo = git::open_repo(&repo_path)?;
+        let mut commits = git::get_commits_cancellable(
+            &repo,
+            branch.as_deref(),
+            limit,
+            offset,
+            since,
+            until,
+            &cancel,
+        )?;
+        // Signature checks spawn gpg/ssh-keygen per signed commit, so they're opt-in
+        if verify_signatures.unwrap_or(false) {
+            git::verify_commit_signatures(&repo_path, &mut commits)?;
+        }
+        Ok(commits)
+    })
+    .await
+    .map_err(|e| AppError::io(format!("Task join error: {}", e)));
+
+    operations.finish(request_id.as_deref());
+    result?
+}
+
+#[tauri::command]
+#[instrument(skip_all, fields(branch = ?branch, limit), err(Debug))]
+pub async fn get_commit_history_cursor(
+    repo_path: String,
+    branch: Option<String>,
+    start_after: Option<String>,
+    limit: usize,
+) -> Result<git::CommitHistoryPage> {
     // Run blocking git operation on dedicated thread pool
     tokio::task::spawn_blocking(move || {
         let repo = git::open_repo(&repo_path)?;
-        Ok(git::get_commits(&repo, branch.as_deref(), limit, offset)?)
+        Ok(git::get_commits_page(&repo, branch.as_deref(), start_after.as_deref(), limit)?)
     })
     .await
     .map_err(|e| AppError::io(format!("Task join error: {}", e)))?
 }
 
+#[tauri::command]
+#[instrument(skip_all, fields(path = %path, follow, limit, offset), err(Debug))]
+pub async fn get_commits_for_path(
+    repo_path: String,
+    path: String,
+    follow: bool,
+    limit: usize,
+    offset: usize,
+) -> Result<Vec<CommitInfo>> {
+    // Run blocking git operation on dedicated thread pool
+    tokio::task::spawn_blocking(move || {
+        let repo = git::open_repo(&repo_path)?;
+        Ok(git::get_commits_for_path(&repo, &path, follow, limit, offset)?)
+    })
+    .await
+    .map_err(|e| AppError::io(format!("Task join error: {}", e)))?
+}
+
+#[tauri::command]
+#[instrument(skip_all, fields(limit), err(Debug))]
+pub async fn search_commits(
+    repo_path: String,
+    query: String,
+    fields: Option<git::SearchFields>,
+    limit: usize,
+) -> Result<Vec<CommitInfo>> {
+    // Run blocking git operation on dedicated thread pool
+    tokio::task::spawn_blocking(move || {
+        let repo = git::open_repo(&repo_path)?;
+        Ok(git::search_commits(&repo, &query, fields.unwrap_or_default(), limit)?)
+    })
+    .await
+    .map_err(|e| AppError::io(format!("Task join error: {}", e)))?
+}
+
+/// Drop cached per-commit stats used when listing history
+#[tauri::command]
+pub async fn clear_commit_cache() -> Result<()> {
+    git::clear_commit_stats_cache();
+    Ok(())
+}
+
 #[tauri::command]
 #[instrument(skip_all, fields(limit, offset), err(Debug))]
 pub async fn get_commit_history_all_branches(
@@ -474,31 +1045,78 @@ pub async fn get_changelog_commits_all_branches(
 
 #[tauri::command]
 #[instrument(skip_all, fields(commit_count = commit_ids.len()), err(Debug))]
-pub async fn get_commit_graph(repo_path: String, commit_ids: Vec<String>) -> Result<CommitGraph> {
+pub async fn get_commit_graph(
+    operations: tauri::State<'_, CancellationRegistry>,
+    repo_path: String,
+    commit_ids: Vec<String>,
+    request_id: Option<String>,
+) -> Result<CommitGraph> {
     use std::time::Instant;
     let cmd_start = Instant::now();
     let commit_count = commit_ids.len();
+    let cancel = operations.register(request_id.as_deref());
     
     // Run blocking git operation on dedicated thread pool
     let result = tokio::task::spawn_blocking(move || {
         let spawn_start = Instant::now();
         let repo = git::open_repo(&repo_path)?;
-        let graph = git::build_commit_graph(&repo, &commit_ids)?;
+        let graph = git::build_commit_graph_cancellable(&repo, &commit_ids, &cancel)?;
         tracing::info!("get_commit_graph spawn_blocking inner took {:?} for {} commits", spawn_start.elapsed(), commit_count);
         Ok(graph)
     })
     .await
-    .map_err(|e| AppError::io(format!("Task join error: {}", e)))?;
+    .map_err(|e| AppError::io(format!("Task join error: {}", e)));
     
+    operations.finish(request_id.as_deref());
+    let result = result?;
     tracing::info!("get_commit_graph command total took {:?}", cmd_start.elapsed());
     result
 }
 
 #[tauri::command]
 #[instrument(skip_all, fields(commit_id = %commit_id), err(Debug))]
-pub async fn get_commit_diff(repo_path: String, commit_id: String) -> Result<UnifiedDiff> {
+pub async fn get_commit_diff(
+    repo_path: String,
+    commit_id: String,
+    max_patch_bytes: Option<usize>,
+    ignore_whitespace: Option<git::WhitespaceMode>,
+    function_context: Option<bool>,
+) -> Result<UnifiedDiff> {
     let repo = git::open_repo(&repo_path)?;
-    Ok(git::get_commit_diff(&repo, &commit_id)?)
+    Ok(git::get_commit_diff_capped(
+        &repo,
+        &commit_id,
+        max_patch_bytes,
+        ignore_whitespace.unwrap_or_default(),
+        function_context.unwrap_or(false),
+    )?)
+}
+
+#[tauri::command]
+#[instrument(skip_all, fields(commit_id = %commit_id), err(Debug))]
+pub async fn get_commit_files(repo_path: String, commit_id: String) -> Result<Vec<DiffFile>> {
+    let repo = git::open_repo(&repo_path)?;
+    Ok(git::get_commit_files(&repo, &commit_id)?)
+}
+
+/// File count and line totals for a commit, without the patch text
+#[tauri::command]
+#[instrument(skip_all, fields(commit_id = %commit_id), err(Debug))]
+pub async fn get_diff_stats(repo_path: String, commit_id: String) -> Result<git::DiffStats> {
+    let repo = git::open_repo(&repo_path)?;
+    Ok(git::get_diff_stats(&repo, &commit_id)?)
+}
+
+/// File count and line totals for staged or unstaged changes, without the patch text
+#[tauri::command]
+#[instrument(skip_all, fields(staged), err(Debug))]
+pub async fn get_working_diff_stats(repo_path: String, staged: bool) -> Result<git::DiffStats> {
+    tokio::task::spawn_blocking(move || {
+        let repo = git::open_repo(&repo_path)?;
+        Ok(git::get_working_diff_stats(&repo, staged)?)
+    })
+    .await
+    .map_err(|e| AppError::io(format!("Task join error: {}", e)))?
 }
 
 #[tauri::command]
@@ -506,18 +1124,58 @@ pub async fn get_file_diff(
     repo_path: String,
     commit_id: String,
     file_path: String,
+    max_patch_bytes: Option<usize>,
+    ignore_whitespace: Option<git::WhitespaceMode>,
 ) -> Result<FileDiff> {
     let repo = git::open_repo(&repo_path)?;
-    Ok(git::get_file_diff(&repo, &commit_id, &file_path)?)
+    Ok(git::get_file_diff_capped(
+        &repo,
+        &commit_id,
+        &file_path,
+        max_patch_bytes,
+        ignore_whitespace.unwrap_or_default(),
+    )?)
 }
 
 #[tauri::command]
 #[instrument(skip_all, fields(staged), err(Debug))]
-pub async fn get_working_diff(repo_path: String, staged: bool) -> Result<UnifiedDiff> {
+pub async fn get_working_diff(
+    repo_path: String,
+    staged: bool,
+    max_patch_bytes: Option<usize>,
+    ignore_whitespace: Option<git::WhitespaceMode>,
+) -> Result<UnifiedDiff> {
     // Run blocking git operation on dedicated thread pool
     tokio::task::spawn_blocking(move || {
         let repo = git::open_repo(&repo_path)?;
-        Ok(git::get_working_diff(&repo, staged)?)
+        Ok(git::get_working_diff_capped(
+            &repo,
+            staged,
+            max_patch_bytes,
+            ignore_whitespace.unwrap_or_default(),
+        )?)
+    })
+    .await
+    .map_err(|e| AppError::io(format!("Task join error: {}", e)))?
+}
+
+/// Everything that differs between `ref_name` and the working tree, committed or not
+#[tauri::command]
+#[instrument(skip_all, fields(ref_name = %ref_name), err(Debug))]
+pub async fn get_workdir_vs_ref_diff(
+    repo_path: String,
+    ref_name: String,
+    max_patch_bytes: Option<usize>,
+    ignore_whitespace: Option<git::WhitespaceMode>,
+) -> Result<UnifiedDiff> {
+    tokio::task::spawn_blocking(move || {
+        let repo = git::open_repo(&repo_path)?;
+        Ok(git::get_workdir_vs_ref_diff_capped(
+            &repo,
+            &ref_name,
+            max_patch_bytes,
+            ignore_whitespace.unwrap_or_default(),
+        )?)
     })
     .await
     .map_err(|e| AppError::io(format!("Task join error: {}", e)))?
@@ -525,11 +1183,25 @@ pub async fn get_working_diff(repo_path: String, staged: bool) -> Result<Unified
 
 #[tauri::command]
 #[instrument(skip_all, fields(base_ref = %base_ref, head_ref = %head_ref), err(Debug))]
-pub async fn get_compare_diff(repo_path: String, base_ref: String, head_ref: String) -> Result<UnifiedDiff> {
+pub async fn get_compare_diff(
+    repo_path: String,
+    base_ref: String,
+    head_ref: String,
+    max_patch_bytes: Option<usize>,
+    mode: Option<git::CompareMode>,
+    ignore_whitespace: Option<git::WhitespaceMode>,
+) -> Result<UnifiedDiff> {
     // Run blocking git operation on dedicated thread pool
     tokio::task::spawn_blocking(move || {
         let repo = git::open_repo(&repo_path)?;
-        Ok(git::get_compare_diff(&repo, &base_ref, &head_ref)?)
+        Ok(git::get_compare_diff_capped(
+            &repo,
+            &base_ref,
+            &head_ref,
+            mode.unwrap_or_default(),
+            max_patch_bytes,
+            ignore_whitespace.unwrap_or_default(),
+        )?)
     })
     .await
     .map_err(|e| AppError::io(format!("Task join error: {}", e)))?
@@ -542,11 +1214,39 @@ pub async fn get_compare_file_diff(
     base_ref: String,
     head_ref: String,
     file_path: String,
+    max_patch_bytes: Option<usize>,
+    mode: Option<git::CompareMode>,
+    ignore_whitespace: Option<git::WhitespaceMode>,
 ) -> Result<FileDiff> {
     // Run blocking git operation on dedicated thread pool
     tokio::task::spawn_blocking(move || {
         let repo = git::open_repo(&repo_path)?;
-        Ok(git::get_compare_file_diff(&repo, &base_ref, &head_ref, &file_path)?)
+        Ok(git::get_compare_file_diff_capped(
+            &repo,
+            &base_ref,
+            &head_ref,
+            &file_path,
+            mode.unwrap_or_default(),
+            max_patch_bytes,
+            ignore_whitespace.unwrap_or_default(),
+        )?)
+    })
+    .await
+    .map_err(|e| AppError::io(format!("Task join error: {}", e)))?
+}
+
+#[tauri::command]
+#[instrument(skip_all, fields(base_commit = %base_commit, head_commit = %head_commit, file_path = %file_path), err(Debug))]
+pub async fn get_commit_pair_file_diff(
+    repo_path: String,
+    base_commit: String,
+    head_commit: String,
+    file_path: String,
+) -> Result<git::CommitPairFileDiff> {
+    // Run blocking git operation on dedicated thread pool
+    tokio::task::spawn_blocking(move || {
+        let repo = git::open_repo(&repo_path)?;
+        Ok(git::get_commit_pair_file_diff(&repo, &base_commit, &head_commit, &file_path)?)
     })
     .await
     .map_err(|e| AppError::io(format!("Task join error: {}", e)))?
@@ -571,21 +1271,28 @@ pub async fn get_commit_range(
 
 #[tauri::command]
 #[instrument(skip_all, err(Debug))]
-pub async fn get_status(repo_path: String) -> Result<StatusInfo> {
+pub async fn get_status(
+    operations: tauri::State<'_, CancellationRegistry>,
+    repo_path: String,
+    request_id: Option<String>,
+) -> Result<StatusInfo> {
     use std::time::Instant;
     let cmd_start = Instant::now();
+    let cancel = operations.register(request_id.as_deref());
     
     // Run blocking git operation on dedicated thread pool to avoid blocking async runtime
     let result = tokio::task::spawn_blocking(move || {
         let spawn_start = Instant::now();
         let repo = git::open_repo(&repo_path)?;
-        let status = git::get_status(&repo)?;
+        let status = git::get_status_cancellable(&repo, &cancel)?;
         tracing::info!("get_status spawn_blocking inner took {:?}", spawn_start.elapsed());
         Ok(status)
     })
     .await
-    .map_err(|e| AppError::io(format!("Task join error: {}", e)))?;
+    .map_err(|e| AppError::io(format!("Task join error: {}", e)));
     
+    operations.finish(request_id.as_deref());
+    let result = result?;
     tracing::info!("get_status command total took {:?}", cmd_start.elapsed());
     result
 }
@@ -604,6 +1311,15 @@ pub async fn unstage_files(repo_path: String, paths: Vec<String>) -> Result<()>
     Ok(git::unstage_files(&repo, &paths)?)
 }
 
+#[tauri::command]
+#[instrument(skip_all, fields(source_ref = %source_ref, file_path = %file_path, staged), err(Debug))]
+pub async fn checkout_file(repo_path: String, source_ref: String, file_path: String, staged: bool) -> Result<()> {
+    tokio::task::spawn_blocking(move || git::checkout_file(&repo_path, &source_ref, &file_path, staged))
+        .await
+        .map_err(|e| AppError::io(format!("Task join error: {}", e)))?
+        .map_err(AppError::from)
+}
+
 #[tauri::command]
 pub async fn discard_changes(repo_path: String, paths: Vec<String>) -> Result<()> {
     let repo = git::open_repo(&repo_path)?;
@@ -611,29 +1327,135 @@ pub async fn discard_changes(repo_path: String, paths: Vec<String>) -> Result<()
 }
 
 #[tauri::command]
-pub async fn create_commit(repo_path: String, message: String) -> Result<String> {
+pub async fn create_commit(
+    repo_path: String,
+    message: String,
+    author_name: Option<String>,
+    author_email: Option<String>,
+    author_time: Option<i64>,
+) -> Result<String> {
+    let repo = git::open_repo(&repo_path)?;
+    let author = git::AuthorOverride {
+        name: author_name,
+        email: author_email,
+        time: author_time,
+    };
+    Ok(git::create_commit_as(&repo, &message, &author)?)
+}
+
+#[tauri::command]
+#[instrument(skip_all, fields(keys = ?keys), err(Debug))]
+pub async fn get_git_config(repo_path: String, keys: Vec<String>) -> Result<std::collections::HashMap<String, Option<String>>> {
+    let repo = git::open_repo(&repo_path)?;
+    Ok(git::get_git_config(&repo, &keys)?)
+}
+
+#[tauri::command]
+#[instrument(skip_all, fields(key = %key, global = ?global), err(Debug))]
+pub async fn set_git_config(repo_path: String, key: String, value: String, global: Option<bool>) -> Result<()> {
+    let repo = git::open_repo(&repo_path)?;
+    Ok(git::set_git_config(&repo, &key, &value, global.unwrap_or(false))?)
+}
+
+#[tauri::command]
+#[instrument(skip_all, err(Debug))]
+pub async fn list_remotes(repo_path: String) -> Result<Vec<RemoteInfo>> {
+    let repo = git::open_repo(&repo_path)?;
+    Ok(git::list_remotes(&repo)?)
+}
+
+#[tauri::command]
+#[instrument(skip_all, fields(name = %name), err(Debug))]
+pub async fn add_remote(repo_path: String, name: String, url: String) -> Result<()> {
     let repo = git::open_repo(&repo_path)?;
-    Ok(git::create_commit(&repo, &message)?)
+    Ok(git::add_remote(&repo, &name, &url)?)
+}
+
+#[tauri::command]
+#[instrument(skip_all, fields(old_name = %old_name, new_name = %new_name), err(Debug))]
+pub async fn rename_remote(repo_path: String, old_name: String, new_name: String) -> Result<()> {
+    let repo = git::open_repo(&repo_path)?;
+    Ok(git::rename_remote(&repo, &old_name, &new_name)?)
+}
+
+#[tauri::command]
+#[instrument(skip_all, fields(name = %name), err(Debug))]
+pub async fn remove_remote(repo_path: String, name: String) -> Result<()> {
+    let repo = git::open_repo(&repo_path)?;
+    Ok(git::remove_remote(&repo, &name)?)
+}
+
+// Remote commands accept `timeout_secs`; without it they use git::DEFAULT_REMOTE_TIMEOUT
+#[tauri::command]
+pub async fn git_fetch(repo_path: String, remote: Option<String>, timeout_secs: Option<u64>) -> Result<String> {
+    Ok(git::git_fetch(&repo_path, remote.as_deref(), timeout_secs.map(Duration::from_secs))?)
+}
+
+#[tauri::command]
+#[instrument(skip_all, fields(remote = %remote), err(Debug))]
+pub async fn git_fetch_remote(
+    repo_path: String,
+    remote: String,
+    refspec: Option<String>,
+    prune: Option<bool>,
+    timeout_secs: Option<u64>,
+) -> Result<Vec<git::FetchedRef>> {
+    Ok(git::git_fetch_remote(
+        &repo_path,
+        &remote,
+        refspec.as_deref(),
+        prune.unwrap_or(false),
+        timeout_secs.map(Duration::from_secs),
+    )?)
+}
+
+#[tauri::command]
+pub async fn git_pull(
+    repo_path: String,
+    strategy: Option<git::PullStrategy>,
+    autostash: Option<bool>,
+    timeout_secs: Option<u64>,
+) -> Result<git::PullResult> {
+    Ok(git::git_pull(
+        &repo_path,
+        strategy,
+        autostash.unwrap_or(false),
+        timeout_secs.map(Duration::from_secs),
+    )?)
 }
 
 #[tauri::command]
-pub async fn git_fetch(repo_path: String) -> Result<String> {
-    Ok(git::git_fetch(&repo_path)?)
+pub async fn git_push(
+    repo_path: String,
+    remote: Option<String>,
+    refspec: Option<String>,
+    force: Option<git::ForceMode>,
+    timeout_secs: Option<u64>,
+) -> Result<String> {
+    Ok(git::git_push(
+        &repo_path,
+        remote.as_deref(),
+        refspec.as_deref(),
+        force.unwrap_or_default(),
+        timeout_secs.map(Duration::from_secs),
+    )?)
 }
 
 #[tauri::command]
-pub async fn git_pull(repo_path: String) -> Result<String> {
-    Ok(git::git_pull(&repo_path)?)
+pub async fn git_remote_action(repo_path: String, action: String, timeout_secs: Option<u64>) -> Result<String> {
+    Ok(git::git_remote_action(&repo_path, &action, timeout_secs.map(Duration::from_secs))?)
 }
 
 #[tauri::command]
-pub async fn git_push(repo_path: String) -> Result<String> {
-    Ok(git::git_push(&repo_path)?)
+#[instrument(skip_all, fields(commit_id = ?commit_id), err(Debug))]
+pub async fn describe_commit(repo_path: String, commit_id: Option<String>) -> Result<String> {
+    Ok(git::describe(&repo_path, commit_id.as_deref())?)
 }
 
 #[tauri::command]
-pub async fn git_remote_action(repo_path: String, action: String) -> Result<String> {
-    Ok(git::git_remote_action(&repo_path, &action)?)
+#[instrument(skip_all, fields(commit_id = %commit_id), err(Debug))]
+pub async fn get_commit_signature(repo_path: String, commit_id: String) -> Result<git::CommitSignature> {
+    Ok(git::get_commit_signature(&repo_path, &commit_id)?)
 }
 
 #[tauri::command]
@@ -651,6 +1473,21 @@ pub async fn reset_hard(repo_path: String, commit_id: String) -> Result<String>
     Ok(git::reset_hard(&repo_path, &commit_id)?)
 }
 
+#[tauri::command]
+pub async fn reset_commit(
+    repo_path: String,
+    commit_id: String,
+    mode: git::ResetMode,
+    confirm: Option<bool>,
+) -> Result<String> {
+    Ok(git::reset_commit(&repo_path, &commit_id, mode, confirm.unwrap_or(false))?)
+}
+
+#[tauri::command]
+pub async fn uncommit(repo_path: String) -> Result<()> {
+    Ok(git::uncommit(&repo_path)?)
+}
+
 #[tauri::command]
 pub async fn squash_commits(
     repo_path: String,
@@ -665,49 +1502,51 @@ pub async fn squash_commits(
     Ok(result)
 }
 
-#[tauri::command]
-#[instrument(skip_all, err(Debug))]
-pub async fn generate_commit_message(repo_path: String) -> Result<String> {
-    // Get the staged diff
-    let repo = git::open_repo(&repo_path)?;
-    let diff = git::get_working_diff(&repo, true)?;
+/// Max diff bytes sent to Claude for a commit message (Claude has context limits)
+const COMMIT_MESSAGE_MAX_DIFF_LEN: usize = 8000;
 
-    if diff.patch.is_empty() {
-        return Err(AppError::validation("No staged changes to generate a commit message for"));
-    }
+/// Render the commit message prompt, truncating `patch` to `COMMIT_MESSAGE_MAX_DIFF_LEN`
+fn build_commit_message_prompt(template: &str, patch: &str) -> String {
+    let truncated_diff = diff_for_prompt(patch, COMMIT_MESSAGE_MAX_DIFF_LEN);
+    render_prompt_template(template, &[("diff", &truncated_diff)])
+}
 
-    // Truncate diff if too long (Claude has context limits)
-    let max_diff_len = 8000;
-    let truncated_diff = if diff.patch.len() > max_diff_len {
-        format!("{}...\n[diff truncated]", &diff.patch[..max_diff_len])
-    } else {
-        diff.patch.clone()
+/// Generate a commit message for the staged changes, or for an existing commit's
+/// diff when `commit_id` is given (e.g. to reword it)
+#[tauri::command]
+#[instrument(skip_all, fields(commit_id = ?commit_id, backend = ?backend), err(Debug))]
+pub async fn generate_commit_message(
+    app: tauri::AppHandle,
+    repo_path: String,
+    commit_id: Option<String>,
+    backend: Option<AiBackendKind>,
+) -> Result<String> {
+    let repo = git::open_repo(&repo_path)?;
+    let patch = match commit_id.as_deref() {
+        Some(cid) => {
+            let diff = git::get_commit_diff(&repo, cid)?;
+            if diff.patch.is_empty() {
+                return Err(AppError::no_changes(format!("Commit {} has no changes", cid)));
+            }
+            diff.patch
+        }
+        None => {
+            // Get the staged diff
+            let diff = git::get_working_diff(&repo, true)?;
+            if diff.patch.is_empty() {
+                return Err(AppError::no_staged_changes("No staged changes to generate a commit message for"));
+            }
+            diff.patch
+        }
     };
 
-    let prompt = format!(
-        "Generate a concise git commit message (max 72 chars for title) for these changes. \
-        Use conventional commit format (feat:, fix:, refactor:, etc) if appropriate. \
-        Only output the commit message, nothing else:\n\n{}",
-        truncated_diff
-    );
-
-    // Call claude CLI with -p flag for non-interactive mode
-    let claude_path = find_claude_binary()?;
-    let output = Command::new(&claude_path)
-        .args(["-p", &prompt])
-        .output()
-        .map_err(|e| AppError::ai(format!("Failed to run claude at {:?}: {}", claude_path, e)))?;
+    let template = load_app_settings(&app).prompts.resolve(PromptKind::CommitMessage);
+    let prompt = build_commit_message_prompt(&template, &patch);
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(AppError::ai(format!("Claude failed: {}", stderr)));
-    }
-
-    let message = String::from_utf8_lossy(&output.stdout).trim().to_string();
-    Ok(message)
+    backend.unwrap_or_default().backend().run_prompt(&prompt)
 }
 
-#[derive(serde::Serialize, Clone)]
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct AIReviewIssue {
     pub id: String,
@@ -721,12 +1560,62 @@ pub struct AIReviewIssue {
     pub file_path: Option<String>,
 }
 
-#[derive(serde::Serialize)]
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct AIReviewData {
     pub overview: String,
     pub issues: Vec<AIReviewIssue>,
     pub generated_at: u64,
+    /// True when served from the on-disk review cache instead of a fresh Claude run
+    #[serde(default)]
+    pub cached: bool,
+}
+
+/// Cache key for an AI review: a SHA-1 over everything that shapes the result.
+/// The prompt template is included so editing it doesn't serve stale reviews.
+fn review_cache_key(provider: &str, model: &str, skill_ids: &[String], template: &str, diff_patch: &str) -> String {
+    let mut skill_ids = skill_ids.to_vec();
+    skill_ids.sort();
+
+    let mut input = String::new();
+    for part in [provider, model, &skill_ids.join(","), template] {
+        input.push_str(part);
+        input.push('\0');
+    }
+    input.push_str(diff_patch);
+
+    git2::Oid::hash_object(git2::ObjectType::Blob, input.as_bytes())
+        .map(|oid| oid.to_string())
+        .unwrap_or_default()
+}
+
+/// Load a cached review, marked `cached: true`. Missing or unreadable entries are misses.
+fn read_cached_review(cache_dir: &std::path::Path, key: &str) -> Option<AIReviewData> {
+    let content = fs::read_to_string(cache_dir.join(format!("{}.json", key))).ok()?;
+    let mut review: AIReviewData = serde_json::from_str(&content).ok()?;
+    review.cached = true;
+    Some(review)
+}
+
+fn write_cached_review(cache_dir: &std::path::Path, key: &str, review: &AIReviewData) -> Result<()> {
+    fs::create_dir_all(cache_dir)
+        .map_err(|e| AppError::io(format!("Failed to create review cache dir: {}", e)))?;
+    let json = serde_json::to_string(review)
+        .map_err(|e| AppError::io(format!("Failed to serialize review: {}", e)))?;
+    fs::write(cache_dir.join(format!("{}.json", key)), json)
+        .map_err(|e| AppError::io(format!("Failed to write review cache: {}", e)))
+}
+
+/// Delete all cached AI reviews
+#[tauri::command]
+#[instrument(skip_all, err(Debug))]
+pub async fn clear_review_cache(app: tauri::AppHandle) -> Result<()> {
+    let cache_dir = get_review_cache_dir_path(&app)?;
+    if cache_dir.exists() {
+        fs::remove_dir_all(&cache_dir)
+            .map_err(|e| AppError::io(format!("Failed to clear review cache: {}", e)))?;
+    }
+    Ok(())
 }
 
 /// Normalize a category string to a known value, defaulting to "other".
@@ -752,129 +1641,57 @@ fn normalize_severity(sev: &str) -> String {
     }
 }
 
-#[tauri::command]
-#[instrument(skip_all, fields(commit_id = ?commit_id, skill_count = skill_ids.as_ref().map(|s| s.len()).unwrap_or(0)), err(Debug))]
-pub async fn generate_ai_review(
-    app: tauri::AppHandle,
-    repo_path: String,
-    commit_id: Option<String>,
-    skill_ids: Option<Vec<String>>,
-) -> Result<AIReviewData> {
-    let repo = git::open_repo(&repo_path)?;
+/// Patch text for an AI review or diagram: the `base_ref..head_ref` range when both
+/// are given, else a commit, else staged plus unstaged changes. `file_paths` limits the
+/// diff to those paths; errors with "No changes to review" when nothing (in scope) changed.
+fn review_diff_patch(
+    repo: &git2::Repository,
+    commit_id: Option<&str>,
+    base_ref: Option<&str>,
+    head_ref: Option<&str>,
+    file_paths: Option<&[String]>,
+) -> Result<String> {
+    let paths = file_paths.unwrap_or_default();
 
-    // Get diff based on whether we're reviewing a commit or working changes
-    let diff_patch = if let Some(ref cid) = commit_id {
-        let diff = git::get_commit_diff(&repo, cid)?;
-        diff.patch
+    // Get diff based on review type: compare refs, commit, or working changes
+    let diff_patch = if let (Some(base), Some(head)) = (base_ref, head_ref) {
+        // Compare diff between two refs
+        git::get_compare_diff_for_paths(repo, base, head, git::CompareMode::TwoDot, paths)?.patch
+    } else if let Some(cid) = commit_id {
+        git::get_commit_diff_for_paths(repo, cid, paths)?.patch
     } else {
         // Get combined staged and unstaged diff for working changes
-        let staged = git::get_working_diff(&repo, true)?;
-        let unstaged = git::get_working_diff(&repo, false)?;
+        let staged = git::get_working_diff_for_paths(repo, true, paths)?;
+        let unstaged = git::get_working_diff_for_paths(repo, false, paths)?;
         format!("{}\n{}", staged.patch, unstaged.patch)
     };
 
     if diff_patch.trim().is_empty() {
-        return Err(AppError::validation("No changes to review"));
+        return Err(AppError::no_changes("No changes to review"));
     }
+    Ok(diff_patch)
+}
 
-    // Truncate diff if too long
-    let max_diff_len = 12000;
-    let truncated_diff = if diff_patch.len() > max_diff_len {
-        format!("{}...\n[diff truncated]", &diff_patch[..max_diff_len])
-    } else {
-        diff_patch
-    };
-
-    // Load skill content if skills provided
-    let skills_context = if let Some(ids) = &skill_ids {
-        let skills_dir = get_skills_dir_path(&app)?;
-        let mut context = String::new();
-        for id in ids {
-            let path = skills_dir.join(format!("{}.md", id));
-            if path.exists() {
-                if let Ok(content) = fs::read_to_string(&path) {
-                    // Extract body after frontmatter
-                    let (_name, _desc, body) = parse_skill_frontmatter(&content);
-                    context.push_str(&format!("\n\n{}", body));
-                }
-            }
-        }
-        context
-    } else {
-        String::new()
-    };
-
-    let prompt = format!(
-        r#"You are an expert code reviewer. Your goal is to provide actionable feedback.{skills_context}
-
-Analyze this git diff and provide a PR-style review with specific, actionable issues.
-
-For each issue you find, explain:
-- **problem**: What is wrong or concerning
-- **why**: Why it matters (impact on correctness, security, performance, maintainability)
-- **suggestion**: A concrete fix or improvement
-
-Categories: logic_bugs, edge_cases, security, performance, accidental_code, other
-Severities: low, medium, high, critical
-
-Respond ONLY with valid JSON (no markdown, no code blocks):
-{{
-  "overview": "1-2 sentence summary of the changes",
-  "issues": [
-    {{
-      "id": "issue-1",
-      "category": "logic_bugs",
-      "severity": "high",
-      "title": "brief title",
-      "problem": "what is wrong",
-      "why": "why it matters",
-      "suggestion": "how to fix it",
-      "filePath": "path/to/file.ts"
-    }}
-  ]
-}}
-
-If there are no issues, use an empty array for "issues".
-
-Diff to review:
-{diff}"#,
-        skills_context = skills_context,
-        diff = truncated_diff
-    );
-
-    // Call claude CLI
-    let claude_path = find_claude_binary()?;
-    let output = Command::new(&claude_path)
-        .args(["-p", &prompt])
-        .output()
-        .map_err(|e| AppError::ai(format!("Failed to run claude at {:?}: {}", claude_path, e)))?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(AppError::ai(format!("Claude failed: {}", stderr)));
-    }
-
-    let response = String::from_utf8_lossy(&output.stdout).trim().to_string();
-
-    if response.is_empty() {
-        return Err(AppError::ai("Claude returned an empty response"));
-    }
+/// Run the review prompt on `backend` and parse its JSON reply
+fn request_review(backend: &dyn AiBackend, prompt: &str) -> Result<AIReviewData> {
+    parse_review_response(&backend.run_prompt(prompt)?)
+}
 
-    // Try to extract JSON from the response (Claude sometimes includes explanation text)
-    let json_str = extract_json_object(&response)
+/// Parse a review reply, tolerating loosely-shaped issues (missing ids,
+/// alternate field names)
+fn parse_review_response(response: &str) -> Result<AIReviewData> {
+    // Try to extract JSON from the response (models sometimes include explanation text)
+    let json = extract_json_value(response)
         .ok_or_else(|| AppError::parse(format!("Could not find valid JSON in response: {}", response)))?;
 
-    // Parse the JSON response
-    let json: serde_json::Value = serde_json::from_str(json_str)
-        .map_err(|e| AppError::parse(format!("Failed to parse AI response as JSON: {}. JSON was: {}", e, json_str)))?;
-
     let overview = json["overview"]
         .as_str()
         .unwrap_or("Unable to generate overview")
         .to_string();
 
-    // Parse issues with graceful defaulting
-    let issues: Vec<AIReviewIssue> = json["issues"]
+    // Parse issues with graceful defaulting; a bare array is taken as the issue list
+    let issues_json = if json.is_array() { &json } else { &json["issues"] };
+    let issues: Vec<AIReviewIssue> = issues_json
         .as_array()
         .map(|arr| {
             arr.iter()
@@ -941,9 +1758,252 @@ Diff to review:
         overview,
         issues,
         generated_at,
+        cached: false,
+    })
+}
+
+/// A rendered review prompt plus where its result is cached
+struct AiReviewJob {
+    prompt: String,
+    cache_dir: PathBuf,
+    cache_key: String,
+}
+
+impl AiReviewJob {
+    /// Cache a finished review; failures are logged, not fatal
+    fn store(&self, review: &AIReviewData) {
+        if let Err(e) = write_cached_review(&self.cache_dir, &self.cache_key, review) {
+            tracing::warn!("Failed to cache AI review: {}", e.message);
+        }
+    }
+}
+
+/// Collect the diff and skills for a review and render its prompt
+#[allow(clippy::too_many_arguments)]
+fn prepare_ai_review(
+    app: &tauri::AppHandle,
+    repo_path: &str,
+    commit_id: Option<&str>,
+    skill_ids: Option<&[String]>,
+    file_paths: Option<&[String]>,
+    base_ref: Option<&str>,
+    head_ref: Option<&str>,
+    provider_id: &str,
+) -> Result<AiReviewJob> {
+    let repo = git::open_repo(repo_path)?;
+    let review_template = load_app_settings(app).prompts.resolve(PromptKind::Review);
+
+    let diff_patch = review_diff_patch(&repo, commit_id, base_ref, head_ref, file_paths)?;
+
+    let cache_dir = get_review_cache_dir_path(app)?;
+    let cache_key = review_cache_key(
+        provider_id,
+        "default",
+        skill_ids.unwrap_or_default(),
+        &review_template,
+        &diff_patch,
+    );
+
+    // Truncate diff if too long
+    let truncated_diff = diff_for_prompt(&diff_patch, REVIEW_MAX_DIFF_LEN);
+
+    // Load skill content if skills provided
+    let skills_context = if let Some(ids) = skill_ids {
+        let skills_dir = get_skills_dir_path(app)?;
+        let mut context = String::new();
+        for id in ids {
+            let path = skills_dir.join(format!("{}.md", id));
+            if path.exists() {
+                if let Ok(content) = fs::read_to_string(&path) {
+                    // Extract body after frontmatter
+                    let (_name, _desc, body) = parse_skill_frontmatter(&content);
+                    context.push_str(&format!("\n\n{}", body));
+                }
+            }
+        }
+        context
+    } else {
+        String::new()
+    };
+
+    let prompt = render_prompt_template(
+        &review_template,
+        &[("skills", &skills_context), ("diff", &truncated_diff)],
+    );
+
+    Ok(AiReviewJob {
+        prompt,
+        cache_dir,
+        cache_key,
     })
 }
 
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+#[instrument(
+    skip_all,
+    fields(
+        commit_id = ?commit_id,
+        base_ref = ?base_ref,
+        head_ref = ?head_ref,
+        skill_count = skill_ids.as_ref().map(|s| s.len()).unwrap_or(0),
+        file_count = file_paths.as_ref().map(|p| p.len()),
+        force_refresh = ?force_refresh,
+        backend = ?backend,
+        request_id = ?request_id
+    ),
+    err(Debug)
+)]
+pub async fn generate_ai_review(
+    app: tauri::AppHandle,
+    processes: tauri::State<'_, CancellationState>,
+    repo_path: String,
+    commit_id: Option<String>,
+    skill_ids: Option<Vec<String>>,
+    file_paths: Option<Vec<String>>,
+    force_refresh: Option<bool>,
+    base_ref: Option<String>,
+    head_ref: Option<String>,
+    backend: Option<AiBackendKind>,
+    request_id: Option<String>,
+) -> Result<AIReviewData> {
+    let backend = backend.unwrap_or_default();
+    let job = prepare_ai_review(
+        &app,
+        &repo_path,
+        commit_id.as_deref(),
+        skill_ids.as_deref(),
+        file_paths.as_deref(),
+        base_ref.as_deref(),
+        head_ref.as_deref(),
+        backend.provider_id(),
+    )?;
+
+    // Reuse a stored review of the identical diff instead of re-spending tokens
+    if !force_refresh.unwrap_or(false) {
+        if let Some(review) = read_cached_review(&job.cache_dir, &job.cache_key) {
+            return Ok(review);
+        }
+    }
+
+    let response = backend
+        .backend()
+        .run_prompt_tracked(&job.prompt, &processes, request_id.as_deref())?;
+    let review = parse_review_response(&response)?;
+    job.store(&review);
+    Ok(review)
+}
+
+/// Payload of `ai_review_chunk`: raw model output as it arrives
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AIReviewChunk {
+    pub stream_id: String,
+    pub text: String,
+}
+
+/// Payload of `ai_review_complete`
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AIReviewComplete {
+    pub stream_id: String,
+    pub review: AIReviewData,
+}
+
+/// Payload of `ai_review_error`
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AIReviewStreamError {
+    pub stream_id: String,
+    pub error: AppError,
+}
+
+fn emit_review_event<S: serde::Serialize + Clone>(app: &tauri::AppHandle, event: &str, payload: S) {
+    if let Err(e) = app.emit(event, payload) {
+        tracing::warn!("Failed to emit {} event: {}", event, e);
+    }
+}
+
+/// Streaming variant of `generate_ai_review` (Claude only). Output is emitted as
+/// `ai_review_chunk` events tagged with `stream_id`, followed by either
+/// `ai_review_complete` with the parsed review or `ai_review_error`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+#[instrument(
+    skip_all,
+    fields(
+        stream_id = %stream_id,
+        commit_id = ?commit_id,
+        base_ref = ?base_ref,
+        head_ref = ?head_ref,
+        skill_count = skill_ids.as_ref().map(|s| s.len()).unwrap_or(0),
+        force_refresh = ?force_refresh
+    ),
+    err(Debug)
+)]
+pub async fn generate_ai_review_streaming(
+    app: tauri::AppHandle,
+    stream_id: String,
+    repo_path: String,
+    commit_id: Option<String>,
+    skill_ids: Option<Vec<String>>,
+    file_paths: Option<Vec<String>>,
+    force_refresh: Option<bool>,
+    base_ref: Option<String>,
+    head_ref: Option<String>,
+) -> Result<()> {
+    let result: Result<AIReviewData> = async {
+        let job = prepare_ai_review(
+            &app,
+            &repo_path,
+            commit_id.as_deref(),
+            skill_ids.as_deref(),
+            file_paths.as_deref(),
+            base_ref.as_deref(),
+            head_ref.as_deref(),
+            AiBackendKind::Claude.provider_id(),
+        )?;
+
+        if !force_refresh.unwrap_or(false) {
+            if let Some(review) = read_cached_review(&job.cache_dir, &job.cache_key) {
+                return Ok(review);
+            }
+        }
+
+        let response = ClaudeCli
+            .stream_prompt(&job.prompt, |text| {
+                let chunk = AIReviewChunk {
+                    stream_id: stream_id.clone(),
+                    text: text.to_string(),
+                };
+                emit_review_event(&app, "ai_review_chunk", chunk);
+            })
+            .await?;
+        let review = parse_review_response(&response)?;
+        job.store(&review);
+        Ok(review)
+    }
+    .await;
+
+    match result {
+        Ok(review) => {
+            emit_review_event(&app, "ai_review_complete", AIReviewComplete { stream_id, review });
+            Ok(())
+        }
+        Err(error) => {
+            emit_review_event(
+                &app,
+                "ai_review_error",
+                AIReviewStreamError {
+                    stream_id,
+                    error: error.clone(),
+                },
+            );
+            Err(error)
+        }
+    }
+}
+
 #[tauri::command]
 #[instrument(skip_all, fields(since, until, contributor_email = ?contributor_email), err(Debug))]
 pub async fn generate_changelog_summary(
@@ -1030,25 +2090,7 @@ Instructions:
         commits_section = commits_section
     );
 
-    // Call claude CLI
-    let claude_path = find_claude_binary()?;
-    let output = Command::new(&claude_path)
-        .args(["-p", &prompt])
-        .output()
-        .map_err(|e| AppError::ai(format!("Failed to run claude at {:?}: {}", claude_path, e)))?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(AppError::ai(format!("Claude failed: {}", stderr)));
-    }
-
-    let summary = String::from_utf8_lossy(&output.stdout).trim().to_string();
-
-    if summary.is_empty() {
-        return Err(AppError::ai("Claude returned an empty response"));
-    }
-
-    Ok(summary)
+    ClaudeCli.run_prompt(&prompt)
 }
 
 #[derive(serde::Deserialize)]
@@ -1060,8 +2102,264 @@ pub struct IssueToFix {
     pub file_path: Option<String>,
 }
 
+/// Outcome of `fix_ai_review_issues`. In a dry run `files` holds the proposed
+/// contents and nothing is written; otherwise they have been applied.
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AIFixResult {
+    pub message: String,
+    pub summary: String,
+    /// (path, new content) per changed file
+    pub files: Vec<(String, String)>,
+    /// Paths from the response that resolve outside the repository or into `.git`; never written
+    pub skipped: Vec<String>,
+    pub applied: bool,
+    /// Pass to `undo_ai_fix` to restore the files; None for dry runs
+    pub backup_id: Option<String>,
+}
+
+/// A file captured before an AI fix wrote to it, or a directory the fix created
+#[derive(serde::Serialize, serde::Deserialize)]
+struct FixBackupEntry {
+    path: PathBuf,
+    /// Name of the copy inside the backup dir; None if the fix created the file
+    backup: Option<String>,
+    /// A directory created to hold a new file; removed on undo if it's empty again
+    #[serde(default)]
+    created_dir: bool,
+}
+
+/// Copy `targets` into a new timestamped dir under `backup_root` before they are
+/// overwritten and return its id. Missing files, and missing directories above
+/// them, are recorded so that undo deletes them again.
+fn backup_files(backup_root: &std::path::Path, targets: &[PathBuf]) -> Result<String> {
+    fs::create_dir_all(backup_root)
+        .map_err(|e| AppError::io(format!("Failed to create backup directory: {}", e)))?;
+
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    let (backup_id, backup_dir) = (0..)
+        .map(|n| (stamp + n).to_string())
+        .find_map(|id| {
+            let dir = backup_root.join(&id);
+            match fs::create_dir(&dir) {
+                Ok(()) => Some(Ok((id, dir))),
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => None,
+                Err(e) => Some(Err(AppError::io(format!("Failed to create backup: {}", e)))),
+            }
+        })
+        .expect("unbounded id search")?;
+
+    let mut entries = Vec::new();
+    for (idx, target) in targets.iter().enumerate() {
+        let backup = if target.exists() {
+            let name = idx.to_string();
+            fs::copy(target, backup_dir.join(&name))
+                .map_err(|e| AppError::io(format!("Failed to back up {}: {}", target.display(), e)))?;
+            Some(name)
+        } else {
+            None
+        };
+        entries.push(FixBackupEntry { path: target.clone(), backup, created_dir: false });
+    }
+
+    // Deepest first, so undo can remove them in manifest order
+    let mut missing_dirs: Vec<&std::path::Path> = Vec::new();
+    for target in targets {
+        let mut dir = target.parent();
+        while let Some(d) = dir.filter(|d| !d.exists()) {
+            if !missing_dirs.contains(&d) {
+                missing_dirs.push(d);
+            }
+            dir = d.parent();
+        }
+    }
+    missing_dirs.sort_by_key(|d| std::cmp::Reverse(d.components().count()));
+    entries.extend(missing_dirs.into_iter().map(|d| FixBackupEntry {
+        path: d.to_path_buf(),
+        backup: None,
+        created_dir: true,
+    }));
+
+    fs::write(backup_dir.join("manifest.json"), serde_json::to_string(&entries)?)
+        .map_err(|e| AppError::io(format!("Failed to save backup manifest: {}", e)))?;
+    Ok(backup_id)
+}
+
+/// Restore every file of a backup, remove the directories the fix created and delete
+/// the backup. Returns the number of files restored or removed.
+fn restore_fix_backup(backup_root: &std::path::Path, backup_id: &str) -> Result<usize> {
+    if backup_id.is_empty() || !backup_id.chars().all(|c| c.is_ascii_digit()) {
+        return Err(AppError::validation(format!("Invalid fix backup id: {}", backup_id)));
+    }
+    let backup_dir = backup_root.join(backup_id);
+    let manifest = fs::read_to_string(backup_dir.join("manifest.json"))
+        .map_err(|_| AppError::validation(format!("No AI fix backup found with id {}", backup_id)))?;
+    let entries: Vec<FixBackupEntry> = serde_json::from_str(&manifest)?;
+
+    let (dirs, files): (Vec<_>, Vec<_>) = entries.iter().partition(|entry| entry.created_dir);
+    for entry in &files {
+        match &entry.backup {
+            Some(name) => {
+                fs::copy(backup_dir.join(name), &entry.path)
+                    .map_err(|e| AppError::io(format!("Failed to restore {}: {}", entry.path.display(), e)))?;
+            }
+            None if entry.path.exists() => {
+                fs::remove_file(&entry.path)
+                    .map_err(|e| AppError::io(format!("Failed to remove {}: {}", entry.path.display(), e)))?;
+            }
+            None => {}
+        }
+    }
+    for entry in &dirs {
+        // Leave directories that have gained other files since the fix
+        if let Err(e) = fs::remove_dir(&entry.path) {
+            if entry.path.exists() {
+                tracing::warn!("Keeping {} on undo: {}", entry.path.display(), e);
+            }
+        }
+    }
+
+    fs::remove_dir_all(&backup_dir)
+        .map_err(|e| AppError::io(format!("Failed to remove backup: {}", e)))?;
+    Ok(files.len())
+}
+
+/// Undo an applied AI fix, restoring the files it overwrote
+#[tauri::command]
+#[instrument(skip_all, fields(backup_id = %backup_id), err(Debug))]
+pub async fn undo_ai_fix(app: tauri::AppHandle, backup_id: String) -> Result<usize> {
+    let backup_root = get_fix_backup_dir_path(&app)?;
+    restore_fix_backup(&backup_root, &backup_id)
+}
+
+/// Resolve an AI-supplied relative path under `repo_path` for writing. None if it
+/// is absolute, uses `..`, goes through a `.git` directory (hooks and config there
+/// would run on the next git command), or resolves (through symlinks) outside the
+/// repository.
+fn resolve_repo_write_path(repo_path: &str, rel: &str) -> Option<PathBuf> {
+    let rel_path = std::path::Path::new(rel);
+    let is_plain = rel_path.components().all(|c| match c {
+        std::path::Component::Normal(name) => !name.to_string_lossy().eq_ignore_ascii_case(".git"),
+        std::path::Component::CurDir => true,
+        _ => false,
+    });
+    if rel.is_empty() || !is_plain {
+        return None;
+    }
+
+    let repo_canonical = std::path::Path::new(repo_path).canonicalize().ok()?;
+    let full_path = repo_canonical.join(rel_path);
+
+    // Canonicalize the deepest existing ancestor so symlinks can't point outside
+    let mut existing = full_path.as_path();
+    while !existing.exists() {
+        existing = existing.parent()?;
+    }
+    let canonical = existing.canonicalize().ok()?;
+    canonical.starts_with(&repo_canonical).then_some(full_path)
+}
+
+/// Parse a fix response into per-file contents and write them under `repo_path`
+/// unless `dry_run`. Every file is validated before any is written, and paths
+/// escaping the repository are skipped and reported. Originals are backed up
+/// under `backup_root` first.
+fn apply_fix_response(
+    repo_path: &str,
+    response: &str,
+    dry_run: bool,
+    backup_root: &std::path::Path,
+) -> Result<AIFixResult> {
+    // Try to extract JSON from the response (Claude sometimes includes explanation text)
+    let json = extract_json_value(response)
+        .ok_or_else(|| AppError::parse(format!("Could not find valid JSON in response. Response was: {}", response)))?;
+
+    let proposed = json["files"].as_array()
+        .ok_or_else(|| AppError::parse("Invalid response: missing files array"))?
+        .iter()
+        .map(|file| {
+            let path = file["path"].as_str()
+                .ok_or_else(|| AppError::parse("Invalid response: file missing path"))?;
+            let content = file["content"].as_str()
+                .ok_or_else(|| AppError::parse("Invalid response: file missing content"))?;
+            Ok((path.to_string(), content.to_string()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    // Drop anything that would land outside the repository or inside .git
+    let mut files = Vec::new();
+    let mut targets = Vec::new();
+    let mut skipped = Vec::new();
+    for (path, content) in proposed {
+        match resolve_repo_write_path(repo_path, &path) {
+            Some(target) => {
+                targets.push(target);
+                files.push((path, content));
+            }
+            None => {
+                tracing::warn!("Skipping AI fix for unsafe path: {}", path);
+                skipped.push(path);
+            }
+        }
+    }
+
+    let summary = json["summary"].as_str().unwrap_or("Fixes applied").to_string();
+    let skipped_note = if skipped.is_empty() {
+        String::new()
+    } else {
+        format!(" (skipped {} path(s) outside the repository or in .git: {})", skipped.len(), skipped.join(", "))
+    };
+
+    if dry_run {
+        return Ok(AIFixResult {
+            message: format!("{} file(s) would be updated: {}{}", files.len(), summary, skipped_note),
+            summary,
+            files,
+            skipped,
+            applied: false,
+            backup_id: None,
+        });
+    }
+
+    // Apply the fixes; a failed write rolls back the files already written
+    let backup_id = backup_files(backup_root, &targets)?;
+    for ((path, content), target) in files.iter().zip(&targets) {
+        let written = match target.parent() {
+            Some(dir) => fs::create_dir_all(dir).and_then(|_| fs::write(target, content)),
+            None => fs::write(target, content),
+        };
+        if let Err(e) = written {
+            let rollback = match restore_fix_backup(backup_root, &backup_id) {
+                Ok(_) => "earlier files were restored".to_string(),
+                Err(restore_err) => format!("restoring backup {} also failed: {}", backup_id, restore_err.message),
+            };
+            return Err(AppError::io(format!("Failed to write {}: {}; {}", path, e, rollback)));
+        }
+    }
+
+    Ok(AIFixResult {
+        message: format!("{} file(s) updated: {}{}", files.len(), summary, skipped_note),
+        summary,
+        files,
+        skipped,
+        applied: true,
+        backup_id: Some(backup_id),
+    })
+}
+
+/// Ask Claude to fix review issues. With `dry_run` the proposed file contents are
+/// returned for confirmation instead of being written.
 #[tauri::command]
-pub async fn fix_ai_review_issues(repo_path: String, issues: Vec<IssueToFix>) -> Result<String> {
+pub async fn fix_ai_review_issues(
+    app: tauri::AppHandle,
+    processes: tauri::State<'_, CancellationState>,
+    repo_path: String,
+    issues: Vec<IssueToFix>,
+    request_id: Option<String>,
+    dry_run: Option<bool>,
+) -> Result<AIFixResult> {
     if issues.is_empty() {
         return Err(AppError::validation("No issues selected to fix"));
     }
@@ -1139,11 +2437,12 @@ Include only files that need changes. Preserve all existing code that doesn't ne
 
     // Call claude CLI
     let claude_path = find_claude_binary()?;
-    let output = Command::new(&claude_path)
-        .args(["-p", &prompt])
-        .current_dir(&repo_path)
-        .output()
-        .map_err(|e| AppError::ai(format!("Failed to run claude at {:?}: {}", claude_path, e)))?;
+    let mut cmd = Command::new(&claude_path);
+    cmd.args(["-p", &prompt]).current_dir(&repo_path);
+    let output = processes
+        .output(request_id.as_deref(), cmd)
+        .map_err(|e| AppError::ai(format!("Failed to run claude at {:?}: {}", claude_path, e)))?
+        .ok_or_else(|| AppError::cancelled("Fixing issues was cancelled"))?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
@@ -1168,33 +2467,8 @@ Include only files that need changes. Preserve all existing code that doesn't ne
         return Err(AppError::ai("Claude returned an empty response. This may indicate an issue with the Claude CLI or the prompt was too large."));
     }
 
-    // Try to extract JSON from the response (Claude sometimes includes explanation text)
-    let json_str = extract_json_object(&response)
-        .ok_or_else(|| AppError::parse(format!("Could not find valid JSON in response. Response was: {}", response)))?;
-
-    // Parse the JSON response
-    let json: serde_json::Value = serde_json::from_str(json_str)
-        .map_err(|e| AppError::parse(format!("Failed to parse AI response as JSON: {}. JSON was: {}", e, json_str)))?;
-
-    // Apply the fixes
-    let files = json["files"].as_array()
-        .ok_or_else(|| AppError::parse("Invalid response: missing files array"))?;
-
-    let mut fixed_count = 0;
-    for file in files {
-        let path = file["path"].as_str()
-            .ok_or_else(|| AppError::parse("Invalid response: file missing path"))?;
-        let content = file["content"].as_str()
-            .ok_or_else(|| AppError::parse("Invalid response: file missing content"))?;
-
-        let full_path = std::path::Path::new(&repo_path).join(path);
-        std::fs::write(&full_path, content)
-            .map_err(|e| AppError::io(format!("Failed to write {}: {}", path, e)))?;
-        fixed_count += 1;
-    }
-
-    let summary = json["summary"].as_str().unwrap_or("Fixes applied");
-    Ok(format!("{} file(s) updated: {}", fixed_count, summary))
+    let backup_root = get_fix_backup_dir_path(&app)?;
+    apply_fix_response(&repo_path, &response, dry_run.unwrap_or(false), &backup_root)
 }
 
 // CodeRabbit issue fix using Claude
@@ -1208,7 +2482,11 @@ pub struct CodeRabbitIssueFix {
 }
 
 #[tauri::command]
-pub async fn fix_coderabbit_issue(repo_path: String, issue: CodeRabbitIssueFix) -> Result<String> {
+pub async fn fix_coderabbit_issue(
+    app: tauri::AppHandle,
+    repo_path: String,
+    issue: CodeRabbitIssueFix,
+) -> Result<AIFixResult> {
     // Validate file path to prevent path traversal
     let file_path = std::path::Path::new(&issue.file);
     if file_path.is_absolute() || issue.file.contains("..") {
@@ -1285,12 +2563,9 @@ Return ONLY valid JSON, no markdown, no explanation."#,
     .map_err(|e| AppError::io(format!("Task join error: {}", e)))??;
 
     // Parse the JSON response
-    let json_str = extract_json_object(&result)
+    let json = extract_json_value(&result)
         .ok_or_else(|| AppError::ai("Claude response did not contain valid JSON"))?;
 
-    let json: serde_json::Value = serde_json::from_str(json_str)
-        .map_err(|e| AppError::ai(format!("Failed to parse Claude response: {}", e)))?;
-
     // Extract and write the fixed file
     let files = json["files"]
         .as_object()
@@ -1301,14 +2576,23 @@ Return ONLY valid JSON, no markdown, no explanation."#,
         .and_then(|v| v.as_str())
         .ok_or_else(|| AppError::ai(format!("Response missing content for {}", file_name)))?;
 
+    let backup_id = backup_files(&get_fix_backup_dir_path(&app)?, std::slice::from_ref(&full_path))?;
     std::fs::write(&full_path, new_content)
         .map_err(|e| AppError::io(format!("Failed to write {}: {}", file_name, e)))?;
 
     let summary = json["summary"]
         .as_str()
-        .unwrap_or("Fix applied successfully");
+        .unwrap_or("Fix applied successfully")
+        .to_string();
 
-    Ok(format!("{}: {}", file_name, summary))
+    Ok(AIFixResult {
+        message: format!("{}: {}", file_name, summary),
+        summary,
+        files: vec![(file_name, new_content.to_string())],
+        skipped: Vec::new(),
+        applied: true,
+        backup_id: Some(backup_id),
+    })
 }
 
 // Skills commands
@@ -1321,13 +2605,17 @@ pub async fn get_skills_dir(app: tauri::AppHandle) -> Result<String> {
 #[tauri::command]
 pub async fn list_skills(app: tauri::AppHandle) -> Result<Vec<SkillMetadata>> {
     let skills_dir = get_skills_dir_path(&app)?;
+    list_skills_in(&skills_dir)
+}
 
+/// List the skills installed in `skills_dir`, sorted by name
+fn list_skills_in(skills_dir: &std::path::Path) -> Result<Vec<SkillMetadata>> {
     if !skills_dir.exists() {
         return Ok(Vec::new());
     }
 
     let mut skills = Vec::new();
-    let entries = fs::read_dir(&skills_dir)
+    let entries = fs::read_dir(skills_dir)
         .map_err(|e| AppError::io(format!("Failed to read skills directory: {}", e)))?;
 
     for entry in entries.flatten() {
@@ -1419,14 +2707,109 @@ fn get_skill_url_candidates(url: &str) -> Vec<String> {
     candidates
 }
 
-#[tauri::command]
-pub async fn install_skill_from_url(app: tauri::AppHandle, url: String) -> Result<SkillMetadata> {
-    let skills_dir = get_skills_dir_path(&app)?;
+/// Validate skill markdown and save it, plus its `.meta.json`, into `skills_dir`.
+/// The skill id is derived from the frontmatter name.
+fn save_skill(
+    skills_dir: &std::path::Path,
+    content: &str,
+    meta: serde_json::Value,
+    source_url: Option<String>,
+) -> Result<SkillMetadata> {
+    // Parse and validate the frontmatter before saving anything
+    let (name, description, _body) = parse_skill_frontmatter_strict(content)?;
+
+    let id = generate_skill_id(&name);
 
     // Ensure skills directory exists
-    fs::create_dir_all(&skills_dir)
+    fs::create_dir_all(skills_dir)
         .map_err(|e| AppError::io(format!("Failed to create skills directory: {}", e)))?;
 
+    // Save the skill file
+    let skill_path = skills_dir.join(format!("{}.md", id));
+    fs::write(&skill_path, content)
+        .map_err(|e| AppError::io(format!("Failed to save skill file: {}", e)))?;
+
+    // Save metadata describing where the skill came from
+    let meta_path = skills_dir.join(format!("{}.meta.json", id));
+    fs::write(&meta_path, meta.to_string())
+        .map_err(|e| AppError::io(format!("Failed to save skill metadata: {}", e)))?;
+
+    Ok(SkillMetadata {
+        id,
+        name,
+        description,
+        source_url,
+    })
+}
+
+/// Install a skill from a local markdown file. Only plain paths to existing `.md`
+/// files are accepted; `..` components are rejected outright.
+fn install_skill_from_path(skills_dir: &std::path::Path, path: &std::path::Path) -> Result<SkillMetadata> {
+    if path.components().any(|c| matches!(c, std::path::Component::ParentDir)) {
+        return Err(AppError::validation("Skill file path must not contain '..'"));
+    }
+    let path = path
+        .canonicalize()
+        .map_err(|e| AppError::io(format!("Failed to resolve skill file {}: {}", path.display(), e)))?;
+    if !path.is_file() || path.extension().map(|e| e != "md").unwrap_or(true) {
+        return Err(AppError::validation(format!(
+            "Skill file must be a markdown (.md) file: {}",
+            path.display()
+        )));
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| AppError::io(format!("Failed to read skill file: {}", e)))?;
+    let meta = serde_json::json!({ "source_path": path.to_string_lossy() });
+    save_skill(skills_dir, &content, meta, None)
+}
+
+#[tauri::command]
+#[instrument(skip_all, fields(path = %path), err(Debug))]
+pub async fn install_skill_from_file(app: tauri::AppHandle, path: String) -> Result<SkillMetadata> {
+    let skills_dir = get_skills_dir_path(&app)?;
+    install_skill_from_path(&skills_dir, std::path::Path::new(&path))
+}
+
+/// Install a skill from pasted markdown. `source_label` is kept in the metadata file
+/// as a note of where the content came from.
+#[tauri::command]
+#[instrument(skip_all, fields(source_label = ?source_label), err(Debug))]
+pub async fn install_skill_from_content(
+    app: tauri::AppHandle,
+    content: String,
+    source_label: Option<String>,
+) -> Result<SkillMetadata> {
+    let skills_dir = get_skills_dir_path(&app)?;
+    let meta = serde_json::json!({ "source_label": source_label });
+    save_skill(&skills_dir, &content, meta, None)
+}
+
+/// Download a skill file, rejecting non-2xx responses and content without frontmatter
+async fn fetch_skill_content(url: &str) -> std::result::Result<String, String> {
+    let response = reqwest::get(url)
+        .await
+        .map_err(|e| format!("Failed to fetch {}: {}", url, e))?;
+    if !response.status().is_success() {
+        return Err(format!("HTTP {} for {}", response.status(), url));
+    }
+    let text = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read response from {}: {}", url, e))?;
+    if !text.trim().starts_with("---") {
+        return Err(format!(
+            "Content at {} is not a valid skill file (no YAML frontmatter)",
+            url
+        ));
+    }
+    Ok(text)
+}
+
+#[tauri::command]
+pub async fn install_skill_from_url(app: tauri::AppHandle, url: String) -> Result<SkillMetadata> {
+    let skills_dir = get_skills_dir_path(&app)?;
+
     // Get candidate URLs to try (for skills.sh URL transformation)
     let candidates = get_skill_url_candidates(&url);
 
@@ -1436,33 +2819,13 @@ pub async fn install_skill_from_url(app: tauri::AppHandle, url: String) -> Resul
     let mut successful_url = String::new();
 
     for candidate_url in &candidates {
-        match reqwest::get(candidate_url).await {
-            Ok(response) => {
-                if response.status().is_success() {
-                    match response.text().await {
-                        Ok(text) => {
-                            if text.trim().starts_with("---") {
-                                content = Some(text);
-                                successful_url = candidate_url.clone();
-                                break;
-                            } else {
-                                last_error = format!(
-                                    "Content at {} is not a valid skill file (no YAML frontmatter)",
-                                    candidate_url
-                                );
-                            }
-                        }
-                        Err(e) => {
-                            last_error = format!("Failed to read response from {}: {}", candidate_url, e);
-                        }
-                    }
-                } else {
-                    last_error = format!("HTTP {} for {}", response.status(), candidate_url);
-                }
-            }
-            Err(e) => {
-                last_error = format!("Failed to fetch {}: {}", candidate_url, e);
+        match fetch_skill_content(candidate_url).await {
+            Ok(text) => {
+                content = Some(text);
+                successful_url = candidate_url.clone();
+                break;
             }
+            Err(e) => last_error = e,
         }
     }
 
@@ -1478,39 +2841,12 @@ pub async fn install_skill_from_url(app: tauri::AppHandle, url: String) -> Resul
         }
     };
 
-    // Parse frontmatter to get name (content already validated to have frontmatter)
-    let (name, description, _body) = parse_skill_frontmatter(&content);
-
-    // Validate we got a proper name
-    if name == "Unnamed Skill" {
-        return Err(AppError::skill(
-            "Invalid skill file: could not parse name from frontmatter. \
-            The file should have YAML frontmatter with a 'name' field."
-        ));
-    }
-
-    let id = generate_skill_id(&name);
-
-    // Save the skill file
-    let skill_path = skills_dir.join(format!("{}.md", id));
-    fs::write(&skill_path, &content)
-        .map_err(|e| AppError::io(format!("Failed to save skill file: {}", e)))?;
-
-    // Save metadata with source URL
-    let meta_path = skills_dir.join(format!("{}.meta.json", id));
+    // Save with metadata recording the source URL
     let meta = serde_json::json!({
         "source_url": url,
         "fetch_url": successful_url
     });
-    fs::write(&meta_path, meta.to_string())
-        .map_err(|e| AppError::io(format!("Failed to save skill metadata: {}", e)))?;
-
-    Ok(SkillMetadata {
-        id,
-        name,
-        description,
-        source_url: Some(url),
-    })
+    save_skill(&skills_dir, &content, meta, Some(url))
 }
 
 #[tauri::command]
@@ -1571,6 +2907,17 @@ pub async fn update_skill(
     new_id: Option<String>,
 ) -> Result<SkillMetadata> {
     let skills_dir = get_skills_dir_path(&app)?;
+    update_skill_in(&skills_dir, &skill_id, &content, new_id)
+}
+
+/// Overwrite a skill's markdown, renaming its files (metadata included) when the
+/// id changes. Without `new_id` the id is derived from the frontmatter name.
+fn update_skill_in(
+    skills_dir: &std::path::Path,
+    skill_id: &str,
+    content: &str,
+    new_id: Option<String>,
+) -> Result<SkillMetadata> {
     let old_path = skills_dir.join(format!("{}.md", skill_id));
     let old_meta_path = skills_dir.join(format!("{}.meta.json", skill_id));
 
@@ -1579,7 +2926,7 @@ pub async fn update_skill(
     }
 
     // Parse the new content to get metadata
-    let (name, description, _body) = parse_skill_frontmatter(&content);
+    let (name, description, _body) = parse_skill_frontmatter(content);
 
     // Determine the new ID
     let final_id = new_id.unwrap_or_else(|| generate_skill_id(&name));
@@ -1594,14 +2941,8 @@ pub async fn update_skill(
     }
 
     // Read existing metadata
-    let source_url = if old_meta_path.exists() {
-        fs::read_to_string(&old_meta_path)
-            .ok()
-            .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
-            .and_then(|v| v["source_url"].as_str().map(|s| s.to_string()))
-    } else {
-        None
-    };
+    let source_url = read_skill_meta(skills_dir, skill_id)
+        .and_then(|v| v["source_url"].as_str().map(|s| s.to_string()));
 
     // If ID changed, we need to rename the files
     if final_id != skill_id {
@@ -1614,24 +2955,20 @@ pub async fn update_skill(
         }
 
         // Write to new path
-        fs::write(&new_path, &content)
+        fs::write(&new_path, content)
             .map_err(|e| AppError::io(format!("Failed to save skill file: {}", e)))?;
 
-        // Move metadata if exists
-        if let Some(ref url) = source_url {
-            let meta = serde_json::json!({ "source_url": url });
-            fs::write(&new_meta_path, meta.to_string())
+        // Move metadata as-is so the fetch URL survives a rename
+        if old_meta_path.exists() {
+            fs::rename(&old_meta_path, &new_meta_path)
                 .map_err(|e| AppError::io(format!("Failed to save skill metadata: {}", e)))?;
         }
 
-        // Delete old files
+        // Delete old file
         fs::remove_file(&old_path).ok();
-        if old_meta_path.exists() {
-            fs::remove_file(&old_meta_path).ok();
-        }
     } else {
         // Just update the content in place
-        fs::write(&old_path, &content)
+        fs::write(&old_path, content)
             .map_err(|e| AppError::io(format!("Failed to save skill file: {}", e)))?;
     }
 
@@ -1643,6 +2980,41 @@ pub async fn update_skill(
     })
 }
 
+/// Parsed `.meta.json` for a skill, if it has one
+fn read_skill_meta(skills_dir: &std::path::Path, skill_id: &str) -> Option<serde_json::Value> {
+    fs::read_to_string(skills_dir.join(format!("{}.meta.json", skill_id)))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+}
+
+/// The URL a skill was downloaded from, as recorded by `install_skill_from_url`
+fn skill_fetch_url(skills_dir: &std::path::Path, skill_id: &str) -> Result<String> {
+    read_skill_meta(skills_dir, skill_id)
+        .and_then(|v| v["fetch_url"].as_str().map(|s| s.to_string()))
+        .ok_or_else(|| AppError::skill(format!("Skill '{}' has no source URL to refresh from", skill_id)))
+}
+
+/// Replace a skill with freshly fetched `content`. The id is kept unless the
+/// frontmatter name changed, in which case it is re-derived like on install.
+fn refresh_skill_in(skills_dir: &std::path::Path, skill_id: &str, content: &str) -> Result<SkillMetadata> {
+    let (name, _description, _body) = parse_skill_frontmatter_strict(content)?;
+    let current = fs::read_to_string(skills_dir.join(format!("{}.md", skill_id)))
+        .map_err(|_| AppError::skill(format!("Skill '{}' not found", skill_id)))?;
+    let (current_name, _, _) = parse_skill_frontmatter(&current);
+    let new_id = (current_name == name).then(|| skill_id.to_string());
+    update_skill_in(skills_dir, skill_id, content, new_id)
+}
+
+/// Re-download a URL-installed skill from its stored fetch URL and overwrite it
+#[tauri::command]
+#[instrument(skip_all, fields(skill_id = %skill_id), err(Debug))]
+pub async fn refresh_skill(app: tauri::AppHandle, skill_id: String) -> Result<SkillMetadata> {
+    let skills_dir = get_skills_dir_path(&app)?;
+    let fetch_url = skill_fetch_url(&skills_dir, &skill_id)?;
+    let content = fetch_skill_content(&fetch_url).await.map_err(AppError::network)?;
+    refresh_skill_in(&skills_dir, &skill_id, &content)
+}
+
 // Worktree commands
 #[tauri::command]
 pub async fn list_worktrees(repo_path: String) -> Result<Vec<WorktreeInfo>> {
@@ -1692,32 +3064,46 @@ pub async fn list_stashes(repo_path: String) -> Result<Vec<StashEntry>> {
 
 #[tauri::command]
 #[instrument(skip_all, fields(message = ?message), err(Debug))]
-pub async fn create_stash(repo_path: String, message: Option<String>) -> Result<()> {
+pub async fn create_stash(
+    repo_path: String,
+    message: Option<String>,
+    options: Option<StashOptions>,
+) -> Result<()> {
     let mut repo = git::open_repo(&repo_path)?;
+    let options = options.unwrap_or_default();
 
     // Check if there are any changes to stash
     let status = git::get_status(&repo)?;
-    if status.staged.is_empty() && status.unstaged.is_empty() {
+    let has_untracked = options.include_untracked && !status.untracked.is_empty();
+    if status.staged.is_empty() && status.unstaged.is_empty() && !has_untracked {
         return Err(AppError::validation("No local changes to stash"));
     }
+    if options.staged_only && status.staged.is_empty() {
+        return Err(AppError::no_staged_changes("No staged changes to stash"));
+    }
 
-    git::create_stash(&mut repo, message.as_deref())?;
+    git::create_stash(&mut repo, message.as_deref(), &options)?;
     Ok(())
 }
 
+/// Replace conflict errors from applying a stash with an actionable message
+fn stash_apply_error(err: git::GitError, action: &str) -> AppError {
+    let err = AppError::from(err);
+    if err.kind() == ErrorKind::Conflict {
+        AppError::conflict(format!(
+            "Stash {} failed due to conflicts. Resolve conflicts in the affected files and stage them.",
+            action
+        ))
+    } else {
+        err
+    }
+}
+
 #[tauri::command]
 #[instrument(skip_all, fields(stash_index), err(Debug))]
 pub async fn apply_stash(repo_path: String, stash_index: usize) -> Result<()> {
     let mut repo = git::open_repo(&repo_path)?;
-    git::apply_stash(&mut repo, stash_index).map_err(|e| {
-        // Check if the error is due to conflicts
-        let err_msg = e.to_string();
-        if err_msg.contains("conflict") || err_msg.contains("CONFLICT") {
-            AppError::validation("Stash apply failed due to conflicts. Resolve conflicts in the affected files and stage them.")
-        } else {
-            AppError::from(e)
-        }
-    })?;
+    git::apply_stash(&mut repo, stash_index).map_err(|e| stash_apply_error(e, "apply"))?;
     Ok(())
 }
 
@@ -1725,26 +3111,65 @@ pub async fn apply_stash(repo_path: String, stash_index: usize) -> Result<()> {
 #[instrument(skip_all, fields(stash_index), err(Debug))]
 pub async fn pop_stash(repo_path: String, stash_index: usize) -> Result<()> {
     let mut repo = git::open_repo(&repo_path)?;
-    git::pop_stash(&mut repo, stash_index).map_err(|e| {
-        // Check if the error is due to conflicts
-        let err_msg = e.to_string();
-        if err_msg.contains("conflict") || err_msg.contains("CONFLICT") {
-            AppError::validation("Stash pop failed due to conflicts. Resolve conflicts in the affected files and stage them.")
-        } else {
-            AppError::from(e)
-        }
-    })?;
+    git::pop_stash(&mut repo, stash_index).map_err(|e| stash_apply_error(e, "pop"))?;
     Ok(())
 }
 
 #[tauri::command]
 #[instrument(skip_all, fields(stash_index), err(Debug))]
-pub async fn drop_stash(repo_path: String, stash_index: usize) -> Result<()> {
+pub async fn drop_stash(repo_path: String, stash_index: usize, expected_oid: Option<String>) -> Result<()> {
+    let mut repo = git::open_repo(&repo_path)?;
+    git::drop_stash(&mut repo, stash_index, expected_oid.as_deref())?;
+    Ok(())
+}
+
+#[tauri::command]
+#[instrument(skip_all, fields(stash_oid = %stash_oid), err(Debug))]
+pub async fn apply_stash_by_oid(repo_path: String, stash_oid: String) -> Result<()> {
+    let mut repo = git::open_repo(&repo_path)?;
+    git::apply_stash_by_oid(&mut repo, &stash_oid).map_err(|e| stash_apply_error(e, "apply"))?;
+    Ok(())
+}
+
+#[tauri::command]
+#[instrument(skip_all, fields(stash_oid = %stash_oid), err(Debug))]
+pub async fn pop_stash_by_oid(repo_path: String, stash_oid: String) -> Result<()> {
     let mut repo = git::open_repo(&repo_path)?;
-    git::drop_stash(&mut repo, stash_index)?;
+    git::pop_stash_by_oid(&mut repo, &stash_oid).map_err(|e| stash_apply_error(e, "pop"))?;
     Ok(())
 }
 
+#[tauri::command]
+#[instrument(skip_all, fields(stash_oid = %stash_oid), err(Debug))]
+pub async fn drop_stash_by_oid(repo_path: String, stash_oid: String) -> Result<()> {
+    let mut repo = git::open_repo(&repo_path)?;
+    git::drop_stash_by_oid(&mut repo, &stash_oid)?;
+    Ok(())
+}
+
+#[tauri::command]
+#[instrument(skip_all, fields(stash_index, branch_name = %branch_name), err(Debug))]
+pub async fn stash_branch(repo_path: String, stash_index: usize, branch_name: String) -> Result<String> {
+    Ok(git::stash_branch(&repo_path, stash_index, &branch_name)?)
+}
+
+#[tauri::command]
+#[instrument(skip_all, fields(stash_index), err(Debug))]
+pub async fn get_stash_diff(
+    repo_path: String,
+    stash_index: usize,
+    max_patch_bytes: Option<usize>,
+    ignore_whitespace: Option<git::WhitespaceMode>,
+) -> Result<UnifiedDiff> {
+    let repo = git::open_repo(&repo_path)?;
+    Ok(git::get_stash_diff_capped(
+        &repo,
+        stash_index,
+        max_patch_bytes,
+        ignore_whitespace.unwrap_or_default(),
+    )?)
+}
+
 // Reflog command
 #[tauri::command]
 #[instrument(skip_all, fields(limit), err(Debug))]
@@ -1757,13 +3182,40 @@ pub async fn get_reflog(repo_path: String, limit: usize) -> Result<Vec<ReflogEnt
     .map_err(|e| AppError::io(format!("Task join error: {}", e)))?
 }
 
-#[tauri::command]
-#[instrument(skip_all, err(Debug))]
-pub async fn get_ahead_behind(repo_path: String) -> Result<Option<AheadBehind>> {
-    let repo = git::open_repo(&repo_path)?;
-    Ok(git::get_ahead_behind(&repo)?)
-}
-
+#[tauri::command]
+#[instrument(skip_all, err(Debug))]
+pub async fn get_ahead_behind(repo_path: String) -> Result<Option<AheadBehind>> {
+    let repo = git::open_repo(&repo_path)?;
+    Ok(git::get_ahead_behind(&repo)?)
+}
+
+#[tauri::command]
+#[instrument(skip_all, fields(ref_a = %ref_a, ref_b = %ref_b), err(Debug))]
+pub async fn get_merge_base(repo_path: String, ref_a: String, ref_b: String) -> Result<Option<CommitInfo>> {
+    let repo = git::open_repo(&repo_path)?;
+    Ok(git::get_merge_base(&repo, &ref_a, &ref_b)?)
+}
+
+// "What changed since I last looked" commands
+#[tauri::command]
+#[instrument(skip_all, err(Debug))]
+pub async fn set_seen_marker(repo_path: String) -> Result<SeenMarker> {
+    let repo = git::open_repo(&repo_path)?;
+    Ok(git::set_seen_marker(&repo)?)
+}
+
+#[tauri::command]
+#[instrument(skip_all, err(Debug))]
+pub async fn get_changes_since_marker(repo_path: String) -> Result<ChangesSinceMarker> {
+    // Run blocking git operation on dedicated thread pool
+    tokio::task::spawn_blocking(move || {
+        let repo = git::open_repo(&repo_path)?;
+        Ok(git::get_changes_since_marker(&repo)?)
+    })
+    .await
+    .map_err(|e| AppError::io(format!("Task join error: {}", e)))?
+}
+
 // Merge conflict commands
 #[tauri::command]
 #[instrument(skip_all, err(Debug))]
@@ -1777,6 +3229,21 @@ pub async fn parse_file_conflicts(repo_path: String, file_path: String) -> Resul
     Ok(git::parse_file_conflicts(&repo_path, &file_path)?)
 }
 
+#[tauri::command]
+#[instrument(skip_all, fields(file_path = %file_path), err(Debug))]
+pub async fn get_conflict_diffs(repo_path: String, file_path: String) -> Result<git::ConflictDiffs> {
+    let repo = git::open_repo(&repo_path)?;
+    Ok(git::get_conflict_diffs(&repo, &file_path)?)
+}
+
+/// Base/ours/theirs of a conflicted file, read from the index stages
+#[tauri::command]
+#[instrument(skip_all, fields(file_path = %file_path), err(Debug))]
+pub async fn get_conflict_versions(repo_path: String, file_path: String) -> Result<git::ConflictVersions> {
+    let repo = git::open_repo(&repo_path)?;
+    Ok(git::get_conflict_versions(&repo, &file_path)?)
+}
+
 #[tauri::command]
 pub async fn save_resolved_file(repo_path: String, file_path: String, content: String) -> Result<()> {
     Ok(git::save_resolved_file(&repo_path, &file_path, &content)?)
@@ -1788,19 +3255,91 @@ pub async fn mark_file_resolved(repo_path: String, file_path: String) -> Result<
     Ok(git::mark_file_resolved(&repo, &file_path)?)
 }
 
+#[tauri::command]
+#[instrument(skip_all, fields(file_path = %file_path), err(Debug))]
+pub async fn resolve_conflict_ours(repo_path: String, file_path: String) -> Result<()> {
+    Ok(git::resolve_conflict_ours(&repo_path, &file_path)?)
+}
+
+#[tauri::command]
+#[instrument(skip_all, fields(file_path = %file_path), err(Debug))]
+pub async fn resolve_conflict_theirs(repo_path: String, file_path: String) -> Result<()> {
+    Ok(git::resolve_conflict_theirs(&repo_path, &file_path)?)
+}
+
+/// Resolve every conflicting file in favor of one side; returns the resolved paths
+#[tauri::command]
+#[instrument(skip_all, fields(strategy = ?strategy), err(Debug))]
+pub async fn resolve_all_conflicts(repo_path: String, strategy: git::ConflictSide) -> Result<Vec<String>> {
+    tokio::task::spawn_blocking(move || Ok(git::resolve_all_conflicts(&repo_path, strategy)?))
+        .await
+        .map_err(|e| AppError::io(format!("Task join error: {}", e)))?
+}
+
 #[tauri::command]
 pub async fn abort_merge(repo_path: String) -> Result<String> {
     Ok(git::abort_merge(&repo_path)?)
 }
 
+#[tauri::command]
+#[instrument(skip_all, err(Debug))]
+pub async fn abort_cherry_pick(repo_path: String) -> Result<String> {
+    Ok(git::abort_cherry_pick(&repo_path)?)
+}
+
+#[tauri::command]
+#[instrument(skip_all, err(Debug))]
+pub async fn abort_revert(repo_path: String) -> Result<String> {
+    Ok(git::abort_revert(&repo_path)?)
+}
+
+#[tauri::command]
+pub async fn get_repository_state(repo_path: String) -> Result<String> {
+    let repo = git::open_repo(&repo_path)?;
+    Ok(git::get_repository_state(&repo))
+}
+
 #[tauri::command]
 pub async fn continue_merge(repo_path: String) -> Result<String> {
     Ok(git::continue_merge(&repo_path)?)
 }
 
+/// Reject flag combinations git would refuse, and strategy options that could
+/// be read as extra arguments
+fn validate_merge_options(options: &git::MergeOptions) -> Result<()> {
+    if options.ff_only && options.no_ff {
+        return Err(AppError::validation("Fast-forward only and no fast-forward can't be combined"));
+    }
+    if options.squash && options.no_ff {
+        return Err(AppError::validation("Squash and no fast-forward can't be combined"));
+    }
+    if let Some(option) = &options.strategy_option {
+        if option.is_empty() || option.starts_with('-') || option.chars().any(char::is_whitespace) {
+            return Err(AppError::validation(format!("Invalid merge strategy option: {:?}", option)));
+        }
+    }
+    Ok(())
+}
+
+#[tauri::command]
+#[instrument(skip_all, fields(branch_name = %branch_name, options = ?options), err(Debug))]
+pub async fn merge_branch(
+    repo_path: String,
+    branch_name: String,
+    options: Option<git::MergeOptions>,
+) -> Result<String> {
+    let options = options.unwrap_or_default();
+    validate_merge_options(&options)?;
+    Ok(git::merge_branch(&repo_path, &branch_name, &options)?)
+}
+
+/// Trial-merge a branch and report the files it would change or conflict on, then abort
 #[tauri::command]
-pub async fn merge_branch(repo_path: String, branch_name: String) -> Result<String> {
-    Ok(git::merge_branch(&repo_path, &branch_name)?)
+#[instrument(skip_all, fields(branch_name = %branch_name), err(Debug))]
+pub async fn preview_merge(repo_path: String, branch_name: String) -> Result<git::MergePreview> {
+    tokio::task::spawn_blocking(move || Ok(git::preview_merge(&repo_path, &branch_name)?))
+        .await
+        .map_err(|e| AppError::io(format!("Task join error: {}", e)))?
 }
 
 // Rebase commands
@@ -1813,8 +3352,8 @@ pub async fn get_rebase_status(repo_path: String) -> Result<RebaseStatus> {
 
 #[tauri::command]
 #[instrument(skip_all, fields(onto_ref = %onto_ref), err(Debug))]
-pub async fn rebase_onto(repo_path: String, onto_ref: String) -> Result<String> {
-    Ok(git::rebase_onto(&repo_path, &onto_ref)?)
+pub async fn rebase_onto(repo_path: String, onto_ref: String, autostash: Option<bool>) -> Result<String> {
+    Ok(git::rebase_onto(&repo_path, &onto_ref, autostash.unwrap_or(false))?)
 }
 
 #[tauri::command]
@@ -1825,8 +3364,8 @@ pub async fn continue_rebase(repo_path: String) -> Result<String> {
 
 #[tauri::command]
 #[instrument(skip_all, err(Debug))]
-pub async fn abort_rebase(repo_path: String) -> Result<String> {
-    Ok(git::abort_rebase(&repo_path)?)
+pub async fn abort_rebase(repo_path: String, force: Option<bool>) -> Result<String> {
+    Ok(git::abort_rebase(&repo_path, force.unwrap_or(false))?)
 }
 
 #[tauri::command]
@@ -1885,80 +3424,35 @@ pub async fn continue_interactive_rebase(
     .map_err(AppError::from)
 }
 
+#[tauri::command]
+#[instrument(skip_all, err(Debug))]
+pub async fn split_commit_reset(repo_path: String) -> Result<String> {
+    tokio::task::spawn_blocking(move || git::split_commit_reset(&repo_path))
+        .await
+        .map_err(|e| AppError::io(format!("Task join error: {}", e)))?
+        .map_err(AppError::from)
+}
+
 #[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct AIResolveConflictResponse {
     pub resolved: String,
     pub explanation: String,
+    /// True when the resolution was written to the file and staged
+    pub applied: bool,
+    /// The conflict regions in the file on disk that the resolution replaces; empty
+    /// without a `repo_path`
+    pub resolved_regions: Vec<git::ConflictRegion>,
 }
 
-#[tauri::command]
-pub async fn ai_resolve_conflict(
-    file_path: String,
-    ours_content: String,
-    theirs_content: String,
-    instructions: Option<String>,
-) -> Result<AIResolveConflictResponse> {
-    let instructions_text = instructions.unwrap_or_default();
-    
-    let prompt = format!(
-        r#"You are resolving a Git merge conflict.
-
-File: {file_path}
-
-## Current Branch (Ours)
-```
-{ours_content}
-```
-
-## Incoming Branch (Theirs)
-```
-{theirs_content}
-```
-
-{instructions_section}
-
-Analyze both versions and produce a merged result that:
-1. Preserves all intended functionality from both branches
-2. Resolves any conflicts logically
-3. Maintains code style consistency
-
-Respond ONLY with valid JSON in this exact format (no markdown, no code blocks, just raw JSON):
-{{"resolved": "the merged code here", "explanation": "brief explanation of how you resolved the conflict"}}"#,
-        file_path = file_path,
-        ours_content = ours_content,
-        theirs_content = theirs_content,
-        instructions_section = if instructions_text.is_empty() {
-            String::new()
-        } else {
-            format!("## User Instructions\n{}\n", instructions_text)
-        }
-    );
-
-    // Call claude CLI
-    let claude_path = find_claude_binary()?;
-    let output = Command::new(&claude_path)
-        .args(["-p", &prompt])
-        .output()
-        .map_err(|e| AppError::ai(format!("Failed to run claude at {:?}: {}", claude_path, e)))?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(AppError::ai(format!("Claude failed: {}", stderr)));
-    }
-
-    let response = String::from_utf8_lossy(&output.stdout).trim().to_string();
-
-    if response.is_empty() {
-        return Err(AppError::ai("Claude returned an empty response"));
-    }
+/// Run the conflict prompt on `backend`; the reply must carry a `resolved` field
+fn request_conflict_resolution(backend: &dyn AiBackend, prompt: &str) -> Result<AIResolveConflictResponse> {
+    let response = backend.run_prompt(prompt)?;
 
     // Extract JSON from response
-    let json_str = extract_json_object(&response)
+    let json = extract_json_value(&response)
         .ok_or_else(|| AppError::parse(format!("Could not find valid JSON in response: {}", response)))?;
 
-    let json: serde_json::Value = serde_json::from_str(json_str)
-        .map_err(|e| AppError::parse(format!("Failed to parse AI response as JSON: {}. JSON was: {}", e, json_str)))?;
-
     let resolved = json["resolved"]
         .as_str()
         .ok_or_else(|| AppError::parse("Invalid response: missing 'resolved' field"))?
@@ -1972,9 +3466,100 @@ Respond ONLY with valid JSON in this exact format (no markdown, no code blocks,
     Ok(AIResolveConflictResponse {
         resolved,
         explanation,
+        applied: false,
+        resolved_regions: Vec::new(),
     })
 }
 
+/// Fill the conflict prompt template. The base and instructions sections are optional
+/// and render as nothing when absent; a base for a template without `{base}` is appended.
+fn build_conflict_prompt(
+    template: &str,
+    file_path: &str,
+    ours: &str,
+    theirs: &str,
+    base: Option<&str>,
+    instructions: Option<&str>,
+) -> String {
+    let base_section = match base {
+        Some(base) => format!("## Common Ancestor (Base)\n```\n{}\n```\n", base),
+        None => String::new(),
+    };
+    let instructions_section = match instructions.filter(|text| !text.is_empty()) {
+        Some(text) => format!("## User Instructions\n{}\n", text),
+        None => String::new(),
+    };
+
+    let mut prompt = render_prompt_template(
+        template,
+        &[
+            ("file_path", file_path),
+            ("ours", ours),
+            ("base", &base_section),
+            ("theirs", theirs),
+            ("instructions", &instructions_section),
+        ],
+    );
+    if !base_section.is_empty() && !template.contains("{base}") {
+        prompt.push_str("\n\n");
+        prompt.push_str(&base_section);
+    }
+    prompt
+}
+
+/// Ask the AI backend to resolve a conflict. `base_content` is the common ancestor, which
+/// lets the model see what each side changed. With `auto_apply` the resolution is written
+/// to `file_path` in `repo_path` and staged.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn ai_resolve_conflict(
+    app: tauri::AppHandle,
+    file_path: String,
+    ours_content: String,
+    theirs_content: String,
+    base_content: Option<String>,
+    instructions: Option<String>,
+    backend: Option<AiBackendKind>,
+    repo_path: Option<String>,
+    auto_apply: Option<bool>,
+) -> Result<AIResolveConflictResponse> {
+    let auto_apply = auto_apply.unwrap_or(false);
+    if auto_apply && repo_path.is_none() {
+        return Err(AppError::validation("auto_apply requires repo_path"));
+    }
+    let regions = match &repo_path {
+        Some(repo_path) => {
+            if auto_apply {
+                // Fail before spending an AI request on a file we can't apply to
+                git::ensure_file_conflicted(&git::open_repo(repo_path)?, &file_path)?;
+            }
+            // Read before any auto-apply overwrites the markers
+            git::parse_file_conflicts(repo_path, &file_path)
+                .map(|info| info.conflicts)
+                .unwrap_or_default()
+        }
+        None => Vec::new(),
+    };
+
+    let template = load_app_settings(&app).prompts.resolve(PromptKind::Conflict);
+    let prompt = build_conflict_prompt(
+        &template,
+        &file_path,
+        &ours_content,
+        &theirs_content,
+        base_content.as_deref(),
+        instructions.as_deref(),
+    );
+
+    let mut response = request_conflict_resolution(backend.unwrap_or_default().backend().as_ref(), &prompt)?;
+    response.resolved_regions = regions;
+    if let (true, Some(repo_path)) = (auto_apply, &repo_path) {
+        git::apply_conflict_resolution(repo_path, &file_path, &response.resolved)?;
+        response.applied = true;
+    }
+    Ok(response)
+}
+
 // =============================================================================
 // File Watcher Commands
 // =============================================================================
@@ -2225,15 +3810,50 @@ fn save_section(
     }
 }
 
-/// Run CodeRabbit CLI for working changes (staged + unstaged)
-fn run_coderabbit_review(repo_path: &str) -> Result<ReviewResult> {
+/// Message for commit/compare reviews when the installed CLI can't take a patch file
+const CODERABBIT_PATCH_UNSUPPORTED: &str = "This CodeRabbit CLI version only supports working changes. Update it, or select a different reviewer to review commits or compare diffs.";
+
+/// Run CodeRabbit CLI for working changes (staged + unstaged), or on `patch`
+/// (a commit or compare diff) when given
+fn run_coderabbit_review(repo_path: &str, patch: Option<&str>) -> Result<ReviewResult> {
     let cr_path = find_coderabbit_binary()?;
+    run_coderabbit_review_with(&cr_path, repo_path, patch)
+}
 
+fn run_coderabbit_review_with(
+    cr_path: &std::path::Path,
+    repo_path: &str,
+    patch: Option<&str>,
+) -> Result<ReviewResult> {
     // Use --plain for structured text output that we can parse
-    let output = Command::new(&cr_path)
-        .args(["--plain", "--no-color", "--type", "uncommitted"])
-        .current_dir(repo_path)
-        .output()
+    let mut cmd = Command::new(cr_path);
+    cmd.args(["--plain", "--no-color"]).current_dir(repo_path);
+
+    // The CLI only diffs the working tree itself; committed ranges go in as a patch file
+    let patch_file = match patch {
+        Some(patch) => {
+            let nanos = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.subsec_nanos())
+                .unwrap_or(0);
+            let path = std::env::temp_dir()
+                .join(format!("diffy-coderabbit-{}-{}.patch", std::process::id(), nanos));
+            fs::write(&path, patch)
+                .map_err(|e| AppError::io(format!("Failed to write patch file: {}", e)))?;
+            cmd.args(["--type", "patch", "--diff"]).arg(&path);
+            Some(path)
+        }
+        None => {
+            cmd.args(["--type", "uncommitted"]);
+            None
+        }
+    };
+
+    let output = cmd.output();
+    if let Some(path) = &patch_file {
+        let _ = fs::remove_file(path);
+    }
+    let output = output
         .map_err(|e| {
             if e.kind() == std::io::ErrorKind::NotFound {
                 AppError::ai(format!(
@@ -2257,6 +3877,9 @@ fn run_coderabbit_review(repo_path: &str) -> Result<ReviewResult> {
         } else {
             error_msg.push_str(". The CLI may not be properly configured. Run 'coderabbit auth login' to authenticate.");
         }
+        if patch_file.is_some() && is_unknown_cli_option(&format!("{}\n{}", stderr, stdout)) {
+            return Err(AppError::validation(CODERABBIT_PATCH_UNSUPPORTED));
+        }
         return Err(AppError::ai(error_msg));
     }
 
@@ -2283,11 +3906,21 @@ fn run_coderabbit_review(repo_path: &str) -> Result<ReviewResult> {
     })
 }
 
+/// Whether CLI output looks like an argument parser rejecting an option
+fn is_unknown_cli_option(output: &str) -> bool {
+    let output = output.to_lowercase();
+    ["unknown option", "unknown argument", "unrecognized option", "unexpected argument", "invalid value"]
+        .iter()
+        .any(|needle| output.contains(needle))
+}
+
 /// Run Claude CLI for structured review (reuses existing logic)
 /// 
-/// Takes an optional skills_dir path instead of AppHandle to allow running in spawn_blocking
+/// Takes an optional skills_dir path and a resolved prompt template instead of AppHandle
+/// to allow running in spawn_blocking
 fn run_claude_review(
     skills_dir: Option<PathBuf>,
+    review_template: &str,
     repo_path: &str,
     commit_id: Option<&str>,
     skill_ids: Option<&[String]>,
@@ -2295,33 +3928,10 @@ fn run_claude_review(
     head_ref: Option<&str>,
 ) -> Result<ReviewResult> {
     let repo = git::open_repo(repo_path)?;
-
-    // Get diff based on review type: compare refs, commit, or working changes
-    let diff_patch = if let (Some(base), Some(head)) = (base_ref, head_ref) {
-        // Compare diff between two refs
-        let diff = git::get_compare_diff(&repo, base, head)?;
-        diff.patch
-    } else if let Some(cid) = commit_id {
-        let diff = git::get_commit_diff(&repo, cid)?;
-        diff.patch
-    } else {
-        // Get combined staged and unstaged diff for working changes
-        let staged = git::get_working_diff(&repo, true)?;
-        let unstaged = git::get_working_diff(&repo, false)?;
-        format!("{}\n{}", staged.patch, unstaged.patch)
-    };
-
-    if diff_patch.trim().is_empty() {
-        return Err(AppError::validation("No changes to review"));
-    }
+    let diff_patch = review_diff_patch(&repo, commit_id, base_ref, head_ref, None)?;
 
     // Truncate diff if too long
-    let max_diff_len = 12000;
-    let truncated_diff = if diff_patch.len() > max_diff_len {
-        format!("{}...\n[diff truncated]", &diff_patch[..max_diff_len])
-    } else {
-        diff_patch
-    };
+    let truncated_diff = diff_for_prompt(&diff_patch, REVIEW_MAX_DIFF_LEN);
 
     // Load skill content if skills provided
     let skills_context = if let (Some(ids), Some(dir)) = (skill_ids, skills_dir) {
@@ -2340,144 +3950,16 @@ fn run_claude_review(
         String::new()
     };
 
-    let prompt = format!(
-        r#"You are an expert code reviewer. Your goal is to provide actionable feedback.{skills_context}
-
-Analyze this git diff and provide a PR-style review with specific, actionable issues.
-
-For each issue you find, explain:
-- **problem**: What is wrong or concerning
-- **why**: Why it matters (impact on correctness, security, performance, maintainability)
-- **suggestion**: A concrete fix or improvement
-
-Categories: logic_bugs, edge_cases, security, performance, accidental_code, other
-Severities: low, medium, high, critical
-
-Respond ONLY with valid JSON (no markdown, no code blocks):
-{{
-  "overview": "1-2 sentence summary of the changes",
-  "issues": [
-    {{
-      "id": "issue-1",
-      "category": "logic_bugs",
-      "severity": "high",
-      "title": "brief title",
-      "problem": "what is wrong",
-      "why": "why it matters",
-      "suggestion": "how to fix it",
-      "filePath": "path/to/file.ts"
-    }}
-  ]
-}}
-
-If there are no issues, use an empty array for "issues".
-
-Diff to review:
-{diff}"#,
-        skills_context = skills_context,
-        diff = truncated_diff
+    let prompt = render_prompt_template(
+        review_template,
+        &[("skills", &skills_context), ("diff", &truncated_diff)],
     );
 
-    // Call claude CLI
-    let claude_path = find_claude_binary()?;
-    let output = Command::new(&claude_path)
-        .args(["-p", &prompt])
-        .output()
-        .map_err(|e| AppError::ai(format!("Failed to run claude at {:?}: {}", claude_path, e)))?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(AppError::ai(format!("Claude failed: {}", stderr)));
-    }
-
-    let response = String::from_utf8_lossy(&output.stdout).trim().to_string();
-
-    if response.is_empty() {
-        return Err(AppError::ai("Claude returned an empty response"));
-    }
-
-    // Try to extract JSON from the response
-    let json_str = extract_json_object(&response)
-        .ok_or_else(|| AppError::parse(format!("Could not find valid JSON in response: {}", response)))?;
-
-    let json: serde_json::Value = serde_json::from_str(json_str)
-        .map_err(|e| AppError::parse(format!("Failed to parse AI response as JSON: {}. JSON was: {}", e, json_str)))?;
-
-    let overview = json["overview"]
-        .as_str()
-        .unwrap_or("Unable to generate overview")
-        .to_string();
-
-    // Parse issues with graceful defaulting
-    let issues: Vec<AIReviewIssue> = json["issues"]
-        .as_array()
-        .map(|arr| {
-            arr.iter()
-                .enumerate()
-                .filter_map(|(idx, issue)| {
-                    let title = issue["title"].as_str()
-                        .or_else(|| issue["problem"].as_str().map(|p| &p[..p.len().min(50)]))
-                        .map(|s| s.to_string())?;
-
-                    let id = issue["id"].as_str()
-                        .map(|s| s.to_string())
-                        .unwrap_or_else(|| format!("issue-{}", idx + 1));
-
-                    let category = issue["category"].as_str()
-                        .map(normalize_category)
-                        .unwrap_or_else(|| "other".to_string());
-
-                    let severity = issue["severity"].as_str()
-                        .map(normalize_severity)
-                        .unwrap_or_else(|| "medium".to_string());
-
-                    let problem = issue["problem"].as_str()
-                        .or_else(|| issue["description"].as_str())
-                        .unwrap_or("")
-                        .to_string();
-
-                    let why = issue["why"].as_str()
-                        .or_else(|| issue["explanation"].as_str())
-                        .unwrap_or("")
-                        .to_string();
-
-                    let suggestion = issue["suggestion"].as_str()
-                        .or_else(|| issue["fix"].as_str())
-                        .unwrap_or("")
-                        .to_string();
-
-                    let file_path = issue["filePath"].as_str()
-                        .or_else(|| issue["file_path"].as_str())
-                        .or_else(|| issue["file"].as_str())
-                        .map(|s| s.to_string());
-
-                    Some(AIReviewIssue {
-                        id,
-                        category,
-                        severity,
-                        title,
-                        problem,
-                        why,
-                        suggestion,
-                        file_path,
-                    })
-                })
-                .collect()
-        })
-        .unwrap_or_default();
-
-    let generated_at = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .map(|d| d.as_secs())
-        .unwrap_or(0);
+    let data = request_review(&ClaudeCli, &prompt)?;
 
     Ok(ReviewResult::Structured {
         provider_id: "claude-cli".to_string(),
-        data: AIReviewData {
-            overview,
-            issues,
-            generated_at,
-        },
+        data,
     })
 }
 
@@ -2492,15 +3974,9 @@ pub async fn generate_review(
     base_ref: Option<String>,
     head_ref: Option<String>,
 ) -> Result<ReviewResult> {
-    // CodeRabbit v1 only supports working changes - check before spawning
-    if reviewer_id == ReviewerId::CoderabbitCli && (commit_id.is_some() || base_ref.is_some()) {
-        return Err(AppError::validation(
-            "CodeRabbit CLI currently supports working changes only. Select a different reviewer to review commits or compare diffs."
-        ));
-    }
-
-    // Extract skills_dir before spawning (AppHandle is not Send)
+    // Extract skills_dir and prompt template before spawning (AppHandle is not Send)
     let skills_dir = get_skills_dir_path(&app).ok();
+    let review_template = load_app_settings(&app).prompts.resolve(PromptKind::Review);
 
     // Run blocking CLI operations on dedicated thread pool
     tokio::task::spawn_blocking(move || {
@@ -2508,6 +3984,7 @@ pub async fn generate_review(
             ReviewerId::ClaudeCli => {
                 run_claude_review(
                     skills_dir,
+                    &review_template,
                     &repo_path,
                     commit_id.as_deref(),
                     skill_ids.as_deref(),
@@ -2516,7 +3993,20 @@ pub async fn generate_review(
                 )
             }
             ReviewerId::CoderabbitCli => {
-                run_coderabbit_review(&repo_path)
+                // Commits and compare ranges are reviewed from their patch
+                let patch = if commit_id.is_some() || base_ref.is_some() {
+                    let repo = git::open_repo(&repo_path)?;
+                    Some(review_diff_patch(
+                        &repo,
+                        commit_id.as_deref(),
+                        base_ref.as_deref(),
+                        head_ref.as_deref(),
+                        None,
+                    )?)
+                } else {
+                    None
+                };
+                run_coderabbit_review(&repo_path, patch.as_deref())
             }
         }
     })
@@ -2534,24 +4024,112 @@ pub struct ContributorReviewData {
     pub generated_at: i64,
 }
 
+/// Review input. With `repo_path` and `since` set, the stats and summaries are
+/// computed here from the repository and the pre-aggregated fields may be omitted.
 #[derive(serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ContributorReviewRequest {
+    #[serde(default)]
     pub contributor_name: String,
     pub contributor_email: String,
     pub time_range_label: String,
+    #[serde(default)]
     pub commit_summaries: Vec<String>,
+    #[serde(default)]
     pub total_commits: usize,
+    #[serde(default)]
     pub total_files_changed: usize,
+    #[serde(default)]
     pub total_additions: usize,
+    #[serde(default)]
     pub total_deletions: usize,
+    #[serde(default)]
+    pub repo_path: Option<String>,
+    #[serde(default)]
+    pub since: Option<i64>,
+    /// End of the range (unix seconds); defaults to now
+    #[serde(default)]
+    pub until: Option<i64>,
+}
+
+/// Aggregate one author's commits across all local branches in a time range
+#[tauri::command]
+#[instrument(skip_all, fields(repo_path = %repo_path, email = %email), err(Debug))]
+pub async fn get_contributor_stats(
+    repo_path: String,
+    email: String,
+    since: i64,
+    until: Option<i64>,
+) -> Result<git::ContributorStats> {
+    tokio::task::spawn_blocking(move || {
+        let repo = git::open_repo(&repo_path)?;
+        Ok(git::get_contributor_stats(&repo, &email, since, until.unwrap_or(i64::MAX))?)
+    })
+    .await
+    .map_err(|e| AppError::io(format!("Task join error: {}", e)))?
+}
+
+/// Stats for every author across all local branches in a time range, most commits first
+#[tauri::command]
+#[instrument(skip_all, fields(repo_path = %repo_path), err(Debug))]
+pub async fn get_contributor_leaderboard(
+    repo_path: String,
+    since: i64,
+    until: Option<i64>,
+) -> Result<Vec<git::ContributorStats>> {
+    tokio::task::spawn_blocking(move || {
+        let repo = git::open_repo(&repo_path)?;
+        Ok(git::get_contributor_leaderboard(&repo, since, until.unwrap_or(i64::MAX))?)
+    })
+    .await
+    .map_err(|e| AppError::io(format!("Task join error: {}", e)))?
+}
+
+/// The most frequently changed files in a time range (default top 20)
+#[tauri::command]
+#[instrument(skip_all, fields(repo_path = %repo_path), err(Debug))]
+pub async fn get_file_hotspots(
+    repo_path: String,
+    since: i64,
+    until: Option<i64>,
+    limit: Option<usize>,
+) -> Result<Vec<git::FileHotspot>> {
+    tokio::task::spawn_blocking(move || {
+        let repo = git::open_repo(&repo_path)?;
+        Ok(git::get_file_hotspots(&repo, since, until.unwrap_or(i64::MAX), limit.unwrap_or(20))?)
+    })
+    .await
+    .map_err(|e| AppError::io(format!("Task join error: {}", e)))?
+}
+
+#[tauri::command]
+#[instrument(skip_all, fields(repo_path = %repo_path), err(Debug))]
+pub async fn get_repo_summary(repo_path: String) -> Result<git::RepoSummary> {
+    tokio::task::spawn_blocking(move || {
+        let repo = git::open_repo(&repo_path)?;
+        Ok(git::get_repo_summary(&repo)?)
+    })
+    .await
+    .map_err(|e| AppError::io(format!("Task join error: {}", e)))?
 }
 
 #[tauri::command]
 #[instrument(skip_all, fields(contributor = %request.contributor_email, commits = request.total_commits), err(Debug))]
 pub async fn generate_contributor_review(
-    request: ContributorReviewRequest,
+    mut request: ContributorReviewRequest,
 ) -> Result<ContributorReviewData> {
+    if let (Some(repo_path), Some(since)) = (request.repo_path.clone(), request.since) {
+        let stats = get_contributor_stats(repo_path, request.contributor_email.clone(), since, request.until).await?;
+        if request.contributor_name.is_empty() {
+            request.contributor_name = stats.author_name;
+        }
+        request.commit_summaries = stats.commit_summaries;
+        request.total_commits = stats.total_commits;
+        request.total_files_changed = stats.total_files_changed;
+        request.total_additions = stats.total_additions;
+        request.total_deletions = stats.total_deletions;
+    }
+
     if request.commit_summaries.is_empty() {
         return Err(AppError::validation("No commits to review"));
     }
@@ -2612,23 +4190,11 @@ Be constructive and professional. Focus on the work, not the person."#,
         truncation = truncation_note,
     );
 
-    // Call claude CLI
-    let claude_path = find_claude_binary()?;
-    let output = Command::new(&claude_path)
-        .args(["-p", &prompt])
-        .output()
-        .map_err(|e| AppError::ai(format!("Failed to run claude at {:?}: {}", claude_path, e)))?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(AppError::ai(format!("Claude failed: {}", stderr)));
-    }
-
-    let response = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let response = ClaudeCli.run_prompt(&prompt)?;
 
     // Parse the JSON response
-    let json: serde_json::Value = serde_json::from_str(&response)
-        .map_err(|e| AppError::parse(format!("Failed to parse AI response as JSON: {}. Response was: {}", e, response)))?;
+    let json = extract_json_value(&response)
+        .ok_or_else(|| AppError::parse(format!("Could not find valid JSON in response: {}", response)))?;
 
     let grade = json["grade"]
         .as_str()
@@ -2681,83 +4247,147 @@ pub async fn stop_watching(state: tauri::State<'_, WatcherState>) -> Result<()>
         .map_err(|e| AppError::io(format!("Failed to stop watcher: {}", e)))
 }
 
-/// Generate a Mermaid sequence diagram from working changes using Claude CLI
-#[tauri::command]
-#[instrument(skip_all, fields(repo_path = %repo_path), err(Debug))]
-pub async fn generate_diagram(repo_path: String) -> Result<String> {
-    tokio::task::spawn_blocking(move || {
-        let repo = git::open_repo(&repo_path)?;
-
-        // Get both staged and unstaged changes
-        let staged_diff = git::get_working_diff(&repo, true)?;
-        let unstaged_diff = git::get_working_diff(&repo, false)?;
-
-        let combined_patch = format!(
-            "=== STAGED CHANGES ===\n{}\n\n=== UNSTAGED CHANGES ===\n{}",
-            staged_diff.patch, unstaged_diff.patch
-        );
+/// Mermaid diagram kind to generate; `Auto` lets the model choose
+#[derive(serde::Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum DiagramType {
+    #[default]
+    Auto,
+    Sequence,
+    Flowchart,
+    ClassDiagram,
+    StateDiagram,
+    EntityRelationship,
+}
 
-        if combined_patch.trim().is_empty() || 
-           (staged_diff.patch.is_empty() && unstaged_diff.patch.is_empty()) {
-            return Err(AppError::validation("No changes to analyze"));
+impl DiagramType {
+    fn label(self) -> &'static str {
+        match self {
+            DiagramType::Auto => "diagram",
+            DiagramType::Sequence => "sequence diagram",
+            DiagramType::Flowchart => "flowchart",
+            DiagramType::ClassDiagram => "class diagram",
+            DiagramType::StateDiagram => "state diagram",
+            DiagramType::EntityRelationship => "entity relationship diagram",
         }
+    }
 
-        // Truncate if too long (keep first ~50k chars)
-        let truncated_diff = if combined_patch.len() > 50000 {
-            format!(
-                "{}\n\n... (truncated, {} more characters)",
-                &combined_patch[..50000],
-                combined_patch.len() - 50000
-            )
-        } else {
-            combined_patch
-        };
+    /// Keywords a diagram of this type may start with; the first is the preferred one
+    fn keywords(self) -> &'static [&'static str] {
+        match self {
+            DiagramType::Auto => &[],
+            DiagramType::Sequence => &["sequenceDiagram"],
+            DiagramType::Flowchart => &["flowchart", "graph"],
+            DiagramType::ClassDiagram => &["classDiagram"],
+            DiagramType::StateDiagram => &["stateDiagram"],
+            DiagramType::EntityRelationship => &["erDiagram"],
+        }
+    }
 
-        let prompt = format!(
-            r#"Analyze this git diff and generate a Mermaid sequence diagram showing the key interactions and data flow in the changed code. Focus on:
-1. Which components/modules/functions are involved
-2. How they communicate or pass data
-3. The order of operations
+    /// Append an explicit type directive to a rendered prompt; `Auto` leaves it as is
+    fn apply_to_prompt(self, prompt: String) -> String {
+        match self.keywords().first() {
+            Some(keyword) => format!(
+                "{}\n\nIgnore any other diagram type mentioned above: generate a Mermaid {} and return ONLY its code, starting with \"{}\".",
+                prompt,
+                self.label(),
+                keyword
+            ),
+            None => prompt,
+        }
+    }
+}
 
-Return ONLY valid Mermaid code starting with "sequenceDiagram" - no markdown fences, no explanation, just the diagram code.
+/// Run the diagram prompt on `backend`, strip markdown fences and check the
+/// diagram is of the requested type
+fn request_diagram(backend: &dyn AiBackend, prompt: &str, diagram_type: DiagramType) -> Result<String> {
+    let response = backend.run_prompt(prompt)?;
+
+    // Clean up the response - remove markdown fences if present
+    let diagram = response
+        .trim()
+        .trim_start_matches("```mermaid")
+        .trim_start_matches("```")
+        .trim_end_matches("```")
+        .trim()
+        .to_string();
 
-If the changes are primarily configuration, styling, or don't have meaningful interactions to diagram, generate a simple flowchart instead starting with "flowchart TB" showing what was changed and why.
+    let keywords = diagram_type.keywords();
+    if !keywords.is_empty() && !keywords.iter().any(|k| diagram.starts_with(k)) {
+        let first_line = diagram.lines().next().unwrap_or_default();
+        return Err(AppError::parse(format!(
+            "Expected a Mermaid {} but got: {}",
+            diagram_type.label(),
+            first_line
+        )));
+    }
+    Ok(diagram)
+}
 
-Git diff:
-```
-{diff}
-```"#,
-            diff = truncated_diff
-        );
+fn build_diagram(
+    repo_path: &str,
+    commit_id: Option<&str>,
+    base_ref: Option<&str>,
+    head_ref: Option<&str>,
+    diagram_type: DiagramType,
+    template: &str,
+    backend: &dyn AiBackend,
+) -> Result<String> {
+    let repo = git::open_repo(repo_path)?;
+    let patch = review_diff_patch(&repo, commit_id, base_ref, head_ref, None)?;
 
-        // Call claude CLI
-        let claude_path = find_claude_binary()?;
-        let output = Command::new(&claude_path)
-            .args(["-p", &prompt])
-            .output()
-            .map_err(|e| AppError::ai(format!("Failed to run claude at {:?}: {}", claude_path, e)))?;
+    // Truncate if too long
+    let truncated_diff = diff_for_prompt(&patch, DIAGRAM_MAX_DIFF_LEN);
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(AppError::ai(format!("Claude failed: {}", stderr)));
-        }
+    let prompt = render_prompt_template(template, &[("diff", &truncated_diff)]);
+    let prompt = diagram_type.apply_to_prompt(prompt);
 
-        let response = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    request_diagram(backend, &prompt, diagram_type)
+}
 
-        if response.is_empty() {
-            return Err(AppError::ai("Claude returned an empty response"));
-        }
+/// Generate a Mermaid diagram using the selected AI backend. Describes
+/// `commit_id` or the `base_ref..head_ref` range when given, else working changes.
+/// `diagram_type` forces a specific Mermaid diagram; by default the model picks.
+#[tauri::command]
+#[instrument(skip_all, fields(repo_path = %repo_path, commit_id = ?commit_id, diagram_type = ?diagram_type, backend = ?backend), err(Debug))]
+pub async fn generate_diagram(
+    app: tauri::AppHandle,
+    repo_path: String,
+    backend: Option<AiBackendKind>,
+    commit_id: Option<String>,
+    base_ref: Option<String>,
+    head_ref: Option<String>,
+    diagram_type: Option<DiagramType>,
+) -> Result<String> {
+    let backend = backend.unwrap_or_default();
+    let diagram_template = load_app_settings(&app).prompts.resolve(PromptKind::Diagram);
 
-        // Clean up the response - remove markdown fences if present
-        let diagram = response
-            .trim()
-            .trim_start_matches("```mermaid")
-            .trim_start_matches("```")
-            .trim_end_matches("```")
-            .trim()
-            .to_string();
+    tokio::task::spawn_blocking(move || {
+        build_diagram(
+            &repo_path,
+            commit_id.as_deref(),
+            base_ref.as_deref(),
+            head_ref.as_deref(),
+            diagram_type.unwrap_or_default(),
+            &diagram_template,
+            backend.backend().as_ref(),
+        )
+    })
+    .await
+    .map_err(|e| AppError::io(format!("Task join error: {}", e)))?
+}
 
-        Ok(diagram)
+/// List a directory's immediate children at a commit, or in the working directory
+#[tauri::command]
+#[instrument(skip_all, fields(dir_path = %dir_path, commit_id = ?commit_id), err(Debug))]
+pub async fn list_tree(
+    repo_path: String,
+    commit_id: Option<String>,
+    dir_path: String,
+) -> Result<Vec<git::TreeEntry>> {
+    tokio::task::spawn_blocking(move || {
+        let repo = git::open_repo(&repo_path)?;
+        Ok(git::list_tree(&repo, commit_id.as_deref(), &dir_path)?)
     })
     .await
     .map_err(|e| AppError::io(format!("Task join error: {}", e)))?
@@ -2827,3 +4457,907 @@ pub async fn read_repo_file(
     .await
     .map_err(|e| AppError::io(format!("Task join error: {}", e)))?
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_prompt_template() {
+        let rendered = render_prompt_template(
+            "Review{skills}\n{\"json\": true}\n{diff}\n{unknown}",
+            &[("skills", " with skills"), ("diff", "+added {skills}")],
+        );
+        // Values are not re-scanned, JSON braces and unknown placeholders are kept
+        assert_eq!(rendered, "Review with skills\n{\"json\": true}\n+added {skills}\n{unknown}");
+    }
+
+    #[test]
+    fn test_default_prompts_have_required_placeholders() {
+        for kind in [PromptKind::Review, PromptKind::CommitMessage, PromptKind::Diagram, PromptKind::Conflict] {
+            assert!(validate_prompt_template(kind, kind.default_template()).is_ok(), "{:?}", kind);
+        }
+    }
+
+    #[test]
+    fn test_prompt_template_validation_and_fallback() {
+        let prompts = PromptTemplates {
+            review: Some("Be strict.\n{diff}".to_string()),
+            commit_message: Some("No diff placeholder".to_string()),
+            ..Default::default()
+        };
+        assert!(prompts.validate().is_err());
+        assert_eq!(prompts.resolve(PromptKind::Review), "Be strict.\n{diff}");
+        assert_eq!(prompts.resolve(PromptKind::CommitMessage), DEFAULT_COMMIT_MESSAGE_PROMPT);
+        assert_eq!(prompts.resolve(PromptKind::Diagram), DEFAULT_DIAGRAM_PROMPT);
+    }
+
+    #[test]
+    fn test_commit_message_prompt_truncates_oversized_diff() {
+        let template = "Write a message.\n{diff}\nEnd.";
+
+        let small = build_commit_message_prompt(template, "+one line\n");
+        assert_eq!(small, "Write a message.\n+one line\n\nEnd.");
+
+        // Multi-byte chars so the byte limit falls inside one
+        let patch = format!("diff --git a/a.txt b/a.txt\n{}", "+héllo wörld\n".repeat(2000));
+        assert!(patch.len() > COMMIT_MESSAGE_MAX_DIFF_LEN);
+        let prompt = build_commit_message_prompt(template, &patch);
+        assert!(prompt.starts_with("Write a message.\ndiff --git a/a.txt b/a.txt\n+héllo wörld\n"));
+        assert!(prompt.ends_with("[diff truncated: long files were shortened to fit]\nEnd."));
+        assert!(prompt.len() < COMMIT_MESSAGE_MAX_DIFF_LEN + 200);
+    }
+
+    /// A file patch with `lines` added lines of `content`
+    fn file_patch(path: &str, content: &str, lines: usize) -> String {
+        format!(
+            "diff --git a/{path} b/{path}\n--- a/{path}\n+++ b/{path}\n@@ -0,0 +1,{lines} @@\n{}",
+            format!("+{}\n", content).repeat(lines)
+        )
+    }
+
+    #[test]
+    fn test_truncate_diff_gives_every_file_a_share() {
+        let big = file_patch("big.rs", "let x = 1;", 5000);
+        let small = file_patch("small.rs", "let y = 2;", 3);
+        let later = file_patch("later.rs", "let z = 3;", 5000);
+        let patch = format!("{}{}{}", big, small, later);
+
+        let (out, truncated) = truncate_diff(&patch, 4000);
+        assert!(truncated);
+        assert!(out.len() <= 4000 + 3 * FILE_TRUNCATED_MARKER.len());
+        // The small file is kept whole and the large ones both appear
+        assert!(out.contains(&small));
+        assert!(out.contains("+++ b/big.rs") && out.contains("+++ b/later.rs"));
+        // Cuts land on whole lines
+        for line in out.lines().filter(|l| l.starts_with('+') && !l.starts_with("+++")) {
+            assert!(matches!(line, "+let x = 1;" | "+let y = 2;" | "+let z = 3;"), "partial line: {}", line);
+        }
+        assert_eq!(out.matches(FILE_TRUNCATED_MARKER).count(), 2);
+
+        let (unchanged, truncated) = truncate_diff(&small, 4000);
+        assert!(!truncated);
+        assert_eq!(unchanged, small);
+    }
+
+    #[test]
+    fn test_truncate_diff_multibyte_never_splits_chars() {
+        // Multibyte path and content, with no newlines to fall back on inside the cut
+        let line = "日本語のテキスト🦀".repeat(400);
+        let patch = format!(
+            "{}{}",
+            file_patch("ドキュメント/説明.md", &line, 1),
+            file_patch("src/ü.rs", "ünïcödé", 200)
+        );
+        for max_bytes in [1, 7, 100, 1001, 2003, 5000] {
+            let (out, truncated) = truncate_diff(&patch, max_bytes);
+            assert!(truncated);
+            // Building `out` would have panicked on a bad slice; also check the cut points
+            for piece in out.split(FILE_TRUNCATED_MARKER) {
+                assert!(patch.contains(piece), "cut produced text not in the patch");
+            }
+        }
+    }
+
+    #[test]
+    fn test_skill_frontmatter_quoted_values() {
+        let content = "---\nname: \"Security: OWASP\"\ndescription: 'Checks for \"injection\" bugs'\n---\n# Body\n";
+        let (name, description, body) = parse_skill_frontmatter_strict(content).unwrap();
+        assert_eq!(name, "Security: OWASP");
+        assert_eq!(description, "Checks for \"injection\" bugs");
+        assert_eq!(body, "# Body\n");
+
+        // Multi-line YAML values are handled too
+        let content = "---\nname: perf\ndescription: >\n  Looks for slow\n  loops\n---\nBody";
+        let (_, description, _) = parse_skill_frontmatter_strict(content).unwrap();
+        assert_eq!(description, "Looks for slow loops");
+    }
+
+    #[test]
+    fn test_skill_frontmatter_body_containing_dashes() {
+        let content = "---\nname: style\ndescription: a---b\n---\nIntro\n\n---\n\nMore\n";
+        let (name, description, body) = parse_skill_frontmatter_strict(content).unwrap();
+        assert_eq!(name, "style");
+        assert_eq!(description, "a---b");
+        assert_eq!(body, "Intro\n\n---\n\nMore\n");
+        assert_eq!(parse_skill_frontmatter(content).2, body);
+    }
+
+    #[test]
+    fn test_skill_frontmatter_missing_required_keys() {
+        let err = parse_skill_frontmatter_strict("---\nname: only-name\n---\nBody").unwrap_err();
+        assert!(err.message.contains("'description'"), "{}", err.message);
+        let err = parse_skill_frontmatter_strict("---\ndescription: no name\n---\nBody").unwrap_err();
+        assert!(err.message.contains("'name'"), "{}", err.message);
+
+        // Malformed YAML and unterminated frontmatter are rejected
+        assert!(parse_skill_frontmatter_strict("---\nname: [unclosed\ndescription: x\n---\n").is_err());
+        assert!(parse_skill_frontmatter_strict("---\nname: x\ndescription: y\n").is_err());
+        assert!(parse_skill_frontmatter_strict("# No frontmatter").is_err());
+
+        // The lenient parser still falls back to defaults
+        let (name, description, body) = parse_skill_frontmatter("# No frontmatter");
+        assert_eq!((name.as_str(), description.as_str(), body.as_str()), ("Unnamed Skill", "", "# No frontmatter"));
+    }
+
+    #[test]
+    fn test_install_skill_from_file_and_content() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let skills_dir = tmp.path().join("skills");
+
+        let file = tmp.path().join("offline.md");
+        fs::write(&file, "---\nname: Offline Skill\ndescription: Written locally\n---\nCheck things.\n").unwrap();
+        let installed = install_skill_from_path(&skills_dir, &file).unwrap();
+        assert_eq!(installed.id, "offline-skill");
+
+        let pasted = save_skill(
+            &skills_dir,
+            "---\nname: Pasted Skill\ndescription: From the clipboard\n---\nBody\n",
+            serde_json::json!({ "source_label": "clipboard" }),
+            None,
+        )
+        .unwrap();
+        assert_eq!(pasted.id, "pasted-skill");
+
+        let skills = list_skills_in(&skills_dir).unwrap();
+        let names: Vec<_> = skills.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["Offline Skill", "Pasted Skill"]);
+        assert_eq!(skills[1].description, "From the clipboard");
+        assert!(skills_dir.join("offline-skill.meta.json").exists());
+    }
+
+    #[test]
+    fn test_install_skill_rejects_bad_input() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let skills_dir = tmp.path().join("skills");
+        let nested = tmp.path().join("nested");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(tmp.path().join("skill.md"), "---\nname: x\ndescription: y\n---\n").unwrap();
+        fs::write(tmp.path().join("notes.txt"), "---\nname: x\ndescription: y\n---\n").unwrap();
+
+        // Path traversal, non-markdown and missing files
+        assert!(install_skill_from_path(&skills_dir, &nested.join("../skill.md")).is_err());
+        assert!(install_skill_from_path(&skills_dir, &tmp.path().join("notes.txt")).is_err());
+        assert!(install_skill_from_path(&skills_dir, &tmp.path().join("missing.md")).is_err());
+
+        // Content without valid frontmatter
+        assert!(save_skill(&skills_dir, "# Just markdown", serde_json::Value::Null, None).is_err());
+        assert!(list_skills_in(&skills_dir).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_refresh_skill_from_changed_remote() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let skills_dir = tmp.path().join("skills");
+        let meta = serde_json::json!({
+            "source_url": "https://skills.sh/acme/skills/lint",
+            "fetch_url": "https://raw.githubusercontent.com/acme/skills/main/skills/lint/SKILL.md"
+        });
+        save_skill(&skills_dir, "---\nname: Lint\ndescription: v1\n---\nOld body\n", meta, None).unwrap();
+        assert!(skill_fetch_url(&skills_dir, "lint").unwrap().ends_with("/lint/SKILL.md"));
+
+        // Same name: the id is kept and the body replaced
+        let refreshed = refresh_skill_in(&skills_dir, "lint", "---\nname: Lint\ndescription: v2\n---\nNew body\n").unwrap();
+        assert_eq!(refreshed.id, "lint");
+        assert_eq!(refreshed.description, "v2");
+        assert!(fs::read_to_string(skills_dir.join("lint.md")).unwrap().contains("New body"));
+
+        // Renamed upstream: files move, and the fetch URL moves with them
+        let renamed = refresh_skill_in(&skills_dir, "lint", "---\nname: Lint Rules\ndescription: v3\n---\nBody\n").unwrap();
+        assert_eq!(renamed.id, "lint-rules");
+        assert_eq!(renamed.source_url.as_deref(), Some("https://skills.sh/acme/skills/lint"));
+        assert!(!skills_dir.join("lint.md").exists());
+        assert!(skill_fetch_url(&skills_dir, "lint-rules").is_ok());
+
+        // Invalid remote content leaves the skill untouched
+        assert!(refresh_skill_in(&skills_dir, "lint-rules", "# no frontmatter").is_err());
+        assert!(fs::read_to_string(skills_dir.join("lint-rules.md")).unwrap().contains("v3"));
+
+        // Skills without a stored URL can't be refreshed
+        save_skill(&skills_dir, "---\nname: Local\ndescription: d\n---\n", serde_json::json!({}), None).unwrap();
+        assert_eq!(skill_fetch_url(&skills_dir, "local").err().unwrap().code, crate::error::Code::SkillError);
+    }
+
+    #[test]
+    fn test_review_cache_hits_identical_requests() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let cache_dir = tmp.path().join("review-cache");
+        let skills = vec!["security".to_string(), "perf".to_string()];
+        let key = review_cache_key("claude-cli", "default", &skills, DEFAULT_REVIEW_PROMPT, "+fn a() {}\n");
+
+        // First request misses and stores its result
+        assert!(read_cached_review(&cache_dir, &key).is_none());
+        let review = AIReviewData {
+            overview: "Looks fine".to_string(),
+            issues: Vec::new(),
+            generated_at: 42,
+            cached: false,
+        };
+        write_cached_review(&cache_dir, &key, &review).unwrap();
+
+        // An identical request (skill order doesn't matter) hits
+        let reordered = vec!["perf".to_string(), "security".to_string()];
+        let same_key = review_cache_key("claude-cli", "default", &reordered, DEFAULT_REVIEW_PROMPT, "+fn a() {}\n");
+        assert_eq!(same_key, key);
+        let hit = read_cached_review(&cache_dir, &same_key).expect("second request should hit the cache");
+        assert!(hit.cached);
+        assert_eq!(hit.overview, "Looks fine");
+        assert_eq!(hit.generated_at, 42);
+
+        // A changed diff, skill set or provider misses
+        let changed = review_cache_key("claude-cli", "default", &skills, DEFAULT_REVIEW_PROMPT, "+fn b() {}\n");
+        assert_ne!(changed, key);
+        assert!(read_cached_review(&cache_dir, &changed).is_none());
+        assert_ne!(review_cache_key("claude-cli", "default", &[], DEFAULT_REVIEW_PROMPT, "+fn a() {}\n"), key);
+        assert_ne!(review_cache_key("other", "default", &skills, DEFAULT_REVIEW_PROMPT, "+fn a() {}\n"), key);
+    }
+
+    /// Run git in `dir` with an isolated config, panicking on failure
+    fn run_git(dir: &std::path::Path, args: &[&str]) {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .env("GIT_CONFIG_GLOBAL", "/dev/null")
+            .env("GIT_CONFIG_SYSTEM", "/dev/null")
+            .env("GIT_AUTHOR_NAME", "Test Author")
+            .env("GIT_AUTHOR_EMAIL", "test@example.com")
+            .env("GIT_COMMITTER_NAME", "Test Committer")
+            .env("GIT_COMMITTER_EMAIL", "test@example.com")
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+    }
+
+    #[test]
+    fn test_review_diff_scoped_to_file_paths() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path();
+        let git = |args: &[&str]| run_git(path, args);
+        git(&["init", "-b", "main"]);
+        fs::write(path.join("api.rs"), "fn api() {}\n").unwrap();
+        fs::write(path.join("ui.rs"), "fn ui() {}\n").unwrap();
+        git(&["add", "-A"]);
+        git(&["commit", "-m", "Initial"]);
+
+        fs::write(path.join("api.rs"), "fn api() { todo!() }\n").unwrap();
+        fs::write(path.join("ui.rs"), "fn ui() { todo!() }\n").unwrap();
+        let repo = git::open_repo(path).unwrap();
+
+        let scoped = vec!["api.rs".to_string()];
+        let diff = review_diff_patch(&repo, None, None, None, Some(&scoped)).unwrap();
+        let prompt = render_prompt_template(DEFAULT_REVIEW_PROMPT, &[("skills", ""), ("diff", &diff)]);
+        assert!(prompt.contains("api.rs"));
+        assert!(!prompt.contains("ui.rs"));
+
+        let all = review_diff_patch(&repo, None, None, None, None).unwrap();
+        assert!(all.contains("api.rs") && all.contains("ui.rs"));
+
+        git(&["commit", "-am", "Both"]);
+        let commit_id = repo.head().unwrap().target().unwrap().to_string();
+        let diff = review_diff_patch(&repo, Some(&commit_id), None, None, Some(&["ui.rs".to_string()])).unwrap();
+        assert!(diff.contains("ui.rs") && !diff.contains("api.rs"));
+
+        // None of the requested paths changed
+        let err = review_diff_patch(&repo, Some(&commit_id), None, None, Some(&["missing.rs".to_string()])).unwrap_err();
+        assert_eq!(err.message, "No changes to review");
+    }
+
+    #[test]
+    fn test_review_diff_uses_compare_range_when_both_refs_given() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path();
+        let git = |args: &[&str]| run_git(path, args);
+        git(&["init", "-b", "main"]);
+        fs::write(path.join("base.txt"), "base\n").unwrap();
+        git(&["add", "-A"]);
+        git(&["commit", "-m", "Initial"]);
+        git(&["checkout", "-b", "feature"]);
+        fs::write(path.join("one.txt"), "one\n").unwrap();
+        git(&["add", "-A"]);
+        git(&["commit", "-m", "One"]);
+        fs::write(path.join("two.txt"), "two\n").unwrap();
+        git(&["add", "-A"]);
+        git(&["commit", "-m", "Two"]);
+        let repo = git::open_repo(path).unwrap();
+        let head = repo.head().unwrap().target().unwrap().to_string();
+
+        // Both refs: the whole range, even if a commit id is also passed
+        let range = review_diff_patch(&repo, Some(&head), Some("main"), Some("feature"), None).unwrap();
+        assert!(range.contains("+++ b/one.txt") && range.contains("+++ b/two.txt"));
+
+        // Only one ref: falls back to the commit
+        let commit = review_diff_patch(&repo, Some(&head), Some("main"), None, None).unwrap();
+        assert!(commit.contains("+++ b/two.txt") && !commit.contains("one.txt"));
+
+        // Range reviews honour file scoping too
+        let scoped = review_diff_patch(&repo, None, Some("main"), Some("feature"), Some(&["one.txt".to_string()])).unwrap();
+        assert!(scoped.contains("one.txt") && !scoped.contains("two.txt"));
+    }
+
+    #[test]
+    fn test_parse_skills_html_listing() {
+        // Attribute order varies, links repeat, and non-skill pages are mixed in
+        let html = r#"<!doctype html>
+<html><body>
+  <nav><a href="/docs/getting/started">Docs</a><a href="/trending">Trending</a></nav>
+  <ol>
+    <li><a class="row" href="/vercel-labs/agent-skills/react-best-practices">
+      <span>react-best-practices</span><span class="installs-count">12.3K</span>
+    </a></li>
+    <li><a data-rank="2" href='https://skills.sh/anthropics/skills/pdf?ref=home'>pdf <small>1,204 installs</small></a></li>
+    <li><a href="/vercel-labs/agent-skills/react-best-practices/">again</a></li>
+    <li><a href="/agents/claude/code">Agent</a></li>
+    <li><a href="//cdn.example.com/a/b/c">CDN</a><a href="https://github.com/a/b/c">GitHub</a></li>
+    <li><a href="/acme/tools/lint"><div><p>lint</p></div></a></li>
+  </ol>
+</body></html>"#;
+
+        let skills = parse_skills_html(html);
+        let parsed: Vec<_> = skills
+            .iter()
+            .map(|s| (s.owner.as_str(), s.repo.as_str(), s.skill.as_str(), s.installs.as_deref()))
+            .collect();
+        assert_eq!(
+            parsed,
+            vec![
+                ("vercel-labs", "agent-skills", "react-best-practices", Some("12.3K")),
+                ("anthropics", "skills", "pdf", Some("1,204")),
+                ("acme", "tools", "lint", None),
+            ]
+        );
+        assert_eq!(skills[1].url, "https://skills.sh/anthropics/skills/pdf");
+    }
+
+    #[test]
+    fn test_parse_skills_html_malformed() {
+        // Unclosed tags and stray quotes shouldn't derail the parser
+        let html = r#"<div><a href="/o/r/one">one<a href=/o/r/two class=x>two</div><p "oops"><a href="/o/r">short</a>"#;
+        let names: Vec<_> = parse_skills_html(html).into_iter().map(|s| s.skill).collect();
+        assert_eq!(names, vec!["one", "two"]);
+        assert!(parse_skills_html("").is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_coderabbit_review_from_patch_file() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path();
+        let git = |args: &[&str]| run_git(path, args);
+        git(&["init", "-b", "main"]);
+        fs::write(path.join("app.rs"), "fn main() {}\n").unwrap();
+        git(&["add", "-A"]);
+        git(&["commit", "-m", "Initial"]);
+        fs::write(path.join("app.rs"), "fn main() { panic!() }\n").unwrap();
+        git(&["commit", "-am", "Panic"]);
+
+        // Fake CLI: reviews the file named in the patch, rejects --type uncommitted
+        let fake = path.join("coderabbit");
+        fs::write(
+            &fake,
+            r#"#!/bin/sh
+while [ $# -gt 0 ]; do
+  case "$1" in
+    --type) [ "$2" = patch ] || { echo "error: unknown option '--type $2'" >&2; exit 2; }; shift ;;
+    --diff) patch="$2"; shift ;;
+  esac
+  shift
+done
+file=$(sed -n 's|^+++ b/||p' "$patch" | head -n 1)
+echo "Starting CodeRabbit review"
+echo "============================================================================"
+echo "File: $file"
+echo "Line: 1 to 1"
+echo "Type: potential_issue"
+echo
+echo "Comment:"
+echo "Unconditional panic."
+"#,
+        )
+        .unwrap();
+        fs::set_permissions(&fake, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let repo = git::open_repo(path).unwrap();
+        let head = repo.head().unwrap().target().unwrap().to_string();
+        let patch = review_diff_patch(&repo, Some(&head), None, None, None).unwrap();
+        let repo_path = path.to_str().unwrap();
+
+        match run_coderabbit_review_with(&fake, repo_path, Some(&patch)).unwrap() {
+            ReviewResult::Coderabbit { issues, .. } => {
+                assert_eq!(issues.len(), 1);
+                assert_eq!(issues[0].file, "app.rs");
+                assert_eq!(issues[0].lines, "1-1");
+                assert!(issues[0].description.contains("Unconditional panic"));
+            }
+            _ => panic!("expected a CodeRabbit result"),
+        }
+
+        // A CLI without patch support is reported as the old working-changes-only limit
+        let old = path.join("coderabbit-old");
+        fs::write(&old, "#!/bin/sh\necho \"error: unknown option '--diff'\" >&2\nexit 2\n").unwrap();
+        fs::set_permissions(&old, fs::Permissions::from_mode(0o755)).unwrap();
+        let err = run_coderabbit_review_with(&old, repo_path, Some(&patch)).err().unwrap();
+        assert_eq!(err.code, crate::error::Code::Validation);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_check_binary_available_captures_version() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let fake = tmp.path().join("fakecli");
+        fs::write(&fake, "#!/bin/sh\n[ \"$1\" = --version ] || exit 1\necho\necho '1.2.3 (Fake CLI)'\n").unwrap();
+        fs::set_permissions(&fake, fs::Permissions::from_mode(0o755)).unwrap();
+        let fake = fake.to_str().unwrap().to_string();
+
+        // Lookup by name (the PATH route) runs --version
+        let status = check_binary_available(&fake, &[], "install it");
+        assert!(status.available);
+        assert_eq!(status.version.as_deref(), Some("1.2.3 (Fake CLI)"));
+
+        // Candidate fast-path still reports the version
+        let missing = tmp.path().join("missing").to_str().unwrap().to_string();
+        let status = check_binary_available("diffy-no-such-cli", &[missing.clone(), fake.clone()], "install it");
+        assert_eq!(status.path.as_deref(), Some(fake.as_str()));
+        assert_eq!(status.version.as_deref(), Some("1.2.3 (Fake CLI)"));
+
+        let status = check_binary_available("diffy-no-such-cli", &[missing], "install it");
+        assert!(!status.available && status.version.is_none());
+        assert_eq!(status.install_instructions, "install it");
+    }
+
+    #[test]
+    fn test_extract_json_value_fenced() {
+        let text = "Here you go:\n```json\n{\"overview\": \"ok\", \"issues\": []}\n```\nLet me know!";
+        assert_eq!(extract_json_value(text).unwrap()["overview"], "ok");
+
+        // Braces in a fenced code example before the real answer are ignored
+        let text = "The fix:\n```rust\nfn main() { let x = vec![1]; }\n```\nResult: {\"resolved\": \"a\", \"explanation\": \"b\"}";
+        assert_eq!(extract_json_value(text).unwrap()["resolved"], "a");
+    }
+
+    #[test]
+    fn test_extract_json_value_leading_prose() {
+        let text = "I reviewed the diff carefully.\n\n{\"grade\": \"A\", \"highlights\": [\"tests\"]}\n\nThanks.";
+        let json = extract_json_value(text).unwrap();
+        assert_eq!(json["grade"], "A");
+        assert_eq!(json["highlights"][0], "tests");
+        assert!(extract_json_value("no json at all").is_none());
+        assert!(extract_json_value("{ unbalanced").is_none());
+    }
+
+    #[test]
+    fn test_extract_json_value_array() {
+        let text = "Issues found:\n[{\"title\": \"Leak\", \"severity\": \"high\"}, {\"title\": \"Typo\"}]";
+        let json = extract_json_value(text).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 2);
+
+        // A bare array review is read as the issue list
+        let review = parse_review_response(text).unwrap();
+        assert_eq!(review.issues.len(), 2);
+        assert_eq!(review.issues[0].severity, "high");
+    }
+
+    #[test]
+    fn test_extract_json_value_braces_in_strings() {
+        let text = r#"Sure: {"resolved": "fn f() { if a { \"}\" } }", "explanation": "kept [both] sides {"}"#;
+        let json = extract_json_value(text).unwrap();
+        assert_eq!(json["resolved"], "fn f() { if a { \"}\" } }");
+        assert_eq!(json["explanation"], "kept [both] sides {");
+    }
+
+    #[test]
+    fn test_fix_response_dry_run_leaves_files_untouched() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let repo_path = tmp.path().to_str().unwrap();
+        fs::write(tmp.path().join("lib.rs"), "fn a() { unwrap() }\n").unwrap();
+        let backups = tmp.path().join("backups");
+        let response = r#"Done.
+{"files": [{"path": "lib.rs", "content": "fn a() -> Result<()> { Ok(()) }\n"}], "summary": "Removed unwrap"}"#;
+
+        let preview = apply_fix_response(repo_path, response, true, &backups).unwrap();
+        assert!(!preview.applied);
+        assert_eq!(preview.summary, "Removed unwrap");
+        assert_eq!(
+            preview.files,
+            vec![("lib.rs".to_string(), "fn a() -> Result<()> { Ok(()) }\n".to_string())]
+        );
+        assert_eq!(fs::read_to_string(tmp.path().join("lib.rs")).unwrap(), "fn a() { unwrap() }\n");
+
+        let applied = apply_fix_response(repo_path, response, false, &backups).unwrap();
+        assert!(applied.applied);
+        assert_eq!(applied.message, "1 file(s) updated: Removed unwrap");
+        assert_eq!(fs::read_to_string(tmp.path().join("lib.rs")).unwrap(), "fn a() -> Result<()> { Ok(()) }\n");
+
+        // A malformed entry aborts before anything is written
+        let bad = r#"{"files": [{"path": "new.rs", "content": "x"}, {"path": "lib.rs"}]}"#;
+        assert!(apply_fix_response(repo_path, bad, false, &backups).is_err());
+        assert!(!tmp.path().join("new.rs").exists());
+    }
+
+    #[test]
+    fn test_fix_response_skips_paths_outside_repo() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let repo = tmp.path().join("repo");
+        let outside = tmp.path().join("outside");
+        fs::create_dir_all(&repo).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&outside, repo.join("link")).unwrap();
+        let abs = outside.join("abs.txt");
+
+        let response = serde_json::json!({
+            "files": [
+                { "path": "../outside/escape.txt", "content": "x" },
+                { "path": "src/../../outside/sneaky.txt", "content": "x" },
+                { "path": abs.to_str().unwrap(), "content": "x" },
+                { "path": "link/via-symlink.txt", "content": "x" },
+                { "path": ".git/hooks/pre-commit", "content": "x" },
+                { "path": "./.GIT/config", "content": "x" },
+                { "path": "sub/.git/config", "content": "x" },
+                { "path": "ok.txt", "content": "fine\n" }
+            ],
+            "summary": "Mixed"
+        })
+        .to_string();
+
+        let backups = tmp.path().join("backups");
+        let result = apply_fix_response(repo.to_str().unwrap(), &response, false, &backups).unwrap();
+        assert_eq!(result.files, vec![("ok.txt".to_string(), "fine\n".to_string())]);
+        let expected_skips = if cfg!(unix) { 7 } else { 6 };
+        assert_eq!(result.skipped.len(), expected_skips);
+        assert!(result.message.contains("skipped"));
+        assert_eq!(fs::read_to_string(repo.join("ok.txt")).unwrap(), "fine\n");
+        assert!(!repo.join(".git").exists(), "nothing written into .git");
+        assert!(!repo.join("sub").exists());
+        assert_eq!(fs::read_dir(&outside).unwrap().count(), 0, "nothing written outside the repo");
+    }
+
+    #[test]
+    fn test_undo_ai_fix_restores_original_files() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let repo = tmp.path().join("repo");
+        let backups = tmp.path().join("backups");
+        fs::create_dir_all(&repo).unwrap();
+        fs::write(repo.join("lib.rs"), "fn original() {}\n").unwrap();
+        fs::write(repo.join("main.rs"), "fn main() {}\n").unwrap();
+        let response = r#"{"files": [
+            {"path": "lib.rs", "content": "fn fixed() {}\n"},
+            {"path": "main.rs", "content": "fn main() { fixed() }\n"},
+            {"path": "new/deep/new.rs", "content": "// added\n"}
+        ], "summary": "Fixed"}"#;
+
+        let result = apply_fix_response(repo.to_str().unwrap(), response, false, &backups).unwrap();
+        assert_eq!(fs::read_to_string(repo.join("lib.rs")).unwrap(), "fn fixed() {}\n");
+        assert_eq!(fs::read_to_string(repo.join("new/deep/new.rs")).unwrap(), "// added\n");
+        let backup_id = result.backup_id.expect("applied fix has a backup");
+
+        assert_eq!(restore_fix_backup(&backups, &backup_id).unwrap(), 3);
+        assert_eq!(fs::read_to_string(repo.join("lib.rs")).unwrap(), "fn original() {}\n");
+        assert_eq!(fs::read_to_string(repo.join("main.rs")).unwrap(), "fn main() {}\n");
+        assert!(!repo.join("new").exists(), "created files and their directories are removed");
+
+        // The backup is consumed, and ids can't traverse out of the backup dir
+        let err = restore_fix_backup(&backups, &backup_id).unwrap_err();
+        assert_eq!(err.code, crate::error::Code::Validation);
+        let err = restore_fix_backup(&backups, "../repo").unwrap_err();
+        assert_eq!(err.code, crate::error::Code::Validation);
+    }
+
+    #[test]
+    fn test_fix_write_failure_restores_earlier_files() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let repo = tmp.path().join("repo");
+        let backups = tmp.path().join("backups");
+        fs::create_dir_all(&repo).unwrap();
+        fs::write(repo.join("lib.rs"), "fn original() {}\n").unwrap();
+        fs::write(repo.join("notes.txt"), "notes\n").unwrap();
+        // The last target's parent is a file, so its write fails after the others succeeded
+        let response = r#"{"files": [
+            {"path": "lib.rs", "content": "fn fixed() {}\n"},
+            {"path": "added/new.rs", "content": "// added\n"},
+            {"path": "notes.txt/new.rs", "content": "// unreachable\n"}
+        ], "summary": "Fixed"}"#;
+
+        let err = apply_fix_response(repo.to_str().unwrap(), response, false, &backups).err().unwrap();
+        assert_eq!(err.code, crate::error::Code::IoError);
+        assert!(err.message.contains("Failed to write notes.txt/new.rs"), "{}", err.message);
+        assert!(err.message.contains("restored"), "{}", err.message);
+        assert_eq!(fs::read_to_string(repo.join("lib.rs")).unwrap(), "fn original() {}\n");
+        assert!(!repo.join("added").exists());
+        // The backup was consumed by the rollback
+        assert_eq!(fs::read_dir(&backups).unwrap().count(), 0);
+    }
+
+    /// Backend that answers every prompt with a canned response
+    struct MockBackend(&'static str);
+
+    impl AiBackend for MockBackend {
+        fn name(&self) -> &'static str {
+            "Mock"
+        }
+
+        fn run_prompt(&self, _prompt: &str) -> Result<String> {
+            Ok(self.0.to_string())
+        }
+    }
+
+    #[test]
+    fn test_request_review_with_mock_backend() {
+        let backend = MockBackend(
+            r#"Here is my review:
+{"overview": "Adds a parser", "issues": [
+  {"title": "Unchecked index", "category": "bugs", "severity": "HIGH", "description": "May panic", "file": "src/lib.rs"},
+  {"problem": "Missing docs"},
+  {"severity": "low"}
+]}"#,
+        );
+        let review = request_review(&backend, "prompt").unwrap();
+        assert_eq!(review.overview, "Adds a parser");
+        assert!(!review.cached);
+        // Issues without a title or problem are dropped
+        assert_eq!(review.issues.len(), 2);
+        let first = &review.issues[0];
+        assert_eq!(first.id, "issue-1");
+        assert_eq!(first.problem, "May panic");
+        assert_eq!(first.file_path.as_deref(), Some("src/lib.rs"));
+        assert_eq!(review.issues[1].title, "Missing docs");
+        assert_eq!(review.issues[1].severity, "medium");
+
+        let err = request_review(&MockBackend("no json here"), "prompt").err().unwrap();
+        assert_eq!(err.code, crate::error::Code::ParseError);
+    }
+
+    #[test]
+    fn test_request_conflict_resolution_with_mock_backend() {
+        let backend = MockBackend(r#"{"resolved": "merged\n", "explanation": "Kept both"}"#);
+        let response = request_conflict_resolution(&backend, "prompt").unwrap();
+        assert_eq!(response.resolved, "merged\n");
+        assert_eq!(response.explanation, "Kept both");
+
+        let missing = request_conflict_resolution(&MockBackend(r#"{"explanation": "?"}"#), "prompt");
+        assert_eq!(missing.err().unwrap().code, crate::error::Code::ParseError);
+    }
+
+    #[test]
+    fn test_conflict_prompt_includes_base_when_supplied() {
+        /// Records the prompt it was sent
+        struct RecordingBackend(std::sync::Mutex<String>);
+
+        impl AiBackend for RecordingBackend {
+            fn name(&self) -> &'static str {
+                "Recording"
+            }
+
+            fn run_prompt(&self, prompt: &str) -> Result<String> {
+                *self.0.lock().unwrap() = prompt.to_string();
+                Ok(r#"{"resolved": "merged", "explanation": "ok"}"#.to_string())
+            }
+        }
+
+        let prompt = build_conflict_prompt(
+            DEFAULT_CONFLICT_PROMPT,
+            "src/lib.rs",
+            "ours line",
+            "theirs line",
+            Some("base line"),
+            None,
+        );
+        let backend = RecordingBackend(std::sync::Mutex::new(String::new()));
+        request_conflict_resolution(&backend, &prompt).unwrap();
+        let sent = backend.0.lock().unwrap().clone();
+
+        let base = sent.find("## Common Ancestor (Base)\n```\nbase line\n```").expect("base section");
+        assert!(sent.find("ours line").unwrap() < base);
+        assert!(base < sent.find("theirs line").unwrap());
+        assert!(!sent.contains("{base}"));
+
+        let two_way = build_conflict_prompt(DEFAULT_CONFLICT_PROMPT, "src/lib.rs", "ours", "theirs", None, Some(""));
+        assert!(!two_way.contains("Common Ancestor"));
+        assert!(!two_way.contains("User Instructions"));
+
+        // Custom templates written before {base} existed still get the ancestor
+        let custom = "Merge {file_path}:\n{ours}\n---\n{theirs}";
+        let prompt = build_conflict_prompt(custom, "a.rs", "ours", "theirs", Some("base line"), None);
+        assert!(prompt.starts_with("Merge a.rs:\nours\n---\ntheirs"));
+        assert!(prompt.ends_with("## Common Ancestor (Base)\n```\nbase line\n```\n"));
+        assert!(!build_conflict_prompt(custom, "a.rs", "ours", "theirs", None, None).contains("Common Ancestor"));
+    }
+
+    #[test]
+    fn test_request_diagram_strips_fences() {
+        let backend = MockBackend("```mermaid\nsequenceDiagram\n  A->>B: hi\n```");
+        assert_eq!(
+            request_diagram(&backend, "prompt", DiagramType::Auto).unwrap(),
+            "sequenceDiagram\n  A->>B: hi"
+        );
+    }
+
+    #[test]
+    fn test_diagram_type_directives() {
+        assert_eq!(DiagramType::Auto.apply_to_prompt("base".to_string()), "base");
+        for (diagram_type, keyword) in [
+            (DiagramType::Sequence, "\"sequenceDiagram\""),
+            (DiagramType::Flowchart, "\"flowchart\""),
+            (DiagramType::ClassDiagram, "\"classDiagram\""),
+            (DiagramType::StateDiagram, "\"stateDiagram\""),
+            (DiagramType::EntityRelationship, "\"erDiagram\""),
+        ] {
+            let prompt = diagram_type.apply_to_prompt("base".to_string());
+            assert!(prompt.starts_with("base\n\n"));
+            assert!(prompt.contains(keyword), "{:?}: {}", diagram_type, prompt);
+            assert!(prompt.contains(diagram_type.label()));
+        }
+    }
+
+    #[test]
+    fn test_request_diagram_rejects_wrong_type() {
+        let backend = MockBackend("```mermaid\nsequenceDiagram\n  A->>B: hi\n```");
+        let err = request_diagram(&backend, "prompt", DiagramType::ClassDiagram).unwrap_err();
+        assert_eq!(err.code, crate::error::Code::ParseError);
+        assert!(request_diagram(&backend, "prompt", DiagramType::Sequence).is_ok());
+
+        let backend = MockBackend("graph TD\n  A --> B");
+        assert!(request_diagram(&backend, "prompt", DiagramType::Flowchart).is_ok());
+        assert!(request_diagram(&backend, "prompt", DiagramType::StateDiagram).is_err());
+    }
+
+    /// Backend that records the prompt it was given
+    #[derive(Default)]
+    struct RecordingBackend(std::sync::Mutex<String>);
+
+    impl AiBackend for RecordingBackend {
+        fn name(&self) -> &'static str {
+            "Recording"
+        }
+
+        fn run_prompt(&self, prompt: &str) -> Result<String> {
+            *self.0.lock().unwrap() = prompt.to_string();
+            Ok("```mermaid\nsequenceDiagram\n```".to_string())
+        }
+    }
+
+    #[test]
+    fn test_build_diagram_for_commit() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let dir = tmp.path();
+        run_git(dir, &["init", "-q", "-b", "main"]);
+        fs::write(dir.join("first.txt"), "first\n").unwrap();
+        run_git(dir, &["add", "."]);
+        run_git(dir, &["commit", "-q", "-m", "first"]);
+        let repo = git::open_repo(dir.to_str().unwrap()).unwrap();
+        let first = repo.head().unwrap().peel_to_commit().unwrap().id().to_string();
+        fs::write(dir.join("second.txt"), "second\n").unwrap();
+        run_git(dir, &["add", "."]);
+        run_git(dir, &["commit", "-q", "-m", "second"]);
+        let repo_path = dir.to_str().unwrap();
+
+        let backend = RecordingBackend::default();
+        let diagram = build_diagram(repo_path, Some(&first), None, None, DiagramType::Auto, "{diff}", &backend).unwrap();
+        assert_eq!(diagram, "sequenceDiagram");
+        let prompt = backend.0.lock().unwrap().clone();
+        assert!(prompt.contains("+first"));
+        assert!(!prompt.contains("second"));
+
+        build_diagram(repo_path, None, Some(&first), Some("HEAD"), DiagramType::Auto, "{diff}", &backend).unwrap();
+        assert!(backend.0.lock().unwrap().contains("+second"));
+
+        // A clean working tree still has nothing to diagram
+        let err = build_diagram(repo_path, None, None, None, DiagramType::Auto, "{diff}", &backend).unwrap_err();
+        assert_eq!(err.code, crate::error::Code::NoChanges);
+    }
+
+    #[tokio::test]
+    async fn test_stream_cli_emits_chunks_then_parses() {
+        let mut cmd = tokio::process::Command::new("sh");
+        cmd.args([
+            "-c",
+            r#"printf '{"overview": "Strea'; sleep 0.2; printf 'med", "issues": []}\n'"#,
+        ]);
+        let mut chunks = Vec::new();
+        let response = ai::stream_cli("Shell", std::path::Path::new("sh"), cmd, |t| chunks.push(t.to_string()))
+            .await
+            .unwrap();
+        assert!(chunks.len() >= 2, "{:?}", chunks);
+        assert_eq!(chunks.concat().trim(), response);
+        assert_eq!(parse_review_response(&response).unwrap().overview, "Streamed");
+
+        let mut failing = tokio::process::Command::new("sh");
+        failing.args(["-c", "printf partial; echo boom >&2; exit 3"]);
+        let err = ai::stream_cli("Shell", std::path::Path::new("sh"), failing, |_| {})
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, crate::error::Code::AiError);
+        assert!(err.message.contains("boom"));
+    }
+
+    #[test]
+    fn test_cancel_ai_request_kills_and_reaps_child() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let pid_file = tmp.path().join("pid");
+        let processes = std::sync::Arc::new(CancellationState::new());
+
+        let runner = {
+            let processes = processes.clone();
+            let mut cmd = Command::new("sh");
+            cmd.arg("-c")
+                .arg(format!("echo $$ > '{}'; exec sleep 30", pid_file.display()));
+            std::thread::spawn(move || processes.output(Some("slow"), cmd))
+        };
+
+        // Wait until the slow process is registered and running
+        let started = Instant::now();
+        while !pid_file.exists() || fs::read_to_string(&pid_file).unwrap().trim().is_empty() {
+            assert!(started.elapsed() < Duration::from_secs(10), "mock process never started");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let pid = fs::read_to_string(&pid_file).unwrap().trim().to_string();
+
+        // A second run under the same id is refused rather than replacing the first
+        let mut duplicate = Command::new("sh");
+        duplicate.args(["-c", "exit 0"]);
+        let err = processes.output(Some("slow"), duplicate).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+
+        while !processes.cancel("slow") {
+            assert!(started.elapsed() < Duration::from_secs(10), "mock process never registered");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(runner.join().unwrap().unwrap().is_none(), "cancelled run reports no output");
+        assert!(started.elapsed() < Duration::from_secs(10));
+        // Killed and waited on: no zombie left behind, and the handle is gone
+        if cfg!(target_os = "linux") {
+            assert!(!std::path::Path::new(&format!("/proc/{}", pid)).exists());
+        }
+        assert!(!processes.cancel("slow"));
+
+        // Finished processes are unregistered too
+        let mut quick = Command::new("sh");
+        quick.args(["-c", "printf done"]);
+        let output = processes.output(Some("quick"), quick).unwrap().unwrap();
+        assert_eq!(output.stdout, b"done");
+        assert!(!processes.cancel("quick"));
+    }
+
+    #[test]
+    fn test_take_utf8_prefix_keeps_split_chars() {
+        let bytes = "héllo".as_bytes();
+        // Cut inside the two-byte 'é'
+        let mut pending = bytes[..2].to_vec();
+        assert_eq!(ai::take_utf8_prefix(&mut pending), "h");
+        assert_eq!(pending, bytes[1..2]);
+        pending.extend_from_slice(&bytes[2..]);
+        assert_eq!(ai::take_utf8_prefix(&mut pending), "éllo");
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn test_ai_backend_kind_defaults_to_claude() {
+        assert_eq!(AiBackendKind::default(), AiBackendKind::Claude);
+        let kind: AiBackendKind = serde_json::from_str("\"codex\"").unwrap();
+        assert_eq!(kind.backend().name(), "Codex");
+        assert_eq!(kind.provider_id(), "codex-cli");
+    }
+}
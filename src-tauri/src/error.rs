@@ -1,7 +1,12 @@
 //! Structured error types for Tauri commands.
 //!
 //! All errors returned to the frontend have a consistent shape:
-//! `{ "code": "errors.xxx", "message": "Human readable message" }`
+//! `{ "code": "errors.xxx", "kind": "git", "message": "Human readable message" }`
+//!
+//! `code` is the specific error; `kind` is a coarse category for generic handling.
+//! Conflicts from merge/rebase also carry `conflictingFiles`, and dirty working tree
+//! errors carry the modified `paths`. (A conflicting pull is not an error; its result
+//! reports the files instead.)
 
 use std::fmt;
 
@@ -30,6 +35,14 @@ pub enum Code {
     AiError,
     /// Skill not found or invalid
     SkillError,
+    /// Nothing is staged (e.g. when generating a commit message)
+    NoStagedChanges,
+    /// There are no changes to operate on (e.g. nothing to review)
+    NoChanges,
+    /// The operation was cancelled by the caller
+    Cancelled,
+    /// Local modifications block the operation (stash or commit them first)
+    DirtyWorkingTree,
 }
 
 impl Code {
@@ -47,6 +60,57 @@ impl Code {
             Code::GitError => "errors.git",
             Code::AiError => "errors.ai",
             Code::SkillError => "errors.skill",
+            Code::NoStagedChanges => "errors.no_staged_changes",
+            Code::NoChanges => "errors.no_changes",
+            Code::Cancelled => "errors.cancelled",
+            Code::DirtyWorkingTree => "errors.dirty_working_tree",
+        }
+    }
+}
+
+/// Coarse error category, derived from [`Code`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    Unknown,
+    Io,
+    Git,
+    Ai,
+    Validation,
+    Conflict,
+    Auth,
+    Network,
+}
+
+impl ErrorKind {
+    /// Returns the string kind for serialization (e.g., "conflict")
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorKind::Unknown => "unknown",
+            ErrorKind::Io => "io",
+            ErrorKind::Git => "git",
+            ErrorKind::Ai => "ai",
+            ErrorKind::Validation => "validation",
+            ErrorKind::Conflict => "conflict",
+            ErrorKind::Auth => "auth",
+            ErrorKind::Network => "network",
+        }
+    }
+}
+
+impl Code {
+    /// The coarse category this code belongs to
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Code::Unknown | Code::Cancelled => ErrorKind::Unknown,
+            Code::Validation | Code::NoStagedChanges | Code::NoChanges | Code::DirtyWorkingTree => {
+                ErrorKind::Validation
+            }
+            Code::RepoNotFound | Code::GitError => ErrorKind::Git,
+            Code::GitAuth => ErrorKind::Auth,
+            Code::MergeConflict => ErrorKind::Conflict,
+            Code::NetworkError => ErrorKind::Network,
+            Code::IoError | Code::ParseError => ErrorKind::Io,
+            Code::AiError | Code::SkillError => ErrorKind::Ai,
         }
     }
 }
@@ -58,10 +122,14 @@ impl fmt::Display for Code {
 }
 
 /// Application error that serializes to `{ code, message }` for the frontend.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct AppError {
     pub code: Code,
     pub message: String,
+    /// Files left conflicted by the failed operation; only set on conflicts
+    pub conflicting_files: Vec<String>,
+    /// Locally modified files; only set on dirty working tree errors
+    pub paths: Vec<String>,
 }
 
 impl AppError {
@@ -69,6 +137,8 @@ impl AppError {
         Self {
             code,
             message: message.into(),
+            conflicting_files: Vec::new(),
+            paths: Vec::new(),
         }
     }
 
@@ -110,6 +180,46 @@ impl AppError {
     pub fn skill(message: impl Into<String>) -> Self {
         Self::new(Code::SkillError, message)
     }
+
+    pub fn no_staged_changes(message: impl Into<String>) -> Self {
+        Self::new(Code::NoStagedChanges, message)
+    }
+
+    pub fn no_changes(message: impl Into<String>) -> Self {
+        Self::new(Code::NoChanges, message)
+    }
+
+    pub fn cancelled(message: impl Into<String>) -> Self {
+        Self::new(Code::Cancelled, message)
+    }
+
+    pub fn conflict(message: impl Into<String>) -> Self {
+        Self::new(Code::MergeConflict, message)
+    }
+
+    /// A conflict that names the files the UI should open for resolution
+    pub fn conflict_in(message: impl Into<String>, files: Vec<String>) -> Self {
+        Self {
+            conflicting_files: files,
+            ..Self::conflict(message)
+        }
+    }
+
+    /// Local modifications to `paths` block the operation, so the UI can offer to stash and retry
+    pub fn dirty_working_tree(message: impl Into<String>, paths: Vec<String>) -> Self {
+        Self {
+            paths,
+            ..Self::new(Code::DirtyWorkingTree, message)
+        }
+    }
+
+    pub fn auth(message: impl Into<String>) -> Self {
+        Self::new(Code::GitAuth, message)
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.code.kind()
+    }
 }
 
 impl fmt::Display for AppError {
@@ -120,16 +230,24 @@ impl fmt::Display for AppError {
 
 impl std::error::Error for AppError {}
 
-/// Serialize to `{ "code": "...", "message": "..." }` for Tauri IPC
+/// Serialize to `{ "code": "...", "kind": "...", "message": "..." }` for Tauri IPC,
+/// plus `conflictingFiles` / `paths` when there are any
 impl serde::Serialize for AppError {
     fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
         use serde::ser::SerializeMap;
-        let mut map = serializer.serialize_map(Some(2))?;
+        let mut map = serializer.serialize_map(None)?;
         map.serialize_entry("code", self.code.as_str())?;
+        map.serialize_entry("kind", self.kind().as_str())?;
         map.serialize_entry("message", &self.message)?;
+        if !self.conflicting_files.is_empty() {
+            map.serialize_entry("conflictingFiles", &self.conflicting_files)?;
+        }
+        if !self.paths.is_empty() {
+            map.serialize_entry("paths", &self.paths)?;
+        }
         map.end()
     }
 }
@@ -141,20 +259,32 @@ impl From<crate::git::GitError> for AppError {
         match &err {
             GitError::NotFound(path) => AppError::repo_not_found(path),
             GitError::Git(e) => {
-                // Check for auth-related errors
                 let msg = e.to_string();
-                if msg.contains("authentication")
-                    || msg.contains("credential")
-                    || msg.contains("Permission denied")
-                {
-                    AppError::new(Code::GitAuth, msg)
-                } else {
-                    AppError::git(msg)
+                match (e.code(), e.class()) {
+                    (git2::ErrorCode::Conflict | git2::ErrorCode::MergeConflict, _) => {
+                        AppError::conflict(msg)
+                    }
+                    (git2::ErrorCode::Auth | git2::ErrorCode::Certificate, _) => AppError::auth(msg),
+                    (_, git2::ErrorClass::Net) => AppError::network(msg),
+                    // The CLI-backed operations only surface auth failures in stderr text
+                    _ if msg.contains("authentication")
+                        || msg.contains("credential")
+                        || msg.contains("Permission denied") =>
+                    {
+                        AppError::auth(msg)
+                    }
+                    _ => AppError::git(msg),
                 }
             }
             GitError::InvalidPath(path) => {
                 AppError::validation(format!("Invalid path: {}", path))
             }
+            GitError::Validation(msg) => AppError::validation(msg.clone()),
+            GitError::Conflict { message, files } => AppError::conflict_in(message.clone(), files.clone()),
+            GitError::DirtyWorkingTree { message, paths } => {
+                AppError::dirty_working_tree(message.clone(), paths.clone())
+            }
+            GitError::Cancelled => AppError::new(Code::Cancelled, err.to_string()),
         }
     }
 }
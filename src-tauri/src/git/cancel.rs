@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use super::GitError;
+
+/// Cooperative cancellation flag for long-running git operations.
+///
+/// Heavy functions check it at loop boundaries (revwalk steps, graph rows, status
+/// entries) and bail out with `GitError::Cancelled`. Clones share the same flag.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Return `Err(GitError::Cancelled)` if cancellation was requested
+    pub fn check(&self) -> Result<(), GitError> {
+        if self.is_cancelled() {
+            Err(GitError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Tracks tokens for in-flight operations keyed by a frontend-supplied request id
+#[derive(Debug, Default)]
+pub struct CancellationRegistry {
+    tokens: Mutex<HashMap<String, CancellationToken>>,
+}
+
+impl CancellationRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a token for `request_id`. Without an id the token is untracked and
+    /// can't be cancelled.
+    pub fn register(&self, request_id: Option<&str>) -> CancellationToken {
+        let token = CancellationToken::new();
+        if let Some(id) = request_id {
+            if let Ok(mut tokens) = self.tokens.lock() {
+                tokens.insert(id.to_string(), token.clone());
+            }
+        }
+        token
+    }
+
+    /// Forget the token for a finished operation
+    pub fn finish(&self, request_id: Option<&str>) {
+        if let (Some(id), Ok(mut tokens)) = (request_id, self.tokens.lock()) {
+            tokens.remove(id);
+        }
+    }
+
+    /// Cancel the operation registered under `request_id`.
+    /// Returns false if no such operation is in flight.
+    pub fn cancel(&self, request_id: &str) -> bool {
+        match self.tokens.lock() {
+            Ok(tokens) => match tokens.get(request_id) {
+                Some(token) => {
+                    token.cancel();
+                    true
+                }
+                None => false,
+            },
+            Err(_) => false,
+        }
+    }
+}
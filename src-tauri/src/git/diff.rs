@@ -1,7 +1,9 @@
 use git2::{Diff, DiffFindOptions, DiffOptions, Repository};
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
+use super::repository::git_command;
 use super::GitError;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -16,6 +18,12 @@ pub struct DiffFile {
     /// Whether the file is binary
     #[serde(default)]
     pub is_binary: bool,
+    /// Size in bytes before the change; only set for binary files that existed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old_size: Option<u64>,
+    /// Size in bytes after the change; only set for binary files that still exist
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new_size: Option<u64>,
     /// Old file mode (e.g., 0o100644 for regular file, 0o120000 for symlink)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub old_mode: Option<u32>,
@@ -31,6 +39,9 @@ pub struct DiffFile {
     /// Whether file is a submodule
     #[serde(default)]
     pub is_submodule: bool,
+    /// Whether the file's patch body was replaced by a placeholder for exceeding `max_patch_bytes`
+    #[serde(default)]
+    pub truncated: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -47,6 +58,58 @@ pub struct FileDiff {
     pub patch: String,
 }
 
+/// Totals for a diff, for "N files changed, +X -Y" headers
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffStats {
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+/// A single file's diff between two arbitrary commits, with its status
+/// (A/D/M/R/...) so callers can tell whether it exists on both sides
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitPairFileDiff {
+    pub file: DiffFile,
+    pub patch: String,
+}
+
+/// Which whitespace differences a diff should ignore
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum WhitespaceMode {
+    /// Show every whitespace change
+    #[default]
+    None,
+    /// Ignore all whitespace (`git diff -w`)
+    IgnoreAll,
+    /// Ignore changes in the amount of whitespace (`git diff -b`)
+    IgnoreChange,
+    /// Ignore whitespace at end of line (`--ignore-space-at-eol`)
+    IgnoreEol,
+}
+
+/// Base options shared by the diff functions: 3 lines of context plus `whitespace`
+fn diff_options(whitespace: WhitespaceMode) -> DiffOptions {
+    let mut opts = DiffOptions::new();
+    opts.context_lines(3);
+    match whitespace {
+        WhitespaceMode::None => {}
+        WhitespaceMode::IgnoreAll => {
+            opts.ignore_whitespace(true);
+        }
+        WhitespaceMode::IgnoreChange => {
+            opts.ignore_whitespace_change(true);
+        }
+        WhitespaceMode::IgnoreEol => {
+            opts.ignore_whitespace_eol(true);
+        }
+    }
+    opts
+}
+
 /// Configure and run rename/copy detection on a diff
 fn detect_renames_and_copies(diff: &mut Diff) -> Result<(), GitError> {
     let mut find_opts = DiffFindOptions::new();
@@ -68,6 +131,176 @@ fn detect_renames_and_copies(diff: &mut Diff) -> Result<(), GitError> {
 
 /// Get diff for a specific commit compared to its parent
 pub fn get_commit_diff(repo: &Repository, commit_id: &str) -> Result<UnifiedDiff, GitError> {
+    get_commit_diff_capped(repo, commit_id, None, WhitespaceMode::None, false)
+}
+
+/// `get_commit_diff` with per-file patches over `max_patch_bytes` replaced by a placeholder
+/// and whitespace differences ignored according to `whitespace`. With `function_context`,
+/// hunks are widened to the whole enclosing function (`git diff -W`).
+pub fn get_commit_diff_capped(
+    repo: &Repository,
+    commit_id: &str,
+    max_patch_bytes: Option<usize>,
+    whitespace: WhitespaceMode,
+    function_context: bool,
+) -> Result<UnifiedDiff, GitError> {
+    let diff = commit_diff(repo, commit_id, whitespace, &[])?;
+    if !function_context {
+        return diff_to_unified_capped(&diff, Some(repo), max_patch_bytes, whitespace);
+    }
+
+    let patches = function_context_patches(repo, commit_id, whitespace)?;
+    diff_to_unified_with(&diff, Some(repo), max_patch_bytes, whitespace, |idx| {
+        let delta = diff.get_delta(idx)?;
+        let path = delta.new_file().path().or_else(|| delta.old_file().path())?;
+        patches.get(path).cloned()
+    })
+}
+
+/// Per-file patches for a commit from `git diff --function-context`, keyed by new path
+/// (old path for deletions). libgit2 can't widen hunks to function boundaries, so this
+/// shells out; file metadata still comes from the libgit2 diff.
+fn function_context_patches(
+    repo: &Repository,
+    commit_id: &str,
+    whitespace: WhitespaceMode,
+) -> Result<HashMap<PathBuf, String>, GitError> {
+    let commit = repo.find_commit(git2::Oid::from_str(commit_id)?)?;
+    // Root commits diff against the empty tree, which git knows without it being stored
+    let parent = match commit.parent_id(0) {
+        Ok(oid) => oid,
+        Err(_) => git2::Oid::hash_object(git2::ObjectType::Tree, &[])?,
+    };
+    let workdir = repo.workdir().unwrap_or_else(|| repo.path());
+
+    let mut cmd = git_command();
+    cmd.args(["-c", "core.quotePath=false", "diff", "--no-color", "--no-ext-diff", "--no-textconv"])
+        // Same rename/copy detection as `detect_renames_and_copies`
+        .args(["--function-context", "-M50%", "-C50%", "--find-copies-harder"])
+        // Pin the path prefixes and root so diff.noprefix, diff.mnemonicPrefix and
+        // diff.relative can't change the headers split_patch_by_file looks for
+        .args(["--src-prefix=a/", "--dst-prefix=b/", "--no-relative"]);
+    match whitespace {
+        WhitespaceMode::None => {}
+        WhitespaceMode::IgnoreAll => {
+            cmd.arg("-w");
+        }
+        WhitespaceMode::IgnoreChange => {
+            cmd.arg("-b");
+        }
+        WhitespaceMode::IgnoreEol => {
+            cmd.arg("--ignore-space-at-eol");
+        }
+    }
+    let output = cmd
+        .arg(parent.to_string())
+        .arg(commit.id().to_string())
+        .current_dir(workdir)
+        .output()
+        .map_err(|e| git2::Error::from_str(&format!("Failed to run git diff: {}", e)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(git2::Error::from_str(&format!("git diff failed: {}", stderr)).into());
+    }
+
+    Ok(split_patch_by_file(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// The path from a `diff --git a/<path> b/<path>` header when both sides are the same,
+/// which holds for everything but renames and copies (those carry their own lines)
+fn header_path(line: &str) -> Option<&str> {
+    let rest = line.strip_prefix("diff --git a/")?;
+    let len = rest.len().checked_sub(3)? / 2;
+    let (old, new) = (rest.get(..len)?, rest.get(len..)?);
+    (new.strip_prefix(" b/")? == old).then_some(old)
+}
+
+/// Split `git diff` output into per-file patches keyed by the file's new path
+fn split_patch_by_file(patch: &str) -> HashMap<PathBuf, String> {
+    let mut files = HashMap::new();
+
+    let mut chunks: Vec<String> = Vec::new();
+    for line in patch.split_inclusive('\n') {
+        match chunks.last_mut() {
+            Some(chunk) if !line.starts_with("diff --git ") => chunk.push_str(line),
+            _ => chunks.push(line.to_string()),
+        }
+    }
+
+    for chunk in chunks {
+        // Mode-only and binary changes have no ---/+++ lines, only the header
+        let mut old_path = chunk.lines().next().and_then(header_path).map(str::to_string);
+        let mut new_path = None;
+        for line in chunk.lines() {
+            if line.starts_with("@@") {
+                break;
+            }
+            if let Some(path) = line.strip_prefix("--- a/").or_else(|| line.strip_prefix("rename from ")) {
+                old_path = Some(path.to_string());
+            } else if let Some(path) = line
+                .strip_prefix("+++ b/")
+                .or_else(|| line.strip_prefix("rename to "))
+                .or_else(|| line.strip_prefix("copy to "))
+            {
+                new_path = Some(path.to_string());
+            }
+        }
+        if let Some(path) = new_path.or(old_path) {
+            files.insert(PathBuf::from(path), chunk);
+        }
+    }
+
+    files
+}
+
+/// Get the list of files changed in a commit without generating patch text.
+/// Cheaper than `get_commit_diff` for large commits when only a file tree is needed.
+pub fn get_commit_files(repo: &Repository, commit_id: &str) -> Result<Vec<DiffFile>, GitError> {
+    let diff = commit_diff(repo, commit_id, WhitespaceMode::None, &[])?;
+    Ok(diff_files(&diff, Some(repo)))
+}
+
+/// Totals for a commit against its first parent, without generating patch text
+pub fn get_diff_stats(repo: &Repository, commit_id: &str) -> Result<DiffStats, GitError> {
+    let diff = commit_diff(repo, commit_id, WhitespaceMode::None, &[])?;
+    diff_stats(&diff)
+}
+
+/// Totals for staged (HEAD to index) or unstaged (index to workdir) changes
+pub fn get_working_diff_stats(repo: &Repository, staged: bool) -> Result<DiffStats, GitError> {
+    let diff = working_diff(repo, staged, WhitespaceMode::None, &[])?;
+    diff_stats(&diff)
+}
+
+fn diff_stats(diff: &Diff) -> Result<DiffStats, GitError> {
+    let stats = diff.stats()?;
+    Ok(DiffStats {
+        files_changed: stats.files_changed(),
+        insertions: stats.insertions(),
+        deletions: stats.deletions(),
+    })
+}
+
+/// `get_commit_diff` restricted to `paths` (pathspecs: files or directories).
+/// Renames across the pathspec boundary show up as additions or deletions.
+pub fn get_commit_diff_for_paths(
+    repo: &Repository,
+    commit_id: &str,
+    paths: &[String],
+) -> Result<UnifiedDiff, GitError> {
+    let diff = commit_diff(repo, commit_id, WhitespaceMode::None, paths)?;
+    diff_to_unified_capped(&diff, Some(repo), None, WhitespaceMode::None)
+}
+
+/// Diff a commit against its first parent (or the empty tree for root commits),
+/// with rename/copy detection applied. Empty `paths` means the whole tree.
+fn commit_diff<'a>(
+    repo: &'a Repository,
+    commit_id: &str,
+    whitespace: WhitespaceMode,
+    paths: &[String],
+) -> Result<Diff<'a>, GitError> {
     let oid = git2::Oid::from_str(commit_id)?;
     let commit = repo.find_commit(oid)?;
     let tree = commit.tree()?;
@@ -78,15 +311,55 @@ pub fn get_commit_diff(repo: &Repository, commit_id: &str) -> Result<UnifiedDiff
         None
     };
 
-    let mut opts = DiffOptions::new();
-    opts.context_lines(3);
+    let mut opts = diff_options(whitespace);
+    for path in paths {
+        opts.pathspec(path);
+    }
 
     let mut diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut opts))?;
     
     // Run rename/copy detection
     detect_renames_and_copies(&mut diff)?;
 
-    diff_to_unified(&diff, Some(repo))
+    Ok(diff)
+}
+
+/// Get the changes stored in a stash entry, diffed against the commit it was created on.
+/// Untracked files (stored in the stash's third parent) are included as additions.
+pub fn get_stash_diff(repo: &Repository, stash_index: usize) -> Result<UnifiedDiff, GitError> {
+    get_stash_diff_capped(repo, stash_index, None, WhitespaceMode::None)
+}
+
+/// `get_stash_diff` with per-file patches over `max_patch_bytes` replaced by a placeholder
+/// and whitespace differences ignored according to `whitespace`
+pub fn get_stash_diff_capped(
+    repo: &Repository,
+    stash_index: usize,
+    max_patch_bytes: Option<usize>,
+    whitespace: WhitespaceMode,
+) -> Result<UnifiedDiff, GitError> {
+    let stash_commit = repo
+        .revparse_single(&format!("stash@{{{}}}", stash_index))
+        .map_err(|_| GitError::Validation(format!("Stash entry {} does not exist", stash_index)))?
+        .peel_to_commit()?;
+    let base_tree = stash_commit.parent(0)?.tree()?;
+    let stash_tree = stash_commit.tree()?;
+
+    let mut opts = diff_options(whitespace);
+
+    let mut diff = repo.diff_tree_to_tree(Some(&base_tree), Some(&stash_tree), Some(&mut opts))?;
+
+    // Stashes created with --include-untracked store untracked files in a third parent
+    if stash_commit.parent_count() > 2 {
+        let untracked_tree = stash_commit.parent(2)?.tree()?;
+        let untracked_diff = repo.diff_tree_to_tree(None, Some(&untracked_tree), Some(&mut opts))?;
+        diff.merge(&untracked_diff)?;
+    }
+
+    // Run rename/copy detection
+    detect_renames_and_copies(&mut diff)?;
+
+    diff_to_unified_capped(&diff, Some(repo), max_patch_bytes, whitespace)
 }
 
 /// Get diff for a specific file in a commit
@@ -94,6 +367,18 @@ pub fn get_file_diff(
     repo: &Repository,
     commit_id: &str,
     file_path: &str,
+    whitespace: WhitespaceMode,
+) -> Result<FileDiff, GitError> {
+    get_file_diff_capped(repo, commit_id, file_path, None, whitespace)
+}
+
+/// `get_file_diff` with a patch over `max_patch_bytes` replaced by a placeholder
+pub fn get_file_diff_capped(
+    repo: &Repository,
+    commit_id: &str,
+    file_path: &str,
+    max_patch_bytes: Option<usize>,
+    whitespace: WhitespaceMode,
 ) -> Result<FileDiff, GitError> {
     let oid = git2::Oid::from_str(commit_id)?;
     let commit = repo.find_commit(oid)?;
@@ -105,16 +390,16 @@ pub fn get_file_diff(
         None
     };
 
-    let mut opts = DiffOptions::new();
-    opts.context_lines(3);
-    opts.pathspec(file_path);
+    // No pathspec here: restricting to one path before rename detection would hide
+    // the other side of a rename, so filter by old/new path afterwards instead
+    let mut opts = diff_options(whitespace);
 
     let mut diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut opts))?;
     
     // Run rename/copy detection (in case file was renamed)
     detect_renames_and_copies(&mut diff)?;
 
-    let patch_text = generate_patch_text(&diff, Some(repo))?;
+    let patch_text = generate_patch_text_for_path(&diff, Some(repo), file_path, max_patch_bytes)?;
 
     Ok(FileDiff {
         path: file_path.to_string(),
@@ -124,8 +409,44 @@ pub fn get_file_diff(
 
 /// Get diff for working directory changes (staged and unstaged)
 pub fn get_working_diff(repo: &Repository, staged: bool) -> Result<UnifiedDiff, GitError> {
-    let mut opts = DiffOptions::new();
-    opts.context_lines(3);
+    get_working_diff_capped(repo, staged, None, WhitespaceMode::None)
+}
+
+/// `get_working_diff` with per-file patches over `max_patch_bytes` replaced by a placeholder
+/// and whitespace differences ignored according to `whitespace`
+pub fn get_working_diff_capped(
+    repo: &Repository,
+    staged: bool,
+    max_patch_bytes: Option<usize>,
+    whitespace: WhitespaceMode,
+) -> Result<UnifiedDiff, GitError> {
+    let diff = working_diff(repo, staged, whitespace, &[])?;
+    diff_to_unified_capped(&diff, Some(repo), max_patch_bytes, whitespace)
+}
+
+/// `get_working_diff` restricted to `paths` (pathspecs: files or directories)
+pub fn get_working_diff_for_paths(
+    repo: &Repository,
+    staged: bool,
+    paths: &[String],
+) -> Result<UnifiedDiff, GitError> {
+    let diff = working_diff(repo, staged, WhitespaceMode::None, paths)?;
+    diff_to_unified_capped(&diff, Some(repo), None, WhitespaceMode::None)
+}
+
+/// Diff HEAD to index (`staged`) or index to workdir, with rename/copy detection applied.
+/// Empty `paths` means the whole tree.
+fn working_diff<'a>(
+    repo: &'a Repository,
+    staged: bool,
+    whitespace: WhitespaceMode,
+    paths: &[String],
+) -> Result<Diff<'a>, GitError> {
+    super::require_workdir(repo)?;
+    let mut opts = diff_options(whitespace);
+    for path in paths {
+        opts.pathspec(path);
+    }
 
     let mut diff = if staged {
         // Staged changes: HEAD to index
@@ -141,60 +462,157 @@ pub fn get_working_diff(repo: &Repository, staged: bool) -> Result<UnifiedDiff,
     // Run rename/copy detection
     detect_renames_and_copies(&mut diff)?;
 
-    diff_to_unified(&diff, Some(repo))
+    Ok(diff)
+}
+
+/// Diff a ref's tree against the working directory (through the index), so both
+/// committed and uncommitted changes since that ref show up, untracked files included
+pub fn get_workdir_vs_ref_diff(repo: &Repository, ref_name: &str) -> Result<UnifiedDiff, GitError> {
+    get_workdir_vs_ref_diff_capped(repo, ref_name, None, WhitespaceMode::None)
 }
 
-/// Resolve a ref string (branch name, tag, commit hash) to a tree
-fn resolve_ref_to_tree<'a>(repo: &'a Repository, ref_str: &str) -> Result<git2::Tree<'a>, GitError> {
+/// `get_workdir_vs_ref_diff` with per-file patches over `max_patch_bytes` replaced by a
+/// placeholder and whitespace differences ignored according to `whitespace`
+pub fn get_workdir_vs_ref_diff_capped(
+    repo: &Repository,
+    ref_name: &str,
+    max_patch_bytes: Option<usize>,
+    whitespace: WhitespaceMode,
+) -> Result<UnifiedDiff, GitError> {
+    super::require_workdir(repo)?;
+    let tree = resolve_ref_to_commit(repo, ref_name)?.tree()?;
+
+    let mut opts = diff_options(whitespace);
+    opts.include_untracked(true);
+
+    let mut diff = repo.diff_tree_to_workdir_with_index(Some(&tree), Some(&mut opts))?;
+
+    // Run rename/copy detection
+    detect_renames_and_copies(&mut diff)?;
+
+    diff_to_unified_capped(&diff, Some(repo), max_patch_bytes, whitespace)
+}
+
+/// Resolve a ref string (branch name, tag, commit hash) to a commit
+fn resolve_ref_to_commit<'a>(repo: &'a Repository, ref_str: &str) -> Result<git2::Commit<'a>, GitError> {
     // Try as a branch first
     if let Ok(branch) = repo.find_branch(ref_str, git2::BranchType::Local) {
         if let Some(target) = branch.get().target() {
-            let commit = repo.find_commit(target)?;
-            return Ok(commit.tree()?);
+            return Ok(repo.find_commit(target)?);
         }
     }
     
     // Try as a remote branch
     if let Ok(branch) = repo.find_branch(ref_str, git2::BranchType::Remote) {
         if let Some(target) = branch.get().target() {
-            let commit = repo.find_commit(target)?;
-            return Ok(commit.tree()?);
+            return Ok(repo.find_commit(target)?);
         }
     }
     
     // Try as a reference (refs/heads/..., refs/tags/..., etc.)
     if let Ok(reference) = repo.find_reference(ref_str) {
-        let commit = reference.peel_to_commit()?;
-        return Ok(commit.tree()?);
+        return Ok(reference.peel_to_commit()?);
     }
     
     // Try as refs/heads/{ref}
     if let Ok(reference) = repo.find_reference(&format!("refs/heads/{}", ref_str)) {
-        let commit = reference.peel_to_commit()?;
-        return Ok(commit.tree()?);
+        return Ok(reference.peel_to_commit()?);
     }
     
     // Try as a commit hash (full or short)
     let oid = git2::Oid::from_str(ref_str)
         .or_else(|_| repo.revparse_single(ref_str).and_then(|obj| Ok(obj.id())))?;
-    let commit = repo.find_commit(oid)?;
-    Ok(commit.tree()?)
+    Ok(repo.find_commit(oid)?)
+}
+
+/// How to pick the "before" side of a compare
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum CompareMode {
+    /// `base..head`: diff the base tree directly against the head tree
+    #[default]
+    TwoDot,
+    /// `base...head`: diff from the merge-base of base and head to head, i.e. only
+    /// the changes made on head's side (PR-style)
+    ThreeDot,
+}
+
+/// Resolve the base and head trees for a compare, honoring `mode`
+fn resolve_compare_trees<'a>(
+    repo: &'a Repository,
+    base_ref: &str,
+    head_ref: &str,
+    mode: CompareMode,
+) -> Result<(git2::Tree<'a>, git2::Tree<'a>), GitError> {
+    let base_commit = resolve_ref_to_commit(repo, base_ref)?;
+    let head_commit = resolve_ref_to_commit(repo, head_ref)?;
+
+    let base_tree = match mode {
+        CompareMode::TwoDot => base_commit.tree()?,
+        CompareMode::ThreeDot => {
+            let merge_base = repo.merge_base(base_commit.id(), head_commit.id()).map_err(|_| {
+                GitError::Validation(format!("'{}' and '{}' have no common ancestor", base_ref, head_ref))
+            })?;
+            repo.find_commit(merge_base)?.tree()?
+        }
+    };
+
+    Ok((base_tree, head_commit.tree()?))
 }
 
 /// Get diff comparing two refs (branches, tags, or commit hashes)
-pub fn get_compare_diff(repo: &Repository, base_ref: &str, head_ref: &str) -> Result<UnifiedDiff, GitError> {
-    let base_tree = resolve_ref_to_tree(repo, base_ref)?;
-    let head_tree = resolve_ref_to_tree(repo, head_ref)?;
+pub fn get_compare_diff(
+    repo: &Repository,
+    base_ref: &str,
+    head_ref: &str,
+    mode: CompareMode,
+) -> Result<UnifiedDiff, GitError> {
+    get_compare_diff_capped(repo, base_ref, head_ref, mode, None, WhitespaceMode::None)
+}
+
+/// `get_compare_diff` with per-file patches over `max_patch_bytes` replaced by a placeholder
+/// and whitespace differences ignored according to `whitespace`
+pub fn get_compare_diff_capped(
+    repo: &Repository,
+    base_ref: &str,
+    head_ref: &str,
+    mode: CompareMode,
+    max_patch_bytes: Option<usize>,
+    whitespace: WhitespaceMode,
+) -> Result<UnifiedDiff, GitError> {
+    let (base_tree, head_tree) = resolve_compare_trees(repo, base_ref, head_ref, mode)?;
     
-    let mut opts = DiffOptions::new();
-    opts.context_lines(3);
+    let mut opts = diff_options(whitespace);
     
     let mut diff = repo.diff_tree_to_tree(Some(&base_tree), Some(&head_tree), Some(&mut opts))?;
     
     // Run rename/copy detection
     detect_renames_and_copies(&mut diff)?;
     
-    diff_to_unified(&diff, Some(repo))
+    diff_to_unified_capped(&diff, Some(repo), max_patch_bytes, whitespace)
+}
+
+/// `get_compare_diff` restricted to `paths` (pathspecs: files or directories)
+pub fn get_compare_diff_for_paths(
+    repo: &Repository,
+    base_ref: &str,
+    head_ref: &str,
+    mode: CompareMode,
+    paths: &[String],
+) -> Result<UnifiedDiff, GitError> {
+    let (base_tree, head_tree) = resolve_compare_trees(repo, base_ref, head_ref, mode)?;
+
+    let mut opts = diff_options(WhitespaceMode::None);
+    for path in paths {
+        opts.pathspec(path);
+    }
+
+    let mut diff = repo.diff_tree_to_tree(Some(&base_tree), Some(&head_tree), Some(&mut opts))?;
+
+    // Run rename/copy detection
+    detect_renames_and_copies(&mut diff)?;
+
+    diff_to_unified_capped(&diff, Some(repo), None, WhitespaceMode::None)
 }
 
 /// Get diff for a specific file comparing two refs
@@ -203,20 +621,32 @@ pub fn get_compare_file_diff(
     base_ref: &str,
     head_ref: &str,
     file_path: &str,
+    mode: CompareMode,
+    whitespace: WhitespaceMode,
 ) -> Result<FileDiff, GitError> {
-    let base_tree = resolve_ref_to_tree(repo, base_ref)?;
-    let head_tree = resolve_ref_to_tree(repo, head_ref)?;
+    get_compare_file_diff_capped(repo, base_ref, head_ref, file_path, mode, None, whitespace)
+}
+
+/// `get_compare_file_diff` with a patch over `max_patch_bytes` replaced by a placeholder
+pub fn get_compare_file_diff_capped(
+    repo: &Repository,
+    base_ref: &str,
+    head_ref: &str,
+    file_path: &str,
+    mode: CompareMode,
+    max_patch_bytes: Option<usize>,
+    whitespace: WhitespaceMode,
+) -> Result<FileDiff, GitError> {
+    let (base_tree, head_tree) = resolve_compare_trees(repo, base_ref, head_ref, mode)?;
     
-    let mut opts = DiffOptions::new();
-    opts.context_lines(3);
-    opts.pathspec(file_path);
+    let mut opts = diff_options(whitespace);
     
     let mut diff = repo.diff_tree_to_tree(Some(&base_tree), Some(&head_tree), Some(&mut opts))?;
     
     // Run rename/copy detection (in case file was renamed)
     detect_renames_and_copies(&mut diff)?;
     
-    let patch_text = generate_patch_text(&diff, Some(repo))?;
+    let patch_text = generate_patch_text_for_path(&diff, Some(repo), file_path, max_patch_bytes)?;
     
     Ok(FileDiff {
         path: file_path.to_string(),
@@ -224,45 +654,98 @@ pub fn get_compare_file_diff(
     })
 }
 
-/// Generate proper unified diff patch text using Patch::to_buf for each delta
-fn generate_patch_text(diff: &Diff, repo: Option<&Repository>) -> Result<String, GitError> {
+/// Get the diff of a single file between two commits (not necessarily parent and child).
+/// `file_path` may be either side's path of a rename. Files that only exist in one of
+/// the commits are reported as added or deleted.
+pub fn get_commit_pair_file_diff(
+    repo: &Repository,
+    base_commit: &str,
+    head_commit: &str,
+    file_path: &str,
+) -> Result<CommitPairFileDiff, GitError> {
+    let base_tree = repo.revparse_single(base_commit)?.peel_to_commit()?.tree()?;
+    let head_tree = repo.revparse_single(head_commit)?.peel_to_commit()?.tree()?;
+
+    let mut opts = DiffOptions::new();
+    opts.context_lines(3);
+
+    let mut diff = repo.diff_tree_to_tree(Some(&base_tree), Some(&head_tree), Some(&mut opts))?;
+
+    // Run rename/copy detection (in case file was renamed)
+    detect_renames_and_copies(&mut diff)?;
+
+    let target = Path::new(file_path);
+    let idx = diff
+        .deltas()
+        .position(|delta| delta.old_file().path() == Some(target) || delta.new_file().path() == Some(target))
+        .ok_or_else(|| {
+            GitError::Validation(format!(
+                "File '{}' does not differ between {} and {}",
+                file_path, base_commit, head_commit
+            ))
+        })?;
+
+    let file = diff_files(&diff, Some(repo)).swap_remove(idx);
+    let patch = delta_patch_text(&diff, idx, Some(repo)).unwrap_or_default();
+
+    Ok(CommitPairFileDiff { file, patch })
+}
+
+/// Generate patch text only for deltas whose old or new path matches `file_path`,
+/// so a renamed file can be requested by either name
+fn generate_patch_text_for_path(
+    diff: &Diff,
+    repo: Option<&Repository>,
+    file_path: &str,
+    max_patch_bytes: Option<usize>,
+) -> Result<String, GitError> {
+    let target = Path::new(file_path);
     let mut patch_text = String::new();
 
-    // Generate patch for each file
-    let num_deltas = diff.deltas().len();
-    for idx in 0..num_deltas {
-        let delta = diff.get_delta(idx);
-        
-        // Try to get patch from git2
-        let mut got_patch = false;
-        if let Ok(Some(mut patch)) = git2::Patch::from_diff(diff, idx) {
-            // git2 may return a patch with 0 hunks for untracked files
-            if patch.num_hunks() > 0 {
-                if let Ok(buf) = patch.to_buf() {
-                    if !buf.is_empty() {
-                        // Use lossy conversion to avoid silently dropping content
-                        patch_text.push_str(&String::from_utf8_lossy(&buf));
-                        got_patch = true;
-                    }
-                }
+    for (idx, delta) in diff.deltas().enumerate() {
+        let matches = delta.old_file().path() == Some(target) || delta.new_file().path() == Some(target);
+        if !matches {
+            continue;
+        }
+        if let Some(text) = delta_patch_text(diff, idx, repo) {
+            if max_patch_bytes.is_some_and(|max_bytes| text.len() > max_bytes) {
+                patch_text.push_str(&truncated_patch_placeholder(&text));
+            } else {
+                patch_text.push_str(&text);
             }
         }
-        
-        // If git2 didn't give us a patch, generate manually for untracked files
-        if !got_patch {
-            if let Some(delta) = delta {
-                if delta.status() == git2::Delta::Untracked {
-                    if let Some(path) = delta.new_file().path() {
-                        if let Some(manual_patch) = generate_untracked_file_patch(repo, path) {
-                            patch_text.push_str(&manual_patch);
-                        }
-                    }
+    }
+
+    Ok(patch_text)
+}
+
+fn delta_patch_text(diff: &Diff, idx: usize, repo: Option<&Repository>) -> Option<String> {
+    // Try to get patch from git2
+    if let Ok(Some(mut patch)) = git2::Patch::from_diff(diff, idx) {
+        // git2 may return a patch with 0 hunks for untracked files
+        if patch.num_hunks() > 0 {
+            if let Ok(buf) = patch.to_buf() {
+                if !buf.is_empty() {
+                    // Use lossy conversion to avoid silently dropping content
+                    return Some(String::from_utf8_lossy(&buf).into_owned());
                 }
             }
         }
     }
 
-    Ok(patch_text)
+    // If git2 didn't give us a patch, generate manually for untracked files
+    let delta = diff.get_delta(idx)?;
+    if delta.status() == git2::Delta::Untracked {
+        let path = delta.new_file().path()?;
+        return generate_untracked_file_patch(repo, path);
+    }
+
+    // Binary files have no hunks, and git2 prints an unhashed workdir side as /dev/null
+    if delta.flags().is_binary() {
+        return binary_patch_stub(&delta);
+    }
+
+    None
 }
 
 /// Generate a unified diff patch for an untracked file (showing all lines as additions)
@@ -275,8 +758,9 @@ fn generate_untracked_file_patch(repo: Option<&Repository>, path: &Path) -> Opti
     // Read file as bytes to handle both text and binary files
     let bytes = std::fs::read(&full_path).ok()?;
     
-    // Determine if binary: contains null bytes OR is not valid UTF-8
-    let is_binary = bytes.contains(&0u8) || std::str::from_utf8(&bytes).is_err();
+    // .gitattributes wins; otherwise binary means null bytes OR invalid UTF-8
+    let is_binary = attribute_binary(repo, path)
+        .unwrap_or_else(|| bytes.contains(&0u8) || std::str::from_utf8(&bytes).is_err());
     
     if is_binary {
         // Generate a standard binary diff stub
@@ -325,6 +809,36 @@ fn generate_untracked_file_patch(repo: Option<&Repository>, path: &Path) -> Opti
     Some(patch)
 }
 
+/// `git diff`'s "Binary files ... differ" patch for a tracked binary file
+fn binary_patch_stub(delta: &git2::DiffDelta) -> Option<String> {
+    let old_path = delta.old_file().path().or(delta.new_file().path())?.display();
+    let new_path = delta.new_file().path().or(delta.old_file().path())?.display();
+    let old_side = match delta.status() {
+        git2::Delta::Added => "/dev/null".to_string(),
+        _ => format!("a/{}", old_path),
+    };
+    let new_side = match delta.status() {
+        git2::Delta::Deleted => "/dev/null".to_string(),
+        _ => format!("b/{}", new_path),
+    };
+    Some(format!(
+        "diff --git a/{} b/{}\nBinary files {} and {} differ\n",
+        old_path, new_path, old_side, new_side
+    ))
+}
+
+/// Binary/text setting for `path` from its `diff` attribute, the only one `git diff`
+/// consults (`binary` expands to `-diff -merge -text`). None when it's unset or names a
+/// driver, so content heuristics apply.
+fn attribute_binary(repo: &Repository, path: &Path) -> Option<bool> {
+    let flags = git2::AttrCheckFlags::FILE_THEN_INDEX;
+    match git2::AttrValue::from_string(repo.get_attr(path, "diff", flags).ok().flatten()) {
+        git2::AttrValue::False => Some(true),
+        git2::AttrValue::True => Some(false),
+        _ => None,
+    }
+}
+
 /// Check if a file mode indicates a symlink (mode 0o120000)
 fn is_symlink_mode(mode: u32) -> bool {
     // Symlink mode is 0o120000 (S_IFLNK)
@@ -337,7 +851,91 @@ fn is_submodule_mode(mode: u32) -> bool {
     (mode & 0o170000) == 0o160000
 }
 
-fn diff_to_unified(diff: &Diff, repo: Option<&Repository>) -> Result<UnifiedDiff, GitError> {
+fn diff_to_unified_capped(
+    diff: &Diff,
+    repo: Option<&Repository>,
+    max_patch_bytes: Option<usize>,
+    whitespace: WhitespaceMode,
+) -> Result<UnifiedDiff, GitError> {
+    diff_to_unified_with(diff, repo, max_patch_bytes, whitespace, |idx| {
+        delta_patch_text(diff, idx, repo)
+    })
+}
+
+/// Build a `UnifiedDiff` from `diff`'s file metadata, taking each delta's patch text
+/// from `patch_for`
+fn diff_to_unified_with(
+    diff: &Diff,
+    repo: Option<&Repository>,
+    max_patch_bytes: Option<usize>,
+    whitespace: WhitespaceMode,
+    patch_for: impl Fn(usize) -> Option<String>,
+) -> Result<UnifiedDiff, GitError> {
+    let mut files = diff_files(diff, repo);
+
+    let mut patch_text = String::new();
+    for idx in 0..diff.deltas().len() {
+        let Some(text) = patch_for(idx) else { continue };
+        if max_patch_bytes.is_some_and(|max_bytes| text.len() > max_bytes) {
+            patch_text.push_str(&truncated_patch_placeholder(&text));
+            if let Some(file) = files.get_mut(idx) {
+                file.truncated = true;
+            }
+        } else {
+            patch_text.push_str(&text);
+        }
+    }
+
+    // Files whose only changes were ignored whitespace have no hunks left (and no patch
+    // text), so drop them from the file list too
+    if whitespace != WhitespaceMode::None {
+        let mut idx = 0;
+        files.retain(|_| {
+            let keep = !is_ignored_whitespace_change(diff, idx);
+            idx += 1;
+            keep
+        });
+    }
+
+    Ok(UnifiedDiff {
+        files,
+        patch: patch_text,
+    })
+}
+
+/// A modified file with no hunks, same mode and no binary content: every line change in it
+/// was filtered out by the whitespace options
+fn is_ignored_whitespace_change(diff: &Diff, idx: usize) -> bool {
+    let Some(delta) = diff.get_delta(idx) else { return false };
+    if delta.status() != git2::Delta::Modified
+        || delta.old_file().mode() != delta.new_file().mode()
+        || delta.old_file().is_binary()
+        || delta.new_file().is_binary()
+    {
+        return false;
+    }
+    matches!(git2::Patch::from_diff(diff, idx), Ok(Some(patch)) if patch.num_hunks() == 0)
+}
+
+/// Keep a file patch's headers but replace its hunks with a single placeholder hunk
+fn truncated_patch_placeholder(patch: &str) -> String {
+    let hunks_start = patch
+        .find("\n@@ ")
+        .map(|pos| pos + 1)
+        .unwrap_or(patch.len());
+    let (header, body) = patch.split_at(hunks_start);
+    let line_count = body.lines().filter(|line| !line.starts_with("@@ ")).count();
+
+    let mut text = header.to_string();
+    if !text.is_empty() && !text.ends_with('\n') {
+        text.push('\n');
+    }
+    text.push_str(&format!("@@ -1,1 +1,1 @@\n Large diff truncated, {} lines\n", line_count));
+    text
+}
+
+/// Build per-file metadata (status, stats, modes) for every delta in a diff
+fn diff_files(diff: &Diff, repo: Option<&Repository>) -> Vec<DiffFile> {
     let mut files = Vec::new();
 
     let num_deltas = diff.deltas().len();
@@ -393,7 +991,17 @@ fn diff_to_unified(diff: &Diff, repo: Option<&Repository>) -> Result<UnifiedDiff
         let new_file = delta.new_file();
         
         // Binary detection: check flags on both old and new files
-        let is_binary = old_file.is_binary() || new_file.is_binary();
+        // git2 only learns untracked files' attributes once it loads their content
+        let is_binary = old_file.is_binary()
+            || new_file.is_binary()
+            || (delta.status() == git2::Delta::Untracked
+                && repo.zip(new_file.path()).and_then(|(r, p)| attribute_binary(r, p)) == Some(true));
+        // Binary patches carry no line counts, so report the size change instead
+        let (old_size, new_size) = if is_binary {
+            (diff_file_size(repo, &old_file), diff_file_size(repo, &new_file))
+        } else {
+            (None, None)
+        };
         
         // File modes - convert FileMode to u32 (only include if non-zero/meaningful)
         let old_mode_raw: u32 = old_file.mode().into();
@@ -430,20 +1038,32 @@ fn diff_to_unified(diff: &Diff, repo: Option<&Repository>) -> Result<UnifiedDiff
             additions,
             deletions,
             is_binary,
+            old_size,
+            new_size,
             old_mode,
             new_mode,
             similarity,
             is_symlink,
             is_submodule,
+            truncated: false,
         });
     }
 
-    let patch_text = generate_patch_text(diff, repo)?;
+    files
+}
 
-    Ok(UnifiedDiff {
-        files,
-        patch: patch_text,
-    })
+/// Size in bytes of one side of a delta: its blob if it's in the object database,
+/// else the working tree file. None when that side doesn't exist.
+fn diff_file_size(repo: Option<&Repository>, file: &git2::DiffFile) -> Option<u64> {
+    if !file.exists() {
+        return None;
+    }
+    let repo = repo?;
+    if let Ok(blob) = repo.find_blob(file.id()) {
+        return Some(blob.size() as u64);
+    }
+    let path = file.path()?;
+    std::fs::metadata(repo.workdir()?.join(path)).ok().map(|m| m.len())
 }
 
 /// Count lines in an untracked file for stats
@@ -458,6 +1078,10 @@ fn count_file_lines(repo: Option<&Repository>, path: &Path) -> (usize, usize) {
         None => return (0, 0),
     };
     
+    if attribute_binary(repo, path) == Some(true) {
+        return (0, 0);
+    }
+
     let full_path = workdir.join(path);
     
     // Read as bytes to handle both text and binary files
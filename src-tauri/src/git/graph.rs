@@ -2,7 +2,7 @@ use git2::Repository;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use super::GitError;
+use super::{CancellationToken, GitError};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -10,6 +10,26 @@ pub struct GraphNode {
     pub commit_id: String,
     pub column: usize,
     pub connections: Vec<GraphConnection>,
+    /// Branches, remote-tracking refs and tags pointing at this commit
+    #[serde(default)]
+    pub decorations: Vec<RefDecoration>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub enum RefKind {
+    LocalBranch,
+    RemoteBranch,
+    Tag,
+}
+
+/// A ref label to render on a graph node
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RefDecoration {
+    /// Short name, e.g. "main", "origin/main" or "v1.0"
+    pub name: String,
+    pub kind: RefKind,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -33,6 +53,15 @@ pub struct CommitGraph {
 pub fn build_commit_graph(
     repo: &Repository,
     commit_ids: &[String],
+) -> Result<CommitGraph, GitError> {
+    build_commit_graph_cancellable(repo, commit_ids, &CancellationToken::new())
+}
+
+/// `build_commit_graph` that stops early with `GitError::Cancelled` when `cancel` is triggered
+pub fn build_commit_graph_cancellable(
+    repo: &Repository,
+    commit_ids: &[String],
+    cancel: &CancellationToken,
 ) -> Result<CommitGraph, GitError> {
     use std::time::Instant;
     let start = Instant::now();
@@ -44,6 +73,7 @@ pub fn build_commit_graph(
         });
     }
 
+    let mut decorations = get_ref_decorations(repo)?;
     let mut nodes = Vec::new();
     let mut active_columns: Vec<Option<String>> = vec![]; // Track which commit each column is waiting for
     let mut commit_to_row: HashMap<String, usize> = HashMap::new();
@@ -54,6 +84,7 @@ pub fn build_commit_graph(
     }
 
     for (_row, commit_id) in commit_ids.iter().enumerate() {
+        cancel.check()?;
         let commit = repo.find_commit(git2::Oid::from_str(commit_id)?)?;
         let parent_ids: Vec<String> = commit.parent_ids().map(|id| id.to_string()).collect();
 
@@ -105,6 +136,7 @@ pub fn build_commit_graph(
             commit_id: commit_id.clone(),
             column,
             connections,
+            decorations: decorations.remove(commit_id).unwrap_or_default(),
         });
 
         // Compact columns (remove empty columns from the right)
@@ -120,6 +152,44 @@ pub fn build_commit_graph(
     Ok(CommitGraph { nodes, max_columns })
 }
 
+/// Map commit id to the branches, remote-tracking refs and tags that point at it.
+/// Annotated tags are peeled to their commit; symbolic refs like `origin/HEAD` are skipped.
+pub fn get_ref_decorations(repo: &Repository) -> Result<HashMap<String, Vec<RefDecoration>>, GitError> {
+    let mut decorations: HashMap<String, Vec<RefDecoration>> = HashMap::new();
+
+    for reference in repo.references()? {
+        let Ok(reference) = reference else { continue };
+        if reference.kind() == Some(git2::ReferenceType::Symbolic) {
+            continue;
+        }
+
+        let kind = if reference.is_branch() {
+            RefKind::LocalBranch
+        } else if reference.is_remote() {
+            RefKind::RemoteBranch
+        } else if reference.is_tag() {
+            RefKind::Tag
+        } else {
+            continue;
+        };
+
+        let (Some(name), Ok(commit)) = (reference.shorthand(), reference.peel_to_commit()) else {
+            continue;
+        };
+
+        decorations
+            .entry(commit.id().to_string())
+            .or_default()
+            .push(RefDecoration { name: name.to_string(), kind });
+    }
+
+    for refs in decorations.values_mut() {
+        refs.sort_by(|a, b| a.kind.cmp(&b.kind).then_with(|| a.name.cmp(&b.name)));
+    }
+
+    Ok(decorations)
+}
+
 fn find_column_for_commit(active_columns: &mut Vec<Option<String>>, commit_id: &str) -> usize {
     // Check if any column is waiting for this commit
     for (i, col) in active_columns.iter().enumerate() {
@@ -4,7 +4,7 @@ use std::fs;
 use std::path::Path;
 use std::process::Command;
 
-use super::GitError;
+use super::{FileDiff, GitError};
 
 // =============================================================================
 // Interactive Rebase Types
@@ -32,6 +32,19 @@ impl RebaseTodoAction {
             RebaseTodoAction::Drop => "drop",
         }
     }
+
+    /// Parse a todo-line command, long or abbreviated (`pick`/`p`, ...)
+    pub fn from_git_command(command: &str) -> Option<Self> {
+        match command {
+            "pick" | "p" => Some(RebaseTodoAction::Pick),
+            "reword" | "r" => Some(RebaseTodoAction::Reword),
+            "edit" | "e" => Some(RebaseTodoAction::Edit),
+            "squash" | "s" => Some(RebaseTodoAction::Squash),
+            "fixup" | "f" => Some(RebaseTodoAction::Fixup),
+            "drop" | "d" => Some(RebaseTodoAction::Drop),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -52,6 +65,9 @@ pub struct InteractiveRebasePlanEntry {
     pub commit_id: String,
     pub action: RebaseTodoAction,
     pub new_message: Option<String>,
+    /// Shell command emitted as an `exec` line right after this entry
+    #[serde(default)]
+    pub exec_command: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
@@ -77,6 +93,10 @@ pub struct InteractiveRebaseState {
     pub conflicting_files: Vec<String>,
     pub onto_ref: Option<String>,
     pub current_message: Option<String>,
+    /// Todo entries not yet applied (`rebase-merge/git-rebase-todo`)
+    pub remaining: Vec<InteractiveRebasePlanEntry>,
+    /// Todo entries already applied (`rebase-merge/done`), the last one being the current step
+    pub done: Vec<InteractiveRebasePlanEntry>,
 }
 
 // =============================================================================
@@ -91,6 +111,31 @@ pub struct RebaseStatus {
     pub onto_ref: Option<String>,
 }
 
+/// Paths with unresolved conflicts in the index
+pub(super) fn conflicted_paths(repo: &Repository) -> Result<Vec<String>, GitError> {
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(false);
+
+    let statuses = repo.statuses(Some(&mut opts))?;
+    Ok(statuses
+        .iter()
+        .filter(|entry| entry.status().is_conflicted())
+        .filter_map(|entry| entry.path().map(str::to_string))
+        .collect())
+}
+
+/// A `GitError::Conflict` carrying the files a failed merge or rebase left conflicted,
+/// so callers don't need a separate status call to find them
+pub(super) fn conflict_error(repo_path: &str, message: &str) -> GitError {
+    let files = super::open_repo(repo_path)
+        .and_then(|repo| conflicted_paths(&repo))
+        .unwrap_or_default();
+    GitError::Conflict {
+        message: message.to_string(),
+        files,
+    }
+}
+
 /// Check if the repository is in a rebase state and list conflicting files
 pub fn get_rebase_status(repo: &Repository) -> Result<RebaseStatus, GitError> {
     let state = repo.state();
@@ -101,23 +146,7 @@ pub fn get_rebase_status(repo: &Repository) -> Result<RebaseStatus, GitError> {
             | RepositoryState::RebaseMerge
     );
 
-    let mut conflicting_files = Vec::new();
-
-    if in_rebase {
-        let mut opts = StatusOptions::new();
-        opts.include_untracked(false);
-
-        let statuses = repo.statuses(Some(&mut opts))?;
-
-        for entry in statuses.iter() {
-            let status = entry.status();
-            if status.is_conflicted() {
-                if let Some(path) = entry.path() {
-                    conflicting_files.push(path.to_string());
-                }
-            }
-        }
-    }
+    let conflicting_files = if in_rebase { conflicted_paths(repo)? } else { Vec::new() };
 
     // Try to get the onto ref from rebase state
     let onto_ref = get_rebase_onto_ref(repo);
@@ -184,10 +213,19 @@ fn resolve_commit_to_ref_name(repo: &Repository, sha: &str) -> Option<String> {
     None
 }
 
-/// Start a rebase onto a target ref
-pub fn rebase_onto(repo_path: &str, onto_ref: &str) -> Result<String, GitError> {
+/// Start a rebase onto a target ref. Without `autostash`, git refuses to rebase a dirty tree.
+pub fn rebase_onto(repo_path: &str, onto_ref: &str, autostash: bool) -> Result<String, GitError> {
+    if !autostash {
+        let repo = super::open_repo(repo_path)?;
+        super::ensure_clean_tree(&repo, "Cannot rebase with uncommitted changes. Commit or stash them, or use autostash.")?;
+    }
+    let mut args = vec!["rebase"];
+    if autostash {
+        args.push("--autostash");
+    }
+    args.push(onto_ref);
     let output = git_command()
-        .args(["rebase", onto_ref])
+        .args(&args)
         .current_dir(repo_path)
         .output()
         .map_err(|e| git2::Error::from_str(&format!("Failed to run git rebase: {}", e)))?;
@@ -202,7 +240,7 @@ pub fn rebase_onto(repo_path: &str, onto_ref: &str) -> Result<String, GitError>
         
         // Check if it's a conflict (rebase stops with conflicts)
         if combined.contains("CONFLICT") || combined.contains("could not apply") || combined.contains("Resolve all conflicts") {
-            Err(git2::Error::from_str("Rebase has conflicts that need to be resolved").into())
+            Err(conflict_error(repo_path, "Rebase has conflicts that need to be resolved"))
         } else {
             Err(git2::Error::from_str(&format!("git rebase failed: {}", combined.trim())).into())
         }
@@ -247,8 +285,9 @@ pub fn continue_rebase(repo_path: &str) -> Result<String, GitError> {
     }
 }
 
-/// Abort the current rebase
-pub fn abort_rebase(repo_path: &str) -> Result<String, GitError> {
+/// Abort the current rebase. With `force`, a failing `git rebase --abort` (e.g.
+/// stale state left by a crash) falls back to `force_abort_rebase`.
+pub fn abort_rebase(repo_path: &str, force: bool) -> Result<String, GitError> {
     let output = git_command()
         .args(["rebase", "--abort"])
         .current_dir(repo_path)
@@ -256,11 +295,52 @@ pub fn abort_rebase(repo_path: &str) -> Result<String, GitError> {
         .map_err(|e| git2::Error::from_str(&format!("Failed to run git rebase --abort: {}", e)))?;
 
     if output.status.success() {
-        Ok("Rebase aborted successfully".to_string())
-    } else {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        Err(git2::Error::from_str(&format!("git rebase --abort failed: {}", stderr)).into())
+        return Ok("Rebase aborted successfully".to_string());
     }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !force {
+        return Err(git2::Error::from_str(&format!("git rebase --abort failed: {}", stderr)).into());
+    }
+    let orig_head = force_abort_rebase(repo_path)?;
+    Ok(format!(
+        "Warning: git rebase --abort failed ({}), so the rebase state was removed and the branch reset to {}",
+        stderr.trim(),
+        &orig_head[..orig_head.len().min(7)]
+    ))
+}
+
+/// Manually undo a rebase: reset to the recorded `orig-head` (back on the
+/// original branch when `head-name` names one) and delete the rebase state
+/// directories. Returns the commit reset to.
+fn force_abort_rebase(repo_path: &str) -> Result<String, GitError> {
+    let repo = super::open_repo(repo_path)?;
+    let state_dirs: Vec<_> = ["rebase-merge", "rebase-apply"]
+        .iter()
+        .map(|name| repo.path().join(name))
+        .filter(|dir| dir.is_dir())
+        .collect();
+
+    let read_state = |file: &str| {
+        state_dirs
+            .iter()
+            .find_map(|dir| fs::read_to_string(dir.join(file)).ok())
+            .map(|s| s.trim().to_string())
+    };
+    let orig_head = read_state("orig-head")
+        .filter(|oid| git2::Oid::from_str(oid).is_ok())
+        .ok_or_else(|| GitError::Validation("No rebase in progress to abort (orig-head not found)".to_string()))?;
+
+    match read_state("head-name").as_deref().and_then(|name| name.strip_prefix("refs/heads/")) {
+        Some(branch) => run_git_in(repo_path, &["checkout", "--force", "-B", branch, &orig_head])?,
+        None => run_git_in(repo_path, &["reset", "--hard", &orig_head])?,
+    }
+
+    for dir in &state_dirs {
+        fs::remove_dir_all(dir)
+            .map_err(|e| git2::Error::from_str(&format!("Failed to remove {}: {}", dir.display(), e)))?;
+    }
+    Ok(orig_head)
 }
 
 /// Skip the current commit during rebase
@@ -351,6 +431,78 @@ pub fn get_interactive_rebase_commits(repo_path: &str, onto_ref: &str) -> Result
     Ok(commits)
 }
 
+/// Check every plan entry refers to a commit in onto_ref..HEAD (full or abbreviated id),
+/// that exec commands are single-line, and that the actions make a todo git accepts.
+/// Returns the full commit id for each entry.
+fn validate_rebase_plan(
+    repo_path: &str,
+    onto_ref: &str,
+    plan: &[InteractiveRebasePlanEntry],
+) -> Result<Vec<String>, GitError> {
+    // Squash/fixup meld into the previous kept commit, so one must come first
+    match plan.iter().map(|entry| &entry.action).find(|action| **action != RebaseTodoAction::Drop) {
+        None => {
+            return Err(GitError::Validation(
+                "Rebase plan drops every commit; keep at least one".to_string(),
+            ))
+        }
+        Some(action @ (RebaseTodoAction::Squash | RebaseTodoAction::Fixup)) => {
+            return Err(GitError::Validation(format!(
+                "Cannot {} without a previous commit; the first kept commit must be pick, reword or edit",
+                action.to_git_command()
+            )))
+        }
+        Some(_) => {}
+    }
+
+    let repo = super::open_repo(repo_path)?;
+    let onto_oid = repo.revparse_single(onto_ref)?.peel_to_commit()?.id();
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push_head()?;
+    revwalk.hide(onto_oid)?;
+    let range_ids: Vec<String> = revwalk
+        .filter_map(|oid| oid.ok())
+        .map(|oid| oid.to_string())
+        .collect();
+
+    let mut resolved = Vec::with_capacity(plan.len());
+    for entry in plan {
+        let prefix = entry.commit_id.trim().to_lowercase();
+        let matches: Vec<&String> = if prefix.len() >= 4 {
+            range_ids.iter().filter(|id| id.starts_with(&prefix)).collect()
+        } else {
+            Vec::new()
+        };
+
+        match matches.as_slice() {
+            [id] => resolved.push((*id).clone()),
+            [] => {
+                return Err(GitError::Validation(format!(
+                    "Commit {} is not in the range {}..HEAD",
+                    entry.commit_id, onto_ref
+                )))
+            }
+            _ => {
+                return Err(GitError::Validation(format!(
+                    "Commit id {} is ambiguous",
+                    entry.commit_id
+                )))
+            }
+        }
+
+        if let Some(cmd) = &entry.exec_command {
+            if cmd.contains('\n') || cmd.contains('\r') {
+                return Err(GitError::Validation(
+                    "Exec commands must be a single line".to_string(),
+                ));
+            }
+        }
+    }
+
+    Ok(resolved)
+}
+
 /// Start an interactive rebase with a pre-defined plan
 /// Uses GIT_SEQUENCE_EDITOR to inject our todo list
 pub fn start_interactive_rebase(
@@ -362,19 +514,21 @@ pub fn start_interactive_rebase(
         return Err(git2::Error::from_str("Rebase plan cannot be empty").into());
     }
 
+    // Validate the plan against the actual onto_ref..HEAD range before touching git,
+    // so a stale or bogus plan can't produce a silently broken rebase
+    let resolved_ids = validate_rebase_plan(repo_path, onto_ref, &plan)?;
+
     // Build the todo content
     let mut todo_content = String::new();
-    for entry in &plan {
-        let short_id = if entry.commit_id.len() > 7 {
-            &entry.commit_id[..7]
-        } else {
-            &entry.commit_id
-        };
+    for (entry, commit_id) in plan.iter().zip(&resolved_ids) {
         todo_content.push_str(&format!(
-            "{} {} \n",
+            "{} {}\n",
             entry.action.to_git_command(),
-            short_id
+            commit_id
         ));
+        if let Some(cmd) = entry.exec_command.as_deref().map(str::trim).filter(|c| !c.is_empty()) {
+            todo_content.push_str(&format!("exec {}\n", cmd));
+        }
     }
 
     // Create a temporary script that will write our todo content
@@ -461,6 +615,8 @@ pub fn get_interactive_rebase_state(repo: &Repository) -> Result<InteractiveReba
             conflicting_files: Vec::new(),
             onto_ref: None,
             current_message: None,
+            remaining: Vec::new(),
+            done: Vec::new(),
         });
     }
 
@@ -521,18 +677,9 @@ pub fn get_interactive_rebase_state(repo: &Repository) -> Result<InteractiveReba
         RebaseStopReason::Conflict
     } else if is_interactive {
         // Check what action caused the stop by examining the done file
-        let done_content = fs::read_to_string(rebase_dir.join("done"))
-            .ok()
-            .unwrap_or_default();
-        let last_action = done_content
-            .lines()
-            .last()
-            .unwrap_or("")
-            .split_whitespace()
-            .next()
-            .unwrap_or("");
-
-        match last_action {
+        let last_action = last_done_action(rebase_dir);
+
+        match last_action.as_str() {
             "edit" | "e" => RebaseStopReason::Edit,
             "reword" | "r" => RebaseStopReason::Reword,
             "squash" | "s" | "fixup" | "f" => {
@@ -568,9 +715,60 @@ pub fn get_interactive_rebase_state(repo: &Repository) -> Result<InteractiveReba
         conflicting_files,
         onto_ref,
         current_message,
+        remaining: read_rebase_todo(&rebase_dir.join("git-rebase-todo")),
+        done: read_rebase_todo(&rebase_dir.join("done")),
     })
 }
 
+/// Parse a rebase todo file into plan entries. `exec` lines attach to the preceding
+/// entry; comments and commands without a plan equivalent (`break`, `label`, ...) are skipped.
+fn read_rebase_todo(path: &Path) -> Vec<InteractiveRebasePlanEntry> {
+    let content = fs::read_to_string(path).unwrap_or_default();
+    let mut entries: Vec<InteractiveRebasePlanEntry> = Vec::new();
+
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (command, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        if matches!(command, "exec" | "x") {
+            if let Some(last) = entries.last_mut() {
+                last.exec_command = Some(rest.trim().to_string());
+            }
+            continue;
+        }
+        let Some(action) = RebaseTodoAction::from_git_command(command) else {
+            continue;
+        };
+        // `fixup -C <sha>` / `fixup -c <sha>` carry a flag before the commit
+        let Some(commit_id) = rest.split_whitespace().find(|word| !word.starts_with('-')) else {
+            continue;
+        };
+        entries.push(InteractiveRebasePlanEntry {
+            commit_id: commit_id.to_string(),
+            action,
+            new_message: None,
+            exec_command: None,
+        });
+    }
+
+    entries
+}
+
+/// The action of the most recently applied todo line (e.g. "pick", "squash")
+fn last_done_action(rebase_dir: &Path) -> String {
+    fs::read_to_string(rebase_dir.join("done"))
+        .ok()
+        .unwrap_or_default()
+        .lines()
+        .last()
+        .unwrap_or("")
+        .split_whitespace()
+        .next()
+        .unwrap_or("")
+        .to_string()
+}
+
 /// Continue interactive rebase with an optional new commit message
 /// Used for reword/squash operations where a message is needed
 pub fn continue_interactive_rebase(repo_path: &str, message: Option<String>) -> Result<String, GitError> {
@@ -586,6 +784,25 @@ pub fn continue_interactive_rebase(repo_path: &str, message: Option<String>) ->
         .into());
     }
 
+    // Steps waiting on a commit message must get one from the caller; continuing with
+    // a no-op editor would either stall or silently keep a message the user meant to edit.
+    // Reword is also reported after a resolved pick conflict, so check the actual step.
+    if message.is_none() {
+        let needs_message = match state.stop_reason {
+            RebaseStopReason::SquashMessage => true,
+            RebaseStopReason::Reword => matches!(
+                last_done_action(&repo.path().join("rebase-merge")).as_str(),
+                "reword" | "r"
+            ),
+            _ => false,
+        };
+        if needs_message {
+            return Err(GitError::Validation(
+                "This rebase step needs a commit message. Provide a message to continue.".to_string(),
+            ));
+        }
+    }
+
     // If a message is provided, we need to use a custom editor
     let mut cmd = git_command();
     cmd.args(["rebase", "--continue"]).current_dir(repo_path);
@@ -637,6 +854,38 @@ pub fn continue_interactive_rebase(repo_path: &str, message: Option<String>) ->
     }
 }
 
+/// Start splitting the commit an interactive rebase stopped on for `edit`: `git reset HEAD^`
+/// un-commits it, leaving its changes unstaged. The caller then stages and commits them in
+/// as many pieces as it likes and finishes with `continue_interactive_rebase`.
+pub fn split_commit_reset(repo_path: &str) -> Result<String, GitError> {
+    let repo = super::open_repo(repo_path)?;
+    let state = get_interactive_rebase_state(&repo)?;
+    if state.stop_reason != RebaseStopReason::Edit {
+        return Err(GitError::Validation(
+            "Commits can only be split while a rebase is stopped to edit one".to_string(),
+        ));
+    }
+
+    // git records the commit it stopped on in `amend`. Once HEAD has moved (already
+    // split or amended) another reset would throw away one of the user's commits.
+    let head = repo.head()?.peel_to_commit()?;
+    let stopped_at = fs::read_to_string(repo.path().join("rebase-merge").join("amend")).unwrap_or_default();
+    if stopped_at.trim() != head.id().to_string() {
+        return Err(GitError::Validation(
+            "HEAD is no longer the commit being edited; it may already have been split".to_string(),
+        ));
+    }
+    if head.parent_count() == 0 {
+        return Err(GitError::Validation("Cannot split a root commit".to_string()));
+    }
+
+    run_git_in(repo_path, &["reset", "HEAD^"])?;
+    Ok(format!(
+        "Reset {} so its changes are unstaged; commit them in pieces, then continue the rebase",
+        &head.id().to_string()[..7]
+    ))
+}
+
 fn handle_rebase_continue_output(output: std::process::Output) -> Result<String, GitError> {
     if output.status.success() {
         let stdout = String::from_utf8_lossy(&output.stdout);
@@ -710,6 +959,15 @@ pub struct ConflictRegion {
     pub end_line: usize,
     pub ours_content: String,
     pub theirs_content: String,
+    /// Common ancestor section, present with `merge.conflictStyle=diff3`/`zdiff3`
+    pub base_content: Option<String>,
+}
+
+/// Which side of a conflict block the parser is in
+enum ConflictSection {
+    Ours,
+    Base,
+    Theirs,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -730,23 +988,7 @@ pub fn get_merge_status(repo: &Repository) -> Result<MergeStatus, GitError> {
         RepositoryState::Merge | RepositoryState::RevertSequence | RepositoryState::CherryPickSequence
     );
 
-    let mut conflicting_files = Vec::new();
-
-    if in_merge {
-        let mut opts = StatusOptions::new();
-        opts.include_untracked(false);
-
-        let statuses = repo.statuses(Some(&mut opts))?;
-
-        for entry in statuses.iter() {
-            let status = entry.status();
-            if status.is_conflicted() {
-                if let Some(path) = entry.path() {
-                    conflicting_files.push(path.to_string());
-                }
-            }
-        }
-    }
+    let conflicting_files = if in_merge { conflicted_paths(repo)? } else { Vec::new() };
 
     // Try to get the branch being merged from MERGE_MSG or MERGE_HEAD
     let their_branch = get_their_branch(repo);
@@ -784,7 +1026,15 @@ fn get_their_branch(repo: &Repository) -> Option<String> {
     None
 }
 
-/// Parse a file with conflict markers and extract conflict regions
+/// Git writes markers as exactly seven marker characters followed by a space
+/// and label, or nothing
+fn is_conflict_marker(line: &str, marker: &str) -> bool {
+    line.strip_prefix(marker)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with([' ', '\r']))
+}
+
+/// Parse a file with conflict markers and extract conflict regions. Nested,
+/// stray or unterminated markers are reported as errors rather than guessed at.
 pub fn parse_file_conflicts(repo_path: &str, file_path: &str) -> Result<FileConflictInfo, GitError> {
     let full_path = Path::new(repo_path).join(file_path);
     let content = fs::read_to_string(&full_path)
@@ -796,30 +1046,55 @@ pub fn parse_file_conflicts(repo_path: &str, file_path: &str) -> Result<FileConf
     let mut theirs_lines: Vec<String> = Vec::new();
 
     let mut i = 0;
-    let mut in_conflict = false;
-    let mut in_ours = false;
+    let mut section: Option<ConflictSection> = None;
     let mut conflict_start = 0;
     let mut current_ours: Vec<String> = Vec::new();
+    let mut current_base: Option<Vec<String>> = None;
     let mut current_theirs: Vec<String> = Vec::new();
 
+    let unbalanced = |problem: &str, line_number: usize| {
+        GitError::Validation(format!(
+            "Unbalanced conflict markers in {}: {} at line {}",
+            file_path, problem, line_number
+        ))
+    };
+
     while i < lines.len() {
         let line = lines[i];
 
-        if line.starts_with("<<<<<<<") {
-            in_conflict = true;
-            in_ours = true;
+        if is_conflict_marker(line, "<<<<<<<") {
+            if section.is_some() {
+                return Err(unbalanced("nested conflict start", i + 1));
+            }
+            section = Some(ConflictSection::Ours);
             conflict_start = i + 1; // 1-based line number
             current_ours.clear();
+            current_base = None;
             current_theirs.clear();
-        } else if line.starts_with("=======") && in_conflict {
-            in_ours = false;
-        } else if line.starts_with(">>>>>>>") && in_conflict {
+        } else if is_conflict_marker(line, "|||||||") && section.is_some() {
+            if !matches!(section, Some(ConflictSection::Ours)) {
+                return Err(unbalanced("misplaced base marker", i + 1));
+            }
+            section = Some(ConflictSection::Base);
+            current_base = Some(Vec::new());
+        } else if is_conflict_marker(line, "=======") && section.is_some() {
+            if matches!(section, Some(ConflictSection::Theirs)) {
+                return Err(unbalanced("repeated separator", i + 1));
+            }
+            section = Some(ConflictSection::Theirs);
+        } else if is_conflict_marker(line, ">>>>>>>") {
+            match section {
+                Some(ConflictSection::Theirs) => {}
+                Some(_) => return Err(unbalanced("conflict end without separator", i + 1)),
+                None => return Err(unbalanced("conflict end without start", i + 1)),
+            }
             // End of conflict block
             conflicts.push(ConflictRegion {
                 start_line: conflict_start,
                 end_line: i + 1, // 1-based, inclusive
                 ours_content: current_ours.join("\n"),
                 theirs_content: current_theirs.join("\n"),
+                base_content: current_base.take().map(|base| base.join("\n")),
             });
 
             // For full file reconstruction, add ours content to ours_lines
@@ -831,13 +1106,12 @@ pub fn parse_file_conflicts(repo_path: &str, file_path: &str) -> Result<FileConf
                 theirs_lines.push(s.clone());
             }
 
-            in_conflict = false;
-            in_ours = false;
-        } else if in_conflict {
-            if in_ours {
-                current_ours.push(line.to_string());
-            } else {
-                current_theirs.push(line.to_string());
+            section = None;
+        } else if let Some(current) = &section {
+            match current {
+                ConflictSection::Ours => current_ours.push(line.to_string()),
+                ConflictSection::Base => current_base.get_or_insert_with(Vec::new).push(line.to_string()),
+                ConflictSection::Theirs => current_theirs.push(line.to_string()),
             }
         } else {
             // Normal line - add to both reconstructions
@@ -848,6 +1122,10 @@ pub fn parse_file_conflicts(repo_path: &str, file_path: &str) -> Result<FileConf
         i += 1;
     }
 
+    if section.is_some() {
+        return Err(unbalanced("unterminated conflict starting", conflict_start));
+    }
+
     Ok(FileConflictInfo {
         file_path: file_path.to_string(),
         conflicts,
@@ -857,6 +1135,93 @@ pub fn parse_file_conflicts(repo_path: &str, file_path: &str) -> Result<FileConf
     })
 }
 
+/// Each side of a conflicted file diffed against the merge base
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictDiffs {
+    pub ours_vs_base: FileDiff,
+    pub theirs_vs_base: FileDiff,
+}
+
+/// The three versions of a conflicted file as recorded in the index. A side is
+/// None when it doesn't exist there (no base for add/add, no ours/theirs when
+/// that side deleted the file).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictVersions {
+    pub file_path: String,
+    pub base: Option<String>,
+    pub ours: Option<String>,
+    pub theirs: Option<String>,
+}
+
+/// Blob contents of the index stages (1 = base, 2 = ours, 3 = theirs) for a
+/// conflicted file
+fn conflict_stages(
+    repo: &Repository,
+    file_path: &str,
+) -> Result<[Option<Vec<u8>>; 3], GitError> {
+    let index = repo.index()?;
+    let path = Path::new(file_path);
+
+    let stage_content = |stage: i32| -> Result<Option<Vec<u8>>, GitError> {
+        match index.get_path(path, stage) {
+            Some(entry) => Ok(Some(repo.find_blob(entry.id)?.content().to_vec())),
+            None => Ok(None),
+        }
+    };
+
+    let stages = [stage_content(1)?, stage_content(2)?, stage_content(3)?];
+    if stages[1].is_none() && stages[2].is_none() {
+        return Err(GitError::Validation(format!("{} is not in a conflicted state", file_path)));
+    }
+    Ok(stages)
+}
+
+/// Read base/ours/theirs for a conflicted file from the index rather than from
+/// conflict markers in the working tree, which tools or users may have edited
+pub fn get_conflict_versions(repo: &Repository, file_path: &str) -> Result<ConflictVersions, GitError> {
+    let [base, ours, theirs] = conflict_stages(repo, file_path)?
+        .map(|stage| stage.map(|bytes| String::from_utf8_lossy(&bytes).into_owned()));
+    Ok(ConflictVersions {
+        file_path: file_path.to_string(),
+        base,
+        ours,
+        theirs,
+    })
+}
+
+/// Build "what ours changed" and "what theirs changed" diffs for a conflicted file
+/// from the index stages (1 = base, 2 = ours, 3 = theirs)
+pub fn get_conflict_diffs(repo: &Repository, file_path: &str) -> Result<ConflictDiffs, GitError> {
+    let path = Path::new(file_path);
+    let [base, ours, theirs] = conflict_stages(repo, file_path)?;
+    // Base is missing for add/add conflicts; diff against empty content then
+    let base = base.unwrap_or_default();
+
+    let diff_side = |side: Option<Vec<u8>>| -> Result<FileDiff, GitError> {
+        let mut opts = git2::DiffOptions::new();
+        opts.context_lines(3);
+        let mut patch = git2::Patch::from_buffers(
+            &base,
+            Some(path),
+            side.as_deref().unwrap_or_default(),
+            Some(path),
+            Some(&mut opts),
+        )?;
+        let buf = patch.to_buf()?;
+        Ok(FileDiff {
+            path: file_path.to_string(),
+            patch: String::from_utf8_lossy(&buf).into_owned(),
+        })
+    };
+
+    Ok(ConflictDiffs {
+        ours_vs_base: diff_side(ours)?,
+        theirs_vs_base: diff_side(theirs)?,
+    })
+}
+
 /// Save resolved content to a file
 pub fn save_resolved_file(repo_path: &str, file_path: &str, content: &str) -> Result<(), GitError> {
     let full_path = Path::new(repo_path).join(file_path);
@@ -873,6 +1238,94 @@ pub fn mark_file_resolved(repo: &Repository, file_path: &str) -> Result<(), GitE
     Ok(())
 }
 
+/// Check that a merge, rebase, cherry-pick or revert is in progress and that
+/// `file_path` is one of its conflicted files
+pub fn ensure_file_conflicted(repo: &Repository, file_path: &str) -> Result<(), GitError> {
+    if matches!(repo.state(), RepositoryState::Clean | RepositoryState::Bisect) {
+        return Err(GitError::Validation("No merge or rebase is in progress".to_string()));
+    }
+    let path = Path::new(file_path);
+    if path.components().any(|c| !matches!(c, std::path::Component::Normal(_))) {
+        return Err(GitError::InvalidPath(file_path.to_string()));
+    }
+    if !repo.status_file(path)?.is_conflicted() {
+        return Err(GitError::Validation(format!("{} is not in a conflicted state", file_path)));
+    }
+    Ok(())
+}
+
+/// Write resolved content for a conflicted file and stage it
+pub fn apply_conflict_resolution(repo_path: &str, file_path: &str, content: &str) -> Result<(), GitError> {
+    let repo = super::open_repo(repo_path)?;
+    ensure_file_conflicted(&repo, file_path)?;
+    save_resolved_file(repo_path, file_path, content)?;
+    mark_file_resolved(&repo, file_path)
+}
+
+/// Which side wins when a conflicted file is resolved wholesale. During a
+/// rebase "ours" is the branch being rebased onto, as with `git checkout --ours`.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ConflictSide {
+    Ours,
+    Theirs,
+}
+
+/// Replace a conflicted file with one side's version and stage it. If that side
+/// deleted the file, the deletion is staged instead.
+pub fn resolve_conflict_side(repo_path: &str, file_path: &str, side: ConflictSide) -> Result<(), GitError> {
+    let repo = super::open_repo(repo_path)?;
+    ensure_file_conflicted(&repo, file_path)?;
+    let [_, ours, theirs] = conflict_stages(&repo, file_path)?;
+    let (chosen, flag) = match side {
+        ConflictSide::Ours => (ours, "--ours"),
+        ConflictSide::Theirs => (theirs, "--theirs"),
+    };
+
+    if chosen.is_some() {
+        run_git_in(repo_path, &["checkout", flag, "--", file_path])?;
+        run_git_in(repo_path, &["add", "--", file_path])
+    } else {
+        run_git_in(repo_path, &["rm", "--quiet", "--", file_path])
+    }
+}
+
+/// Resolve a conflicted file with our version
+pub fn resolve_conflict_ours(repo_path: &str, file_path: &str) -> Result<(), GitError> {
+    resolve_conflict_side(repo_path, file_path, ConflictSide::Ours)
+}
+
+/// Resolve a conflicted file with their version
+pub fn resolve_conflict_theirs(repo_path: &str, file_path: &str) -> Result<(), GitError> {
+    resolve_conflict_side(repo_path, file_path, ConflictSide::Theirs)
+}
+
+/// Resolve every conflicting file of the current merge with `side`.
+/// Returns the paths that were resolved.
+pub fn resolve_all_conflicts(repo_path: &str, side: ConflictSide) -> Result<Vec<String>, GitError> {
+    let repo = super::open_repo(repo_path)?;
+    let files = get_merge_status(&repo)?.conflicting_files;
+    for file in &files {
+        resolve_conflict_side(repo_path, file, side)?;
+    }
+    Ok(files)
+}
+
+fn run_git_in(repo_path: &str, args: &[&str]) -> Result<(), GitError> {
+    let output = git_command()
+        .args(args)
+        .current_dir(repo_path)
+        .output()
+        .map_err(|e| git2::Error::from_str(&format!("Failed to run git {}: {}", args[0], e)))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(git2::Error::from_str(&format!("git {} failed: {}", args[0], stderr)).into())
+    }
+}
+
 /// Abort the current merge
 pub fn abort_merge(repo_path: &str) -> Result<String, GitError> {
     let output = git_command()
@@ -889,10 +1342,84 @@ pub fn abort_merge(repo_path: &str) -> Result<String, GitError> {
     }
 }
 
+/// Abort an in-progress cherry-pick (single or sequence)
+pub fn abort_cherry_pick(repo_path: &str) -> Result<String, GitError> {
+    run_abort(repo_path, "cherry-pick")
+}
+
+/// Abort an in-progress revert (single or sequence)
+pub fn abort_revert(repo_path: &str) -> Result<String, GitError> {
+    run_abort(repo_path, "revert")
+}
+
+fn run_abort(repo_path: &str, operation: &str) -> Result<String, GitError> {
+    let output = git_command()
+        .args([operation, "--abort"])
+        .current_dir(repo_path)
+        .output()
+        .map_err(|e| git2::Error::from_str(&format!("Failed to run git {} --abort: {}", operation, e)))?;
+
+    if output.status.success() {
+        Ok(format!("{} aborted successfully", operation))
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(git2::Error::from_str(&format!("git {} --abort failed: {}", operation, stderr)).into())
+    }
+}
+
+/// The repository's current operation state (e.g. "clean", "merge", "cherryPick"),
+/// so the UI can offer the matching abort/continue action
+pub fn get_repository_state(repo: &Repository) -> String {
+    match repo.state() {
+        RepositoryState::Clean => "clean",
+        RepositoryState::Merge => "merge",
+        RepositoryState::Revert => "revert",
+        RepositoryState::RevertSequence => "revertSequence",
+        RepositoryState::CherryPick => "cherryPick",
+        RepositoryState::CherryPickSequence => "cherryPickSequence",
+        RepositoryState::Bisect => "bisect",
+        RepositoryState::Rebase => "rebase",
+        RepositoryState::RebaseInteractive => "rebaseInteractive",
+        RepositoryState::RebaseMerge => "rebaseMerge",
+        RepositoryState::ApplyMailbox => "applyMailbox",
+        RepositoryState::ApplyMailboxOrRebase => "applyMailboxOrRebase",
+    }
+    .to_string()
+}
+
+/// Flags for `merge_branch`; the default is a plain `git merge`
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MergeOptions {
+    /// Always create a merge commit (`--no-ff`)
+    pub no_ff: bool,
+    /// Refuse unless the merge is a fast-forward (`--ff-only`)
+    pub ff_only: bool,
+    /// Stage the combined changes without committing (`--squash`)
+    pub squash: bool,
+    /// Strategy option passed as `-X <option>`, e.g. "ours", "theirs" or "patience"
+    pub strategy_option: Option<String>,
+}
+
 /// Merge a branch into the current branch
-pub fn merge_branch(repo_path: &str, branch_name: &str) -> Result<String, GitError> {
+pub fn merge_branch(repo_path: &str, branch_name: &str, options: &MergeOptions) -> Result<String, GitError> {
+    let mut args = vec!["merge", "--no-edit"];
+    if options.no_ff {
+        args.push("--no-ff");
+    }
+    if options.ff_only {
+        args.push("--ff-only");
+    }
+    if options.squash {
+        args.push("--squash");
+    }
+    if let Some(option) = &options.strategy_option {
+        args.extend(["-X", option]);
+    }
+    args.push(branch_name);
+
     let output = git_command()
-        .args(["merge", branch_name, "--no-edit"])
+        .args(&args)
         .current_dir(repo_path)
         .output()
         .map_err(|e| git2::Error::from_str(&format!("Failed to run git merge: {}", e)))?;
@@ -902,15 +1429,85 @@ pub fn merge_branch(repo_path: &str, branch_name: &str) -> Result<String, GitErr
         Ok(stdout.trim().to_string())
     } else {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        // Check if it's a conflict (exit code 1 with conflict message)
-        if stderr.contains("CONFLICT") || stderr.contains("Automatic merge failed") {
-            Err(git2::Error::from_str("Merge has conflicts that need to be resolved").into())
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        // Check if it's a conflict (exit code 1; git reports conflicts on stdout)
+        if format!("{}{}", stdout, stderr).contains("CONFLICT") || stdout.contains("Automatic merge failed") {
+            Err(conflict_error(repo_path, "Merge has conflicts that need to be resolved"))
         } else {
             Err(git2::Error::from_str(&format!("git merge failed: {}", stderr)).into())
         }
     }
 }
 
+/// What merging a branch would do, from `preview_merge`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MergePreview {
+    /// Nothing to merge: the branch is already contained in HEAD
+    pub up_to_date: bool,
+    /// Every path the merge would touch, conflicted or not, sorted
+    pub changed_files: Vec<String>,
+    pub conflicting_files: Vec<String>,
+    /// False when `merge --abort` failed and the repository is still mid-merge
+    pub aborted: bool,
+}
+
+/// Trial-merge `branch_name` with `merge --no-commit --no-ff`, record the files it touches
+/// and which of them conflict, then abort so the working tree is left as it was. Requires
+/// a clean tree, since the abort would otherwise take local changes with it.
+pub fn preview_merge(repo_path: &str, branch_name: &str) -> Result<MergePreview, GitError> {
+    {
+        let repo = super::open_repo(repo_path)?;
+        if repo.state() != RepositoryState::Clean {
+            return Err(GitError::Validation(
+                "Cannot preview a merge while another operation is in progress".to_string(),
+            ));
+        }
+        super::ensure_clean_tree(&repo, "Commit or stash local changes before previewing a merge")?;
+    }
+
+    let output = git_command()
+        .args(["merge", "--no-commit", "--no-ff", branch_name])
+        .current_dir(repo_path)
+        .output()
+        .map_err(|e| git2::Error::from_str(&format!("Failed to run git merge: {}", e)))?;
+
+    // A fresh handle so the index reflects the trial merge
+    let repo = super::open_repo(repo_path)?;
+    if repo.state() != RepositoryState::Merge {
+        if output.status.success() {
+            return Ok(MergePreview {
+                up_to_date: true,
+                changed_files: Vec::new(),
+                conflicting_files: Vec::new(),
+                aborted: true,
+            });
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(git2::Error::from_str(&format!("git merge failed: {}", stderr)).into());
+    }
+
+    let conflicting_files = conflicted_paths(&repo)?;
+    let head_tree = repo.head()?.peel_to_tree()?;
+    let diff = repo.diff_tree_to_index(Some(&head_tree), None, None)?;
+    let mut changed_files: Vec<String> = diff
+        .deltas()
+        .filter_map(|delta| delta.new_file().path().or_else(|| delta.old_file().path()))
+        .map(|path| path.to_string_lossy().into_owned())
+        .chain(conflicting_files.iter().cloned())
+        .collect();
+    changed_files.sort();
+    changed_files.dedup();
+
+    let aborted = abort_merge(repo_path).is_ok();
+    Ok(MergePreview {
+        up_to_date: false,
+        changed_files,
+        conflicting_files,
+        aborted,
+    })
+}
+
 /// Continue the merge (create merge commit)
 pub fn continue_merge(repo_path: &str) -> Result<String, GitError> {
     // First check if there are still unresolved conflicts
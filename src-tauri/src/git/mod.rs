@@ -2,11 +2,19 @@ pub mod repository;
 pub mod graph;
 pub mod diff;
 pub mod merge;
+pub mod tags;
+pub mod cancel;
+pub mod signature;
+pub mod tree;
 
 pub use repository::*;
 pub use graph::*;
 pub use diff::*;
 pub use merge::*;
+pub use tags::*;
+pub use cancel::*;
+pub use signature::*;
+pub use tree::*;
 
 // Re-export stash types
 pub use repository::StashEntry;
@@ -1,8 +1,14 @@
 use git2::{Branch, BranchType, Repository, StatusOptions};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
 use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
 use thiserror::Error;
 
+use super::{CancellationToken, SignatureStatus};
+
 #[derive(Error, Debug)]
 pub enum GitError {
     #[error("Git error: {0}")]
@@ -11,6 +17,16 @@ pub enum GitError {
     NotFound(String),
     #[error("Invalid path: {0}")]
     InvalidPath(String),
+    #[error("{0}")]
+    Validation(String),
+    /// The operation stopped with conflicts in `files`
+    #[error("{message}")]
+    Conflict { message: String, files: Vec<String> },
+    /// Local modifications to `paths` block the operation
+    #[error("{message}")]
+    DirtyWorkingTree { message: String, paths: Vec<String> },
+    #[error("Operation was cancelled")]
+    Cancelled,
 }
 
 impl serde::Serialize for GitError {
@@ -38,10 +54,22 @@ pub struct BranchInfo {
     pub is_head: bool,
     pub is_remote: bool,
     pub upstream: Option<String>,
+    pub upstream_state: UpstreamState,
     pub commit_id: String,
     pub commit_message: String,
 }
 
+/// Whether a local branch's configured upstream still exists
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum UpstreamState {
+    Tracking,
+    /// An upstream is configured but its remote-tracking ref no longer exists,
+    /// typically because the remote branch was deleted and pruned
+    Gone,
+    None,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct CommitInfo {
@@ -52,6 +80,14 @@ pub struct CommitInfo {
     pub author_name: String,
     pub author_email: String,
     pub time: i64,
+    /// Committer differs from the author after a rebase, cherry-pick or amend
+    pub committer_name: String,
+    pub committer_email: String,
+    pub committer_time: i64,
+    /// Only filled in by `verify_commit_signatures`; `None` means not checked
+    pub signature_status: Option<SignatureStatus>,
+    /// `(key, value)` pairs from the message's trailer block, e.g. `Co-authored-by`
+    pub trailers: Vec<(String, String)>,
     pub parent_ids: Vec<String>,
     pub files_changed: usize,
     pub additions: usize,
@@ -94,6 +130,17 @@ pub fn discover_repo<P: AsRef<Path>>(start_path: P) -> Result<Repository, GitErr
     })
 }
 
+/// The working directory, or a validation error for bare repositories. Operations
+/// that read or write the working tree or index call this first; history reads don't.
+pub fn require_workdir(repo: &Repository) -> Result<&Path, GitError> {
+    repo.workdir().ok_or_else(|| {
+        GitError::Validation(format!(
+            "{} is a bare repository; this operation needs a working tree",
+            repo.path().display()
+        ))
+    })
+}
+
 pub fn get_repository_info(repo: &Repository) -> Result<RepositoryInfo, GitError> {
     let path = repo
         .workdir()
@@ -132,7 +179,7 @@ pub fn list_all_branches(repo: &Repository) -> Result<Vec<BranchInfo>, GitError>
     // Local branches
     for branch_result in repo.branches(Some(BranchType::Local))? {
         let (branch, _) = branch_result?;
-        if let Some(info) = branch_to_info(&branch, false, &head_name)? {
+        if let Some(info) = branch_to_info(repo, &branch, false, &head_name)? {
             branches.push(info);
         }
     }
@@ -140,7 +187,7 @@ pub fn list_all_branches(repo: &Repository) -> Result<Vec<BranchInfo>, GitError>
     // Remote branches
     for branch_result in repo.branches(Some(BranchType::Remote))? {
         let (branch, _) = branch_result?;
-        if let Some(info) = branch_to_info(&branch, true, &head_name)? {
+        if let Some(info) = branch_to_info(repo, &branch, true, &head_name)? {
             branches.push(info);
         }
     }
@@ -149,6 +196,7 @@ pub fn list_all_branches(repo: &Repository) -> Result<Vec<BranchInfo>, GitError>
 }
 
 fn branch_to_info(
+    repo: &Repository,
     branch: &Branch,
     is_remote: bool,
     head_name: &Option<String>,
@@ -161,13 +209,10 @@ fn branch_to_info(
     let commit = branch.get().peel_to_commit()?;
     let is_head = head_name.as_ref().map(|h| h == &name).unwrap_or(false);
 
-    let upstream = if !is_remote {
-        branch
-            .upstream()
-            .ok()
-            .and_then(|u| u.name().ok().flatten().map(String::from))
+    let (upstream, upstream_state) = if is_remote {
+        (None, UpstreamState::None)
     } else {
-        None
+        upstream_of(repo, branch)
     };
 
     Ok(Some(BranchInfo {
@@ -175,19 +220,77 @@ fn branch_to_info(
         is_head,
         is_remote,
         upstream,
+        upstream_state,
         commit_id: commit.id().to_string(),
         commit_message: commit.summary().unwrap_or("").to_string(),
     }))
 }
 
+/// The upstream's short name and state for a local branch. A gone upstream still reports
+/// the name from config so the UI can say what was being tracked.
+fn upstream_of(repo: &Repository, branch: &Branch) -> (Option<String>, UpstreamState) {
+    if let Ok(upstream) = branch.upstream() {
+        let name = upstream.name().ok().flatten().map(String::from);
+        return (name, UpstreamState::Tracking);
+    }
+
+    let Some(refname) = branch.get().name() else {
+        return (None, UpstreamState::None);
+    };
+    // branch_upstream_name resolves the config entry without requiring the ref to exist
+    match repo.branch_upstream_name(refname) {
+        Ok(buf) => {
+            let full = String::from_utf8_lossy(&buf).into_owned();
+            let short = full.strip_prefix("refs/remotes/").unwrap_or(&full).to_string();
+            (Some(short), UpstreamState::Gone)
+        }
+        Err(_) => (None, UpstreamState::None),
+    }
+}
+
+/// Get commits reachable from `branch_name` (or HEAD). `since`/`until` are inclusive unix
+/// timestamps on commit time; `limit`/`offset` apply to the commits inside that range.
 pub fn get_commits(
     repo: &Repository,
     branch_name: Option<&str>,
     limit: usize,
     offset: usize,
+    since: Option<i64>,
+    until: Option<i64>,
+) -> Result<Vec<CommitInfo>, GitError> {
+    get_commits_cancellable(
+        repo,
+        branch_name,
+        limit,
+        offset,
+        since,
+        until,
+        &CancellationToken::new(),
+    )
+}
+
+/// How many commits `get_commits_cancellable` skips past `offset` between cancellation checks
+const SKIP_CANCEL_CHECK_INTERVAL: usize = 256;
+
+/// `get_commits` that stops early with `GitError::Cancelled` when `cancel` is triggered
+pub fn get_commits_cancellable(
+    repo: &Repository,
+    branch_name: Option<&str>,
+    limit: usize,
+    offset: usize,
+    since: Option<i64>,
+    until: Option<i64>,
+    cancel: &CancellationToken,
 ) -> Result<Vec<CommitInfo>, GitError> {
+    let time_filtered = since.is_some() || until.is_some();
+
     let mut revwalk = repo.revwalk()?;
-    revwalk.set_sorting(git2::Sort::TIME | git2::Sort::TOPOLOGICAL)?;
+    if time_filtered {
+        // Use TIME sorting only (not TOPOLOGICAL) so we can early-stop at `since`
+        revwalk.set_sorting(git2::Sort::TIME)?;
+    } else {
+        revwalk.set_sorting(git2::Sort::TIME | git2::Sort::TOPOLOGICAL)?;
+    }
 
     // Start from specified branch or HEAD
     if let Some(branch) = branch_name {
@@ -197,19 +300,185 @@ pub fn get_commits(
         revwalk.push_head()?;
     }
 
-    let commits: Vec<CommitInfo> = revwalk
-        .skip(offset)
-        .take(limit)
-        .filter_map(|oid_result| {
-            let oid = oid_result.ok()?;
-            let commit = repo.find_commit(oid).ok()?;
-            Some(commit_to_info(repo, &commit))
-        })
-        .collect();
+    let mut oids = Vec::new();
+    if time_filtered {
+        let mut skipped = 0;
+        for oid_result in revwalk {
+            cancel.check()?;
+            if oids.len() >= limit {
+                break;
+            }
+            let Ok(oid) = oid_result else { continue };
+            let Ok(commit) = repo.find_commit(oid) else { continue };
+            let time = commit.time().seconds();
 
+            // Early-stop: commits are time-sorted, so if we're past the range, we're done
+            if since.is_some_and(|since| time < since) {
+                break;
+            }
+            if until.is_some_and(|until| time > until) {
+                continue;
+            }
+
+            if skipped < offset {
+                skipped += 1;
+                continue;
+            }
+            oids.push(oid);
+        }
+    } else {
+        // Skip by hand so a deep page can still be cancelled while walking to it
+        for skipped in 0..offset {
+            if skipped % SKIP_CANCEL_CHECK_INTERVAL == 0 {
+                cancel.check()?;
+            }
+            if revwalk.next().is_none() {
+                break;
+            }
+        }
+        for oid_result in revwalk.take(limit) {
+            cancel.check()?;
+            if let Ok(oid) = oid_result {
+                oids.push(oid);
+            }
+        }
+    }
+
+    commits_to_info(repo, &oids, cancel)
+}
+
+/// Below this many commits, spawning worker threads costs more than it saves
+const PARALLEL_COMMIT_INFO_THRESHOLD: usize = 8;
+const MAX_COMMIT_INFO_THREADS: usize = 8;
+
+/// Build `CommitInfo` for each oid, preserving order. Diff stats dominate the cost, so
+/// larger batches are split across scoped threads, each with its own `Repository`
+/// handle (`git2::Repository` isn't `Sync`).
+fn commits_to_info(
+    repo: &Repository,
+    oids: &[git2::Oid],
+    cancel: &CancellationToken,
+) -> Result<Vec<CommitInfo>, GitError> {
+    // At least two workers: object reads are partly I/O-bound, so this helps even on one core
+    let threads = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .clamp(2, MAX_COMMIT_INFO_THREADS);
+
+    let serial = |repo: &Repository, oids: &[git2::Oid]| -> Result<Vec<CommitInfo>, GitError> {
+        let mut commits = Vec::with_capacity(oids.len());
+        for oid in oids {
+            cancel.check()?;
+            if let Ok(commit) = repo.find_commit(*oid) {
+                commits.push(commit_to_info(repo, &commit));
+            }
+        }
+        Ok(commits)
+    };
+
+    if oids.len() < PARALLEL_COMMIT_INFO_THRESHOLD {
+        return serial(repo, oids);
+    }
+
+    let git_dir = repo.path();
+    let chunk_size = oids.len().div_ceil(threads);
+    let results: Vec<Result<Vec<CommitInfo>, GitError>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = oids
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    let repo = Repository::open(git_dir)?;
+                    serial(&repo, chunk)
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|_| Err(git2::Error::from_str("Commit info worker panicked").into()))
+            })
+            .collect()
+    });
+
+    let mut commits = Vec::with_capacity(oids.len());
+    for chunk in results {
+        commits.extend(chunk?);
+    }
     Ok(commits)
 }
 
+/// A page of commit history plus the cursor to fetch the next page
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitHistoryPage {
+    pub commits: Vec<CommitInfo>,
+    /// Opaque cursor to pass as `start_after` for the next page; None when history is exhausted
+    pub next_cursor: Option<String>,
+}
+
+/// Cursor-based variant of `get_commits` that avoids re-walking skipped commits.
+///
+/// The cursor encodes the walk frontier: the not-yet-returned commits whose ancestors
+/// make up the rest of the history. Resuming from the frontier yields exactly the
+/// commits that come after the previous page, even across merges.
+pub fn get_commits_page(
+    repo: &Repository,
+    branch_name: Option<&str>,
+    start_after: Option<&str>,
+    limit: usize,
+) -> Result<CommitHistoryPage, GitError> {
+    let mut revwalk = repo.revwalk()?;
+    revwalk.set_sorting(git2::Sort::TIME | git2::Sort::TOPOLOGICAL)?;
+
+    let mut frontier: Vec<git2::Oid> = Vec::new();
+    if let Some(cursor) = start_after {
+        for id in cursor.split(',').filter(|s| !s.is_empty()) {
+            let oid = git2::Oid::from_str(id)
+                .map_err(|_| GitError::Validation(format!("Invalid history cursor: {}", cursor)))?;
+            frontier.push(oid);
+        }
+    } else if let Some(branch) = branch_name {
+        let reference = repo.find_reference(&format!("refs/heads/{}", branch))?;
+        frontier.extend(reference.target());
+    } else {
+        frontier.extend(repo.head()?.target());
+    }
+
+    for oid in &frontier {
+        revwalk.push(*oid)?;
+    }
+
+    let mut commits = Vec::new();
+    let mut emitted = std::collections::HashSet::new();
+    let mut parents = Vec::new();
+    for oid_result in revwalk.take(limit) {
+        let oid = oid_result?;
+        let commit = repo.find_commit(oid)?;
+        emitted.insert(oid);
+        parents.extend(commit.parent_ids());
+        commits.push(commit_to_info(repo, &commit));
+    }
+
+    // New frontier: unreturned starting points plus parents of returned commits
+    let mut next = Vec::new();
+    for oid in frontier.into_iter().chain(parents) {
+        if !emitted.contains(&oid) && !next.contains(&oid) {
+            next.push(oid);
+        }
+    }
+
+    let next_cursor = if next.is_empty() {
+        None
+    } else {
+        Some(next.iter().map(|oid| oid.to_string()).collect::<Vec<_>>().join(","))
+    };
+
+    Ok(CommitHistoryPage { commits, next_cursor })
+}
+
 /// Get commits from all local branches for graph visualization
 pub fn get_commits_all_branches(
     repo: &Repository,
@@ -228,17 +497,114 @@ pub fn get_commits_all_branches(
         }
     }
 
-    let commits: Vec<CommitInfo> = revwalk
-        .skip(offset)
-        .take(limit)
-        .filter_map(|oid_result| {
-            let oid = oid_result.ok()?;
-            let commit = repo.find_commit(oid).ok()?;
-            Some(commit_to_info(repo, &commit))
-        })
+    let oids: Vec<git2::Oid> = revwalk.skip(offset).take(limit).filter_map(|oid| oid.ok()).collect();
+
+    commits_to_info(repo, &oids, &CancellationToken::new())
+}
+
+/// Get commits that touched `path`, newest first. With `follow`, history continues
+/// past renames (`git log --follow`), which libgit2 doesn't support directly.
+pub fn get_commits_for_path(
+    repo: &Repository,
+    path: &str,
+    follow: bool,
+    limit: usize,
+    offset: usize,
+) -> Result<Vec<CommitInfo>, GitError> {
+    let workdir = repo.workdir().unwrap_or_else(|| repo.path());
+
+    let mut cmd = git_command();
+    cmd.args(["log", "--format=%H"])
+        .arg(format!("--skip={}", offset))
+        .arg(format!("--max-count={}", limit));
+    if follow {
+        cmd.arg("--follow");
+    }
+    let output = cmd
+        .args(["--", path])
+        .current_dir(workdir)
+        .output()
+        .map_err(|e| git2::Error::from_str(&format!("Failed to run git log: {}", e)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(git2::Error::from_str(&format!("git log failed: {}", stderr)).into());
+    }
+
+    let oids: Vec<git2::Oid> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| git2::Oid::from_str(line.trim()).ok())
         .collect();
 
-    Ok(commits)
+    commits_to_info(repo, &oids, &CancellationToken::new())
+}
+
+/// Which commit fields `search_commits` matches against
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SearchFields {
+    pub summary: bool,
+    pub message: bool,
+    pub author_name: bool,
+    pub author_email: bool,
+}
+
+impl Default for SearchFields {
+    fn default() -> Self {
+        Self {
+            summary: true,
+            message: true,
+            author_name: true,
+            author_email: true,
+        }
+    }
+}
+
+/// Find commits reachable from any local branch (or HEAD) whose selected fields contain
+/// `query`, case-insensitively. Newest first; stops after `limit` matches.
+pub fn search_commits(
+    repo: &Repository,
+    query: &str,
+    fields: SearchFields,
+    limit: usize,
+) -> Result<Vec<CommitInfo>, GitError> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return Err(GitError::Validation("Search query cannot be empty".to_string()));
+    }
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.set_sorting(git2::Sort::TIME | git2::Sort::TOPOLOGICAL)?;
+    let _ = revwalk.push_head();
+    for branch_result in repo.branches(Some(BranchType::Local))? {
+        let (branch, _) = branch_result?;
+        if let Some(target) = branch.get().target() {
+            let _ = revwalk.push(target);
+        }
+    }
+
+    let matches = |text: Option<&str>| text.is_some_and(|t| t.to_lowercase().contains(&query));
+
+    let mut results = Vec::new();
+    for oid_result in revwalk {
+        if results.len() >= limit {
+            break;
+        }
+        let Ok(oid) = oid_result else { continue };
+        let Ok(commit) = repo.find_commit(oid) else { continue };
+        let author = commit.author();
+
+        let is_match = (fields.summary && matches(commit.summary()))
+            || (fields.message && matches(commit.message()))
+            || (fields.author_name && matches(author.name()))
+            || (fields.author_email && matches(author.email()));
+
+        if is_match {
+            results.push(commit_to_info(repo, &commit));
+        }
+    }
+
+    Ok(results)
 }
 
 /// Resolve a ref string (branch name, tag, or commit hash) to an Oid
@@ -312,14 +678,22 @@ fn commit_to_info(repo: &Repository, commit: &git2::Commit) -> CommitInfo {
     // Calculate diff stats
     let (files_changed, additions, deletions) = get_commit_stats(repo, commit).unwrap_or((0, 0, 0));
 
+    let message = commit.message().unwrap_or("").to_string();
+    let trailers = parse_commit_trailers(&message);
+
     CommitInfo {
         id,
         short_id,
-        message: commit.message().unwrap_or("").to_string(),
+        message,
         summary: commit.summary().unwrap_or("").to_string(),
         author_name: commit.author().name().unwrap_or("Unknown").to_string(),
         author_email: commit.author().email().unwrap_or("").to_string(),
         time: commit.time().seconds(),
+        committer_name: commit.committer().name().unwrap_or("Unknown").to_string(),
+        committer_email: commit.committer().email().unwrap_or("").to_string(),
+        committer_time: commit.committer().when().seconds(),
+        signature_status: None,
+        trailers,
         parent_ids: commit.parent_ids().map(|id| id.to_string()).collect(),
         files_changed,
         additions,
@@ -327,7 +701,131 @@ fn commit_to_info(repo: &Repository, commit: &git2::Commit) -> CommitInfo {
     }
 }
 
-fn get_commit_stats(repo: &Repository, commit: &git2::Commit) -> Result<(usize, usize, usize), git2::Error> {
+/// Parse the trailer block (`Key: value` lines) at the end of a commit message.
+/// The block is the last paragraph, and only counts if it isn't the subject and
+/// every line in it is a trailer (or an indented continuation of the previous one).
+pub fn parse_commit_trailers(message: &str) -> Vec<(String, String)> {
+    let paragraphs: Vec<&str> = message
+        .trim_end()
+        .split("\n\n")
+        .filter(|p| !p.trim().is_empty())
+        .collect();
+    if paragraphs.len() < 2 {
+        return Vec::new();
+    }
+    let block = paragraphs[paragraphs.len() - 1];
+
+    let mut trailers: Vec<(String, String)> = Vec::new();
+    for line in block.lines() {
+        if line.starts_with(' ') || line.starts_with('\t') {
+            match trailers.last_mut() {
+                Some((_, value)) => {
+                    value.push(' ');
+                    value.push_str(line.trim());
+                    continue;
+                }
+                None => return Vec::new(),
+            }
+        }
+
+        let Some((key, value)) = line.split_once(':') else {
+            return Vec::new();
+        };
+        let is_token = !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+        if !is_token {
+            return Vec::new();
+        }
+        trailers.push((key.to_string(), value.trim().to_string()));
+    }
+
+    trailers
+}
+
+type CommitStats = (usize, usize, usize);
+
+/// Max number of commits whose stats are kept in memory
+const COMMIT_STATS_CACHE_CAPACITY: usize = 20_000;
+
+/// LRU cache of per-commit diff stats. A commit's stats never change, so entries
+/// are never invalidated, only evicted.
+///
+/// Recency is tracked lazily: every access pushes `(oid, generation)` onto `order`
+/// and eviction skips queue entries whose generation is stale.
+#[derive(Default)]
+struct CommitStatsCache {
+    entries: HashMap<git2::Oid, (CommitStats, u64)>,
+    order: VecDeque<(git2::Oid, u64)>,
+    generation: u64,
+}
+
+impl CommitStatsCache {
+    fn get(&mut self, oid: git2::Oid) -> Option<CommitStats> {
+        self.generation += 1;
+        let generation = self.generation;
+        let (stats, last_used) = self.entries.get_mut(&oid)?;
+        *last_used = generation;
+        let stats = *stats;
+        self.order.push_back((oid, generation));
+        self.compact();
+        Some(stats)
+    }
+
+    fn insert(&mut self, oid: git2::Oid, stats: CommitStats) {
+        self.generation += 1;
+        self.entries.insert(oid, (stats, self.generation));
+        self.order.push_back((oid, self.generation));
+
+        while self.entries.len() > COMMIT_STATS_CACHE_CAPACITY {
+            let Some((oid, generation)) = self.order.pop_front() else { break };
+            if self.entries.get(&oid).map(|(_, last_used)| *last_used) == Some(generation) {
+                self.entries.remove(&oid);
+            }
+        }
+        self.compact();
+    }
+
+    /// Drop stale recency records so `order` doesn't grow without bound on repeated hits
+    fn compact(&mut self) {
+        if self.order.len() > COMMIT_STATS_CACHE_CAPACITY * 2 {
+            let entries = &self.entries;
+            self.order
+                .retain(|(oid, generation)| entries.get(oid).map(|(_, last_used)| last_used) == Some(generation));
+        }
+    }
+}
+
+static COMMIT_STATS_CACHE: OnceLock<Mutex<CommitStatsCache>> = OnceLock::new();
+static COMMIT_STATS_CACHE_HITS: AtomicUsize = AtomicUsize::new(0);
+
+/// Forget all cached commit stats
+pub fn clear_commit_stats_cache() {
+    if let Some(cache) = COMMIT_STATS_CACHE.get() {
+        if let Ok(mut cache) = cache.lock() {
+            *cache = CommitStatsCache::default();
+        }
+    }
+}
+
+/// Total number of commit stats lookups served from the cache (for diagnostics)
+pub fn commit_stats_cache_hits() -> usize {
+    COMMIT_STATS_CACHE_HITS.load(Ordering::Relaxed)
+}
+
+fn get_commit_stats(repo: &Repository, commit: &git2::Commit) -> Result<CommitStats, git2::Error> {
+    let cache = COMMIT_STATS_CACHE.get_or_init(|| Mutex::new(CommitStatsCache::default()));
+    if let Some(stats) = cache.lock().ok().and_then(|mut cache| cache.get(commit.id())) {
+        COMMIT_STATS_CACHE_HITS.fetch_add(1, Ordering::Relaxed);
+        return Ok(stats);
+    }
+
+    let stats = compute_commit_stats(repo, commit)?;
+    if let Ok(mut cache) = cache.lock() {
+        cache.insert(commit.id(), stats);
+    }
+    Ok(stats)
+}
+
+fn compute_commit_stats(repo: &Repository, commit: &git2::Commit) -> Result<CommitStats, git2::Error> {
     let tree = commit.tree()?;
 
     // Get parent tree (or empty tree for initial commit)
@@ -348,8 +846,14 @@ fn get_commit_stats(repo: &Repository, commit: &git2::Commit) -> Result<(usize,
 }
 
 pub fn get_status(repo: &Repository) -> Result<StatusInfo, GitError> {
+    get_status_cancellable(repo, &CancellationToken::new())
+}
+
+/// `get_status` that stops early with `GitError::Cancelled` when `cancel` is triggered
+pub fn get_status_cancellable(repo: &Repository, cancel: &CancellationToken) -> Result<StatusInfo, GitError> {
     use std::time::Instant;
     let start = Instant::now();
+    require_workdir(repo)?;
     
     let mut opts = StatusOptions::new();
     opts.include_untracked(true);
@@ -361,6 +865,7 @@ pub fn get_status(repo: &Repository) -> Result<StatusInfo, GitError> {
     // Don't refresh the index from disk - use cached state (faster)
     opts.update_index(false);
 
+    cancel.check()?;
     let statuses = repo.statuses(Some(&mut opts))?;
     tracing::info!("git status took {:?} for {} entries", start.elapsed(), statuses.len());
 
@@ -369,6 +874,7 @@ pub fn get_status(repo: &Repository) -> Result<StatusInfo, GitError> {
     let mut untracked = Vec::new();
 
     for entry in statuses.iter() {
+        cancel.check()?;
         let path = entry.path().unwrap_or("").to_string();
         let status = entry.status();
 
@@ -438,10 +944,8 @@ fn wt_status_string(status: git2::Status) -> String {
 }
 
 pub fn stage_files(repo: &Repository, paths: &[String]) -> Result<(), GitError> {
+    let workdir = require_workdir(repo)?;
     let mut index = repo.index()?;
-    let workdir = repo.workdir().ok_or_else(|| {
-        git2::Error::from_str("Repository has no working directory")
-    })?;
 
     for path in paths {
         // Handle directory paths (may have trailing slash from recurse_untracked_dirs=false)
@@ -465,12 +969,14 @@ pub fn stage_files(repo: &Repository, paths: &[String]) -> Result<(), GitError>
 }
 
 pub fn unstage_files(repo: &Repository, paths: &[String]) -> Result<(), GitError> {
+    require_workdir(repo)?;
     let head = repo.head()?.peel_to_commit()?;
     repo.reset_default(Some(head.as_object()), paths.iter().map(Path::new))?;
     Ok(())
 }
 
 pub fn discard_changes(repo: &Repository, paths: &[String]) -> Result<(), GitError> {
+    require_workdir(repo)?;
     let mut checkout_opts = git2::build::CheckoutBuilder::new();
     checkout_opts.force();
     for path in paths {
@@ -481,17 +987,35 @@ pub fn discard_changes(repo: &Repository, paths: &[String]) -> Result<(), GitErr
 }
 
 pub fn create_commit(repo: &Repository, message: &str) -> Result<String, GitError> {
+    create_commit_as(repo, message, &AuthorOverride::default())
+}
+
+/// Author fields to record instead of the configured identity (e.g. when committing
+/// someone else's patch). Unset fields fall back to the committer's.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthorOverride {
+    pub name: Option<String>,
+    pub email: Option<String>,
+    /// Unix seconds, in the committer's timezone offset
+    pub time: Option<i64>,
+}
+
+/// `create_commit` with the author taken from `author`; the committer stays the configured user
+pub fn create_commit_as(repo: &Repository, message: &str, author: &AuthorOverride) -> Result<String, GitError> {
+    require_workdir(repo)?;
     let mut index = repo.index()?;
     let tree_id = index.write_tree()?;
     let tree = repo.find_tree(tree_id)?;
 
-    let signature = repo.signature()?;
+    let committer = configured_signature(repo)?;
+    let author_signature = override_signature(&committer, author)?;
     let parent = repo.head()?.peel_to_commit()?;
 
     let commit_id = repo.commit(
         Some("HEAD"),
-        &signature,
-        &signature,
+        &author_signature,
+        &committer,
         message,
         &tree,
         &[&parent],
@@ -500,10 +1024,232 @@ pub fn create_commit(repo: &Repository, message: &str) -> Result<String, GitErro
     Ok(commit_id.to_string())
 }
 
+fn override_signature(
+    committer: &git2::Signature<'static>,
+    author: &AuthorOverride,
+) -> Result<git2::Signature<'static>, GitError> {
+    let name = match author.name.as_deref().map(str::trim) {
+        Some("") => return Err(GitError::Validation("Author name cannot be empty".to_string())),
+        Some(name) => name,
+        None => committer.name().unwrap_or_default(),
+    };
+    let email = match author.email.as_deref().map(str::trim) {
+        Some("") => return Err(GitError::Validation("Author email cannot be empty".to_string())),
+        Some(email) => email,
+        None => committer.email().unwrap_or_default(),
+    };
+    let time = git2::Time::new(
+        author.time.unwrap_or_else(|| committer.when().seconds()),
+        committer.when().offset_minutes(),
+    );
+    Ok(git2::Signature::new(name, email, &time)?)
+}
+
+/// Staged and unstaged paths, sorted; untracked files don't count
+fn dirty_paths(repo: &Repository) -> Result<Vec<String>, GitError> {
+    let status = get_status(repo)?;
+    let mut paths: Vec<String> = status
+        .staged
+        .into_iter()
+        .chain(status.unstaged)
+        .map(|file| file.path)
+        .collect();
+    paths.sort();
+    paths.dedup();
+    Ok(paths)
+}
+
+/// Reject paths that could escape the repository: absolute paths or any `..` component
+pub fn validate_relative_path(path: &str) -> Result<(), GitError> {
+    if path.starts_with('/') || path.starts_with('\\') || Path::new(path).is_absolute() {
+        return Err(GitError::Validation("File path cannot be absolute".to_string()));
+    }
+    if path.split(['/', '\\']).any(|part| part == "..") {
+        return Err(GitError::Validation("File path cannot contain '..'".to_string()));
+    }
+    Ok(())
+}
+
+/// Restore one file to its version at `source_ref`, leaving the rest of the tree alone.
+/// With `staged`, the index is updated too (like `git checkout <ref> -- <file>`).
+pub fn checkout_file(repo_path: &str, source_ref: &str, file_path: &str, staged: bool) -> Result<(), GitError> {
+    validate_relative_path(file_path)?;
+    let repo = open_repo(repo_path)?;
+    require_workdir(&repo)?;
+    let commit = repo.revparse_single(source_ref)?.peel_to_commit()?;
+    commit.tree()?.get_path(Path::new(file_path)).map_err(|_| {
+        GitError::Validation(format!("'{}' does not exist at {}", file_path, source_ref))
+    })?;
+
+    let source = format!("--source={}", commit.id());
+    let mut args = vec!["restore", source.as_str(), "--worktree"];
+    if staged {
+        args.push("--staged");
+    }
+    args.extend(["--", file_path]);
+
+    let output = git_command()
+        .args(&args)
+        .current_dir(repo_path)
+        .output()
+        .map_err(|e| git2::Error::from_str(&format!("Failed to run git restore: {}", e)))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(git2::Error::from_str(&format!("git restore failed: {}", stderr)).into())
+    }
+}
+
+/// Fail with `GitError::DirtyWorkingTree` listing the staged and unstaged paths, if any
+pub fn ensure_clean_tree(repo: &Repository, message: &str) -> Result<(), GitError> {
+    let paths = dirty_paths(repo)?;
+    if paths.is_empty() {
+        return Ok(());
+    }
+    Err(GitError::DirtyWorkingTree {
+        message: message.to_string(),
+        paths,
+    })
+}
+
+/// Dry-run a safe checkout of `target` and return the local files it would overwrite
+fn checkout_conflicts(repo: &Repository, target: &git2::Object) -> Result<Vec<String>, GitError> {
+    let mut blocking = Vec::new();
+    let result = {
+        let mut opts = git2::build::CheckoutBuilder::new();
+        opts.dry_run()
+            .notify_on(git2::CheckoutNotificationType::CONFLICT)
+            .notify(|_, path, _, _, _| {
+                if let Some(path) = path {
+                    blocking.push(path.to_string_lossy().to_string());
+                }
+                true
+            });
+        repo.checkout_tree(target, Some(&mut opts))
+    };
+    match result {
+        Err(e) if e.code() != git2::ErrorCode::Conflict => Err(e.into()),
+        _ => Ok(blocking),
+    }
+}
+
+/// The user.name/user.email signature, with a validation error saying how to fix a
+/// missing or empty identity instead of libgit2's config lookup failure
+fn configured_signature(repo: &Repository) -> Result<git2::Signature<'static>, GitError> {
+    repo.signature().map_err(|e| {
+        GitError::Validation(format!(
+            "Git identity is not configured ({}). Set user.name and user.email for this repository or globally.",
+            e.message()
+        ))
+    })
+}
+
+/// Read config values (all levels, most specific wins). Unset keys map to None.
+pub fn get_git_config(repo: &Repository, keys: &[String]) -> Result<HashMap<String, Option<String>>, GitError> {
+    let config = repo.config()?.snapshot()?;
+    Ok(keys
+        .iter()
+        .map(|key| (key.clone(), config.get_string(key).ok()))
+        .collect())
+}
+
+/// Write a config value to the repository's own config, or the user's global one
+pub fn set_git_config(repo: &Repository, key: &str, value: &str, global: bool) -> Result<(), GitError> {
+    let mut config = if global {
+        match repo.config()?.open_level(git2::ConfigLevel::Global) {
+            Ok(config) => config,
+            // No global config file yet: let git pick and create it, since where it
+            // lives depends on the platform (HOME is usually unset on Windows)
+            Err(_) => {
+                let output = git_command()
+                    .args(["config", "--global", key, value])
+                    .current_dir(repo.workdir().unwrap_or_else(|| repo.path()))
+                    .output()
+                    .map_err(|e| git2::Error::from_str(&format!("Failed to run git config: {}", e)))?;
+                if !output.status.success() {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    return Err(git2::Error::from_str(&format!("git config failed: {}", stderr.trim())).into());
+                }
+                return Ok(());
+            }
+        }
+    } else {
+        repo.config()?.open_level(git2::ConfigLevel::Local)?
+    };
+    config.set_str(key, value)?;
+    Ok(())
+}
+
 pub fn checkout_branch(repo: &Repository, branch_name: &str) -> Result<(), GitError> {
+    checkout_branch_with(repo, branch_name, &BranchCheckoutOptions::default())
+}
+
+/// How `checkout_branch_with` treats local changes the checkout would overwrite
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BranchCheckoutOptions {
+    /// Overwrite conflicting local changes
+    pub force: bool,
+    /// Stash local changes first; the stash is left for the user to apply
+    pub stash_before: bool,
+}
+
+/// Check out a branch (or any revision, detached). Unless `force` or `stash_before` is set,
+/// local changes the checkout would overwrite are reported as `GitError::DirtyWorkingTree`
+/// and nothing is touched.
+pub fn checkout_branch_with(
+    repo: &Repository,
+    branch_name: &str,
+    options: &BranchCheckoutOptions,
+) -> Result<(), GitError> {
+    require_workdir(repo)?;
     let (object, reference) = repo.revparse_ext(branch_name)?;
 
-    repo.checkout_tree(&object, None)?;
+    let stashed = options.stash_before && !dirty_paths(repo)?.is_empty();
+    if stashed {
+        // Stashing needs a mutable handle; a second one on the same repository is fine
+        let mut stash_repo = Repository::open(repo.path())?;
+        let message = format!("Auto-stash before checking out {}", branch_name);
+        create_stash(&mut stash_repo, Some(&message), &StashOptions::default())?;
+    }
+
+    let result = checkout_object(repo, branch_name, &object, reference, options.force);
+    if result.is_err() && stashed {
+        // The checkout didn't happen, so put the user's changes back where they were
+        if let Err(e) = Repository::open(repo.path()).map_err(GitError::from).and_then(|mut r| pop_stash(&mut r, 0)) {
+            tracing::warn!("Failed to restore auto-stash after refused checkout of {}: {}", branch_name, e);
+        }
+    }
+    result
+}
+
+fn checkout_object(
+    repo: &Repository,
+    branch_name: &str,
+    object: &git2::Object,
+    reference: Option<git2::Reference>,
+    force: bool,
+) -> Result<(), GitError> {
+    if !force {
+        let blocking = checkout_conflicts(repo, object)?;
+        if !blocking.is_empty() {
+            return Err(GitError::DirtyWorkingTree {
+                message: format!(
+                    "Cannot check out {}: local changes would be overwritten. Commit or stash them first.",
+                    branch_name
+                ),
+                paths: blocking,
+            });
+        }
+    }
+
+    let mut opts = git2::build::CheckoutBuilder::new();
+    if force {
+        opts.force();
+    }
+    repo.checkout_tree(object, Some(&mut opts))?;
 
     match reference {
         Some(gref) => repo.set_head(gref.name().unwrap())?,
@@ -529,6 +1275,105 @@ pub fn create_branch(repo: &Repository, branch_name: &str, checkout: bool) -> Re
     Ok(())
 }
 
+/// Delete a local branch.
+/// Without `force`, refuses to delete a branch whose tip is not reachable from HEAD
+/// (mirrors `git branch -d` vs `git branch -D`).
+pub fn delete_branch(repo: &Repository, branch_name: &str, force: bool) -> Result<(), GitError> {
+    let mut branch = repo.find_branch(branch_name, BranchType::Local)?;
+
+    if branch.is_head() {
+        return Err(GitError::Validation(format!(
+            "Cannot delete branch '{}' because it is currently checked out",
+            branch_name
+        )));
+    }
+
+    if !force {
+        if let (Some(branch_oid), Ok(head)) = (branch.get().target(), repo.head()) {
+            if let Some(head_oid) = head.target() {
+                let merged = branch_oid == head_oid
+                    || repo.graph_descendant_of(head_oid, branch_oid)?;
+                if !merged {
+                    return Err(git2::Error::from_str(&format!(
+                        "The branch '{}' is not fully merged. Use force to delete it anyway.",
+                        branch_name
+                    ))
+                    .into());
+                }
+            }
+        }
+    }
+
+    branch.delete()?;
+    Ok(())
+}
+
+/// Rename a local branch. HEAD follows the branch if it was checked out.
+pub fn rename_branch(repo: &Repository, old_name: &str, new_name: &str) -> Result<(), GitError> {
+    let mut branch = repo.find_branch(old_name, BranchType::Local)?;
+    branch.rename(new_name, false)?;
+    Ok(())
+}
+
+// Remote configuration
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteInfo {
+    pub name: String,
+    pub fetch_url: Option<String>,
+    /// Push URL; falls back to the fetch URL when no separate pushurl is configured
+    pub push_url: Option<String>,
+}
+
+/// List configured remotes with their fetch and push URLs
+pub fn list_remotes(repo: &Repository) -> Result<Vec<RemoteInfo>, GitError> {
+    let mut remotes = Vec::new();
+
+    for name in repo.remotes()?.iter().flatten() {
+        let remote = repo.find_remote(name)?;
+        let fetch_url = remote.url().map(String::from);
+        let push_url = remote.pushurl().map(String::from).or_else(|| fetch_url.clone());
+
+        remotes.push(RemoteInfo {
+            name: name.to_string(),
+            fetch_url,
+            push_url,
+        });
+    }
+
+    Ok(remotes)
+}
+
+/// Add a new remote; fails if a remote with the same name already exists
+pub fn add_remote(repo: &Repository, name: &str, url: &str) -> Result<(), GitError> {
+    if repo.find_remote(name).is_ok() {
+        return Err(GitError::Validation(format!("Remote '{}' already exists", name)));
+    }
+    repo.remote(name, url)?;
+    Ok(())
+}
+
+pub fn rename_remote(repo: &Repository, old_name: &str, new_name: &str) -> Result<(), GitError> {
+    if repo.find_remote(new_name).is_ok() {
+        return Err(GitError::Validation(format!("Remote '{}' already exists", new_name)));
+    }
+    // Refspecs that couldn't be rewritten are returned as "problems"; the rename itself still succeeded
+    let problems = repo.remote_rename(old_name, new_name)?;
+    for problem in problems.iter().flatten() {
+        tracing::warn!("Remote rename left refspec unchanged: {}", problem);
+    }
+    Ok(())
+}
+
+/// Remove a remote; succeeds if the remote doesn't exist
+pub fn remove_remote(repo: &Repository, name: &str) -> Result<(), GitError> {
+    match repo.remote_delete(name) {
+        Ok(()) => Ok(()),
+        Err(e) if e.code() == git2::ErrorCode::NotFound => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
 // Remote operations - using git CLI for better credential handling
 use std::process::Command;
 
@@ -558,7 +1403,7 @@ fn get_user_path() -> String {
 }
 
 /// Create a git Command with proper environment for packaged app
-fn git_command() -> Command {
+pub(crate) fn git_command() -> Command {
     let mut cmd = Command::new("git");
     cmd.env("PATH", get_user_path());
     // Ensure git can find SSH keys and config
@@ -566,49 +1411,280 @@ fn git_command() -> Command {
         cmd.env("HOME", &home);
         cmd.env("SSH_AUTH_SOCK", std::env::var("SSH_AUTH_SOCK").unwrap_or_default());
     }
-    cmd
+    cmd
+}
+
+/// Default time limit for git commands that talk to a remote
+pub const DEFAULT_REMOTE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Run a git command that talks to a remote, killing it if it runs longer than
+/// `timeout` (default [`DEFAULT_REMOTE_TIMEOUT`]). A timeout is reported as a
+/// network-class error. If the killed process leaves behind an `index.lock` that
+/// didn't exist before it started, the lock is removed.
+fn run_remote_git(
+    repo_path: &str,
+    args: &[&str],
+    label: &str,
+    timeout: Option<Duration>,
+) -> Result<std::process::Output, GitError> {
+    use std::io::Read;
+    use std::process::Stdio;
+
+    let timeout = timeout.unwrap_or(DEFAULT_REMOTE_TIMEOUT);
+    let index_lock = Repository::open(repo_path)
+        .map(|repo| repo.path().join("index.lock"))
+        .unwrap_or_else(|_| Path::new(repo_path).join(".git").join("index.lock"));
+    let lock_existed = index_lock.exists();
+
+    let mut child = git_command()
+        .args(args)
+        .current_dir(repo_path)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| git2::Error::from_str(&format!("Failed to run git {}: {}", label, e)))?;
+
+    // Drain the pipes on separate threads so a chatty process can't block on a full pipe
+    let mut stdout_pipe = child.stdout.take();
+    let mut stderr_pipe = child.stderr.take();
+    let stdout_reader = std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(pipe) = stdout_pipe.as_mut() {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    });
+    let stderr_reader = std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(pipe) = stderr_pipe.as_mut() {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    });
+
+    let started = std::time::Instant::now();
+    let status = loop {
+        if let Some(status) = child
+            .try_wait()
+            .map_err(|e| git2::Error::from_str(&format!("Failed to wait for git {}: {}", label, e)))?
+        {
+            break status;
+        }
+        if started.elapsed() >= timeout {
+            let _ = child.kill();
+            let _ = child.wait();
+            if !lock_existed && index_lock.exists() {
+                let _ = std::fs::remove_file(&index_lock);
+            }
+            // Don't join the readers: a grandchild (ssh, credential helper) may still hold the pipes
+            return Err(git2::Error::new(
+                git2::ErrorCode::GenericError,
+                git2::ErrorClass::Net,
+                format!("git {} timed out after {}s", label, timeout.as_secs()),
+            )
+            .into());
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    };
+
+    Ok(std::process::Output {
+        status,
+        stdout: stdout_reader.join().unwrap_or_default(),
+        stderr: stderr_reader.join().unwrap_or_default(),
+    })
+}
+
+/// Fetch from a single remote, or from all remotes when `remote` is None
+pub fn git_fetch(repo_path: &str, remote: Option<&str>, timeout: Option<Duration>) -> Result<String, GitError> {
+    let args = match remote {
+        Some(name) => vec!["fetch", "--prune", name],
+        None => vec!["fetch", "--all", "--prune"],
+    };
+
+    let output = run_remote_git(repo_path, &args, "fetch", timeout)?;
+
+    if output.status.success() {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Ok(format!("{}{}", stdout, stderr).trim().to_string())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(git2::Error::from_str(&format!("git fetch failed: {}", stderr)).into())
+    }
+}
+
+/// A ref whose target changed during a fetch
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FetchedRef {
+    pub ref_name: String,
+    /// None when the ref was created by the fetch
+    pub old_id: Option<String>,
+    /// None when the ref was pruned
+    pub new_id: Option<String>,
+}
+
+fn snapshot_refs(repo_path: &str) -> Result<HashMap<String, String>, GitError> {
+    let repo = Repository::open(repo_path)?;
+    let mut refs = HashMap::new();
+    for reference in repo.references()?.flatten() {
+        if let (Some(name), Some(target)) = (reference.name(), reference.target()) {
+            refs.insert(name.to_string(), target.to_string());
+        }
+    }
+    Ok(refs)
+}
+
+/// Fetch `remote` (optionally just `refspec`) and report the refs it created, moved or
+/// pruned, sorted by name. Refs are compared before and after the fetch so full ids are
+/// reported regardless of how git abbreviates them in its output.
+pub fn git_fetch_remote(
+    repo_path: &str,
+    remote: &str,
+    refspec: Option<&str>,
+    prune: bool,
+    timeout: Option<Duration>,
+) -> Result<Vec<FetchedRef>, GitError> {
+    if remote.trim().is_empty() || remote.starts_with('-') {
+        return Err(GitError::Validation(format!("Invalid remote name '{}'", remote)));
+    }
+    if refspec.is_some_and(|spec| spec.trim().is_empty() || spec.starts_with('-')) {
+        return Err(GitError::Validation("Invalid refspec".to_string()));
+    }
+
+    let before = snapshot_refs(repo_path)?;
+
+    let mut args = vec!["fetch"];
+    if prune {
+        args.push("--prune");
+    }
+    args.push(remote);
+    args.extend(refspec);
+
+    let output = run_remote_git(repo_path, &args, "fetch", timeout)?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(git2::Error::from_str(&format!("git fetch failed: {}", stderr)).into());
+    }
+
+    let after = snapshot_refs(repo_path)?;
+    let mut updates: Vec<FetchedRef> = after
+        .iter()
+        .filter(|(name, new_id)| before.get(*name) != Some(*new_id))
+        .map(|(name, new_id)| FetchedRef {
+            ref_name: name.clone(),
+            old_id: before.get(name).cloned(),
+            new_id: Some(new_id.clone()),
+        })
+        .chain(before.iter().filter(|(name, _)| !after.contains_key(*name)).map(|(name, old_id)| FetchedRef {
+            ref_name: name.clone(),
+            old_id: Some(old_id.clone()),
+            new_id: None,
+        }))
+        .collect();
+    updates.sort_by(|a, b| a.ref_name.cmp(&b.ref_name));
+    Ok(updates)
+}
+
+/// How `git_pull` integrates the upstream branch; None in `git_pull` follows the user's
+/// `pull.rebase`/`pull.ff` config
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum PullStrategy {
+    Merge,
+    FfOnly,
+    Rebase,
+}
+
+impl PullStrategy {
+    fn flag(self) -> &'static str {
+        match self {
+            PullStrategy::Merge => "--no-rebase",
+            PullStrategy::FfOnly => "--ff-only",
+            PullStrategy::Rebase => "--rebase",
+        }
+    }
 }
 
-pub fn git_fetch(repo_path: &str) -> Result<String, GitError> {
-    let output = git_command()
-        .args(["fetch", "--all", "--prune"])
-        .current_dir(repo_path)
-        .output()
-        .map_err(|e| git2::Error::from_str(&format!("Failed to run git fetch: {}", e)))?;
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PullResult {
+    pub output: String,
+    /// The pull stopped with conflicts; the repository is left mid-merge or mid-rebase
+    pub has_conflicts: bool,
+    pub conflicting_files: Vec<String>,
+}
+
+/// Pull the current branch's upstream; `autostash` stashes local changes first and
+/// reapplies them afterwards. Conflicts are not an error: the result reports them so the
+/// caller can move straight into resolution.
+pub fn git_pull(
+    repo_path: &str,
+    strategy: Option<PullStrategy>,
+    autostash: bool,
+    timeout: Option<Duration>,
+) -> Result<PullResult, GitError> {
+    let mut args = vec!["pull"];
+    args.extend(strategy.map(PullStrategy::flag));
+    if autostash {
+        args.push("--autostash");
+    }
+    let output = run_remote_git(repo_path, &args, "pull", timeout)?;
 
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
     if output.status.success() {
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        Ok(format!("{}{}", stdout, stderr).trim().to_string())
-    } else {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        Err(git2::Error::from_str(&format!("git fetch failed: {}", stderr)).into())
+        return Ok(PullResult {
+            output: stdout.trim().to_string(),
+            has_conflicts: false,
+            conflicting_files: Vec::new(),
+        });
     }
-}
-
-pub fn git_pull(repo_path: &str) -> Result<String, GitError> {
-    let output = git_command()
-        .args(["pull"])
-        .current_dir(repo_path)
-        .output()
-        .map_err(|e| git2::Error::from_str(&format!("Failed to run git pull: {}", e)))?;
 
-    if output.status.success() {
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        Ok(stdout.trim().to_string())
+    let conflicting_files = super::open_repo(repo_path)
+        .and_then(|repo| super::merge::conflicted_paths(&repo))
+        .unwrap_or_default();
+    if !conflicting_files.is_empty() || stdout.contains("CONFLICT") || stderr.contains("could not apply") {
+        Ok(PullResult {
+            output: format!("{}{}", stdout, stderr).trim().to_string(),
+            has_conflicts: true,
+            conflicting_files,
+        })
     } else {
-        let stderr = String::from_utf8_lossy(&output.stderr);
         Err(git2::Error::from_str(&format!("git pull failed: {}", stderr)).into())
     }
 }
 
-pub fn git_push(repo_path: &str) -> Result<String, GitError> {
+/// How (and whether) to force a push
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum ForceMode {
+    #[default]
+    None,
+    Force,
+    /// Only overwrite the remote ref if it still matches our remote-tracking ref
+    ForceWithLease,
+}
+
+/// Push to `remote` (default "origin") with `refspec` (default "HEAD"), setting upstream
+pub fn git_push(
+    repo_path: &str,
+    remote: Option<&str>,
+    refspec: Option<&str>,
+    force: ForceMode,
+    timeout: Option<Duration>,
+) -> Result<String, GitError> {
     // Use -u origin HEAD to automatically set upstream for new branches
-    let output = git_command()
-        .args(["push", "-u", "origin", "HEAD"])
-        .current_dir(repo_path)
-        .output()
-        .map_err(|e| git2::Error::from_str(&format!("Failed to run git push: {}", e)))?;
+    let mut args = vec!["push", "-u"];
+    match force {
+        ForceMode::None => {}
+        ForceMode::Force => args.push("--force"),
+        ForceMode::ForceWithLease => args.push("--force-with-lease"),
+    }
+    args.push(remote.unwrap_or("origin"));
+    args.push(refspec.unwrap_or("HEAD"));
+
+    let output = run_remote_git(repo_path, &args, "push", timeout)?;
 
     if output.status.success() {
         let stdout = String::from_utf8_lossy(&output.stdout);
@@ -622,7 +1698,7 @@ pub fn git_push(repo_path: &str) -> Result<String, GitError> {
 }
 
 /// Execute a remote action (fetch or pull with various strategies)
-pub fn git_remote_action(repo_path: &str, action: &str) -> Result<String, GitError> {
+pub fn git_remote_action(repo_path: &str, action: &str, timeout: Option<Duration>) -> Result<String, GitError> {
     let args: Vec<&str> = match action {
         "fetch_all" => vec!["fetch", "--all", "--prune"],
         "pull_ff" => vec!["pull", "--ff"],
@@ -633,11 +1709,7 @@ pub fn git_remote_action(repo_path: &str, action: &str) -> Result<String, GitErr
 
     let action_label = if action == "fetch_all" { "fetch" } else { "pull" };
 
-    let output = git_command()
-        .args(&args)
-        .current_dir(repo_path)
-        .output()
-        .map_err(|e| git2::Error::from_str(&format!("Failed to run git {}: {}", action_label, e)))?;
+    let output = run_remote_git(repo_path, &args, action_label, timeout)?;
 
     if output.status.success() {
         let stdout = String::from_utf8_lossy(&output.stdout);
@@ -683,9 +1755,46 @@ pub fn cherry_pick(repo_path: &str, commit_id: &str) -> Result<String, GitError>
     }
 }
 
-pub fn reset_hard(repo_path: &str, commit_id: &str) -> Result<String, GitError> {
+/// What `reset_commit` resets besides the branch tip
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ResetMode {
+    /// Keep the index and working tree; undone commits' changes stay staged
+    Soft,
+    /// Reset the index but keep the working tree (git's default)
+    Mixed,
+    /// Reset the index and working tree, discarding uncommitted changes
+    Hard,
+}
+
+impl ResetMode {
+    pub fn flag(self) -> &'static str {
+        match self {
+            ResetMode::Soft => "--soft",
+            ResetMode::Mixed => "--mixed",
+            ResetMode::Hard => "--hard",
+        }
+    }
+}
+
+/// Move the current branch to `commit_id`. A hard reset of a dirty tree needs `confirm`,
+/// otherwise it's rejected with the number of uncommitted changes that would be lost.
+pub fn reset_commit(repo_path: &str, commit_id: &str, mode: ResetMode, confirm: bool) -> Result<String, GitError> {
+    if mode == ResetMode::Hard && !confirm {
+        let repo = open_repo(repo_path)?;
+        // Untracked files survive `reset --hard`; a file with staged and unstaged edits counts once
+        let changes = dirty_paths(&repo)?.len();
+        if changes > 0 {
+            return Err(GitError::Validation(format!(
+                "Hard reset would discard {} uncommitted change{}; confirm to continue",
+                changes,
+                if changes == 1 { "" } else { "s" }
+            )));
+        }
+    }
+
     let output = git_command()
-        .args(["reset", "--hard", commit_id])
+        .args(["reset", mode.flag(), commit_id])
         .current_dir(repo_path)
         .output()
         .map_err(|e| git2::Error::from_str(&format!("Failed to run git reset: {}", e)))?;
@@ -695,8 +1804,33 @@ pub fn reset_hard(repo_path: &str, commit_id: &str) -> Result<String, GitError>
         Ok(stdout.trim().to_string())
     } else {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        Err(git2::Error::from_str(&format!("git reset --hard failed: {}", stderr)).into())
+        Err(git2::Error::from_str(&format!("git reset {} failed: {}", mode.flag(), stderr)).into())
+    }
+}
+
+/// `git reset --hard` without the dirty-tree guard
+pub fn reset_hard(repo_path: &str, commit_id: &str) -> Result<String, GitError> {
+    reset_commit(repo_path, commit_id, ResetMode::Hard, true)
+}
+
+/// Undo the last commit but keep its changes staged (`git reset --soft HEAD^`)
+pub fn uncommit(repo_path: &str) -> Result<(), GitError> {
+    let repo = open_repo(repo_path)?;
+    if repo.state() != git2::RepositoryState::Clean {
+        return Err(GitError::Validation(
+            "Cannot uncommit while a merge, rebase or other operation is in progress".to_string(),
+        ));
     }
+    let head = repo
+        .head()
+        .and_then(|head| head.peel_to_commit())
+        .map_err(|_| GitError::Validation("There is no commit to undo".to_string()))?;
+    if head.parent_count() == 0 {
+        return Err(GitError::Validation("Cannot uncommit the root commit".to_string()));
+    }
+
+    reset_commit(repo_path, "HEAD^", ResetMode::Soft, false)?;
+    Ok(())
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -735,13 +1869,7 @@ pub fn squash_commits(repo_path: &str, commit_ids: Vec<String>, message: &str) -
         ).into());
     }
     
-    // Check for uncommitted changes
-    let status = get_status(&repo)?;
-    if !status.staged.is_empty() || !status.unstaged.is_empty() {
-        return Err(git2::Error::from_str(
-            "Cannot squash with uncommitted changes. Please commit or stash your changes first."
-        ).into());
-    }
+    ensure_clean_tree(&repo, "Cannot squash with uncommitted changes. Please commit or stash your changes first.")?;
     
     // Find the parent of the oldest commit
     let oldest_commit_id = &commit_ids[0];
@@ -1155,6 +2283,22 @@ pub struct StashEntry {
     pub message: String,
     pub oid: String,
     pub time: i64,
+    /// Branch the stash was created on, or None for detached HEAD / unparseable messages
+    pub branch: Option<String>,
+    /// Commit the stash was created on top of (the stash commit's first parent)
+    pub base_commit_id: String,
+}
+
+/// Extract the branch name from a stash message ("WIP on <branch>: ..." or "On <branch>: ...")
+fn parse_stash_branch(message: &str) -> Option<String> {
+    let rest = message
+        .strip_prefix("WIP on ")
+        .or_else(|| message.strip_prefix("On "))?;
+    let (branch, _) = rest.split_once(": ")?;
+    if branch.is_empty() || branch == "(no branch)" {
+        return None;
+    }
+    Some(branch.to_string())
 }
 
 /// List all stashes in the repository
@@ -1167,19 +2311,26 @@ pub fn list_stashes(repo: &mut Repository) -> Result<Vec<StashEntry>, GitError>
         true // continue iteration
     })?;
 
-    // Second pass: look up commit times (immutable borrow)
+    // Second pass: look up commit times and base commits (immutable borrow)
     let stashes = stash_info
         .into_iter()
         .map(|(index, message, oid)| {
-            let time = repo.find_commit(oid)
-                .map(|c| c.time().seconds())
-                .unwrap_or(0);
+            let commit = repo.find_commit(oid).ok();
+            let time = commit.as_ref().map(|c| c.time().seconds()).unwrap_or(0);
+            let base_commit_id = commit
+                .as_ref()
+                .and_then(|c| c.parent_id(0).ok())
+                .map(|id| id.to_string())
+                .unwrap_or_default();
+            let branch = parse_stash_branch(&message);
 
             StashEntry {
                 stash_index: index,
                 message,
                 oid: oid.to_string(),
                 time,
+                branch,
+                base_commit_id,
             }
         })
         .collect();
@@ -1187,11 +2338,23 @@ pub fn list_stashes(repo: &mut Repository) -> Result<Vec<StashEntry>, GitError>
     Ok(stashes)
 }
 
+/// Options controlling what gets stashed
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct StashOptions {
+    /// Leave staged changes in the index after stashing
+    pub keep_index: bool,
+    /// Also stash (and clean) untracked files
+    pub include_untracked: bool,
+    /// Only stash changes to these paths
+    pub paths: Option<Vec<String>>,
+    /// Only stash what is currently staged, leaving unstaged changes in place
+    pub staged_only: bool,
+}
+
 /// Create a new stash with an optional message
 /// If no message is provided, generates one like git: "WIP on branch: shortid message"
-pub fn create_stash(repo: &mut Repository, message: Option<&str>) -> Result<(), GitError> {
-    let signature = repo.signature()?;
-
+pub fn create_stash(repo: &mut Repository, message: Option<&str>, options: &StashOptions) -> Result<(), GitError> {
     // Generate default message if none provided (like git does)
     let stash_message = if let Some(msg) = message {
         if msg.trim().is_empty() {
@@ -1203,7 +2366,66 @@ pub fn create_stash(repo: &mut Repository, message: Option<&str>) -> Result<(),
         generate_stash_message(repo)?
     };
 
-    repo.stash_save(&signature, &stash_message, None)?;
+    if options.staged_only && options.include_untracked {
+        return Err(GitError::Validation(
+            "Cannot stash only staged changes and include untracked files at the same time".to_string(),
+        ));
+    }
+
+    // git2 has no pathspec or staged-only stashing, so fall back to the CLI for those
+    let paths = options.paths.as_deref().filter(|p| !p.is_empty());
+    if options.staged_only || paths.is_some() {
+        return create_stash_cli(repo, &stash_message, options, paths.unwrap_or_default());
+    }
+
+    let mut flags = git2::StashFlags::DEFAULT;
+    if options.keep_index {
+        flags |= git2::StashFlags::KEEP_INDEX;
+    }
+    if options.include_untracked {
+        flags |= git2::StashFlags::INCLUDE_UNTRACKED;
+    }
+
+    let signature = configured_signature(repo)?;
+    repo.stash_save(&signature, &stash_message, Some(flags))?;
+    Ok(())
+}
+
+fn create_stash_cli(
+    repo: &Repository,
+    message: &str,
+    options: &StashOptions,
+    paths: &[String],
+) -> Result<(), GitError> {
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| git2::Error::from_str("Cannot stash in a bare repository"))?;
+
+    let mut cmd = git_command();
+    cmd.args(["stash", "push", "-m", message]);
+    if options.keep_index {
+        cmd.arg("--keep-index");
+    }
+    if options.include_untracked {
+        cmd.arg("--include-untracked");
+    }
+    if options.staged_only {
+        cmd.arg("--staged");
+    }
+    if !paths.is_empty() {
+        cmd.arg("--").args(paths);
+    }
+
+    let output = cmd
+        .current_dir(workdir)
+        .output()
+        .map_err(|e| git2::Error::from_str(&format!("Failed to run git stash: {}", e)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(git2::Error::from_str(&format!("git stash failed: {}", stderr)).into());
+    }
+
     Ok(())
 }
 
@@ -1230,12 +2452,101 @@ pub fn pop_stash(repo: &mut Repository, stash_index: usize) -> Result<(), GitErr
     Ok(())
 }
 
-/// Drop a stash by index without applying
-pub fn drop_stash(repo: &mut Repository, stash_index: usize) -> Result<(), GitError> {
+/// Drop a stash by index without applying.
+///
+/// When `expected_oid` is given, the stash currently at `stash_index` must have that oid.
+/// Indices shift after every drop, so this guards against dropping the wrong entry with
+/// a stale index.
+pub fn drop_stash(
+    repo: &mut Repository,
+    stash_index: usize,
+    expected_oid: Option<&str>,
+) -> Result<(), GitError> {
+    if let Some(expected_oid) = expected_oid {
+        let mut actual_oid = None;
+        repo.stash_foreach(|index, _, oid| {
+            if index == stash_index {
+                actual_oid = Some(*oid);
+                return false;
+            }
+            true
+        })?;
+
+        match actual_oid {
+            None => {
+                return Err(GitError::Validation(format!("Stash entry {} does not exist", stash_index)));
+            }
+            Some(oid) if oid.to_string() != expected_oid => {
+                return Err(GitError::Validation(format!(
+                    "Stash entry {} has changed (expected {}, found {}); refresh the stash list and try again",
+                    stash_index, expected_oid, oid
+                )));
+            }
+            Some(_) => {}
+        }
+    }
+
     repo.stash_drop(stash_index)?;
     Ok(())
 }
 
+/// Resolve a stash oid to its current index. Indices shift whenever stashes are
+/// created or dropped, so oid-based operations look the index up at call time.
+fn stash_index_for_oid(repo: &mut Repository, stash_oid: &str) -> Result<usize, GitError> {
+    let mut found = None;
+    repo.stash_foreach(|index, _, oid| {
+        if oid.to_string() == stash_oid {
+            found = Some(index);
+            return false;
+        }
+        true
+    })?;
+
+    found.ok_or_else(|| GitError::Validation(format!("Stash {} no longer exists", stash_oid)))
+}
+
+/// Apply the stash with the given oid, wherever it currently sits in the stash list
+pub fn apply_stash_by_oid(repo: &mut Repository, stash_oid: &str) -> Result<(), GitError> {
+    let index = stash_index_for_oid(repo, stash_oid)?;
+    apply_stash(repo, index)
+}
+
+/// Pop the stash with the given oid, wherever it currently sits in the stash list
+pub fn pop_stash_by_oid(repo: &mut Repository, stash_oid: &str) -> Result<(), GitError> {
+    let index = stash_index_for_oid(repo, stash_oid)?;
+    pop_stash(repo, index)
+}
+
+/// Drop the stash with the given oid, wherever it currently sits in the stash list
+pub fn drop_stash_by_oid(repo: &mut Repository, stash_oid: &str) -> Result<(), GitError> {
+    let index = stash_index_for_oid(repo, stash_oid)?;
+    drop_stash(repo, index, None)
+}
+
+/// Create a branch at the stash's base commit, check it out and apply the stash there
+/// (`git stash branch`). The stash is dropped if it applies cleanly.
+pub fn stash_branch(repo_path: &str, stash_index: usize, branch_name: &str) -> Result<String, GitError> {
+    let repo = open_repo(repo_path)?;
+    if repo.find_branch(branch_name, BranchType::Local).is_ok() {
+        return Err(GitError::Validation(format!("Branch '{}' already exists", branch_name)));
+    }
+
+    let output = git_command()
+        .args(["stash", "branch", branch_name, &format!("stash@{{{}}}", stash_index)])
+        .current_dir(repo_path)
+        .output()
+        .map_err(|e| git2::Error::from_str(&format!("Failed to run git stash branch: {}", e)))?;
+
+    if output.status.success() {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Ok(format!("{}{}", stdout, stderr).trim().to_string())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(git2::Error::from_str(&format!("git stash branch failed: {}", stderr)).into())
+    }
+}
+
 // Ahead/behind tracking
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -1289,6 +2600,22 @@ pub fn get_ahead_behind(repo: &Repository) -> Result<Option<AheadBehind>, GitErr
     Ok(Some(AheadBehind { ahead, behind }))
 }
 
+/// Find the best common ancestor of two refs (branches, tags or commit ids).
+/// Returns None when the refs share no history.
+pub fn get_merge_base(repo: &Repository, ref_a: &str, ref_b: &str) -> Result<Option<CommitInfo>, GitError> {
+    let oid_a = repo.revparse_single(ref_a)?.peel_to_commit()?.id();
+    let oid_b = repo.revparse_single(ref_b)?.peel_to_commit()?.id();
+
+    match repo.merge_base(oid_a, oid_b) {
+        Ok(base) => {
+            let commit = repo.find_commit(base)?;
+            Ok(Some(commit_to_info(repo, &commit)))
+        }
+        Err(e) if e.code() == git2::ErrorCode::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
 /// Get commit activity from all local branches within a time range.
 /// Returns minimal data (time + author) for contribution calendar visualization.
 /// Uses TIME sorting for efficient early-stop when commits are older than `since`.
@@ -1297,6 +2624,45 @@ pub fn get_commit_activity_all_branches(
     since: i64,
     until: i64,
 ) -> Result<Vec<CommitActivity>, GitError> {
+    let revwalk = time_sorted_revwalk_all_branches(repo)?;
+    let mut activity = Vec::new();
+
+    for oid_result in revwalk {
+        let oid = match oid_result {
+            Ok(o) => o,
+            Err(_) => continue,
+        };
+
+        let commit = match repo.find_commit(oid) {
+            Ok(c) => c,
+            Err(_) => continue,
+        };
+
+        let time = commit.time().seconds();
+
+        // Early-stop: commits are time-sorted, so if we're past the range, we're done
+        if time < since {
+            break;
+        }
+
+        // Skip commits after the range
+        if time > until {
+            continue;
+        }
+
+        activity.push(CommitActivity {
+            time,
+            author_name: commit.author().name().unwrap_or("Unknown").to_string(),
+            author_email: commit.author().email().unwrap_or("").to_string(),
+        });
+    }
+
+    Ok(activity)
+}
+
+/// Revwalk over every local branch (or HEAD if there are none) in commit time
+/// order, so callers scanning a time range can stop at the first older commit
+fn time_sorted_revwalk_all_branches(repo: &Repository) -> Result<git2::Revwalk<'_>, GitError> {
     let mut revwalk = repo.revwalk()?;
     // Use TIME sorting only (not TOPOLOGICAL) so we can early-stop
     revwalk.set_sorting(git2::Sort::TIME)?;
@@ -1317,39 +2683,227 @@ pub fn get_commit_activity_all_branches(
         let _ = revwalk.push_head();
     }
 
-    let mut activity = Vec::new();
+    Ok(revwalk)
+}
 
-    for oid_result in revwalk {
-        let oid = match oid_result {
-            Ok(o) => o,
-            Err(_) => continue,
-        };
+/// Aggregated activity of one author over a time range
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ContributorStats {
+    /// Name on the author's most recent commit in range
+    pub author_name: String,
+    pub author_email: String,
+    pub total_commits: usize,
+    pub total_files_changed: usize,
+    pub total_additions: usize,
+    pub total_deletions: usize,
+    /// Commit summaries, newest first. Left empty in leaderboards, which would otherwise
+    /// carry every commit in range
+    pub commit_summaries: Vec<String>,
+    /// Commit time of the author's oldest and newest commit in range
+    pub first_commit_time: Option<i64>,
+    pub last_commit_time: Option<i64>,
+}
+
+impl ContributorStats {
+    fn add_commit(&mut self, repo: &Repository, commit: &git2::Commit, with_summary: bool) -> Result<(), GitError> {
+        if self.author_name.is_empty() {
+            self.author_name = commit.author().name().unwrap_or("Unknown").to_string();
+        }
+        let (files_changed, additions, deletions) = get_commit_stats(repo, commit)?;
+        let time = commit.time().seconds();
+        self.total_commits += 1;
+        self.total_files_changed += files_changed;
+        self.total_additions += additions;
+        self.total_deletions += deletions;
+        if with_summary {
+            self.commit_summaries.push(commit.summary().unwrap_or("").to_string());
+        }
+        self.first_commit_time = Some(self.first_commit_time.map_or(time, |t| t.min(time)));
+        self.last_commit_time = Some(self.last_commit_time.map_or(time, |t| t.max(time)));
+        Ok(())
+    }
+}
 
-        let commit = match repo.find_commit(oid) {
-            Ok(c) => c,
-            Err(_) => continue,
+/// Sum the commits authored by `email` (case-insensitive) across all local
+/// branches with commit times in `since..=until`
+pub fn get_contributor_stats(
+    repo: &Repository,
+    email: &str,
+    since: i64,
+    until: i64,
+) -> Result<ContributorStats, GitError> {
+    let mut stats = ContributorStats {
+        author_email: email.to_string(),
+        ..Default::default()
+    };
+
+    for commit in commits_in_window(repo, since, until)? {
+        let author = commit.author();
+        if author.email().is_some_and(|e| e.eq_ignore_ascii_case(email)) {
+            stats.add_commit(repo, &commit, true)?;
+        }
+    }
+
+    Ok(stats)
+}
+
+/// Per-author stats for every commit on local branches with commit times in
+/// `since..=until`, most commits first. Authors are grouped by email, ignoring case.
+/// Commit summaries are omitted; use `get_contributor_stats` for one author's list.
+pub fn get_contributor_leaderboard(
+    repo: &Repository,
+    since: i64,
+    until: i64,
+) -> Result<Vec<ContributorStats>, GitError> {
+    let mut by_email: HashMap<String, ContributorStats> = HashMap::new();
+
+    for commit in commits_in_window(repo, since, until)? {
+        let author = commit.author();
+        let email = author.email().unwrap_or("").to_string();
+        let stats = by_email.entry(email.to_lowercase()).or_insert_with(|| ContributorStats {
+            author_email: email,
+            ..Default::default()
+        });
+        stats.add_commit(repo, &commit, false)?;
+    }
+
+    let mut leaderboard: Vec<ContributorStats> = by_email.into_values().collect();
+    leaderboard.sort_by(|a, b| {
+        b.total_commits
+            .cmp(&a.total_commits)
+            .then_with(|| a.author_email.to_lowercase().cmp(&b.author_email.to_lowercase()))
+    });
+    Ok(leaderboard)
+}
+
+/// How often a file changed over a time range
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct FileHotspot {
+    pub path: String,
+    /// Number of commits that touched the file
+    pub change_count: usize,
+    pub total_additions: usize,
+    pub total_deletions: usize,
+    /// Commit time of the most recent change
+    pub last_changed: i64,
+}
+
+/// The `limit` most frequently changed files across local branches in `since..=until`,
+/// most changes first. Merge commits are skipped so changes aren't counted twice.
+pub fn get_file_hotspots(
+    repo: &Repository,
+    since: i64,
+    until: i64,
+    limit: usize,
+) -> Result<Vec<FileHotspot>, GitError> {
+    let mut by_path: HashMap<String, FileHotspot> = HashMap::new();
+
+    for commit in commits_in_window(repo, since, until)? {
+        if commit.parent_count() > 1 {
+            continue;
+        }
+        let parent_tree = match commit.parent_count() {
+            0 => None,
+            _ => Some(commit.parent(0)?.tree()?),
         };
+        let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), None)?;
+        let time = commit.time().seconds();
+
+        for (idx, delta) in diff.deltas().enumerate() {
+            let Some(path) = delta.new_file().path().or_else(|| delta.old_file().path()) else {
+                continue;
+            };
+            // Binary files have no patch; they still count as a change
+            let (additions, deletions) = git2::Patch::from_diff(&diff, idx)?
+                .and_then(|patch| patch.line_stats().ok())
+                .map(|(_, additions, deletions)| (additions, deletions))
+                .unwrap_or((0, 0));
+
+            let path = path.to_string_lossy().into_owned();
+            let hotspot = by_path.entry(path.clone()).or_insert_with(|| FileHotspot {
+                path,
+                ..Default::default()
+            });
+            hotspot.change_count += 1;
+            hotspot.total_additions += additions;
+            hotspot.total_deletions += deletions;
+            hotspot.last_changed = hotspot.last_changed.max(time);
+        }
+    }
+
+    let mut hotspots: Vec<FileHotspot> = by_path.into_values().collect();
+    hotspots.sort_by(|a, b| {
+        b.change_count
+            .cmp(&a.change_count)
+            .then_with(|| b.last_changed.cmp(&a.last_changed))
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    hotspots.truncate(limit);
+    Ok(hotspots)
+}
 
+/// Commits on all local branches with commit times in `since..=until`, newest first
+fn commits_in_window(repo: &Repository, since: i64, until: i64) -> Result<Vec<git2::Commit<'_>>, GitError> {
+    let mut commits = Vec::new();
+    for oid in time_sorted_revwalk_all_branches(repo)?.flatten() {
+        let Ok(commit) = repo.find_commit(oid) else { continue };
         let time = commit.time().seconds();
 
         // Early-stop: commits are time-sorted, so if we're past the range, we're done
         if time < since {
             break;
         }
-
-        // Skip commits after the range
         if time > until {
             continue;
         }
+        commits.push(commit);
+    }
+    Ok(commits)
+}
 
-        activity.push(CommitActivity {
-            time,
-            author_name: commit.author().name().unwrap_or("Unknown").to_string(),
-            author_email: commit.author().email().unwrap_or("").to_string(),
-        });
+/// Headline numbers for a repository dashboard
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RepoSummary {
+    /// Commits reachable from HEAD
+    pub total_commits: usize,
+    /// Local branches
+    pub branch_count: usize,
+    pub tag_count: usize,
+    /// Distinct author emails (case-insensitive) among the commits reachable from HEAD
+    pub contributor_count: usize,
+    /// None for a repository without commits
+    pub head_commit: Option<CommitInfo>,
+}
+
+/// Count commits, branches, tags and contributors in one pass over HEAD's history
+pub fn get_repo_summary(repo: &Repository) -> Result<RepoSummary, GitError> {
+    let head_commit = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+
+    let mut total_commits = 0;
+    let mut contributors = std::collections::HashSet::new();
+    if let Some(head) = &head_commit {
+        let mut revwalk = repo.revwalk()?;
+        revwalk.push(head.id())?;
+        for oid in revwalk {
+            let commit = repo.find_commit(oid?)?;
+            total_commits += 1;
+            let author = commit.author();
+            if let Some(email) = author.email() {
+                contributors.insert(email.to_lowercase());
+            }
+        }
     }
 
-    Ok(activity)
+    Ok(RepoSummary {
+        total_commits,
+        branch_count: repo.branches(Some(BranchType::Local))?.count(),
+        tag_count: repo.tag_names(None)?.len(),
+        contributor_count: contributors.len(),
+        head_commit: head_commit.map(|commit| commit_to_info(repo, &commit)),
+    })
 }
 
 // Changelog commit with richer data for changelog view
@@ -1493,3 +3047,111 @@ pub fn get_reflog(repo_path: &str, limit: usize) -> Result<Vec<ReflogEntry>, Git
 
     Ok(entries)
 }
+
+// "Seen" marker for tracking what changed since the user last looked
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SeenMarker {
+    /// HEAD commit at the time the marker was set (None for an unborn branch)
+    pub head: Option<String>,
+    /// Hash of the index file contents at the time the marker was set
+    pub index_hash: Option<String>,
+    /// Unix timestamp of when the marker was set
+    pub time: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangesSinceMarker {
+    /// Commits reachable from HEAD that weren't reachable from the marked HEAD
+    pub new_commits: Vec<CommitInfo>,
+    pub head_moved: bool,
+    pub index_changed: bool,
+    /// False if no marker has been set yet for this repository
+    pub has_marker: bool,
+}
+
+fn seen_marker_path(repo: &Repository) -> std::path::PathBuf {
+    repo.path().join("diffy").join("seen-marker.json")
+}
+
+fn current_index_hash(repo: &Repository) -> Option<String> {
+    let contents = std::fs::read(repo.path().join("index")).ok()?;
+    git2::Oid::hash_object(git2::ObjectType::Blob, &contents)
+        .ok()
+        .map(|oid| oid.to_string())
+}
+
+/// Record the current HEAD and index state as "seen"
+pub fn set_seen_marker(repo: &Repository) -> Result<SeenMarker, GitError> {
+    let marker = SeenMarker {
+        head: repo.head().ok().and_then(|h| h.target()).map(|oid| oid.to_string()),
+        index_hash: current_index_hash(repo),
+        time: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0),
+    };
+
+    let path = seen_marker_path(repo);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| git2::Error::from_str(&format!("Failed to create marker directory: {}", e)))?;
+    }
+    let json = serde_json::to_string(&marker)
+        .map_err(|e| git2::Error::from_str(&format!("Failed to serialize marker: {}", e)))?;
+    std::fs::write(&path, json)
+        .map_err(|e| git2::Error::from_str(&format!("Failed to write marker: {}", e)))?;
+
+    Ok(marker)
+}
+
+/// Compare the current HEAD and index against the stored "seen" marker
+pub fn get_changes_since_marker(repo: &Repository) -> Result<ChangesSinceMarker, GitError> {
+    let marker: Option<SeenMarker> = std::fs::read_to_string(seen_marker_path(repo))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok());
+
+    let Some(marker) = marker else {
+        return Ok(ChangesSinceMarker {
+            new_commits: Vec::new(),
+            head_moved: false,
+            index_changed: false,
+            has_marker: false,
+        });
+    };
+
+    let head_oid = repo.head().ok().and_then(|h| h.target());
+    let head_moved = head_oid.map(|oid| oid.to_string()) != marker.head;
+    let index_changed = current_index_hash(repo) != marker.index_hash;
+
+    let mut new_commits = Vec::new();
+    if let (true, Some(head_oid)) = (head_moved, head_oid) {
+        let mut revwalk = repo.revwalk()?;
+        revwalk.set_sorting(git2::Sort::TIME | git2::Sort::TOPOLOGICAL)?;
+        revwalk.push(head_oid)?;
+
+        // The marked commit may have been garbage collected; in that case
+        // everything reachable from HEAD is reported as new
+        if let Some(marked_oid) = marker.head.as_deref().and_then(|s| git2::Oid::from_str(s).ok()) {
+            if repo.find_commit(marked_oid).is_ok() {
+                revwalk.hide(marked_oid)?;
+            }
+        }
+
+        new_commits = revwalk
+            .filter_map(|oid_result| {
+                let oid = oid_result.ok()?;
+                let commit = repo.find_commit(oid).ok()?;
+                Some(commit_to_info(repo, &commit))
+            })
+            .collect();
+    }
+
+    Ok(ChangesSinceMarker {
+        new_commits,
+        head_moved,
+        index_changed,
+        has_marker: true,
+    })
+}
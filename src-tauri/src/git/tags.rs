@@ -0,0 +1,25 @@
+use super::repository::git_command;
+use super::GitError;
+
+/// Describe a commit relative to the nearest reachable tag, like `git describe --tags`.
+/// Returns `<tag>` when exactly on a tag, `<tag>-<n>-g<shortsha>` otherwise, and the
+/// short SHA when no tags are reachable.
+pub fn describe(repo_path: &str, commit_id: Option<&str>) -> Result<String, GitError> {
+    let mut cmd = git_command();
+    cmd.args(["describe", "--tags", "--always"]);
+    if let Some(commit_id) = commit_id {
+        cmd.arg(commit_id);
+    }
+
+    let output = cmd
+        .current_dir(repo_path)
+        .output()
+        .map_err(|e| git2::Error::from_str(&format!("Failed to run git describe: {}", e)))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(git2::Error::from_str(&format!("git describe failed: {}", stderr)).into())
+    }
+}
@@ -0,0 +1,132 @@
+use git2::{ObjectType, Repository};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use super::GitError;
+
+/// One child of a directory, from a commit's tree or the working directory
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TreeEntry {
+    pub name: String,
+    /// Repository-relative path
+    pub path: String,
+    pub is_dir: bool,
+    /// Git file mode (0o100644, 0o100755, 0o040000, 0o120000, 0o160000)
+    pub mode: u32,
+    /// Blob size in bytes; None for directories and submodules
+    pub size: Option<u64>,
+}
+
+/// List the immediate children of `dir_path` (the root when empty) at `commit_id`, or in
+/// the working directory when `commit_id` is None. Directories come first, then by name.
+pub fn list_tree(repo: &Repository, commit_id: Option<&str>, dir_path: &str) -> Result<Vec<TreeEntry>, GitError> {
+    let dir_path = dir_path.trim_end_matches('/');
+    super::validate_relative_path(dir_path)?;
+
+    let mut entries = match commit_id {
+        Some(commit_id) => list_commit_tree(repo, commit_id, dir_path)?,
+        None => list_workdir(repo, dir_path)?,
+    };
+    entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+    Ok(entries)
+}
+
+fn child_path(dir_path: &str, name: &str) -> String {
+    if dir_path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", dir_path, name)
+    }
+}
+
+fn list_commit_tree(repo: &Repository, commit_id: &str, dir_path: &str) -> Result<Vec<TreeEntry>, GitError> {
+    let root = repo.revparse_single(commit_id)?.peel_to_commit()?.tree()?;
+    let tree = if dir_path.is_empty() {
+        root
+    } else {
+        let entry = root.get_path(Path::new(dir_path))?;
+        if entry.kind() != Some(ObjectType::Tree) {
+            return Err(GitError::Validation(format!("'{}' is not a directory", dir_path)));
+        }
+        repo.find_tree(entry.id())?
+    };
+
+    let odb = repo.odb()?;
+    tree.iter()
+        .map(|entry| {
+            let name = String::from_utf8_lossy(entry.name_bytes()).into_owned();
+            let is_dir = entry.kind() == Some(ObjectType::Tree);
+            let size = match entry.kind() {
+                // The header has the size without inflating the blob
+                Some(ObjectType::Blob) => Some(odb.read_header(entry.id())?.0 as u64),
+                _ => None,
+            };
+            Ok(TreeEntry {
+                path: child_path(dir_path, &name),
+                name,
+                is_dir,
+                mode: entry.filemode() as u32,
+                size,
+            })
+        })
+        .collect()
+}
+
+fn list_workdir(repo: &Repository, dir_path: &str) -> Result<Vec<TreeEntry>, GitError> {
+    let workdir = super::require_workdir(repo)?;
+    let dir = workdir.join(dir_path);
+    // A committed symlink (e.g. `link -> /`) must not let the listing leave the repository
+    let canonical = dir
+        .canonicalize()
+        .map_err(|e| git2::Error::from_str(&format!("Failed to resolve {}: {}", dir.display(), e)))?;
+    let workdir_canonical = workdir
+        .canonicalize()
+        .map_err(|e| git2::Error::from_str(&format!("Failed to resolve {}: {}", workdir.display(), e)))?;
+    if !canonical.starts_with(&workdir_canonical) {
+        return Err(GitError::Validation(format!("'{}' resolves outside the repository", dir_path)));
+    }
+    let read_dir = std::fs::read_dir(&canonical)
+        .map_err(|e| git2::Error::from_str(&format!("Failed to read {}: {}", dir.display(), e)))?;
+
+    let mut entries = Vec::new();
+    for dir_entry in read_dir.flatten() {
+        let name = dir_entry.file_name().to_string_lossy().into_owned();
+        if dir_path.is_empty() && name == ".git" {
+            continue;
+        }
+        // symlink_metadata so links are listed as links, not followed
+        let Ok(metadata) = dir_entry.path().symlink_metadata() else {
+            continue;
+        };
+        let file_type = metadata.file_type();
+        let (mode, size) = if file_type.is_symlink() {
+            (0o120000, Some(metadata.len()))
+        } else if file_type.is_dir() {
+            (0o040000, None)
+        } else if is_executable(&metadata) {
+            (0o100755, Some(metadata.len()))
+        } else {
+            (0o100644, Some(metadata.len()))
+        };
+        entries.push(TreeEntry {
+            path: child_path(dir_path, &name),
+            name,
+            is_dir: file_type.is_dir(),
+            mode,
+            size,
+        });
+    }
+    Ok(entries)
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_metadata: &std::fs::Metadata) -> bool {
+    false
+}
@@ -27,44 +27,81 @@ pub fn run() {
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
         .manage(WatcherState::new())
+        .manage(git::CancellationRegistry::new())
+        .manage(commands::CancellationState::new())
         .invoke_handler(tauri::generate_handler![
             commands::check_cli_availability,
+            commands::get_app_settings,
+            commands::save_app_settings,
+            commands::get_default_prompt_templates,
             commands::open_repository,
             commands::discover_repository,
             commands::list_branches,
             commands::checkout_branch,
             commands::create_branch,
+            commands::delete_branch,
+            commands::rename_branch,
             commands::get_commit_history,
+            commands::get_commit_history_cursor,
             commands::get_commit_history_all_branches,
+            commands::clear_commit_cache,
+            commands::search_commits,
+            commands::get_commits_for_path,
             commands::get_commit_activity_all_branches,
             commands::get_changelog_commits_all_branches,
             commands::get_commit_graph,
+            commands::cancel_operation,
+            commands::cancel_ai_request,
             commands::get_commit_diff,
+            commands::get_commit_files,
+            commands::get_diff_stats,
+            commands::get_working_diff_stats,
+            commands::get_workdir_vs_ref_diff,
             commands::get_file_diff,
             commands::get_working_diff,
             commands::get_compare_diff,
             commands::get_compare_file_diff,
+            commands::get_commit_pair_file_diff,
             commands::get_commit_range,
             commands::get_status,
             commands::stage_files,
             commands::unstage_files,
             commands::discard_changes,
+            commands::checkout_file,
             commands::create_commit,
+            commands::get_git_config,
+            commands::set_git_config,
+            commands::list_remotes,
+            commands::add_remote,
+            commands::rename_remote,
+            commands::remove_remote,
             commands::git_fetch,
+            commands::git_fetch_remote,
             commands::git_pull,
             commands::git_push,
             commands::git_remote_action,
+            commands::describe_commit,
+            commands::get_commit_signature,
             commands::checkout_commit,
             commands::cherry_pick,
             commands::reset_hard,
+            commands::reset_commit,
+            commands::uncommit,
             commands::squash_commits,
             commands::generate_commit_message,
             commands::generate_ai_review,
+            commands::generate_ai_review_streaming,
+            commands::clear_review_cache,
             commands::generate_review,
             commands::generate_changelog_summary,
+            commands::get_contributor_stats,
+            commands::get_contributor_leaderboard,
+            commands::get_file_hotspots,
+            commands::get_repo_summary,
             commands::generate_contributor_review,
             commands::fix_ai_review_issues,
             commands::fix_coderabbit_issue,
+            commands::undo_ai_fix,
             commands::generate_diagram,
             commands::list_worktrees,
             commands::create_worktree,
@@ -77,6 +114,11 @@ pub fn run() {
             commands::apply_stash,
             commands::pop_stash,
             commands::drop_stash,
+            commands::apply_stash_by_oid,
+            commands::pop_stash_by_oid,
+            commands::drop_stash_by_oid,
+            commands::get_stash_diff,
+            commands::stash_branch,
             // Reflog command
             commands::get_reflog,
             // Skills commands
@@ -84,18 +126,30 @@ pub fn run() {
             commands::list_skills,
             commands::list_remote_skills,
             commands::install_skill_from_url,
+            commands::install_skill_from_file,
+            commands::install_skill_from_content,
             commands::delete_skill,
             commands::get_skill_content,
             commands::get_skill_raw,
             commands::update_skill,
+            commands::refresh_skill,
             // Merge conflict commands
             commands::get_merge_status,
             commands::parse_file_conflicts,
+            commands::get_conflict_diffs,
+            commands::get_conflict_versions,
+            commands::resolve_conflict_ours,
+            commands::resolve_conflict_theirs,
+            commands::resolve_all_conflicts,
             commands::save_resolved_file,
             commands::mark_file_resolved,
             commands::abort_merge,
+            commands::abort_cherry_pick,
+            commands::abort_revert,
+            commands::get_repository_state,
             commands::continue_merge,
             commands::merge_branch,
+            commands::preview_merge,
             commands::ai_resolve_conflict,
             // Rebase commands
             commands::get_rebase_status,
@@ -108,13 +162,18 @@ pub fn run() {
             commands::start_interactive_rebase,
             commands::get_interactive_rebase_state,
             commands::continue_interactive_rebase,
+            commands::split_commit_reset,
             // Ahead/behind
             commands::get_ahead_behind,
+            commands::get_merge_base,
+            commands::set_seen_marker,
+            commands::get_changes_since_marker,
             // Watcher commands
             commands::start_watching,
             commands::stop_watching,
             // Code flow commands
             commands::read_repo_file,
+            commands::list_tree,
         ])
         .setup(|_app| {
             #[cfg(debug_assertions)]
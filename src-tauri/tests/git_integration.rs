@@ -191,6 +191,29 @@ mod repository {
 
         insta::assert_debug_snapshot!(info);
     }
+
+    #[test]
+    fn test_bare_repo_reads_history_but_rejects_writes() {
+        let (_tmp, path) = create_repo_with_history();
+        let bare_tmp = TempDir::new().unwrap();
+        let bare_path = bare_tmp.path().join("bare.git");
+        run_git(bare_tmp.path(), &["clone", "--bare", path.to_str().unwrap(), bare_path.to_str().unwrap()]);
+
+        let repo = git::open_repo(&bare_path).unwrap();
+        assert!(git::get_repository_info(&repo).unwrap().is_bare);
+
+        let commits = git::get_commits(&repo, None, 10, 0, None, None).expect("history should be readable");
+        assert_eq!(commits.len(), 3);
+        git::get_commit_diff(&repo, &commits[0].id).expect("commit diffs should be readable");
+
+        match git::stage_files(&repo, &["file1.txt".to_string()]) {
+            Err(git::GitError::Validation(msg)) => assert!(msg.contains("bare repository"), "{}", msg),
+            other => panic!("expected a validation error, got {:?}", other),
+        }
+        assert!(matches!(git::get_status(&repo), Err(git::GitError::Validation(_))));
+        assert!(matches!(git::get_working_diff(&repo, false), Err(git::GitError::Validation(_))));
+        assert!(matches!(git::get_workdir_vs_ref_diff(&repo, "HEAD"), Err(git::GitError::Validation(_))));
+    }
 }
 
 // =============================================================================
@@ -344,6 +367,33 @@ mod staging {
         assert_eq!(status.untracked.len(), 1);
     }
 
+    #[test]
+    fn test_checkout_file_from_older_commit() {
+        let (_tmp, path) = create_repo_with_history();
+        std::fs::write(path.join("file1.txt"), "content 1 v2\n").unwrap();
+        run_git(&path, &["commit", "-am", "Update file1"]);
+        std::fs::write(path.join("file2.txt"), "uncommitted\n").unwrap();
+        let repo_path = path.to_str().unwrap();
+
+        git::checkout_file(repo_path, "HEAD~1", "file1.txt", false).expect("should restore file");
+        assert_eq!(std::fs::read_to_string(path.join("file1.txt")).unwrap(), "content 1\n");
+        // Other files are untouched, and nothing was staged
+        assert_eq!(std::fs::read_to_string(path.join("file2.txt")).unwrap(), "uncommitted\n");
+        assert_eq!(run_git_output(&path, &["diff", "--cached", "--name-only"]), "");
+
+        git::checkout_file(repo_path, "HEAD~1", "file1.txt", true).expect("should restore and stage");
+        assert_eq!(run_git_output(&path, &["diff", "--cached", "--name-only"]), "file1.txt");
+
+        assert!(matches!(
+            git::checkout_file(repo_path, "HEAD", "../outside.txt", false),
+            Err(git::GitError::Validation(_))
+        ));
+        assert!(matches!(
+            git::checkout_file(repo_path, "HEAD~3", "file1.txt", false),
+            Err(git::GitError::Validation(_))
+        ));
+    }
+
     #[test]
     fn test_discard_changes() {
         let (_tmp, path) = create_test_repo();
@@ -426,6 +476,233 @@ mod diff {
         assert_eq!(diff.files[0].status, "A");
     }
 
+    #[test]
+    fn test_large_diff_is_truncated() {
+        let (_tmp, path) = create_test_repo();
+
+        let big: String = (0..100_000).map(|i| format!("line {}\n", i)).collect();
+        std::fs::write(path.join("generated.txt"), &big).unwrap();
+        std::fs::write(path.join("small.txt"), "small\n").unwrap();
+        run_git(&path, &["add", "-A"]);
+        run_git(&path, &["commit", "-m", "Add generated file"]);
+        let commit_id = run_git_output(&path, &["rev-parse", "HEAD"]);
+
+        let repo = git::open_repo(&path).unwrap();
+        let diff = git::get_commit_diff_capped(&repo, &commit_id, Some(64 * 1024), git::WhitespaceMode::None, false).unwrap();
+
+        let generated = diff.files.iter().find(|f| f.path == "generated.txt").unwrap();
+        assert!(generated.truncated);
+        assert_eq!(generated.additions, 100_000, "metadata stays accurate when truncated");
+        let small = diff.files.iter().find(|f| f.path == "small.txt").unwrap();
+        assert!(!small.truncated);
+
+        assert!(diff.patch.len() < 64 * 1024);
+        assert!(diff.patch.contains("+++ b/generated.txt"));
+        assert!(diff.patch.contains("Large diff truncated, 100000 lines"));
+        assert!(diff.patch.contains("+small"));
+
+        // Single-file views honour the same budget
+        let file = git::get_file_diff_capped(&repo, &commit_id, "generated.txt", Some(64 * 1024), git::WhitespaceMode::None).unwrap();
+        assert!(file.patch.len() < 64 * 1024);
+        assert!(file.patch.contains("Large diff truncated, 100000 lines"));
+        let file = git::get_compare_file_diff_capped(
+            &repo,
+            "HEAD~1",
+            "HEAD",
+            "generated.txt",
+            git::CompareMode::TwoDot,
+            Some(64 * 1024),
+            git::WhitespaceMode::None,
+        ).unwrap();
+        assert!(file.patch.contains("Large diff truncated, 100000 lines"));
+        let small = git::get_file_diff_capped(&repo, &commit_id, "small.txt", Some(64 * 1024), git::WhitespaceMode::None).unwrap();
+        assert!(small.patch.contains("+small"));
+
+        // Without a budget the full patch is returned
+        let full = git::get_commit_diff(&repo, &commit_id).unwrap();
+        assert!(full.files.iter().all(|f| !f.truncated));
+        assert!(full.patch.contains("+line 99999"));
+    }
+
+    #[test]
+    fn test_whitespace_only_changes_ignored() {
+        let (_tmp, path) = create_test_repo();
+
+        std::fs::write(path.join("code.rs"), "fn main() {\n    run();\n}\n").unwrap();
+        std::fs::write(path.join("other.txt"), "before\n").unwrap();
+        run_git(&path, &["add", "-A"]);
+        run_git(&path, &["commit", "-m", "Add code"]);
+
+        // Reindent only code.rs; other.txt gets a real change
+        std::fs::write(path.join("code.rs"), "fn main() {\n\t\trun();\n}\n").unwrap();
+        std::fs::write(path.join("other.txt"), "after\n").unwrap();
+        run_git(&path, &["commit", "-am", "Reindent"]);
+        let commit_id = run_git_output(&path, &["rev-parse", "HEAD"]);
+
+        let repo = git::open_repo(&path).unwrap();
+        let shown = git::get_commit_diff_capped(&repo, &commit_id, None, git::WhitespaceMode::None, false).unwrap();
+        let paths: Vec<_> = shown.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["code.rs", "other.txt"]);
+
+        let ignored = git::get_commit_diff_capped(&repo, &commit_id, None, git::WhitespaceMode::IgnoreAll, false).unwrap();
+        let paths: Vec<_> = ignored.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["other.txt"]);
+        assert!(!ignored.patch.contains("code.rs"));
+
+        let file = git::get_file_diff(&repo, &commit_id, "code.rs", git::WhitespaceMode::IgnoreAll).unwrap();
+        assert!(file.patch.is_empty());
+
+        // Same through the working tree diff
+        std::fs::write(path.join("code.rs"), "fn main() {\n  run();  \n}\n").unwrap();
+        let working = git::get_working_diff_capped(&repo, false, None, git::WhitespaceMode::None).unwrap();
+        assert_eq!(working.files.len(), 1);
+        let working = git::get_working_diff_capped(&repo, false, None, git::WhitespaceMode::IgnoreAll).unwrap();
+        assert!(working.files.is_empty());
+    }
+
+    #[test]
+    fn test_function_context_patch() {
+        let (_tmp, path) = create_test_repo();
+
+        let body: String = (1..=12).map(|i| format!("    int v{} = {};\n", i, i)).collect();
+        let source = format!("int helper(int x)\n{{\n{}    return x;\n}}\n", body);
+        std::fs::write(path.join("lib.c"), &source).unwrap();
+        run_git(&path, &["add", "lib.c"]);
+        run_git(&path, &["commit", "-m", "Add helper"]);
+        std::fs::write(path.join("lib.c"), source.replace("return x;", "return x + 1;")).unwrap();
+        run_git(&path, &["commit", "-am", "Change helper"]);
+        let commit_id = run_git_output(&path, &["rev-parse", "HEAD"]);
+
+        let repo = git::open_repo(&path).unwrap();
+        let plain = git::get_commit_diff(&repo, &commit_id).unwrap();
+        assert!(
+            plain.patch.lines().any(|l| l.starts_with("@@ ") && l.ends_with(" @@ int helper(int x)")),
+            "hunk header names the enclosing function: {}",
+            plain.patch
+        );
+        assert!(!plain.patch.contains(" int v1 = 1;"), "default context is 3 lines");
+
+        let diff = git::get_commit_diff_capped(&repo, &commit_id, None, git::WhitespaceMode::None, true).unwrap();
+        assert_eq!(diff.files.len(), 1);
+        assert_eq!(diff.files[0].additions, 1);
+        // The hunk covers the whole function, so its header line is the signature itself
+        assert!(diff.patch.contains("@@ -1,16 +1,16 @@"), "patch: {}", diff.patch);
+        assert!(diff.patch.contains(" int helper(int x)\n"));
+        assert!(diff.patch.contains(" int v1 = 1;"));
+        assert!(diff.patch.contains("+    return x + 1;"));
+    }
+
+    #[test]
+    fn test_function_context_ignores_prefix_config_and_keeps_header_only_files() {
+        let (_tmp, path) = create_test_repo();
+        std::fs::write(path.join("lib.c"), "int f(void)\n{\n    return 1;\n}\n").unwrap();
+        std::fs::write(path.join("run.sh"), "echo hi\n").unwrap();
+        run_git(&path, &["add", "."]);
+        run_git(&path, &["commit", "-m", "Add files"]);
+
+        std::fs::write(path.join("lib.c"), "int f(void)\n{\n    return 2;\n}\n").unwrap();
+        run_git(&path, &["update-index", "--chmod=+x", "run.sh"]);
+        std::fs::write(path.join("blob.bin"), [0u8, 1, 2, 3, 0, 255]).unwrap();
+        run_git(&path, &["add", "lib.c", "blob.bin"]);
+        run_git(&path, &["commit", "-m", "Change, chmod and add binary"]);
+        let commit_id = run_git_output(&path, &["rev-parse", "HEAD"]);
+
+        for (key, value) in [("diff.noprefix", "true"), ("diff.mnemonicPrefix", "true")] {
+            run_git(&path, &["config", key, value]);
+            let repo = git::open_repo(&path).unwrap();
+            let diff = git::get_commit_diff_capped(&repo, &commit_id, None, git::WhitespaceMode::None, true).unwrap();
+            assert!(diff.patch.contains("+    return 2;"), "{}: {}", key, diff.patch);
+            assert!(diff.patch.contains("old mode 100644"), "{}: mode change kept: {}", key, diff.patch);
+            assert!(diff.patch.contains("blob.bin"), "{}: binary file kept: {}", key, diff.patch);
+            run_git(&path, &["config", "--unset", key]);
+        }
+    }
+
+    #[test]
+    fn test_commit_files_match_commit_diff() {
+        let (_tmp, path) = create_repo_with_history();
+
+        // A commit with a rename, a modification and an addition
+        run_git(&path, &["mv", "file1.txt", "renamed.txt"]);
+        std::fs::write(path.join("README.md"), "# Changed\n").unwrap();
+        std::fs::write(path.join("extra.txt"), "extra\n").unwrap();
+        run_git(&path, &["add", "-A"]);
+        run_git(&path, &["commit", "-m", "Mixed changes"]);
+        let commit_id = run_git_output(&path, &["rev-parse", "HEAD"]);
+
+        let repo = git::open_repo(&path).unwrap();
+        let diff = git::get_commit_diff(&repo, &commit_id).unwrap();
+        let files = git::get_commit_files(&repo, &commit_id).expect("should list commit files");
+
+        let summarize = |files: &[git::DiffFile]| -> Vec<(String, Option<String>, String, usize, usize)> {
+            files
+                .iter()
+                .map(|f| (f.path.clone(), f.old_path.clone(), f.status.clone(), f.additions, f.deletions))
+                .collect()
+        };
+        assert_eq!(summarize(&files), summarize(&diff.files));
+        assert!(files.iter().any(|f| f.status == "R" && f.old_path.as_deref() == Some("file1.txt")));
+    }
+
+    #[test]
+    fn test_workdir_vs_ref_diff() {
+        let (_tmp, path) = create_repo_with_history();
+
+        // Uncommitted: a staged edit, an unstaged edit and an untracked file
+        std::fs::write(path.join("README.md"), "# Edited\n").unwrap();
+        run_git(&path, &["add", "README.md"]);
+        std::fs::write(path.join("file1.txt"), "content 1 edited\n").unwrap();
+        std::fs::write(path.join("new.txt"), "new\n").unwrap();
+
+        let repo = git::open_repo(&path).unwrap();
+        let diff = git::get_workdir_vs_ref_diff(&repo, "HEAD~1").expect("should diff against ref");
+
+        let mut paths: Vec<&str> = diff.files.iter().map(|f| f.path.as_str()).collect();
+        paths.sort();
+        // file2.txt was committed after HEAD~1; the rest are uncommitted
+        assert_eq!(paths, vec!["README.md", "file1.txt", "file2.txt", "new.txt"]);
+        assert!(diff.patch.contains("+content 2"));
+        assert!(diff.patch.contains("+# Edited"));
+        assert!(diff.patch.contains("+content 1 edited"));
+
+        assert!(git::get_workdir_vs_ref_diff(&repo, "no-such-ref").is_err());
+    }
+
+    #[test]
+    fn test_diff_stats_match_diff_files() {
+        let (_tmp, path) = create_repo_with_history();
+
+        run_git(&path, &["mv", "file1.txt", "renamed.txt"]);
+        std::fs::write(path.join("README.md"), "# Changed\nmore\n").unwrap();
+        std::fs::write(path.join("extra.txt"), "extra\n").unwrap();
+        run_git(&path, &["add", "-A"]);
+        run_git(&path, &["commit", "-m", "Mixed changes"]);
+        let commit_id = run_git_output(&path, &["rev-parse", "HEAD"]);
+
+        let totals = |files: &[git::DiffFile]| git::DiffStats {
+            files_changed: files.len(),
+            insertions: files.iter().map(|f| f.additions).sum(),
+            deletions: files.iter().map(|f| f.deletions).sum(),
+        };
+
+        let repo = git::open_repo(&path).unwrap();
+        let diff = git::get_commit_diff(&repo, &commit_id).unwrap();
+        let stats = git::get_diff_stats(&repo, &commit_id).expect("should get diff stats");
+        assert_eq!(stats, totals(&diff.files));
+        assert_eq!(stats.files_changed, 3);
+
+        // Working changes, including an untracked file
+        std::fs::write(path.join("README.md"), "# Changed again\n").unwrap();
+        std::fs::write(path.join("untracked.txt"), "a\nb\nc\n").unwrap();
+        run_git(&path, &["add", "README.md"]);
+        std::fs::write(path.join("renamed.txt"), "edited\n").unwrap();
+        for staged in [true, false] {
+            let diff = git::get_working_diff(&repo, staged).unwrap();
+            let stats = git::get_working_diff_stats(&repo, staged).unwrap();
+            assert_eq!(stats, totals(&diff.files), "staged: {}", staged);
+        }
+    }
+
     #[test]
     fn test_file_diff() {
         let (_tmp, path) = create_repo_with_history();
@@ -433,12 +710,103 @@ mod diff {
         let commit_id = run_git_output(&path, &["rev-parse", "HEAD"]);
 
         let repo = git::open_repo(&path).unwrap();
-        let diff = git::get_file_diff(&repo, &commit_id, "file2.txt").expect("should get file diff");
+        let diff = git::get_file_diff(&repo, &commit_id, "file2.txt", git::WhitespaceMode::None).expect("should get file diff");
 
         assert_eq!(diff.path, "file2.txt");
         assert!(diff.patch.contains("+content 2"));
     }
 
+    #[test]
+    fn test_file_diff_renamed_file_by_either_path() {
+        let (_tmp, path) = create_test_repo();
+
+        let original = "line 1\nline 2\nline 3\nline 4\nline 5\nline 6\nline 7\nline 8\n";
+        std::fs::write(path.join("old_name.txt"), original).unwrap();
+        run_git(&path, &["add", "old_name.txt"]);
+        run_git(&path, &["commit", "-m", "Add old_name"]);
+
+        // Rename and edit in the same commit
+        run_git(&path, &["mv", "old_name.txt", "new_name.txt"]);
+        std::fs::write(path.join("new_name.txt"), original.replace("line 4", "line four")).unwrap();
+        run_git(&path, &["add", "new_name.txt"]);
+        run_git(&path, &["commit", "-m", "Rename and edit"]);
+
+        let commit_id = run_git_output(&path, &["rev-parse", "HEAD"]);
+        let repo = git::open_repo(&path).unwrap();
+
+        for requested in ["new_name.txt", "old_name.txt"] {
+            let diff = git::get_file_diff(&repo, &commit_id, requested, git::WhitespaceMode::None).expect("should get file diff");
+            assert!(diff.patch.contains("rename from old_name.txt"), "{}: {}", requested, diff.patch);
+            assert!(diff.patch.contains("rename to new_name.txt"), "{}: {}", requested, diff.patch);
+            assert!(diff.patch.contains("-line 4"), "{}: {}", requested, diff.patch);
+            assert!(diff.patch.contains("+line four"), "{}: {}", requested, diff.patch);
+        }
+    }
+
+    #[test]
+    fn test_compare_two_dot_vs_three_dot() {
+        // main and feature have diverged: main added main.txt, feature added feature.txt
+        let (_tmp, path) = create_repo_with_branches();
+        let repo = git::open_repo(&path).unwrap();
+
+        let paths = |diff: &git::UnifiedDiff| -> Vec<(String, String)> {
+            let mut files: Vec<_> = diff.files.iter().map(|f| (f.path.clone(), f.status.clone())).collect();
+            files.sort();
+            files
+        };
+
+        let two_dot = git::get_compare_diff(&repo, "main", "feature", git::CompareMode::TwoDot).unwrap();
+        assert_eq!(
+            paths(&two_dot),
+            vec![("feature.txt".to_string(), "A".to_string()), ("main.txt".to_string(), "D".to_string())]
+        );
+
+        let three_dot = git::get_compare_diff(&repo, "main", "feature", git::CompareMode::ThreeDot).unwrap();
+        assert_eq!(paths(&three_dot), vec![("feature.txt".to_string(), "A".to_string())]);
+
+        let file = git::get_compare_file_diff(
+            &repo,
+            "main",
+            "feature",
+            "main.txt",
+            git::CompareMode::ThreeDot,
+            git::WhitespaceMode::None,
+        ).unwrap();
+        assert!(file.patch.is_empty(), "main.txt isn't part of feature's changes");
+    }
+
+    #[test]
+    fn test_commit_pair_file_diff() {
+        let (_tmp, path) = create_repo_with_history();
+        // History: README.md -> +file1.txt -> +file2.txt
+        let root = run_git_output(&path, &["rev-list", "--max-parents=0", "HEAD"]);
+        let head = run_git_output(&path, &["rev-parse", "HEAD"]);
+
+        std::fs::write(path.join("file1.txt"), "content 1 changed\n").unwrap();
+        run_git(&path, &["rm", "-q", "README.md"]);
+        run_git(&path, &["add", "-A"]);
+        run_git(&path, &["commit", "-m", "Edit file1, remove README"]);
+        let tip = run_git_output(&path, &["rev-parse", "HEAD"]);
+
+        let repo = git::open_repo(&path).unwrap();
+
+        // Skips an intermediate commit: only exists in head
+        let added = git::get_commit_pair_file_diff(&repo, &root, &head, "file2.txt").unwrap();
+        assert_eq!(added.file.status, "A");
+        assert!(added.patch.contains("+content 2"));
+
+        let deleted = git::get_commit_pair_file_diff(&repo, &root, &tip, "README.md").unwrap();
+        assert_eq!(deleted.file.status, "D");
+        assert!(deleted.patch.contains("-# Test Repo"));
+
+        let modified = git::get_commit_pair_file_diff(&repo, &head, &tip, "file1.txt").unwrap();
+        assert_eq!(modified.file.status, "M");
+        assert!(modified.patch.contains("+content 1 changed"));
+
+        let err = git::get_commit_pair_file_diff(&repo, &head, &tip, "file2.txt").unwrap_err();
+        assert!(matches!(err, git::GitError::Validation(_)));
+    }
+
     // Snapshot tests for working diffs
 
     #[test]
@@ -572,6 +940,122 @@ mod diff {
 mod commits {
     use super::*;
 
+    #[test]
+    fn test_parallel_commit_info_preserves_order() {
+        let (_tmp, path) = create_test_repo();
+        for i in 0..20 {
+            std::fs::write(path.join(format!("file{}.txt", i)), "one\ntwo\n").unwrap();
+            run_git(&path, &["add", "-A"]);
+            run_git(&path, &["commit", "-m", &format!("Commit {}", i)]);
+        }
+
+        // Serial baseline from the git CLI
+        let expected: Vec<String> = run_git_output(&path, &["rev-list", "--topo-order", "HEAD"])
+            .lines()
+            .map(|l| l.to_string())
+            .collect();
+        assert_eq!(expected.len(), 21);
+
+        let repo = git::open_repo(&path).unwrap();
+        let commits = git::get_commits(&repo, None, 50, 0, None, None).unwrap();
+        let ids: Vec<String> = commits.iter().map(|c| c.id.clone()).collect();
+        assert_eq!(ids, expected);
+        for commit in &commits[..20] {
+            assert_eq!((commit.files_changed, commit.additions, commit.deletions), (1, 2, 0), "{}", commit.summary);
+        }
+
+        let all = git::get_commits_all_branches(&repo, 50, 0).unwrap();
+        let all_ids: Vec<String> = all.iter().map(|c| c.id.clone()).collect();
+        assert_eq!(all_ids, expected);
+    }
+
+    #[test]
+    fn test_commits_for_path_follows_renames() {
+        let (_tmp, path) = create_test_repo();
+
+        let content = "line 1\nline 2\nline 3\nline 4\nline 5\nline 6\n";
+        std::fs::write(path.join("old.txt"), content).unwrap();
+        run_git(&path, &["add", "old.txt"]);
+        run_git(&path, &["commit", "-m", "Add old"]);
+        std::fs::write(path.join("old.txt"), content.replace("line 2", "line two")).unwrap();
+        run_git(&path, &["commit", "-am", "Edit old"]);
+        run_git(&path, &["mv", "old.txt", "new.txt"]);
+        run_git(&path, &["commit", "-m", "Rename to new"]);
+        std::fs::write(path.join("other.txt"), "other\n").unwrap();
+        run_git(&path, &["add", "other.txt"]);
+        run_git(&path, &["commit", "-m", "Unrelated"]);
+        std::fs::write(path.join("new.txt"), content.replace("line 5", "line five")).unwrap();
+        run_git(&path, &["commit", "-am", "Edit new"]);
+
+        let repo = git::open_repo(&path).unwrap();
+        let summaries = |commits: Vec<git::CommitInfo>| -> Vec<String> {
+            commits.into_iter().map(|c| c.summary).collect()
+        };
+
+        let unfollowed = git::get_commits_for_path(&repo, "new.txt", false, 10, 0).unwrap();
+        assert_eq!(summaries(unfollowed), vec!["Edit new", "Rename to new"]);
+
+        let followed = git::get_commits_for_path(&repo, "new.txt", true, 10, 0).unwrap();
+        assert_eq!(summaries(followed), vec!["Edit new", "Rename to new", "Edit old", "Add old"]);
+
+        let paged = git::get_commits_for_path(&repo, "new.txt", true, 2, 1).unwrap();
+        assert_eq!(summaries(paged), vec!["Rename to new", "Edit old"]);
+    }
+
+    #[test]
+    fn test_search_commits() {
+        let (_tmp, path) = create_repo_with_branches();
+
+        // A commit by a different author on another branch
+        run_git(&path, &["checkout", "-b", "docs"]);
+        std::fs::write(path.join("docs.txt"), "docs\n").unwrap();
+        run_git(&path, &["add", "docs.txt"]);
+        run_git(&path, &["commit", "-m", "Write the Docs", "--author", "Jane Doe <jane@example.org>"]);
+        run_git(&path, &["checkout", "main"]);
+
+        let repo = git::open_repo(&path).unwrap();
+
+        let by_email = git::search_commits(&repo, "JANE@example.org", git::SearchFields::default(), 10).unwrap();
+        assert_eq!(by_email.len(), 1);
+        assert_eq!(by_email[0].summary, "Write the Docs");
+
+        // Matches across branches, case-insensitively
+        let by_summary = git::search_commits(&repo, "add", git::SearchFields::default(), 10).unwrap();
+        let mut summaries: Vec<&str> = by_summary.iter().map(|c| c.summary.as_str()).collect();
+        summaries.sort();
+        assert_eq!(summaries, vec!["Add feature", "Add main file"]);
+
+        // Limit stops early
+        let limited = git::search_commits(&repo, "add", git::SearchFields::default(), 1).unwrap();
+        assert_eq!(limited.len(), 1);
+
+        // Restricting fields excludes author matches
+        let summary_only = git::SearchFields { summary: true, message: false, author_name: false, author_email: false };
+        assert!(git::search_commits(&repo, "jane", summary_only, 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_commit_stats_are_cached() {
+        let (_tmp, path) = create_repo_with_history();
+        let repo = git::open_repo(&path).unwrap();
+
+        let first = git::get_commits(&repo, None, 10, 0, None, None).unwrap();
+        let hits_before = git::commit_stats_cache_hits();
+        let second = git::get_commits(&repo, None, 10, 0, None, None).unwrap();
+        let hits_after = git::commit_stats_cache_hits();
+
+        // Other tests share the global counter, so only a lower bound is reliable
+        assert!(hits_after - hits_before >= second.len(), "second walk should hit the cache");
+        assert_eq!(first.len(), second.len());
+        for (a, b) in first.iter().zip(&second) {
+            assert_eq!(a.id, b.id);
+            assert_eq!(
+                (a.files_changed, a.additions, a.deletions),
+                (b.files_changed, b.additions, b.deletions)
+            );
+        }
+    }
+
     #[test]
     fn test_create_commit() {
         let (_tmp, path) = create_test_repo();
@@ -591,12 +1075,65 @@ mod commits {
         assert_eq!(msg, "Test commit message");
     }
 
+    #[test]
+    fn test_create_commit_with_author_override() {
+        let (_tmp, path) = create_test_repo();
+        std::fs::write(path.join("patch.txt"), "contributed\n").unwrap();
+        run_git(&path, &["add", "patch.txt"]);
+
+        let repo = git::open_repo(&path).unwrap();
+        let author = git::AuthorOverride {
+            name: Some("Patch Author".to_string()),
+            email: Some("patch@example.com".to_string()),
+            time: Some(1_600_000_000),
+        };
+        git::create_commit_as(&repo, "Apply patch", &author).expect("should commit");
+
+        let fields = run_git_output(&path, &["log", "-1", "--format=%an|%ae|%at|%cn|%ce"]);
+        assert_eq!(fields, "Patch Author|patch@example.com|1600000000|Test User|test@example.com");
+
+        let empty = git::AuthorOverride { name: Some(" ".to_string()), ..Default::default() };
+        assert!(matches!(git::create_commit_as(&repo, "Nope", &empty), Err(git::GitError::Validation(_))));
+    }
+
+    #[test]
+    fn test_set_and_get_local_git_config() {
+        let (_tmp, path) = create_test_repo();
+        let repo = git::open_repo(&path).unwrap();
+
+        git::set_git_config(&repo, "user.name", "Config Tester", false).expect("should set config");
+        let keys = vec!["user.name".to_string(), "diffy.unset".to_string()];
+        let values = git::get_git_config(&repo, &keys).unwrap();
+
+        assert_eq!(values["user.name"].as_deref(), Some("Config Tester"));
+        assert_eq!(values["diffy.unset"], None);
+        assert_eq!(run_git_output(&path, &["config", "--local", "user.name"]), "Config Tester");
+    }
+
+    #[test]
+    fn test_create_commit_without_identity() {
+        let (_tmp, path) = create_test_repo();
+        // Empty local values override any global identity on the machine running the tests
+        run_git(&path, &["config", "user.name", ""]);
+        run_git(&path, &["config", "user.email", ""]);
+        std::fs::write(path.join("new.txt"), "content").unwrap();
+        run_git(&path, &["add", "new.txt"]);
+
+        let repo = git::open_repo(&path).unwrap();
+        match git::create_commit(&repo, "No identity") {
+            Err(git::GitError::Validation(msg)) => {
+                assert!(msg.contains("user.name and user.email"), "{}", msg)
+            }
+            other => panic!("expected a validation error, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_get_commits() {
         let (_tmp, path) = create_repo_with_history();
 
         let repo = git::open_repo(&path).unwrap();
-        let commits = git::get_commits(&repo, None, 10, 0).expect("should get commits");
+        let commits = git::get_commits(&repo, None, 10, 0, None, None).expect("should get commits");
 
         assert_eq!(commits.len(), 3); // Initial + file1 + file2
         assert_eq!(commits[0].summary, "Add file2");
@@ -609,7 +1146,7 @@ mod commits {
         let (_tmp, path) = create_repo_with_history();
 
         let repo = git::open_repo(&path).unwrap();
-        let commits = git::get_commits(&repo, None, 2, 0).expect("should get commits");
+        let commits = git::get_commits(&repo, None, 2, 0, None, None).expect("should get commits");
 
         assert_eq!(commits.len(), 2);
     }
@@ -619,100 +1156,1052 @@ mod commits {
         let (_tmp, path) = create_repo_with_history();
 
         let repo = git::open_repo(&path).unwrap();
-        let commits = git::get_commits(&repo, None, 10, 1).expect("should get commits");
+        let commits = git::get_commits(&repo, None, 10, 1, None, None).expect("should get commits");
 
         assert_eq!(commits.len(), 2);
         assert_eq!(commits[0].summary, "Add file1");
     }
-}
-
-// =============================================================================
-// Branch Tests
-// =============================================================================
-
-mod branches {
-    use super::*;
 
     #[test]
-    fn test_list_branches() {
-        let (_tmp, path) = create_repo_with_branches();
+    fn test_get_contributor_stats_filters_author_and_range() {
+        let (_tmp, path) = create_test_repo();
+
+        let commit_as = |author: &str, file: &str, content: &str, time: i64, message: &str| {
+            std::fs::write(path.join(file), content).unwrap();
+            run_git(&path, &["add", file]);
+            let date = format!("@{} +0000", time);
+            let output = git_cmd(&path)
+                .env("GIT_AUTHOR_DATE", &date)
+                .env("GIT_COMMITTER_DATE", &date)
+                .args(["commit", "-m", message, "--author", author])
+                .output()
+                .unwrap();
+            assert!(output.status.success());
+        };
+        commit_as("Jane Doe <jane@example.org>", "a.txt", "1\n2\n", 1_700_000_000, "Add a");
+        commit_as("Test Author <test@example.com>", "b.txt", "x\n", 1_700_000_100, "Add b");
+        commit_as("Jane Doe <JANE@example.org>", "a.txt", "1\n3\n", 1_700_000_200, "Change a");
+        commit_as("Jane Doe <jane@example.org>", "c.txt", "late\n", 1_700_000_300, "Too late");
 
         let repo = git::open_repo(&path).unwrap();
-        let branches = git::list_all_branches(&repo).expect("should list branches");
+        let stats = git::get_contributor_stats(&repo, "jane@example.org", 1_700_000_000, 1_700_000_250)
+            .expect("should aggregate stats");
+
+        assert_eq!(stats.author_name, "Jane Doe");
+        assert_eq!(stats.total_commits, 2);
+        assert_eq!(stats.total_files_changed, 2);
+        assert_eq!(stats.total_additions, 3);
+        assert_eq!(stats.total_deletions, 1);
+        assert_eq!(stats.commit_summaries, vec!["Change a", "Add a"]);
+
+        let other = git::get_contributor_stats(&repo, "test@example.com", 1_700_000_000, 1_700_000_250).unwrap();
+        assert_eq!(other.total_commits, 1);
+        assert_eq!(other.commit_summaries, vec!["Add b"]);
+
+        let leaderboard = git::get_contributor_leaderboard(&repo, 1_700_000_000, 1_700_000_250)
+            .expect("should aggregate every author");
+        assert_eq!(leaderboard.len(), 2);
+        assert_eq!(leaderboard[0].author_email.to_lowercase(), "jane@example.org");
+        assert_eq!(leaderboard[0].total_commits, 2);
+        assert_eq!(leaderboard[0].first_commit_time, Some(1_700_000_000));
+        assert_eq!(leaderboard[0].last_commit_time, Some(1_700_000_200));
+        assert_eq!(leaderboard[1].author_email, "test@example.com");
+        assert_eq!(leaderboard[1].total_commits, 1);
+        assert_eq!(leaderboard[1].total_files_changed, 1);
+        assert!(leaderboard.iter().all(|stats| stats.commit_summaries.is_empty()));
+    }
+
+    #[test]
+    fn test_file_hotspots_rank_by_change_count() {
+        let (_tmp, path) = create_test_repo();
 
-        let local_branches: Vec<_> = branches.iter().filter(|b| !b.is_remote).collect();
-        assert_eq!(local_branches.len(), 2);
+        let commit_at = |file: &str, content: &str, time: i64| {
+            std::fs::write(path.join(file), content).unwrap();
+            run_git(&path, &["add", file]);
+            let date = format!("@{} +0000", time);
+            let output = git_cmd(&path)
+                .env("GIT_AUTHOR_DATE", &date)
+                .env("GIT_COMMITTER_DATE", &date)
+                .args(["commit", "-m", &format!("Change {}", file)])
+                .output()
+                .unwrap();
+            assert!(output.status.success());
+        };
+        commit_at("hot.txt", "1\n", 1_700_000_000);
+        commit_at("cold.txt", "a\n", 1_700_000_100);
+        commit_at("hot.txt", "1\n2\n", 1_700_000_200);
+        commit_at("warm.txt", "x\n", 1_700_000_300);
+        commit_at("hot.txt", "2\n", 1_700_000_400);
+        commit_at("warm.txt", "y\n", 1_700_000_500);
 
-        let names: Vec<_> = local_branches.iter().map(|b| b.name.as_str()).collect();
-        assert!(names.contains(&"main"));
-        assert!(names.contains(&"feature"));
+        let repo = git::open_repo(&path).unwrap();
+        let hotspots = git::get_file_hotspots(&repo, 1_700_000_000, i64::MAX, 2).expect("should tally hotspots");
+
+        assert_eq!(hotspots.len(), 2);
+        let hot = &hotspots[0];
+        assert_eq!(hot.path, "hot.txt");
+        assert_eq!(hot.change_count, 3);
+        assert_eq!((hot.total_additions, hot.total_deletions), (2, 1));
+        assert_eq!(hot.last_changed, 1_700_000_400);
+        assert_eq!(hotspots[1].path, "warm.txt");
+        assert_eq!(hotspots[1].change_count, 2);
     }
 
     #[test]
-    fn test_create_branch() {
+    fn test_commit_info_committer_after_amend() {
         let (_tmp, path) = create_test_repo();
 
-        let repo = git::open_repo(&path).unwrap();
-        git::create_branch(&repo, "new-branch", false).expect("should create branch");
+        std::fs::write(path.join("a.txt"), "a\n").unwrap();
+        run_git(&path, &["add", "a.txt"]);
+        let date = "@1700000000 +0000";
+        let output = git_cmd(&path)
+            .env("GIT_AUTHOR_DATE", date)
+            .env("GIT_COMMITTER_DATE", date)
+            .args(["commit", "-m", "Add a"])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+
+        // Amend as someone else, later: the author is kept, the committer changes
+        let output = git_cmd(&path)
+            .env("GIT_COMMITTER_NAME", "Other Committer")
+            .env("GIT_COMMITTER_EMAIL", "other@example.com")
+            .env("GIT_COMMITTER_DATE", "@1700003600 +0000")
+            .args(["commit", "--amend", "--no-edit"])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
 
-        let branches = git::list_all_branches(&repo).unwrap();
-        let names: Vec<_> = branches.iter().map(|b| b.name.as_str()).collect();
-        assert!(names.contains(&"new-branch"));
+        let repo = git::open_repo(&path).unwrap();
+        let commits = git::get_commits(&repo, None, 1, 0, None, None).unwrap();
+        let commit = &commits[0];
+        assert_eq!(commit.author_name, "Test Author");
+        assert_eq!(commit.author_email, "test@example.com");
+        assert_eq!(commit.committer_name, "Other Committer");
+        assert_eq!(commit.committer_email, "other@example.com");
+        assert_eq!(commit.committer_time, 1_700_003_600);
+        assert!(commit.signature_status.is_none(), "signatures aren't checked by default");
+
+        let mut commits = commits;
+        git::verify_commit_signatures(path.to_str().unwrap(), &mut commits).unwrap();
+        assert_eq!(commits[0].signature_status, Some(git::SignatureStatus::Unsigned));
+    }
+
+    #[test]
+    fn test_parse_commit_trailers_with_co_authors() {
+        let message = "Add parser\n\nHandles the trailer block.\n\n\
+            Co-authored-by: Ada Lovelace <ada@example.com>\n\
+            Co-authored-by: Alan Turing <alan@example.com>\n\
+            Signed-off-by: Test Author\n  <test@example.com>\n";
+
+        let trailers = git::parse_commit_trailers(message);
+        assert_eq!(
+            trailers,
+            vec![
+                ("Co-authored-by".to_string(), "Ada Lovelace <ada@example.com>".to_string()),
+                ("Co-authored-by".to_string(), "Alan Turing <alan@example.com>".to_string()),
+                ("Signed-off-by".to_string(), "Test Author <test@example.com>".to_string()),
+            ]
+        );
     }
 
     #[test]
-    fn test_checkout_branch() {
-        let (_tmp, path) = create_repo_with_branches();
+    fn test_parse_commit_trailers_without_trailers() {
+        assert!(git::parse_commit_trailers("Fix: typo in README").is_empty());
+        assert!(git::parse_commit_trailers("Fix typo\n\nThe word was: wrong.\n").is_empty());
+        assert!(git::parse_commit_trailers("").is_empty());
+
+        let (_tmp, path) = create_test_repo();
+        std::fs::write(path.join("a.txt"), "a\n").unwrap();
+        run_git(&path, &["add", "a.txt"]);
+        run_git(&path, &["commit", "-m", "Add a", "-m", "Reviewed-by: Grace Hopper"]);
 
         let repo = git::open_repo(&path).unwrap();
+        let commits = git::get_commits(&repo, None, 2, 0, None, None).unwrap();
+        assert_eq!(commits[0].trailers, vec![("Reviewed-by".to_string(), "Grace Hopper".to_string())]);
+        assert!(commits[1].trailers.is_empty());
+    }
 
-        // Verify on main
-        let info = git::get_repository_info(&repo).unwrap();
-        assert_eq!(info.head_branch, Some("main".to_string()));
+    #[test]
+    fn test_get_commits_time_range() {
+        let (_tmp, path) = create_test_repo();
 
-        // Checkout feature
-        git::checkout_branch(&repo, "feature").expect("should checkout");
+        for (i, time) in [1_000_000_000i64, 1_000_001_000, 1_000_002_000, 1_000_003_000]
+            .iter()
+            .enumerate()
+        {
+            let date = format!("@{} +0000", time);
+            let output = git_cmd(&path)
+                .env("GIT_AUTHOR_DATE", &date)
+                .env("GIT_COMMITTER_DATE", &date)
+                .args(["commit", "--allow-empty", "-m", &format!("Commit {}", i)])
+                .output()
+                .unwrap();
+            assert!(output.status.success());
+        }
 
-        // Need to re-open repo to see updated HEAD
         let repo = git::open_repo(&path).unwrap();
-        let info = git::get_repository_info(&repo).unwrap();
-        assert_eq!(info.head_branch, Some("feature".to_string()));
-    }
-}
+        let summaries = |commits: Vec<git::CommitInfo>| -> Vec<String> {
+            commits.into_iter().map(|c| c.summary).collect()
+        };
 
-// =============================================================================
-// Graph Tests
-// =============================================================================
+        let in_range = git::get_commits(&repo, None, 10, 0, Some(1_000_001_000), Some(1_000_002_000)).unwrap();
+        assert_eq!(summaries(in_range), vec!["Commit 2", "Commit 1"]);
 
-mod graph {
-    use super::*;
+        let until_only = git::get_commits(&repo, None, 10, 0, None, Some(1_000_001_500)).unwrap();
+        assert_eq!(summaries(until_only), vec!["Commit 1", "Commit 0"]);
+
+        // limit/offset count only commits inside the range
+        let paged = git::get_commits(&repo, None, 1, 1, Some(1_000_000_000), Some(1_000_003_000)).unwrap();
+        assert_eq!(summaries(paged), vec!["Commit 2"]);
+    }
 
     #[test]
-    fn test_build_commit_graph() {
+    fn test_get_commits_page_cursor() {
         let (_tmp, path) = create_repo_with_history();
 
         let repo = git::open_repo(&path).unwrap();
-        let commits = git::get_commits(&repo, None, 10, 0).unwrap();
-        let commit_ids: Vec<String> = commits.iter().map(|c| c.id.clone()).collect();
-
-        let graph = git::build_commit_graph(&repo, &commit_ids).expect("should build graph");
+        let first = git::get_commits_page(&repo, None, None, 2).expect("should get first page");
+        assert_eq!(first.commits.len(), 2);
+        assert_eq!(first.commits[0].summary, "Add file2");
+        assert_eq!(first.commits[1].summary, "Add file1");
 
-        assert_eq!(graph.nodes.len(), 3);
-        assert_eq!(graph.max_columns, 1); // Linear history = 1 column
+        let cursor = first.next_cursor.expect("should have a next cursor");
+        let second = git::get_commits_page(&repo, None, Some(&cursor), 2).expect("should get second page");
+        assert_eq!(second.commits.len(), 1);
+        assert_eq!(second.commits[0].summary, "Initial commit");
+        assert!(second.next_cursor.is_none());
     }
 
     #[test]
-    fn test_build_commit_graph_with_merge() {
+    fn test_get_commits_page_matches_offset_paging_across_merges() {
         let (_tmp, path) = create_repo_with_branches();
-
-        // Merge feature into main
-        run_git(&path, &["merge", "feature", "-m", "Merge feature"]);
+        run_git(&path, &["merge", "feature", "--no-ff", "-m", "Merge feature"]);
 
         let repo = git::open_repo(&path).unwrap();
-        let commits = git::get_commits_all_branches(&repo, 10, 0).unwrap();
-        let commit_ids: Vec<String> = commits.iter().map(|c| c.id.clone()).collect();
+        let all = git::get_commits(&repo, None, 100, 0, None, None).unwrap();
+
+        let mut paged = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let page = git::get_commits_page(&repo, None, cursor.as_deref(), 1).unwrap();
+            paged.extend(page.commits.into_iter().map(|c| c.id));
+            cursor = page.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
 
-        let graph = git::build_commit_graph(&repo, &commit_ids).expect("should build graph");
+        let mut expected: Vec<String> = all.into_iter().map(|c| c.id).collect();
+        expected.sort();
+        paged.sort();
+        assert_eq!(paged, expected, "every commit should appear exactly once");
+    }
+
+    #[test]
+    fn test_changes_since_marker() {
+        let (_tmp, path) = create_test_repo();
+
+        let repo = git::open_repo(&path).unwrap();
+
+        // No marker yet
+        let changes = git::get_changes_since_marker(&repo).expect("should get changes");
+        assert!(!changes.has_marker);
+        assert!(changes.new_commits.is_empty());
+
+        git::set_seen_marker(&repo).expect("should set marker");
+        assert!(path.join(".git/diffy/seen-marker.json").exists());
+
+        let changes = git::get_changes_since_marker(&repo).expect("should get changes");
+        assert!(changes.has_marker);
+        assert!(!changes.head_moved);
+        assert!(!changes.index_changed);
+        assert!(changes.new_commits.is_empty());
+
+        // Stage a file: index changes but HEAD doesn't
+        std::fs::write(path.join("a.txt"), "a\n").unwrap();
+        run_git(&path, &["add", "a.txt"]);
+        let changes = git::get_changes_since_marker(&repo).expect("should get changes");
+        assert!(!changes.head_moved);
+        assert!(changes.index_changed);
+
+        run_git(&path, &["commit", "-m", "Add a"]);
+        std::fs::write(path.join("b.txt"), "b\n").unwrap();
+        run_git(&path, &["add", "b.txt"]);
+        run_git(&path, &["commit", "-m", "Add b"]);
+
+        let changes = git::get_changes_since_marker(&repo).expect("should get changes");
+        assert!(changes.head_moved);
+        assert_eq!(changes.new_commits.len(), 2);
+        assert_eq!(changes.new_commits[0].summary, "Add b");
+        assert_eq!(changes.new_commits[1].summary, "Add a");
+
+        // Re-marking resets the baseline
+        git::set_seen_marker(&repo).expect("should set marker");
+        let changes = git::get_changes_since_marker(&repo).expect("should get changes");
+        assert!(!changes.head_moved);
+        assert!(changes.new_commits.is_empty());
+    }
+
+    #[test]
+    fn test_soft_reset_keeps_changes_staged() {
+        let (_tmp, path) = create_repo_with_history();
+        let target = run_git_output(&path, &["rev-parse", "HEAD~1"]);
+
+        git::reset_commit(path.to_str().unwrap(), "HEAD~1", git::ResetMode::Soft, false)
+            .expect("soft reset should succeed");
+
+        assert_eq!(run_git_output(&path, &["rev-parse", "HEAD"]), target);
+        let repo = git::open_repo(&path).unwrap();
+        let status = git::get_status(&repo).unwrap();
+        assert_eq!(status.staged.len(), 1);
+        assert_eq!(status.staged[0].path, "file2.txt");
+        assert!(status.unstaged.is_empty());
+    }
+
+    #[test]
+    fn test_repo_summary() {
+        let (_tmp, path) = create_repo_with_history();
+        run_git(&path, &["branch", "feature"]);
+        run_git(&path, &["tag", "v1.0"]);
+
+        let repo = git::open_repo(&path).unwrap();
+        let summary = git::get_repo_summary(&repo).expect("should summarize repo");
+        assert_eq!(summary.total_commits, 3);
+        assert_eq!(summary.branch_count, 2);
+        assert_eq!(summary.tag_count, 1);
+        assert_eq!(summary.contributor_count, 1);
+        assert_eq!(summary.head_commit.expect("repo has commits").summary, "Add file2");
+
+        let empty_tmp = TempDir::new().expect("failed to create temp dir");
+        run_git(empty_tmp.path(), &["init", "-b", "main"]);
+        let empty = git::open_repo(empty_tmp.path()).unwrap();
+        let summary = git::get_repo_summary(&empty).expect("empty repos have a summary too");
+        assert_eq!(summary.total_commits, 0);
+        assert!(summary.head_commit.is_none());
+    }
+
+    #[test]
+    fn test_uncommit_keeps_changes_staged() {
+        let (_tmp, path) = create_test_repo();
+        let initial = run_git_output(&path, &["rev-parse", "HEAD"]);
+        std::fs::write(path.join("notes.txt"), "notes\n").unwrap();
+        run_git(&path, &["add", "notes.txt"]);
+        run_git(&path, &["commit", "-m", "Add notes"]);
+
+        git::uncommit(path.to_str().unwrap()).expect("uncommit should succeed");
+
+        assert_eq!(run_git_output(&path, &["rev-parse", "HEAD"]), initial);
+        let repo = git::open_repo(&path).unwrap();
+        let commits = git::get_commits(&repo, None, 10, 0, None, None).unwrap();
+        assert!(commits.iter().all(|c| c.summary != "Add notes"));
+        let status = git::get_status(&repo).unwrap();
+        assert_eq!(status.staged.len(), 1);
+        assert_eq!(status.staged[0].path, "notes.txt");
+
+        // Only the root commit is left
+        match git::uncommit(path.to_str().unwrap()) {
+            Err(git::GitError::Validation(msg)) => assert!(msg.contains("root commit"), "{}", msg),
+            other => panic!("expected a validation error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_mixed_reset_leaves_changes_unstaged() {
+        let (_tmp, path) = create_repo_with_history();
+        let target = run_git_output(&path, &["rev-parse", "HEAD~1"]);
+        std::fs::write(path.join("file1.txt"), "local edit\n").unwrap();
+        run_git(&path, &["add", "file1.txt"]);
+
+        git::reset_commit(path.to_str().unwrap(), "HEAD~1", git::ResetMode::Mixed, false)
+            .expect("mixed reset should succeed");
+
+        assert_eq!(run_git_output(&path, &["rev-parse", "HEAD"]), target);
+        let repo = git::open_repo(&path).unwrap();
+        let status = git::get_status(&repo).unwrap();
+        assert!(status.staged.is_empty());
+        // The staged edit and the undone commit's file both end up in the working tree only
+        assert_eq!(std::fs::read_to_string(path.join("file1.txt")).unwrap(), "local edit\n");
+        assert!(status.unstaged.iter().any(|f| f.path == "file1.txt"));
+        assert!(status.untracked.iter().any(|f| f.path == "file2.txt"));
+    }
+
+    #[test]
+    fn test_hard_reset_requires_confirm_on_dirty_tree() {
+        let (_tmp, path) = create_repo_with_history();
+        let head = run_git_output(&path, &["rev-parse", "HEAD"]);
+        let target = run_git_output(&path, &["rev-parse", "HEAD~1"]);
+        // file1.txt has both staged and unstaged edits; it still counts as one change
+        std::fs::write(path.join("file1.txt"), "staged edit\n").unwrap();
+        run_git(&path, &["add", "file1.txt"]);
+        std::fs::write(path.join("file1.txt"), "local edit\n").unwrap();
+        std::fs::write(path.join("file2.txt"), "local edit\n").unwrap();
+        let repo_path = path.to_str().unwrap();
+
+        match git::reset_commit(repo_path, "HEAD~1", git::ResetMode::Hard, false) {
+            Err(git::GitError::Validation(msg)) => assert!(msg.contains("2 uncommitted changes"), "{}", msg),
+            other => panic!("expected a validation error, got {:?}", other),
+        }
+        assert_eq!(run_git_output(&path, &["rev-parse", "HEAD"]), head);
+        assert_eq!(std::fs::read_to_string(path.join("file1.txt")).unwrap(), "local edit\n");
+
+        git::reset_commit(repo_path, "HEAD~1", git::ResetMode::Hard, true).expect("confirmed reset should succeed");
+        assert_eq!(run_git_output(&path, &["rev-parse", "HEAD"]), target);
+        assert!(!path.join("file2.txt").exists());
+    }
+
+    #[test]
+    fn test_squash_dirty_tree_reports_paths() {
+        let (_tmp, path) = create_repo_with_history();
+        let head = run_git_output(&path, &["rev-parse", "HEAD"]);
+        let parent = run_git_output(&path, &["rev-parse", "HEAD~1"]);
+        std::fs::write(path.join("file1.txt"), "unstaged edit\n").unwrap();
+        std::fs::write(path.join("README.md"), "# Staged edit\n").unwrap();
+        run_git(&path, &["add", "README.md"]);
+        std::fs::write(path.join("untracked.txt"), "ignored\n").unwrap();
+
+        let result = git::squash_commits(path.to_str().unwrap(), vec![parent, head.clone()], "Squashed");
+        let err = result.unwrap_err();
+        match &err {
+            git::GitError::DirtyWorkingTree { paths, .. } => {
+                assert_eq!(paths, &vec!["README.md".to_string(), "file1.txt".to_string()])
+            }
+            other => panic!("expected a dirty working tree error, got {:?}", other),
+        }
+        assert_eq!(run_git_output(&path, &["rev-parse", "HEAD"]), head);
+
+        let json = serde_json::to_value(diffy_lib::error::AppError::from(err)).unwrap();
+        assert_eq!(json["code"], "errors.dirty_working_tree");
+        assert_eq!(json["paths"], serde_json::json!(["README.md", "file1.txt"]));
+    }
+
+    #[test]
+    fn test_checkout_blocked_by_local_changes() {
+        let (_tmp, path) = create_repo_with_history();
+        run_git(&path, &["branch", "older", "HEAD~1"]);
+        // file2.txt doesn't exist on `older`, so checking it out would drop this edit
+        std::fs::write(path.join("file2.txt"), "local edit\n").unwrap();
+
+        let repo = git::open_repo(&path).unwrap();
+        match git::checkout_branch(&repo, "older") {
+            Err(git::GitError::DirtyWorkingTree { paths, .. }) => assert_eq!(paths, vec!["file2.txt".to_string()]),
+            other => panic!("expected a dirty working tree error, got {:?}", other),
+        }
+        assert_eq!(run_git_output(&path, &["symbolic-ref", "--short", "HEAD"]), "main");
+    }
+
+    #[test]
+    fn test_unsigned_commit_signature() {
+        let (_tmp, path) = create_test_repo();
+        let head = run_git_output(&path, &["rev-parse", "HEAD"]);
+
+        let sig = git::get_commit_signature(path.to_str().unwrap(), &head).expect("should check signature");
+        assert_eq!(sig.status, git::SignatureStatus::Unsigned);
+        assert!(sig.key_id.is_none());
+        assert!(sig.signer.is_none());
+    }
+
+    /// Needs ssh-keygen; opt in with DIFFY_TEST_SIGNING=1
+    #[test]
+    fn test_ssh_signed_commit_signature() {
+        if std::env::var("DIFFY_TEST_SIGNING").is_err() {
+            return;
+        }
+        let (tmp, path) = create_test_repo();
+        let key = tmp.path().join("signing_key");
+        let status = Command::new("ssh-keygen")
+            .args(["-q", "-t", "ed25519", "-N", "", "-C", "signer@example.com", "-f"])
+            .arg(&key)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let public_key = std::fs::read_to_string(key.with_extension("pub")).unwrap();
+        let allowed = tmp.path().join("allowed_signers");
+        std::fs::write(&allowed, format!("test@example.com {}", public_key)).unwrap();
+
+        run_git(&path, &["config", "gpg.format", "ssh"]);
+        run_git(&path, &["config", "user.signingkey", key.to_str().unwrap()]);
+        run_git(&path, &["config", "gpg.ssh.allowedSignersFile", allowed.to_str().unwrap()]);
+        run_git(&path, &["commit", "--allow-empty", "-S", "-m", "Signed"]);
+        let head = run_git_output(&path, &["rev-parse", "HEAD"]);
+
+        let sig = git::get_commit_signature(path.to_str().unwrap(), &head).expect("should check signature");
+        assert_eq!(sig.status, git::SignatureStatus::Good);
+        assert!(sig.key_id.is_some());
+        assert_eq!(sig.signer.as_deref(), Some("test@example.com"));
+    }
+}
+
+// =============================================================================
+// Branch Tests
+// =============================================================================
+
+mod branches {
+    use super::*;
+
+    #[test]
+    fn test_list_branches() {
+        let (_tmp, path) = create_repo_with_branches();
+
+        let repo = git::open_repo(&path).unwrap();
+        let branches = git::list_all_branches(&repo).expect("should list branches");
+
+        let local_branches: Vec<_> = branches.iter().filter(|b| !b.is_remote).collect();
+        assert_eq!(local_branches.len(), 2);
+
+        let names: Vec<_> = local_branches.iter().map(|b| b.name.as_str()).collect();
+        assert!(names.contains(&"main"));
+        assert!(names.contains(&"feature"));
+    }
+
+    #[test]
+    fn test_create_branch() {
+        let (_tmp, path) = create_test_repo();
+
+        let repo = git::open_repo(&path).unwrap();
+        git::create_branch(&repo, "new-branch", false).expect("should create branch");
+
+        let branches = git::list_all_branches(&repo).unwrap();
+        let names: Vec<_> = branches.iter().map(|b| b.name.as_str()).collect();
+        assert!(names.contains(&"new-branch"));
+    }
+
+    #[test]
+    fn test_checkout_branch() {
+        let (_tmp, path) = create_repo_with_branches();
+
+        let repo = git::open_repo(&path).unwrap();
+
+        // Verify on main
+        let info = git::get_repository_info(&repo).unwrap();
+        assert_eq!(info.head_branch, Some("main".to_string()));
+
+        // Checkout feature
+        git::checkout_branch(&repo, "feature").expect("should checkout");
+
+        // Need to re-open repo to see updated HEAD
+        let repo = git::open_repo(&path).unwrap();
+        let info = git::get_repository_info(&repo).unwrap();
+        assert_eq!(info.head_branch, Some("feature".to_string()));
+    }
+
+    #[test]
+    fn test_checkout_stash_before_and_force() {
+        let (_tmp, path) = create_repo_with_history();
+        run_git(&path, &["branch", "older", "HEAD~1"]);
+        let repo = git::open_repo(&path).unwrap();
+
+        // file2.txt doesn't exist on `older`, so the safe checkout refuses
+        std::fs::write(path.join("file2.txt"), "local edit\n").unwrap();
+        let stash = git::BranchCheckoutOptions { stash_before: true, ..Default::default() };
+        git::checkout_branch_with(&repo, "older", &stash).expect("stash-before checkout should succeed");
+        assert_eq!(run_git_output(&path, &["symbolic-ref", "--short", "HEAD"]), "older");
+        assert!(!path.join("file2.txt").exists());
+        let stashes = run_git_output(&path, &["stash", "list"]);
+        assert!(stashes.contains("Auto-stash before checking out older"), "{}", stashes);
+
+        // An untracked file2.txt would be overwritten by main's; force overwrites it
+        std::fs::write(path.join("file2.txt"), "discard me\n").unwrap();
+        assert!(matches!(
+            git::checkout_branch(&repo, "main"),
+            Err(git::GitError::DirtyWorkingTree { .. })
+        ));
+        let force = git::BranchCheckoutOptions { force: true, ..Default::default() };
+        git::checkout_branch_with(&repo, "main", &force).expect("forced checkout should succeed");
+        assert_eq!(run_git_output(&path, &["symbolic-ref", "--short", "HEAD"]), "main");
+        assert_eq!(std::fs::read_to_string(path.join("file2.txt")).unwrap(), "content 2\n");
+    }
+
+    #[test]
+    fn test_refused_stash_before_checkout_restores_changes() {
+        let (_tmp, path) = create_repo_with_history();
+        run_git(&path, &["checkout", "-b", "older", "HEAD~1"]);
+        let repo = git::open_repo(&path).unwrap();
+
+        // The tracked edit gets stashed, but the untracked file2.txt still blocks main
+        std::fs::write(path.join("file1.txt"), "tracked edit\n").unwrap();
+        std::fs::write(path.join("file2.txt"), "untracked\n").unwrap();
+        let stash = git::BranchCheckoutOptions { stash_before: true, ..Default::default() };
+        match git::checkout_branch_with(&repo, "main", &stash) {
+            Err(git::GitError::DirtyWorkingTree { paths, .. }) => assert_eq!(paths, vec!["file2.txt".to_string()]),
+            other => panic!("expected a dirty working tree error, got {:?}", other),
+        }
+
+        assert_eq!(run_git_output(&path, &["symbolic-ref", "--short", "HEAD"]), "older");
+        assert_eq!(std::fs::read_to_string(path.join("file1.txt")).unwrap(), "tracked edit\n");
+        assert_eq!(run_git_output(&path, &["stash", "list"]), "", "auto-stash was popped");
+    }
+
+    #[test]
+    fn test_rename_and_delete_branch() {
+        let (_tmp, path) = create_test_repo();
+
+        let repo = git::open_repo(&path).unwrap();
+        git::create_branch(&repo, "old-name", false).expect("should create branch");
+        git::rename_branch(&repo, "old-name", "new-name").expect("should rename branch");
+
+        let branches = git::list_all_branches(&repo).unwrap();
+        let names: Vec<_> = branches.iter().map(|b| b.name.as_str()).collect();
+        assert!(names.contains(&"new-name"));
+        assert!(!names.contains(&"old-name"));
+
+        git::delete_branch(&repo, "new-name", false).expect("should delete merged branch");
+
+        let branches = git::list_all_branches(&repo).unwrap();
+        assert!(!branches.iter().any(|b| b.name == "new-name"));
+    }
+
+    #[test]
+    fn test_delete_current_branch_fails() {
+        let (_tmp, path) = create_test_repo();
+
+        let repo = git::open_repo(&path).unwrap();
+        let result = git::delete_branch(&repo, "main", true);
+        assert!(matches!(result, Err(git::GitError::Validation(_))));
+    }
+
+    #[test]
+    fn test_delete_unmerged_branch_requires_force() {
+        let (_tmp, path) = create_repo_with_branches();
+
+        let repo = git::open_repo(&path).unwrap();
+        let result = git::delete_branch(&repo, "feature", false);
+        assert!(result.is_err(), "unmerged branch should not be deleted without force");
+
+        git::delete_branch(&repo, "feature", true).expect("force delete should succeed");
+        let branches = git::list_all_branches(&repo).unwrap();
+        assert!(!branches.iter().any(|b| b.name == "feature"));
+    }
+
+    #[test]
+    fn test_merge_base() {
+        let (_tmp, path) = create_repo_with_branches();
+        let initial = run_git_output(&path, &["rev-list", "--max-parents=0", "HEAD"]);
+
+        let repo = git::open_repo(&path).unwrap();
+        let base = git::get_merge_base(&repo, "main", "feature")
+            .unwrap()
+            .expect("branches share history");
+        assert_eq!(base.id, initial);
+        assert_eq!(base.summary, "Initial commit");
+
+        // Unrelated history has no merge base
+        run_git(&path, &["checkout", "--orphan", "unrelated"]);
+        run_git(&path, &["commit", "--allow-empty", "-m", "Unrelated root"]);
+        let repo = git::open_repo(&path).unwrap();
+        assert!(git::get_merge_base(&repo, "main", "unrelated").unwrap().is_none());
+    }
+}
+
+// =============================================================================
+// Remote Tests
+// =============================================================================
+
+mod remotes {
+    use super::*;
+
+    #[test]
+    fn test_list_remotes_empty() {
+        let (_tmp, path) = create_test_repo();
+
+        let repo = git::open_repo(&path).unwrap();
+        let remotes = git::list_remotes(&repo).expect("should list remotes");
+        assert!(remotes.is_empty());
+    }
+
+    #[test]
+    fn test_list_remotes() {
+        let (_tmp, path) = create_test_repo();
+        run_git(&path, &["remote", "add", "origin", "https://example.com/repo.git"]);
+        run_git(&path, &["remote", "add", "upstream", "https://example.com/upstream.git"]);
+        run_git(&path, &["remote", "set-url", "--push", "upstream", "git@example.com:upstream.git"]);
+
+        let repo = git::open_repo(&path).unwrap();
+        let remotes = git::list_remotes(&repo).expect("should list remotes");
+        assert_eq!(remotes.len(), 2);
+
+        let origin = remotes.iter().find(|r| r.name == "origin").expect("origin should be listed");
+        assert_eq!(origin.fetch_url.as_deref(), Some("https://example.com/repo.git"));
+        // No pushurl configured, so push falls back to fetch
+        assert_eq!(origin.push_url.as_deref(), Some("https://example.com/repo.git"));
+
+        let upstream = remotes.iter().find(|r| r.name == "upstream").expect("upstream should be listed");
+        assert_eq!(upstream.fetch_url.as_deref(), Some("https://example.com/upstream.git"));
+        assert_eq!(upstream.push_url.as_deref(), Some("git@example.com:upstream.git"));
+    }
+
+    #[test]
+    fn test_fetch_single_remote() {
+        let (_tmp, path) = create_test_repo();
+        let (_origin_tmp, origin_path) = create_test_repo();
+        let (_upstream_tmp, upstream_path) = create_test_repo();
+
+        run_git(&path, &["remote", "add", "origin", origin_path.to_str().unwrap()]);
+        run_git(&path, &["remote", "add", "upstream", upstream_path.to_str().unwrap()]);
+
+        git::git_fetch(path.to_str().unwrap(), Some("origin"), None).expect("should fetch origin");
+
+        let origin_head = run_git_output(&origin_path, &["rev-parse", "HEAD"]);
+        let fetched = run_git_output(&path, &["rev-parse", "refs/remotes/origin/main"]);
+        assert_eq!(fetched, origin_head);
+
+        // upstream was not fetched
+        let upstream_ref = git_cmd(&path)
+            .args(["rev-parse", "--verify", "--quiet", "refs/remotes/upstream/main"])
+            .output()
+            .unwrap();
+        assert!(!upstream_ref.status.success(), "upstream refs should not be fetched");
+    }
+
+    #[test]
+    fn test_fetch_times_out_on_hung_remote() {
+        let (_tmp, path) = create_test_repo();
+
+        // An ext:: transport that never speaks the protocol simulates a hung connection
+        run_git(&path, &["config", "protocol.ext.allow", "always"]);
+        run_git(&path, &["remote", "add", "slow", "ext::sh -c sleep% 30"]);
+
+        let started = std::time::Instant::now();
+        let err = git::git_fetch(path.to_str().unwrap(), Some("slow"), Some(std::time::Duration::from_secs(1)))
+            .expect_err("fetch should time out");
+        assert!(started.elapsed() < std::time::Duration::from_secs(10));
+        assert!(err.to_string().contains("timed out"), "got {}", err);
+
+        let app_err = diffy_lib::error::AppError::from(err);
+        assert_eq!(app_err.kind(), diffy_lib::error::ErrorKind::Network);
+        assert!(!path.join(".git/index.lock").exists());
+    }
+
+    /// Create a bare remote, add it as origin and push main to it
+    fn add_bare_origin(path: &Path) -> TempDir {
+        let remote_tmp = TempDir::new().expect("failed to create temp dir");
+        run_git(remote_tmp.path(), &["init", "--bare", "-b", "main"]);
+        run_git(path, &["remote", "add", "origin", remote_tmp.path().to_str().unwrap()]);
+        run_git(path, &["push", "-u", "origin", "main"]);
+        remote_tmp
+    }
+
+    /// Commit `file` with `content` in a separate clone of `remote_tmp` and push it to main,
+    /// returning the new remote head
+    fn push_from_other_clone(remote_tmp: &TempDir, file: &str, content: &str) -> String {
+        let other_tmp = TempDir::new().expect("failed to create temp dir");
+        let other = other_tmp.path().join("other");
+        run_git(other_tmp.path(), &["clone", remote_tmp.path().to_str().unwrap(), "other"]);
+        run_git(&other, &["config", "user.email", "test@example.com"]);
+        run_git(&other, &["config", "user.name", "Test User"]);
+        std::fs::write(other.join(file), content).unwrap();
+        run_git(&other, &["add", file]);
+        run_git(&other, &["commit", "-m", "Remote commit"]);
+        run_git(&other, &["push", "origin", "main"]);
+        run_git_output(&other, &["rev-parse", "HEAD"])
+    }
+
+    #[test]
+    fn test_push_force_with_lease_after_rewrite() {
+        let (_tmp, path) = create_test_repo();
+        let remote_tmp = add_bare_origin(&path);
+
+        run_git(&path, &["commit", "--amend", "-m", "Rewritten initial commit"]);
+
+        // A plain push is rejected as non-fast-forward
+        let result = git::git_push(path.to_str().unwrap(), None, None, git::ForceMode::None, None);
+        assert!(result.is_err());
+
+        git::git_push(path.to_str().unwrap(), Some("origin"), Some("main"), git::ForceMode::ForceWithLease, None)
+            .expect("force-with-lease push should succeed");
+
+        let local_head = run_git_output(&path, &["rev-parse", "HEAD"]);
+        let remote_head = run_git_output(remote_tmp.path(), &["rev-parse", "main"]);
+        assert_eq!(local_head, remote_head);
+    }
+
+    #[test]
+    fn test_fetch_remote_reports_updated_refs() {
+        let (_tmp, path) = create_test_repo();
+        let remote_tmp = add_bare_origin(&path);
+        let old_head = run_git_output(&path, &["rev-parse", "HEAD"]);
+
+        let new_head = push_from_other_clone(&remote_tmp, "new.txt", "new\n");
+
+        let updates = git::git_fetch_remote(path.to_str().unwrap(), "origin", None, true, None)
+            .expect("fetch should succeed");
+        assert_eq!(
+            updates,
+            vec![git::FetchedRef {
+                ref_name: "refs/remotes/origin/main".to_string(),
+                old_id: Some(old_head),
+                new_id: Some(new_head),
+            }]
+        );
+
+        // Nothing changed since the last fetch
+        let updates = git::git_fetch_remote(path.to_str().unwrap(), "origin", Some("main"), false, None).unwrap();
+        assert!(updates.is_empty());
+
+        assert!(git::git_fetch_remote(path.to_str().unwrap(), "--all", None, false, None).is_err());
+    }
+
+    #[test]
+    fn test_branch_with_pruned_upstream_is_gone() {
+        let (_tmp, path) = create_test_repo();
+        let remote_tmp = add_bare_origin(&path);
+        run_git(&path, &["checkout", "-b", "feature"]);
+        run_git(&path, &["push", "-u", "origin", "feature"]);
+        run_git(&path, &["checkout", "main"]);
+
+        // The branch is deleted on the remote, then pruned locally
+        run_git(remote_tmp.path(), &["branch", "-D", "feature"]);
+        git::git_fetch(path.to_str().unwrap(), Some("origin"), None).expect("should fetch origin");
+
+        let repo = git::open_repo(&path).unwrap();
+        let branches = git::list_all_branches(&repo).expect("should list branches");
+        let find = |name: &str| branches.iter().find(|b| b.name == name && !b.is_remote).unwrap();
+
+        let feature = find("feature");
+        assert_eq!(feature.upstream_state, git::UpstreamState::Gone);
+        assert_eq!(feature.upstream.as_deref(), Some("origin/feature"));
+
+        let main = find("main");
+        assert_eq!(main.upstream_state, git::UpstreamState::Tracking);
+        assert_eq!(main.upstream.as_deref(), Some("origin/main"));
+
+        run_git(&path, &["branch", "local-only"]);
+        let repo = git::open_repo(&path).unwrap();
+        let branches = git::list_all_branches(&repo).unwrap();
+        let local_only = branches.iter().find(|b| b.name == "local-only").unwrap();
+        assert_eq!(local_only.upstream_state, git::UpstreamState::None);
+        assert!(branches.iter().filter(|b| b.is_remote).all(|b| b.upstream_state == git::UpstreamState::None));
+    }
+
+    #[test]
+    fn test_pull_reports_conflicting_files() {
+        let (_tmp, path) = create_test_repo();
+        let remote_tmp = add_bare_origin(&path);
+        push_from_other_clone(&remote_tmp, "README.md", "# Remote edit\n");
+
+        std::fs::write(path.join("README.md"), "# Local edit\n").unwrap();
+        run_git(&path, &["commit", "-am", "Local commit"]);
+        let repo_path = path.to_str().unwrap();
+
+        // Diverged histories can't fast-forward, and that's a failure rather than a conflict
+        assert!(git::git_pull(repo_path, Some(git::PullStrategy::FfOnly), false, None).is_err());
+
+        let result = git::git_pull(repo_path, Some(git::PullStrategy::Merge), false, None)
+            .expect("a conflicting pull should still return a result");
+        assert!(result.has_conflicts);
+        assert_eq!(result.conflicting_files, vec!["README.md".to_string()]);
+        assert!(path.join(".git/MERGE_HEAD").exists());
+        run_git(&path, &["merge", "--abort"]);
+
+        let result = git::git_pull(repo_path, Some(git::PullStrategy::Rebase), false, None).unwrap();
+        assert!(result.has_conflicts);
+        assert_eq!(result.conflicting_files, vec!["README.md".to_string()]);
+        assert!(path.join(".git/rebase-merge").exists());
+    }
+
+    #[test]
+    fn test_push_force_with_lease_rejects_stale_ref() {
+        let (_tmp, path) = create_test_repo();
+        let remote_tmp = add_bare_origin(&path);
+
+        // Someone else pushes to the remote; we don't fetch
+        let other_tmp = TempDir::new().expect("failed to create temp dir");
+        run_git(other_tmp.path(), &["clone", remote_tmp.path().to_str().unwrap(), "."]);
+        std::fs::write(other_tmp.path().join("other.txt"), "other\n").unwrap();
+        run_git(other_tmp.path(), &["add", "other.txt"]);
+        run_git(other_tmp.path(), &["commit", "-m", "Other change"]);
+        run_git(other_tmp.path(), &["push", "origin", "main"]);
+        let other_head = run_git_output(other_tmp.path(), &["rev-parse", "HEAD"]);
+
+        run_git(&path, &["commit", "--amend", "-m", "Rewritten initial commit"]);
+
+        let result = git::git_push(path.to_str().unwrap(), None, None, git::ForceMode::ForceWithLease, None);
+        assert!(result.is_err(), "stale force-with-lease should be rejected");
+
+        // Remote still has the other change
+        let remote_head = run_git_output(remote_tmp.path(), &["rev-parse", "main"]);
+        assert_eq!(remote_head, other_head);
+    }
+
+    #[test]
+    fn test_add_rename_remove_remote() {
+        let (_tmp, path) = create_test_repo();
+
+        let repo = git::open_repo(&path).unwrap();
+        git::add_remote(&repo, "origin", "https://example.com/repo.git").expect("should add remote");
+        let remotes = git::list_remotes(&repo).unwrap();
+        assert_eq!(remotes.len(), 1);
+        assert_eq!(remotes[0].name, "origin");
+
+        git::rename_remote(&repo, "origin", "upstream").expect("should rename remote");
+        let remotes = git::list_remotes(&repo).unwrap();
+        assert_eq!(remotes.len(), 1);
+        assert_eq!(remotes[0].name, "upstream");
+        assert_eq!(remotes[0].fetch_url.as_deref(), Some("https://example.com/repo.git"));
+
+        git::remove_remote(&repo, "upstream").expect("should remove remote");
+        assert!(git::list_remotes(&repo).unwrap().is_empty());
+
+        // Removing again is a no-op
+        git::remove_remote(&repo, "upstream").expect("removing a missing remote should succeed");
+    }
+
+    #[test]
+    fn test_add_remote_duplicate_name_fails() {
+        let (_tmp, path) = create_test_repo();
+
+        let repo = git::open_repo(&path).unwrap();
+        git::add_remote(&repo, "origin", "https://example.com/repo.git").unwrap();
+
+        let result = git::add_remote(&repo, "origin", "https://example.com/other.git");
+        assert!(matches!(result, Err(git::GitError::Validation(_))));
+
+        // Original URL is untouched
+        let remotes = git::list_remotes(&repo).unwrap();
+        assert_eq!(remotes[0].fetch_url.as_deref(), Some("https://example.com/repo.git"));
+    }
+}
+
+// =============================================================================
+// Tree Browser Tests
+// =============================================================================
+
+mod tree {
+    use super::*;
+
+    #[test]
+    fn test_list_tree_root_of_commit() {
+        let (_tmp, path) = create_test_repo();
+        std::fs::create_dir_all(path.join("src/nested")).unwrap();
+        std::fs::write(path.join("src/main.rs"), "fn main() {}\n").unwrap();
+        std::fs::write(path.join("src/nested/deep.rs"), "// deep\n").unwrap();
+        std::fs::write(path.join("a.txt"), "abc\n").unwrap();
+        run_git(&path, &["add", "."]);
+        run_git(&path, &["commit", "-m", "Add tree"]);
+        let commit_id = run_git_output(&path, &["rev-parse", "HEAD"]);
+        // Not committed, so only the working tree listing sees it
+        std::fs::write(path.join("local.txt"), "local\n").unwrap();
+
+        let repo = git::open_repo(&path).unwrap();
+        let root = git::list_tree(&repo, Some(&commit_id), "").unwrap();
+        let names: Vec<_> = root.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["src", "README.md", "a.txt"]);
+        assert!(root[0].is_dir);
+        assert_eq!(root[0].size, None);
+        assert_eq!(root[2], git::TreeEntry {
+            name: "a.txt".to_string(),
+            path: "a.txt".to_string(),
+            is_dir: false,
+            mode: 0o100644,
+            size: Some(4),
+        });
+
+        let src = git::list_tree(&repo, Some(&commit_id), "src").unwrap();
+        let paths: Vec<_> = src.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, vec!["src/nested", "src/main.rs"]);
+
+        let workdir = git::list_tree(&repo, None, "").unwrap();
+        let names: Vec<_> = workdir.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["src", "README.md", "a.txt", "local.txt"]);
+
+        assert!(matches!(git::list_tree(&repo, None, "../"), Err(git::GitError::Validation(_))));
+        assert!(matches!(git::list_tree(&repo, Some(&commit_id), "a.txt"), Err(git::GitError::Validation(_))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_list_workdir_refuses_symlink_out_of_repo() {
+        let (_tmp, path) = create_test_repo();
+        let outside = TempDir::new().expect("failed to create temp dir");
+        std::fs::write(outside.path().join("secret.txt"), "secret\n").unwrap();
+        std::os::unix::fs::symlink(outside.path(), path.join("link")).unwrap();
+        std::fs::create_dir(path.join("dir")).unwrap();
+        std::os::unix::fs::symlink(path.join("dir"), path.join("inside")).unwrap();
+        run_git(&path, &["add", "link"]);
+        run_git(&path, &["commit", "-m", "Add link"]);
+
+        let repo = git::open_repo(&path).unwrap();
+        assert!(matches!(git::list_tree(&repo, None, "link"), Err(git::GitError::Validation(_))));
+        // Links that stay inside the repository still list
+        assert!(git::list_tree(&repo, None, "inside").unwrap().is_empty());
+    }
+}
+
+// =============================================================================
+// Tag Tests
+// =============================================================================
+
+mod tags {
+    use super::*;
+
+    #[test]
+    fn test_describe_commit() {
+        let (_tmp, path) = create_test_repo();
+        run_git(&path, &["tag", "-a", "v1.0.0", "-m", "Release 1.0.0"]);
+
+        let described = git::describe(path.to_str().unwrap(), None).expect("should describe");
+        assert_eq!(described, "v1.0.0");
+
+        std::fs::write(path.join("a.txt"), "a\n").unwrap();
+        run_git(&path, &["add", "a.txt"]);
+        run_git(&path, &["commit", "-m", "Add a"]);
+        std::fs::write(path.join("b.txt"), "b\n").unwrap();
+        run_git(&path, &["add", "b.txt"]);
+        run_git(&path, &["commit", "-m", "Add b"]);
+
+        let described = git::describe(path.to_str().unwrap(), None).expect("should describe");
+        assert!(described.starts_with("v1.0.0"), "got {}", described);
+        assert!(described.contains("-2-g"), "got {}", described);
+
+        // Describing an explicit commit
+        let parent = run_git_output(&path, &["rev-parse", "HEAD~1"]);
+        let described = git::describe(path.to_str().unwrap(), Some(&parent)).expect("should describe");
+        assert!(described.contains("v1.0.0-1-g"), "got {}", described);
+    }
+
+    #[test]
+    fn test_describe_without_tags_falls_back_to_short_sha() {
+        let (_tmp, path) = create_test_repo();
+
+        let described = git::describe(path.to_str().unwrap(), None).expect("should describe");
+        let head = run_git_output(&path, &["rev-parse", "HEAD"]);
+        assert!(head.starts_with(&described), "got {}", described);
+    }
+}
+
+// =============================================================================
+// Graph Tests
+// =============================================================================
+
+mod graph {
+    use super::*;
+
+    #[test]
+    fn test_build_commit_graph() {
+        let (_tmp, path) = create_repo_with_history();
+
+        let repo = git::open_repo(&path).unwrap();
+        let commits = git::get_commits(&repo, None, 10, 0, None, None).unwrap();
+        let commit_ids: Vec<String> = commits.iter().map(|c| c.id.clone()).collect();
+
+        let graph = git::build_commit_graph(&repo, &commit_ids).expect("should build graph");
+
+        assert_eq!(graph.nodes.len(), 3);
+        assert_eq!(graph.max_columns, 1); // Linear history = 1 column
+    }
+
+    #[test]
+    fn test_build_commit_graph_with_merge() {
+        let (_tmp, path) = create_repo_with_branches();
+
+        // Merge feature into main
+        run_git(&path, &["merge", "feature", "-m", "Merge feature"]);
+
+        let repo = git::open_repo(&path).unwrap();
+        let commits = git::get_commits_all_branches(&repo, 10, 0).unwrap();
+        let commit_ids: Vec<String> = commits.iter().map(|c| c.id.clone()).collect();
+
+        let graph = git::build_commit_graph(&repo, &commit_ids).expect("should build graph");
 
         // Should have connections for merge
         let merge_node = &graph.nodes[0];
@@ -729,90 +2218,755 @@ mod graph {
         assert!(graph.nodes.is_empty());
         assert_eq!(graph.max_columns, 0);
     }
+
+    #[test]
+    fn test_graph_ref_decorations() {
+        let (_tmp, path) = create_repo_with_history();
+        let (_remote_tmp, remote_path) = create_test_repo();
+        let root = run_git_output(&path, &["rev-list", "--max-parents=0", "HEAD"]);
+        let head = run_git_output(&path, &["rev-parse", "HEAD"]);
+
+        run_git(&path, &["tag", "-a", "v1.0", "-m", "Release", "HEAD~1"]);
+        run_git(&path, &["tag", "light", "HEAD"]);
+        run_git(&path, &["remote", "add", "origin", remote_path.to_str().unwrap()]);
+        run_git(&path, &["update-ref", "refs/remotes/origin/main", &root]);
+        run_git(&path, &["symbolic-ref", "refs/remotes/origin/HEAD", "refs/remotes/origin/main"]);
+
+        let repo = git::open_repo(&path).unwrap();
+        let commits = git::get_commits(&repo, None, 10, 0, None, None).unwrap();
+        let commit_ids: Vec<String> = commits.iter().map(|c| c.id.clone()).collect();
+        let graph = git::build_commit_graph(&repo, &commit_ids).unwrap();
+
+        let decorations_for = |id: &str| -> Vec<(String, git::RefKind)> {
+            graph
+                .nodes
+                .iter()
+                .find(|n| n.commit_id == id)
+                .unwrap()
+                .decorations
+                .iter()
+                .map(|d| (d.name.clone(), d.kind))
+                .collect()
+        };
+
+        assert_eq!(
+            decorations_for(&head),
+            vec![("main".to_string(), git::RefKind::LocalBranch), ("light".to_string(), git::RefKind::Tag)]
+        );
+        assert_eq!(decorations_for(&commit_ids[1]), vec![("v1.0".to_string(), git::RefKind::Tag)]);
+        assert_eq!(
+            decorations_for(&root),
+            vec![("origin/main".to_string(), git::RefKind::RemoteBranch)],
+            "symbolic origin/HEAD is not listed"
+        );
+    }
+
+    #[test]
+    fn test_cancelled_operations_stop_early() {
+        let (_tmp, path) = create_repo_with_history();
+        let repo = git::open_repo(&path).unwrap();
+        let commits = git::get_commits(&repo, None, 10, 0, None, None).unwrap();
+        let commit_ids: Vec<String> = commits.iter().map(|c| c.id.clone()).collect();
+
+        let registry = git::CancellationRegistry::new();
+        let cancel = registry.register(Some("req-1"));
+        assert!(registry.cancel("req-1"));
+        assert!(cancel.is_cancelled());
+
+        assert!(matches!(
+            git::build_commit_graph_cancellable(&repo, &commit_ids, &cancel),
+            Err(git::GitError::Cancelled)
+        ));
+        assert!(matches!(
+            git::get_commits_cancellable(&repo, None, 10, 0, None, None, &cancel),
+            Err(git::GitError::Cancelled)
+        ));
+        // Walking to a page is cancellable too, even when the page itself is empty
+        assert!(matches!(
+            git::get_commits_cancellable(&repo, None, 0, 2, None, None, &cancel),
+            Err(git::GitError::Cancelled)
+        ));
+        assert!(matches!(
+            git::get_status_cancellable(&repo, &cancel),
+            Err(git::GitError::Cancelled)
+        ));
+
+        registry.finish(Some("req-1"));
+        assert!(!registry.cancel("req-1"));
+    }
 }
 
 // =============================================================================
 // Merge Conflict Tests
 // =============================================================================
 
-mod merge_conflict {
+mod merge_conflict {
+    use super::*;
+
+    #[test]
+    fn test_get_merge_status_no_conflict() {
+        let (_tmp, path) = create_test_repo();
+
+        let repo = git::open_repo(&path).unwrap();
+        let status = git::get_merge_status(&repo).expect("should get merge status");
+
+        assert!(!status.in_merge);
+        assert!(status.conflicting_files.is_empty());
+    }
+
+    #[test]
+    fn test_get_merge_status_with_conflict() {
+        let (_tmp, path) = create_repo_with_conflict();
+
+        let repo = git::open_repo(&path).unwrap();
+        let status = git::get_merge_status(&repo).expect("should get merge status");
+
+        assert!(status.in_merge);
+        assert_eq!(status.conflicting_files.len(), 1);
+        assert_eq!(status.conflicting_files[0], "conflict.txt");
+    }
+
+    #[test]
+    fn test_parse_file_conflicts() {
+        let (_tmp, path) = create_repo_with_conflict();
+
+        let info = git::parse_file_conflicts(path.to_str().unwrap(), "conflict.txt")
+            .expect("should parse conflicts");
+
+        assert_eq!(info.file_path, "conflict.txt");
+        assert_eq!(info.conflicts.len(), 1);
+
+        // Check that we extracted the conflict regions
+        let conflict = &info.conflicts[0];
+        assert!(conflict.ours_content.contains("main branch content"));
+        assert!(conflict.theirs_content.contains("feature branch content"));
+        assert!(conflict.base_content.is_none());
+    }
+
+    #[test]
+    fn test_parse_file_conflicts_diff3() {
+        let (_tmp, path) = create_test_repo();
+        std::fs::write(
+            path.join("conflict.txt"),
+            "header\n<<<<<<< HEAD\nmain line\n||||||| base\noriginal line\n=======\nfeature line\n>>>>>>> feature\nfooter\n",
+        )
+        .unwrap();
+
+        let info = git::parse_file_conflicts(path.to_str().unwrap(), "conflict.txt")
+            .expect("should parse conflicts");
+
+        assert_eq!(info.conflicts.len(), 1);
+        let conflict = &info.conflicts[0];
+        assert_eq!(conflict.start_line, 2);
+        assert_eq!(conflict.end_line, 8);
+        assert_eq!(conflict.ours_content, "main line");
+        assert_eq!(conflict.base_content.as_deref(), Some("original line"));
+        assert_eq!(conflict.theirs_content, "feature line");
+        assert_eq!(info.ours_full, "header\nmain line\nfooter");
+        assert_eq!(info.theirs_full, "header\nfeature line\nfooter");
+    }
+
+    #[test]
+    fn test_parse_file_conflicts_from_git_diff3_merge() {
+        let (_tmp, path) = create_repo_with_conflict();
+        run_git(&path, &["merge", "--abort"]);
+        run_git(&path, &["config", "merge.conflictStyle", "diff3"]);
+        let output = git_cmd(&path).args(["merge", "feature"]).output().unwrap();
+        assert!(!output.status.success(), "merge should conflict");
+
+        let info = git::parse_file_conflicts(path.to_str().unwrap(), "conflict.txt")
+            .expect("should parse conflicts");
+
+        assert_eq!(info.conflicts.len(), 1);
+        let conflict = &info.conflicts[0];
+        assert_eq!(conflict.ours_content, "main branch content");
+        assert_eq!(conflict.base_content.as_deref(), Some("original content"));
+        assert_eq!(conflict.theirs_content, "feature branch content");
+    }
+
+    #[test]
+    fn test_parse_file_conflicts_multiple_regions() {
+        let (_tmp, path) = create_test_repo();
+        std::fs::write(
+            path.join("conflict.txt"),
+            "a\n<<<<<<< HEAD\nours 1\n=======\ntheirs 1\n>>>>>>> feature\nb\n========\nc\n<<<<<<< HEAD\nours 2\n=======\ntheirs 2a\ntheirs 2b\n>>>>>>> feature\n",
+        )
+        .unwrap();
+
+        let info = git::parse_file_conflicts(path.to_str().unwrap(), "conflict.txt")
+            .expect("should parse conflicts");
+
+        assert_eq!(info.conflicts.len(), 2);
+        assert_eq!((info.conflicts[0].start_line, info.conflicts[0].end_line), (2, 6));
+        assert_eq!(info.conflicts[0].theirs_content, "theirs 1");
+        assert_eq!((info.conflicts[1].start_line, info.conflicts[1].end_line), (10, 15));
+        assert_eq!(info.conflicts[1].theirs_content, "theirs 2a\ntheirs 2b");
+        // A longer run of '=' outside a conflict is ordinary content
+        assert_eq!(info.ours_full, "a\nours 1\nb\n========\nc\nours 2");
+        assert_eq!(info.theirs_full, "a\ntheirs 1\nb\n========\nc\ntheirs 2a\ntheirs 2b");
+    }
+
+    #[test]
+    fn test_parse_file_conflicts_unbalanced_markers() {
+        let (_tmp, path) = create_test_repo();
+        let repo_path = path.to_str().unwrap();
+        for content in [
+            "<<<<<<< HEAD\nours\n<<<<<<< HEAD\n=======\ntheirs\n>>>>>>> feature\n",
+            "<<<<<<< HEAD\nours\n=======\ntheirs\n",
+            "<<<<<<< HEAD\nours\n>>>>>>> feature\n",
+            "plain\n>>>>>>> feature\n",
+        ] {
+            std::fs::write(path.join("conflict.txt"), content).unwrap();
+            let result = git::parse_file_conflicts(repo_path, "conflict.txt");
+            assert!(matches!(result, Err(git::GitError::Validation(_))), "{:?}", content);
+        }
+    }
+
+    #[test]
+    fn test_abort_conflicting_cherry_pick() {
+        let (_tmp, path) = create_test_repo();
+
+        run_git(&path, &["checkout", "-b", "feature"]);
+        std::fs::write(path.join("README.md"), "feature version\n").unwrap();
+        run_git(&path, &["commit", "-am", "Feature change"]);
+        let feature_commit = run_git_output(&path, &["rev-parse", "HEAD"]);
+
+        run_git(&path, &["checkout", "main"]);
+        std::fs::write(path.join("README.md"), "main version\n").unwrap();
+        run_git(&path, &["commit", "-am", "Main change"]);
+
+        let result = git_cmd(&path).args(["cherry-pick", &feature_commit]).output().unwrap();
+        assert!(!result.status.success(), "cherry-pick should conflict");
+
+        let repo = git::open_repo(&path).unwrap();
+        assert_eq!(git::get_repository_state(&repo), "cherryPick");
+
+        git::abort_cherry_pick(path.to_str().unwrap()).expect("should abort cherry-pick");
+
+        let repo = git::open_repo(&path).unwrap();
+        assert_eq!(git::get_repository_state(&repo), "clean");
+        let status = git::get_status(&repo).unwrap();
+        assert!(status.staged.is_empty());
+        assert!(status.unstaged.is_empty());
+        let content = std::fs::read_to_string(path.join("README.md")).unwrap();
+        assert_eq!(content, "main version\n");
+    }
+
+    #[test]
+    fn test_get_conflict_diffs() {
+        let (_tmp, path) = create_repo_with_conflict();
+
+        let repo = git::open_repo(&path).unwrap();
+        let diffs = git::get_conflict_diffs(&repo, "conflict.txt").expect("should get conflict diffs");
+
+        assert!(diffs.ours_vs_base.patch.contains("-original content"));
+        assert!(diffs.ours_vs_base.patch.contains("+main branch content"));
+        assert!(diffs.theirs_vs_base.patch.contains("-original content"));
+        assert!(diffs.theirs_vs_base.patch.contains("+feature branch content"));
+    }
+
+    #[test]
+    fn test_get_conflict_versions_from_index() {
+        let (_tmp, path) = create_repo_with_conflict();
+
+        // Clobber the markers; the index stages are unaffected
+        std::fs::write(path.join("conflict.txt"), "hand edited\n").unwrap();
+
+        let repo = git::open_repo(&path).unwrap();
+        let versions = git::get_conflict_versions(&repo, "conflict.txt").expect("should read versions");
+
+        assert_eq!(versions.file_path, "conflict.txt");
+        assert_eq!(versions.base.as_deref(), Some("original content\n"));
+        assert_eq!(versions.ours.as_deref(), Some("main branch content\n"));
+        assert_eq!(versions.theirs.as_deref(), Some("feature branch content\n"));
+
+        let result = git::get_conflict_versions(&repo, "README.md");
+        assert!(matches!(result, Err(git::GitError::Validation(_))));
+    }
+
+    #[test]
+    fn test_get_conflict_diffs_not_conflicted() {
+        let (_tmp, path) = create_repo_with_conflict();
+
+        let repo = git::open_repo(&path).unwrap();
+        let result = git::get_conflict_diffs(&repo, "README.md");
+        assert!(matches!(result, Err(git::GitError::Validation(_))));
+    }
+
+    #[test]
+    fn test_save_resolved_file() {
+        let (_tmp, path) = create_repo_with_conflict();
+
+        let resolved_content = "resolved content\n";
+        git::save_resolved_file(path.to_str().unwrap(), "conflict.txt", resolved_content)
+            .expect("should save");
+
+        let content = std::fs::read_to_string(path.join("conflict.txt")).unwrap();
+        assert_eq!(content, resolved_content);
+    }
+
+    #[test]
+    fn test_mark_file_resolved() {
+        let (_tmp, path) = create_repo_with_conflict();
+
+        // Save resolved content first
+        std::fs::write(path.join("conflict.txt"), "resolved\n").unwrap();
+
+        let repo = git::open_repo(&path).unwrap();
+        git::mark_file_resolved(&repo, "conflict.txt").expect("should mark resolved");
+
+        // Check it's staged
+        let status = git::get_status(&repo).unwrap();
+        assert!(status.staged.iter().any(|f| f.path == "conflict.txt"));
+    }
+
+    #[test]
+    fn test_apply_conflict_resolution() {
+        let (_tmp, path) = create_repo_with_conflict();
+        let repo_path = path.to_str().unwrap();
+
+        git::apply_conflict_resolution(repo_path, "conflict.txt", "merged content\n")
+            .expect("should apply resolution");
+
+        let content = std::fs::read_to_string(path.join("conflict.txt")).unwrap();
+        assert_eq!(content, "merged content\n");
+        let repo = git::open_repo(&path).unwrap();
+        let status = git::get_merge_status(&repo).unwrap();
+        assert!(status.in_merge);
+        assert!(status.conflicting_files.is_empty());
+
+        // Already resolved, and never conflicted, files are rejected
+        let result = git::apply_conflict_resolution(repo_path, "conflict.txt", "again\n");
+        assert!(matches!(result, Err(git::GitError::Validation(_))));
+        let result = git::apply_conflict_resolution(repo_path, "README.md", "nope\n");
+        assert!(matches!(result, Err(git::GitError::Validation(_))));
+    }
+
+    #[test]
+    fn test_apply_conflict_resolution_requires_merge() {
+        let (_tmp, path) = create_test_repo();
+
+        let result = git::apply_conflict_resolution(path.to_str().unwrap(), "README.md", "x\n");
+        assert!(matches!(result, Err(git::GitError::Validation(_))));
+        let content = std::fs::read_to_string(path.join("README.md")).unwrap();
+        assert_ne!(content, "x\n");
+    }
+
+    #[test]
+    fn test_resolve_conflict_ours_and_theirs() {
+        for (side, expected) in [
+            (git::ConflictSide::Ours, "main branch content\n"),
+            (git::ConflictSide::Theirs, "feature branch content\n"),
+        ] {
+            let (_tmp, path) = create_repo_with_conflict();
+            let repo_path = path.to_str().unwrap();
+
+            match side {
+                git::ConflictSide::Ours => git::resolve_conflict_ours(repo_path, "conflict.txt"),
+                git::ConflictSide::Theirs => git::resolve_conflict_theirs(repo_path, "conflict.txt"),
+            }
+            .expect("should resolve");
+
+            let content = std::fs::read_to_string(path.join("conflict.txt")).unwrap();
+            assert_eq!(content, expected);
+            let repo = git::open_repo(&path).unwrap();
+            let status = git::get_merge_status(&repo).unwrap();
+            assert!(status.in_merge);
+            assert!(status.conflicting_files.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_resolve_all_conflicts() {
+        let (_tmp, path) = create_repo_with_conflict();
+
+        let resolved = git::resolve_all_conflicts(path.to_str().unwrap(), git::ConflictSide::Theirs)
+            .expect("should resolve all");
+
+        assert_eq!(resolved, vec!["conflict.txt"]);
+        let content = std::fs::read_to_string(path.join("conflict.txt")).unwrap();
+        assert_eq!(content, "feature branch content\n");
+        let repo = git::open_repo(&path).unwrap();
+        assert!(git::get_merge_status(&repo).unwrap().conflicting_files.is_empty());
+        assert!(git::get_status(&repo).unwrap().staged.iter().any(|f| f.path == "conflict.txt"));
+    }
+
+    /// Repo where `feature` is one commit ahead of `main`; with `diverge`,
+    /// `main` also gets a commit of its own
+    fn create_repo_with_feature_branch(diverge: bool) -> (TempDir, PathBuf) {
+        let (tmp, path) = create_test_repo();
+        run_git(&path, &["checkout", "-b", "feature"]);
+        std::fs::write(path.join("feature.txt"), "feature\n").unwrap();
+        run_git(&path, &["add", "feature.txt"]);
+        run_git(&path, &["commit", "-m", "Feature work"]);
+        run_git(&path, &["checkout", "main"]);
+        if diverge {
+            std::fs::write(path.join("main.txt"), "main\n").unwrap();
+            run_git(&path, &["add", "main.txt"]);
+            run_git(&path, &["commit", "-m", "Main work"]);
+        }
+        (tmp, path)
+    }
+
+    #[test]
+    fn test_preview_merge_leaves_tree_untouched() {
+        let (_tmp, path) = create_repo_with_feature_branch(true);
+        let repo_path = path.to_str().unwrap();
+        let head = run_git_output(&path, &["rev-parse", "HEAD"]);
+
+        let preview = git::preview_merge(repo_path, "feature").expect("preview should succeed");
+        assert!(!preview.up_to_date);
+        assert_eq!(preview.changed_files, vec!["feature.txt".to_string()]);
+        assert!(preview.conflicting_files.is_empty());
+        assert!(preview.aborted);
+        assert_eq!(run_git_output(&path, &["rev-parse", "HEAD"]), head);
+        assert!(!path.join("feature.txt").exists());
+        assert!(!path.join(".git/MERGE_HEAD").exists());
+
+        run_git(&path, &["merge", "feature"]);
+        assert!(git::preview_merge(repo_path, "feature").unwrap().up_to_date);
+    }
+
+    #[test]
+    fn test_preview_merge_reports_conflicts() {
+        let (_tmp, path) = create_repo_with_conflict();
+        run_git(&path, &["merge", "--abort"]);
+
+        let preview = git::preview_merge(path.to_str().unwrap(), "feature").expect("preview should succeed");
+        assert_eq!(preview.conflicting_files, vec!["conflict.txt".to_string()]);
+        assert_eq!(preview.changed_files, vec!["conflict.txt".to_string()]);
+        assert!(preview.aborted);
+        assert_eq!(std::fs::read_to_string(path.join("conflict.txt")).unwrap(), "main branch content\n");
+
+        let repo = git::open_repo(&path).unwrap();
+        assert_eq!(git::get_repository_state(&repo), "clean");
+    }
+
+    #[test]
+    fn test_conflicting_merge_and_rebase_report_files() {
+        let (_tmp, path) = create_repo_with_conflict();
+        run_git(&path, &["merge", "--abort"]);
+        let repo_path = path.to_str().unwrap();
+
+        match git::merge_branch(repo_path, "feature", &git::MergeOptions::default()) {
+            Err(git::GitError::Conflict { message, files }) => {
+                assert_eq!(message, "Merge has conflicts that need to be resolved");
+                assert_eq!(files, vec!["conflict.txt".to_string()]);
+            }
+            other => panic!("expected a conflict error, got {:?}", other),
+        }
+        run_git(&path, &["merge", "--abort"]);
+
+        run_git(&path, &["checkout", "feature"]);
+        match git::rebase_onto(repo_path, "main", false) {
+            Err(git::GitError::Conflict { files, .. }) => assert_eq!(files, vec!["conflict.txt".to_string()]),
+            other => panic!("expected a conflict error, got {:?}", other),
+        }
+
+        let err = diffy_lib::error::AppError::from(git::GitError::Conflict {
+            message: "Rebase has conflicts that need to be resolved".to_string(),
+            files: vec!["conflict.txt".to_string()],
+        });
+        let json = serde_json::to_value(&err).unwrap();
+        assert_eq!(json["kind"], "conflict");
+        assert_eq!(json["message"], "Rebase has conflicts that need to be resolved");
+        assert_eq!(json["conflictingFiles"], serde_json::json!(["conflict.txt"]));
+    }
+
+    #[test]
+    fn test_merge_ff_only_refuses_divergence() {
+        let (_tmp, path) = create_repo_with_feature_branch(true);
+        let head_before = run_git_output(&path, &["rev-parse", "HEAD"]);
+
+        let options = git::MergeOptions { ff_only: true, ..Default::default() };
+        let result = git::merge_branch(path.to_str().unwrap(), "feature", &options);
+
+        assert!(result.is_err());
+        assert_eq!(run_git_output(&path, &["rev-parse", "HEAD"]), head_before);
+        assert!(!path.join("feature.txt").exists());
+    }
+
+    #[test]
+    fn test_merge_no_ff_creates_merge_commit() {
+        let (_tmp, path) = create_repo_with_feature_branch(false);
+        let feature_tip = run_git_output(&path, &["rev-parse", "feature"]);
+
+        let options = git::MergeOptions { no_ff: true, ..Default::default() };
+        git::merge_branch(path.to_str().unwrap(), "feature", &options).expect("should merge");
+
+        // A fast-forward was possible, but HEAD is a new commit with both parents
+        let parents = run_git_output(&path, &["rev-list", "--parents", "-n", "1", "HEAD"]);
+        let parents: Vec<&str> = parents.split_whitespace().skip(1).collect();
+        assert_eq!(parents.len(), 2);
+        assert_eq!(parents[1], feature_tip);
+        assert!(path.join("feature.txt").exists());
+    }
+
+    #[test]
+    fn test_abort_merge() {
+        let (_tmp, path) = create_repo_with_conflict();
+
+        git::abort_merge(path.to_str().unwrap()).expect("should abort merge");
+
+        let repo = git::open_repo(&path).unwrap();
+        let status = git::get_merge_status(&repo).unwrap();
+        assert!(!status.in_merge);
+    }
+}
+
+// =============================================================================
+// Interactive Rebase Tests
+// =============================================================================
+
+mod rebase {
     use super::*;
 
-    #[test]
-    fn test_get_merge_status_no_conflict() {
-        let (_tmp, path) = create_test_repo();
-
-        let repo = git::open_repo(&path).unwrap();
-        let status = git::get_merge_status(&repo).expect("should get merge status");
+    fn plan_entry(commit_id: &str, action: git::RebaseTodoAction) -> git::InteractiveRebasePlanEntry {
+        git::InteractiveRebasePlanEntry {
+            commit_id: commit_id.to_string(),
+            action,
+            new_message: None,
+            exec_command: None,
+        }
+    }
 
-        assert!(!status.in_merge);
-        assert!(status.conflicting_files.is_empty());
+    /// Start a rebase that squashes a conflicting commit, then resolve the conflict so the
+    /// rebase is waiting on the squash message
+    fn create_repo_at_squash_message_step() -> (TempDir, PathBuf) {
+        let (tmp, path) = create_test_repo();
+        std::fs::write(path.join("x.txt"), "base\n").unwrap();
+        run_git(&path, &["add", "x.txt"]);
+        run_git(&path, &["commit", "-m", "Add x"]);
+        std::fs::write(path.join("a.txt"), "a\n").unwrap();
+        run_git(&path, &["add", "a.txt"]);
+        run_git(&path, &["commit", "-m", "Add a"]);
+        std::fs::write(path.join("x.txt"), "two\n").unwrap();
+        run_git(&path, &["commit", "-am", "x two"]);
+        std::fs::write(path.join("x.txt"), "three\n").unwrap();
+        run_git(&path, &["commit", "-am", "x three"]);
+
+        let add_a = run_git_output(&path, &["rev-parse", "HEAD~2"]);
+        let x_three = run_git_output(&path, &["rev-parse", "HEAD"]);
+        let plan = vec![
+            plan_entry(&add_a, git::RebaseTodoAction::Pick),
+            plan_entry(&x_three, git::RebaseTodoAction::Squash),
+        ];
+        let result = git::start_interactive_rebase(path.to_str().unwrap(), "HEAD~3", plan);
+        assert!(result.is_err(), "squash should stop on a conflict");
+
+        std::fs::write(path.join("x.txt"), "three\n").unwrap();
+        run_git(&path, &["add", "x.txt"]);
+        (tmp, path)
     }
 
     #[test]
-    fn test_get_merge_status_with_conflict() {
-        let (_tmp, path) = create_repo_with_conflict();
+    fn test_continue_squash_without_message_fails() {
+        let (_tmp, path) = create_repo_at_squash_message_step();
 
         let repo = git::open_repo(&path).unwrap();
-        let status = git::get_merge_status(&repo).expect("should get merge status");
+        let state = git::get_interactive_rebase_state(&repo).unwrap();
+        assert_eq!(state.stop_reason, git::RebaseStopReason::SquashMessage);
 
-        assert!(status.in_merge);
-        assert_eq!(status.conflicting_files.len(), 1);
-        assert_eq!(status.conflicting_files[0], "conflict.txt");
+        let result = git::continue_interactive_rebase(path.to_str().unwrap(), None);
+        assert!(matches!(result, Err(git::GitError::Validation(_))));
+        assert!(path.join(".git/rebase-merge").exists(), "rebase should still be in progress");
+
+        git::continue_interactive_rebase(path.to_str().unwrap(), Some("Squashed".to_string()))
+            .expect("should continue with a message");
+        assert!(!path.join(".git/rebase-merge").exists());
+        assert_eq!(run_git_output(&path, &["log", "-1", "--format=%s"]), "Squashed");
     }
 
     #[test]
-    fn test_parse_file_conflicts() {
-        let (_tmp, path) = create_repo_with_conflict();
+    fn test_interactive_rebase_rejects_unknown_commit() {
+        let (_tmp, path) = create_repo_with_history();
+        let head_before = run_git_output(&path, &["rev-parse", "HEAD"]);
 
-        let info = git::parse_file_conflicts(path.to_str().unwrap(), "conflict.txt")
-            .expect("should parse conflicts");
+        let plan = vec![
+            plan_entry(&head_before, git::RebaseTodoAction::Pick),
+            plan_entry("deadbeefdeadbeef", git::RebaseTodoAction::Pick),
+        ];
+        let result = git::start_interactive_rebase(path.to_str().unwrap(), "HEAD~2", plan);
+        assert!(matches!(result, Err(git::GitError::Validation(_))));
 
-        assert_eq!(info.file_path, "conflict.txt");
-        assert_eq!(info.conflicts.len(), 1);
+        // Nothing was started
+        assert!(!path.join(".git/rebase-merge").exists());
+        assert_eq!(run_git_output(&path, &["rev-parse", "HEAD"]), head_before);
+    }
 
-        // Check that we extracted the conflict regions
-        let conflict = &info.conflicts[0];
-        assert!(conflict.ours_content.contains("main branch content"));
-        assert!(conflict.theirs_content.contains("feature branch content"));
+    #[test]
+    fn test_interactive_rebase_rejects_leading_fixup() {
+        let (_tmp, path) = create_repo_with_history();
+        let file1 = run_git_output(&path, &["rev-parse", "HEAD~1"]);
+        let file2 = run_git_output(&path, &["rev-parse", "HEAD"]);
+
+        let plan = vec![
+            plan_entry(&file1, git::RebaseTodoAction::Drop),
+            plan_entry(&file2, git::RebaseTodoAction::Fixup),
+        ];
+        let result = git::start_interactive_rebase(path.to_str().unwrap(), "HEAD~2", plan);
+        match result {
+            Err(git::GitError::Validation(msg)) => assert!(msg.contains("fixup"), "{}", msg),
+            other => panic!("expected a validation error, got {:?}", other),
+        }
+
+        let all_dropped = vec![
+            plan_entry(&file1, git::RebaseTodoAction::Drop),
+            plan_entry(&file2, git::RebaseTodoAction::Drop),
+        ];
+        let result = git::start_interactive_rebase(path.to_str().unwrap(), "HEAD~2", all_dropped);
+        assert!(matches!(result, Err(git::GitError::Validation(_))));
+        assert!(!path.join(".git/rebase-merge").exists());
+        assert_eq!(run_git_output(&path, &["rev-parse", "HEAD"]), file2);
     }
 
     #[test]
-    fn test_save_resolved_file() {
-        let (_tmp, path) = create_repo_with_conflict();
+    fn test_split_commit_during_edit() {
+        let (_tmp, path) = create_repo_with_history();
+        let repo_path = path.to_str().unwrap();
+        std::fs::write(path.join("a.txt"), "a\n").unwrap();
+        std::fs::write(path.join("b.txt"), "b\n").unwrap();
+        run_git(&path, &["add", "a.txt", "b.txt"]);
+        run_git(&path, &["commit", "-m", "Add a and b"]);
+        let count_before = run_git_output(&path, &["rev-list", "--count", "HEAD"]);
+
+        // Not stopped for an edit yet
+        assert!(matches!(git::split_commit_reset(repo_path), Err(git::GitError::Validation(_))));
+
+        let file2 = run_git_output(&path, &["rev-parse", "HEAD~1"]);
+        let both = run_git_output(&path, &["rev-parse", "HEAD"]);
+        let plan = vec![
+            plan_entry(&file2, git::RebaseTodoAction::Pick),
+            plan_entry(&both, git::RebaseTodoAction::Edit),
+        ];
+        git::start_interactive_rebase(repo_path, "HEAD~2", plan).expect("rebase should stop for edit");
+        let repo = git::open_repo(&path).unwrap();
+        assert_eq!(git::get_interactive_rebase_state(&repo).unwrap().stop_reason, git::RebaseStopReason::Edit);
 
-        let resolved_content = "resolved content\n";
-        git::save_resolved_file(path.to_str().unwrap(), "conflict.txt", resolved_content)
-            .expect("should save");
+        git::split_commit_reset(repo_path).expect("split should reset the edited commit");
+        assert_eq!(run_git_output(&path, &["rev-parse", "HEAD"]), file2);
+        let status = run_git_output(&path, &["status", "--porcelain"]);
+        assert!(status.contains("?? a.txt") && status.contains("?? b.txt"), "{}", status);
 
-        let content = std::fs::read_to_string(path.join("conflict.txt")).unwrap();
-        assert_eq!(content, resolved_content);
+        run_git(&path, &["add", "a.txt"]);
+        run_git(&path, &["commit", "-m", "Add a"]);
+        // HEAD moved, so a second split would discard "Add a"
+        assert!(matches!(git::split_commit_reset(repo_path), Err(git::GitError::Validation(_))));
+        run_git(&path, &["add", "b.txt"]);
+        run_git(&path, &["commit", "-m", "Add b"]);
+
+        git::continue_interactive_rebase(repo_path, None).expect("rebase should finish");
+        assert!(!path.join(".git/rebase-merge").exists());
+        let count_after = run_git_output(&path, &["rev-list", "--count", "HEAD"]);
+        assert_eq!(count_after.parse::<usize>().unwrap(), count_before.parse::<usize>().unwrap() + 1);
+        assert_eq!(run_git_output(&path, &["log", "-2", "--format=%s"]), "Add b\nAdd a");
     }
 
     #[test]
-    fn test_mark_file_resolved() {
-        let (_tmp, path) = create_repo_with_conflict();
-
-        // Save resolved content first
-        std::fs::write(path.join("conflict.txt"), "resolved\n").unwrap();
+    fn test_rebase_state_lists_remaining_and_done() {
+        let (_tmp, path) = create_repo_with_history();
+        std::fs::write(path.join("file3.txt"), "three\n").unwrap();
+        run_git(&path, &["add", "file3.txt"]);
+        run_git(&path, &["commit", "-m", "Add file3"]);
+        let file1 = run_git_output(&path, &["rev-parse", "HEAD~2"]);
+        let file2 = run_git_output(&path, &["rev-parse", "HEAD~1"]);
+        let file3 = run_git_output(&path, &["rev-parse", "HEAD"]);
+
+        let mut fixup = plan_entry(&file3, git::RebaseTodoAction::Fixup);
+        fixup.exec_command = Some("true".to_string());
+        let plan = vec![
+            plan_entry(&file1, git::RebaseTodoAction::Edit),
+            plan_entry(&file2, git::RebaseTodoAction::Pick),
+            fixup,
+        ];
+        git::start_interactive_rebase(path.to_str().unwrap(), "HEAD~3", plan).expect("rebase should stop for edit");
 
         let repo = git::open_repo(&path).unwrap();
-        git::mark_file_resolved(&repo, "conflict.txt").expect("should mark resolved");
+        let state = git::get_interactive_rebase_state(&repo).unwrap();
+        assert_eq!(state.stop_reason, git::RebaseStopReason::Edit);
 
-        // Check it's staged
-        let status = git::get_status(&repo).unwrap();
-        assert!(status.staged.iter().any(|f| f.path == "conflict.txt"));
+        assert_eq!(state.done.len(), 1);
+        assert_eq!(state.done[0].action, git::RebaseTodoAction::Edit);
+        assert!(file1.starts_with(&state.done[0].commit_id));
+
+        let remaining: Vec<_> = state.remaining.iter().map(|e| e.action.clone()).collect();
+        assert_eq!(remaining, vec![git::RebaseTodoAction::Pick, git::RebaseTodoAction::Fixup]);
+        assert!(file2.starts_with(&state.remaining[0].commit_id));
+        assert!(file3.starts_with(&state.remaining[1].commit_id));
+        assert_eq!(state.remaining[1].exec_command.as_deref(), Some("true"));
+
+        git::continue_interactive_rebase(path.to_str().unwrap(), None).expect("rebase should finish");
+        let state = git::get_interactive_rebase_state(&repo).unwrap();
+        assert!(state.remaining.is_empty() && state.done.is_empty());
     }
 
     #[test]
-    fn test_abort_merge() {
-        let (_tmp, path) = create_repo_with_conflict();
+    fn test_rebase_onto_with_autostash() {
+        let (_tmp, path) = create_test_repo();
+        run_git(&path, &["checkout", "-b", "feature"]);
+        std::fs::write(path.join("feature.txt"), "feature\n").unwrap();
+        run_git(&path, &["add", "feature.txt"]);
+        run_git(&path, &["commit", "-m", "Feature work"]);
+        run_git(&path, &["checkout", "main"]);
+        std::fs::write(path.join("main.txt"), "main\n").unwrap();
+        run_git(&path, &["add", "main.txt"]);
+        run_git(&path, &["commit", "-m", "Main work"]);
+        run_git(&path, &["checkout", "feature"]);
+        let repo_path = path.to_str().unwrap();
+
+        std::fs::write(path.join("README.md"), "# Local edit\n").unwrap();
+        assert!(git::rebase_onto(repo_path, "main", false).is_err(), "dirty tree should block a plain rebase");
+
+        git::rebase_onto(repo_path, "main", true).expect("autostash rebase should succeed");
+        assert_eq!(
+            run_git_output(&path, &["log", "-2", "--format=%s"]),
+            "Feature work\nMain work"
+        );
+        assert_eq!(std::fs::read_to_string(path.join("README.md")).unwrap(), "# Local edit\n");
+        assert_eq!(run_git_output(&path, &["stash", "list"]), "");
+    }
 
-        git::abort_merge(path.to_str().unwrap()).expect("should abort merge");
+    #[test]
+    fn test_interactive_rebase_reorder_with_exec() {
+        let (_tmp, path) = create_repo_with_history();
+        let file1 = run_git_output(&path, &["rev-parse", "HEAD~1"]);
+        let file2 = run_git_output(&path, &["rev-parse", "HEAD"]);
+
+        let mut first = plan_entry(&file2[..7], git::RebaseTodoAction::Pick);
+        first.exec_command = Some("touch exec-ran.txt".to_string());
+        let plan = vec![first, plan_entry(&file1, git::RebaseTodoAction::Pick)];
+
+        git::start_interactive_rebase(path.to_str().unwrap(), "HEAD~2", plan).expect("rebase should succeed");
 
+        assert!(path.join("exec-ran.txt").exists(), "exec line should have run");
+        let log = run_git_output(&path, &["log", "--format=%s", "-3"]);
+        assert_eq!(log, "Add file1\nAdd file2\nInitial commit");
+    }
+
+    #[test]
+    fn test_force_abort_stale_rebase() {
+        let (_tmp, path) = create_repo_with_history();
+        let orig_head = run_git_output(&path, &["rev-parse", "HEAD~1"]);
+
+        // Leftovers of a crashed rebase: git can't abort without the `onto` file
+        let rebase_dir = path.join(".git").join("rebase-merge");
+        std::fs::create_dir(&rebase_dir).unwrap();
+        std::fs::write(rebase_dir.join("orig-head"), format!("{}\n", orig_head)).unwrap();
+        std::fs::write(rebase_dir.join("head-name"), "refs/heads/main\n").unwrap();
+        std::fs::write(path.join("file2.txt"), "half-rebased\n").unwrap();
+        let repo_path = path.to_str().unwrap();
+
+        assert!(git::abort_rebase(repo_path, false).is_err());
+        assert!(rebase_dir.exists());
+
+        let message = git::abort_rebase(repo_path, true).expect("force abort should recover");
+        assert!(message.starts_with("Warning:"), "{}", message);
+        assert!(!rebase_dir.exists());
+        assert_eq!(run_git_output(&path, &["rev-parse", "HEAD"]), orig_head);
+        assert_eq!(run_git_output(&path, &["symbolic-ref", "HEAD"]), "refs/heads/main");
         let repo = git::open_repo(&path).unwrap();
-        let status = git::get_merge_status(&repo).unwrap();
-        assert!(!status.in_merge);
+        assert_eq!(git::get_repository_state(&repo), "clean");
+        assert!(git::get_status(&repo).unwrap().unstaged.is_empty());
     }
 }
 
@@ -1079,6 +3233,82 @@ mod diff_metadata {
         assert!(diff.files[0].is_binary, "Should detect binary file");
     }
 
+    #[test]
+    fn test_binary_file_sizes() {
+        let (_tmp, path) = create_test_repo();
+
+        let original = vec![0x89, 0x50, 0x4E, 0x47, 0x00, 0x00, 0x01, 0x02, 0x03, 0x04, 0x05];
+        std::fs::write(path.join("image.png"), &original).unwrap();
+        run_git(&path, &["add", "image.png"]);
+
+        let repo = git::open_repo(&path).unwrap();
+        let added = git::get_working_diff(&repo, true).unwrap();
+        assert!(added.files[0].is_binary);
+        assert_eq!((added.files[0].old_size, added.files[0].new_size), (None, Some(11)));
+        run_git(&path, &["commit", "-m", "Add image"]);
+
+        let mut modified = original.clone();
+        modified.extend_from_slice(&[0x00; 9]);
+        std::fs::write(path.join("image.png"), &modified).unwrap();
+
+        // The new side is only in the working tree
+        let unstaged = git::get_working_diff(&repo, false).unwrap();
+        assert!(unstaged.files[0].is_binary);
+        assert_eq!((unstaged.files[0].old_size, unstaged.files[0].new_size), (Some(11), Some(20)));
+
+        run_git(&path, &["commit", "-am", "Grow image"]);
+        let commit_id = run_git_output(&path, &["rev-parse", "HEAD"]);
+        let diff = git::get_commit_diff(&repo, &commit_id).unwrap();
+        assert_eq!((diff.files[0].old_size, diff.files[0].new_size), (Some(11), Some(20)));
+
+        // Text files don't carry sizes
+        std::fs::write(path.join("README.md"), "# Changed\n").unwrap();
+        let text = git::get_working_diff(&repo, false).unwrap();
+        assert_eq!((text.files[0].old_size, text.files[0].new_size), (None, None));
+    }
+
+    #[test]
+    fn test_gitattributes_binary_text_file() {
+        let (_tmp, path) = create_test_repo();
+        std::fs::write(path.join(".gitattributes"), "*.dat binary\n").unwrap();
+        run_git(&path, &["add", ".gitattributes"]);
+        run_git(&path, &["commit", "-m", "Mark .dat as binary"]);
+
+        // Valid UTF-8, so only the attribute makes it binary
+        std::fs::write(path.join("table.dat"), "plain text\n").unwrap();
+        let repo = git::open_repo(&path).unwrap();
+
+        let untracked = git::get_working_diff(&repo, false).unwrap();
+        assert!(untracked.files[0].is_binary);
+        assert_eq!(untracked.files[0].additions, 0);
+        assert!(untracked.patch.contains("Binary files /dev/null and b/table.dat differ"));
+        assert!(!untracked.patch.contains("+plain text"));
+
+        run_git(&path, &["add", "table.dat"]);
+        run_git(&path, &["commit", "-m", "Add table"]);
+        std::fs::write(path.join("table.dat"), "changed text\n").unwrap();
+
+        let modified = git::get_working_diff(&repo, false).unwrap();
+        assert!(modified.files[0].is_binary);
+        assert!(modified.patch.contains("Binary files a/table.dat and b/table.dat differ"));
+
+        run_git(&path, &["commit", "-am", "Change table"]);
+        let commit_id = run_git_output(&path, &["rev-parse", "HEAD"]);
+        let committed = git::get_commit_diff(&repo, &commit_id).unwrap();
+        assert!(committed.files[0].is_binary);
+        assert!(!committed.patch.contains("+changed text"));
+
+        // `text` only affects line endings; it doesn't decide what diffs as binary
+        std::fs::write(path.join(".gitattributes"), "*.dat binary\n*.log -text\n*.bin text\n").unwrap();
+        std::fs::write(path.join("notes.log"), "plain log\n").unwrap();
+        std::fs::write(path.join("blob.bin"), b"a\0b\n").unwrap();
+        let diff = git::get_working_diff(&repo, false).unwrap();
+        let notes = diff.files.iter().find(|f| f.path == "notes.log").unwrap();
+        assert!(!notes.is_binary);
+        assert!(diff.patch.contains("+plain log"));
+        assert!(diff.patch.contains("Binary files /dev/null and b/blob.bin differ"));
+    }
+
     #[test]
     fn test_file_mode_metadata() {
         let (_tmp, path) = create_test_repo();
@@ -1183,7 +3413,7 @@ mod stash {
         let mut repo = git::open_repo(&path).unwrap();
 
         // Create a stash with a message
-        git::create_stash(&mut repo, Some("Test stash message")).expect("should create stash");
+        git::create_stash(&mut repo, Some("Test stash message"), &git::StashOptions::default()).expect("should create stash");
 
         // Verify the change is gone
         let repo = git::open_repo(&path).unwrap();
@@ -1208,7 +3438,7 @@ mod stash {
         let mut repo = git::open_repo(&path).unwrap();
 
         // Create stash without message (should auto-generate)
-        git::create_stash(&mut repo, None).expect("should create stash");
+        git::create_stash(&mut repo, None, &git::StashOptions::default()).expect("should create stash");
 
         let mut repo = git::open_repo(&path).unwrap();
         let stashes = git::list_stashes(&mut repo).unwrap();
@@ -1217,6 +3447,54 @@ mod stash {
         assert!(stashes[0].message.contains("WIP on main"), "auto-message should contain branch info");
     }
 
+    #[test]
+    fn test_list_stashes_parses_branch_and_base() {
+        let (_tmp, path) = create_repo_with_history();
+        let main_head = run_git_output(&path, &["rev-parse", "HEAD"]);
+
+        // Custom message on a feature branch
+        run_git(&path, &["checkout", "-b", "feature-x"]);
+        std::fs::write(path.join("README.md"), "feature change\n").unwrap();
+        run_git(&path, &["stash", "push", "-m", "my: custom message"]);
+
+        // Auto message on a detached HEAD
+        run_git(&path, &["checkout", "--detach", "HEAD~1"]);
+        let detached_head = run_git_output(&path, &["rev-parse", "HEAD"]);
+        std::fs::write(path.join("README.md"), "detached change\n").unwrap();
+        run_git(&path, &["stash"]);
+
+        let mut repo = git::open_repo(&path).unwrap();
+        let stashes = git::list_stashes(&mut repo).unwrap();
+        assert_eq!(stashes.len(), 2);
+
+        assert_eq!(stashes[0].branch, None);
+        assert_eq!(stashes[0].base_commit_id, detached_head);
+
+        assert_eq!(stashes[1].branch.as_deref(), Some("feature-x"));
+        assert_eq!(stashes[1].base_commit_id, main_head);
+    }
+
+    #[test]
+    fn test_stash_apply_conflict_error_kind() {
+        let (_tmp, path) = create_test_repo();
+
+        std::fs::write(path.join("README.md"), "stashed change\n").unwrap();
+        let mut repo = git::open_repo(&path).unwrap();
+        git::create_stash(&mut repo, None, &git::StashOptions::default()).unwrap();
+
+        // Conflicting local edit to the same file
+        std::fs::write(path.join("README.md"), "local change\n").unwrap();
+
+        let mut repo = git::open_repo(&path).unwrap();
+        let err = git::apply_stash(&mut repo, 0).expect_err("apply should conflict");
+        let app_err = diffy_lib::error::AppError::from(err);
+        assert_eq!(app_err.kind(), diffy_lib::error::ErrorKind::Conflict);
+
+        let json = serde_json::to_value(&app_err).unwrap();
+        assert_eq!(json["kind"], "conflict");
+        assert_eq!(json["code"], "errors.merge_conflict");
+    }
+
     #[test]
     fn test_pop_stash() {
         let (_tmp, path) = create_test_repo();
@@ -1225,7 +3503,7 @@ mod stash {
         std::fs::write(path.join("README.md"), "modified for stash\n").unwrap();
 
         let mut repo = git::open_repo(&path).unwrap();
-        git::create_stash(&mut repo, Some("to be popped")).expect("should create stash");
+        git::create_stash(&mut repo, Some("to be popped"), &git::StashOptions::default()).expect("should create stash");
 
         // Verify clean
         let repo = git::open_repo(&path).unwrap();
@@ -1256,7 +3534,7 @@ mod stash {
         std::fs::write(path.join("README.md"), "modified for apply\n").unwrap();
 
         let mut repo = git::open_repo(&path).unwrap();
-        git::create_stash(&mut repo, Some("to be applied")).expect("should create stash");
+        git::create_stash(&mut repo, Some("to be applied"), &git::StashOptions::default()).expect("should create stash");
 
         // Apply the stash (without removing)
         let mut repo = git::open_repo(&path).unwrap();
@@ -1281,7 +3559,7 @@ mod stash {
         std::fs::write(path.join("README.md"), "modified for drop\n").unwrap();
 
         let mut repo = git::open_repo(&path).unwrap();
-        git::create_stash(&mut repo, Some("to be dropped")).expect("should create stash");
+        git::create_stash(&mut repo, Some("to be dropped"), &git::StashOptions::default()).expect("should create stash");
 
         // Verify stash exists
         let mut repo = git::open_repo(&path).unwrap();
@@ -1290,7 +3568,7 @@ mod stash {
 
         // Drop the stash
         let mut repo = git::open_repo(&path).unwrap();
-        git::drop_stash(&mut repo, 0).expect("should drop stash");
+        git::drop_stash(&mut repo, 0, None).expect("should drop stash");
 
         // Verify stash is removed
         let mut repo = git::open_repo(&path).unwrap();
@@ -1302,6 +3580,70 @@ mod stash {
         assert!(status.unstaged.is_empty(), "working directory should still be clean");
     }
 
+    #[test]
+    fn test_drop_stash_rejects_stale_oid() {
+        let (_tmp, path) = create_test_repo();
+
+        for i in 0..3 {
+            std::fs::write(path.join("README.md"), format!("change {}\n", i)).unwrap();
+            let mut repo = git::open_repo(&path).unwrap();
+            git::create_stash(&mut repo, Some(&format!("stash {}", i)), &git::StashOptions::default()).unwrap();
+        }
+
+        let mut repo = git::open_repo(&path).unwrap();
+        let stashes = git::list_stashes(&mut repo).unwrap();
+        assert_eq!(stashes.len(), 3);
+
+        git::drop_stash(&mut repo, 0, Some(&stashes[0].oid)).expect("should drop matching stash");
+
+        // Index 1 now refers to what used to be index 2
+        let err = git::drop_stash(&mut repo, 1, Some(&stashes[1].oid)).unwrap_err();
+        assert!(matches!(err, git::GitError::Validation(_)), "got {:?}", err);
+        assert_eq!(git::list_stashes(&mut repo).unwrap().len(), 2, "nothing should be dropped");
+
+        git::drop_stash(&mut repo, 1, Some(&stashes[2].oid)).expect("should drop with current oid");
+        let remaining = git::list_stashes(&mut repo).unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].oid, stashes[1].oid);
+
+        let err = git::drop_stash(&mut repo, 5, Some(&stashes[1].oid)).unwrap_err();
+        assert!(matches!(err, git::GitError::Validation(_)));
+    }
+
+    #[test]
+    fn test_stash_operations_by_oid_survive_renumbering() {
+        let (_tmp, path) = create_test_repo();
+
+        std::fs::write(path.join("a.txt"), "a\n").unwrap();
+        run_git(&path, &["add", "a.txt"]);
+        let mut repo = git::open_repo(&path).unwrap();
+        git::create_stash(&mut repo, Some("first"), &git::StashOptions::default()).unwrap();
+        let first_oid = git::list_stashes(&mut repo).unwrap()[0].oid.clone();
+
+        // A new stash pushes "first" from index 0 to index 1
+        std::fs::write(path.join("README.md"), "second\n").unwrap();
+        git::create_stash(&mut repo, Some("second"), &git::StashOptions::default()).unwrap();
+        let second_oid = git::list_stashes(&mut repo).unwrap()[0].oid.clone();
+
+        git::pop_stash_by_oid(&mut repo, &first_oid).expect("should pop by oid");
+        assert!(path.join("a.txt").exists(), "popped the first stash's changes");
+        let remaining = git::list_stashes(&mut repo).unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].oid, second_oid);
+        run_git(&path, &["add", "a.txt"]);
+        run_git(&path, &["commit", "-m", "Add a"]);
+
+        git::apply_stash_by_oid(&mut repo, &second_oid).expect("should apply by oid");
+        assert_eq!(std::fs::read_to_string(path.join("README.md")).unwrap(), "second\n");
+        assert_eq!(git::list_stashes(&mut repo).unwrap().len(), 1, "apply keeps the stash");
+
+        git::drop_stash_by_oid(&mut repo, &second_oid).expect("should drop by oid");
+        assert!(git::list_stashes(&mut repo).unwrap().is_empty());
+
+        let err = git::drop_stash_by_oid(&mut repo, &second_oid).unwrap_err();
+        assert!(matches!(err, git::GitError::Validation(_)));
+    }
+
     #[test]
     fn test_multiple_stashes() {
         let (_tmp, path) = create_test_repo();
@@ -1309,12 +3651,12 @@ mod stash {
         // Create and stash first change (modify existing tracked file)
         std::fs::write(path.join("README.md"), "first modification\n").unwrap();
         let mut repo = git::open_repo(&path).unwrap();
-        git::create_stash(&mut repo, Some("First stash")).expect("should create first stash");
+        git::create_stash(&mut repo, Some("First stash"), &git::StashOptions::default()).expect("should create first stash");
 
         // Create and stash second change (modify existing tracked file again)
         std::fs::write(path.join("README.md"), "second modification\n").unwrap();
         let mut repo = git::open_repo(&path).unwrap();
-        git::create_stash(&mut repo, Some("Second stash")).expect("should create second stash");
+        git::create_stash(&mut repo, Some("Second stash"), &git::StashOptions::default()).expect("should create second stash");
 
         // List stashes
         let mut repo = git::open_repo(&path).unwrap();
@@ -1343,7 +3685,7 @@ mod stash {
         assert_eq!(status.staged.len(), 1);
 
         // Create stash
-        git::create_stash(&mut repo, Some("includes staged")).expect("should create stash");
+        git::create_stash(&mut repo, Some("includes staged"), &git::StashOptions::default()).expect("should create stash");
 
         // Verify clean
         let repo = git::open_repo(&path).unwrap();
@@ -1351,6 +3693,198 @@ mod stash {
         assert!(status.staged.is_empty(), "staged changes should be stashed");
         assert!(status.unstaged.is_empty());
     }
+
+    #[test]
+    fn test_stash_keep_index() {
+        let (_tmp, path) = create_test_repo();
+
+        std::fs::write(path.join("staged.txt"), "staged content\n").unwrap();
+        run_git(&path, &["add", "staged.txt"]);
+        std::fs::write(path.join("README.md"), "# Modified\n").unwrap();
+
+        let mut repo = git::open_repo(&path).unwrap();
+        let options = git::StashOptions {
+            keep_index: true,
+            ..Default::default()
+        };
+        git::create_stash(&mut repo, Some("keep index"), &options).expect("should create stash");
+
+        let repo = git::open_repo(&path).unwrap();
+        let status = git::get_status(&repo).unwrap();
+        assert_eq!(status.staged.len(), 1, "staged changes should remain staged");
+        assert_eq!(status.staged[0].path, "staged.txt");
+        assert!(status.unstaged.is_empty(), "unstaged changes should be stashed");
+    }
+
+    #[test]
+    fn test_stash_include_untracked() {
+        let (_tmp, path) = create_test_repo();
+
+        std::fs::write(path.join("untracked.txt"), "untracked content\n").unwrap();
+
+        let mut repo = git::open_repo(&path).unwrap();
+        let options = git::StashOptions {
+            include_untracked: true,
+            ..Default::default()
+        };
+        git::create_stash(&mut repo, Some("with untracked"), &options).expect("should create stash");
+        assert!(!path.join("untracked.txt").exists(), "untracked file should be stashed");
+
+        git::pop_stash(&mut repo, 0).expect("should pop stash");
+        let content = std::fs::read_to_string(path.join("untracked.txt")).unwrap();
+        assert_eq!(content, "untracked content\n");
+    }
+
+    #[test]
+    fn test_get_stash_diff() {
+        let (_tmp, path) = create_test_repo();
+
+        std::fs::write(path.join("README.md"), "# Test Repo\nstashed line\n").unwrap();
+
+        let mut repo = git::open_repo(&path).unwrap();
+        git::create_stash(&mut repo, Some("to preview"), &git::StashOptions::default()).expect("should create stash");
+
+        let diff = git::get_stash_diff(&repo, 0).expect("should get stash diff");
+        assert_eq!(diff.files.len(), 1);
+        assert_eq!(diff.files[0].path, "README.md");
+        assert!(diff.patch.contains("+stashed line"));
+    }
+
+    #[test]
+    fn test_get_stash_diff_missing_entry() {
+        let (_tmp, path) = create_test_repo();
+
+        let repo = git::open_repo(&path).unwrap();
+        let result = git::get_stash_diff(&repo, 0);
+        assert!(matches!(result, Err(git::GitError::Validation(_))));
+    }
+
+    #[test]
+    fn test_get_stash_diff_includes_untracked() {
+        let (_tmp, path) = create_test_repo();
+
+        std::fs::write(path.join("README.md"), "# Modified\n").unwrap();
+        std::fs::write(path.join("untracked.txt"), "untracked content\n").unwrap();
+
+        let mut repo = git::open_repo(&path).unwrap();
+        let options = git::StashOptions {
+            include_untracked: true,
+            ..Default::default()
+        };
+        git::create_stash(&mut repo, Some("with untracked"), &options).expect("should create stash");
+
+        let diff = git::get_stash_diff(&repo, 0).expect("should get stash diff");
+        let paths: Vec<&str> = diff.files.iter().map(|f| f.path.as_str()).collect();
+        assert!(paths.contains(&"README.md"));
+        assert!(paths.contains(&"untracked.txt"));
+        assert!(diff.patch.contains("+untracked content"));
+    }
+
+    #[test]
+    fn test_stash_branch() {
+        let (_tmp, path) = create_test_repo();
+        let base = run_git_output(&path, &["rev-parse", "HEAD"]);
+
+        std::fs::write(path.join("README.md"), "# Stashed change\n").unwrap();
+        let mut repo = git::open_repo(&path).unwrap();
+        git::create_stash(&mut repo, Some("for branch"), &git::StashOptions::default()).expect("should create stash");
+
+        // Move main forward so the stash base differs from the tip
+        std::fs::write(path.join("other.txt"), "other\n").unwrap();
+        run_git(&path, &["add", "other.txt"]);
+        run_git(&path, &["commit", "-m", "Move main"]);
+
+        git::stash_branch(path.to_str().unwrap(), 0, "from-stash").expect("should create branch from stash");
+
+        assert_eq!(run_git_output(&path, &["rev-parse", "--abbrev-ref", "HEAD"]), "from-stash");
+        assert_eq!(run_git_output(&path, &["rev-parse", "HEAD"]), base);
+        let content = std::fs::read_to_string(path.join("README.md")).unwrap();
+        assert_eq!(content, "# Stashed change\n");
+
+        let mut repo = git::open_repo(&path).unwrap();
+        assert!(git::list_stashes(&mut repo).unwrap().is_empty(), "stash should be dropped");
+    }
+
+    #[test]
+    fn test_stash_branch_existing_name_fails() {
+        let (_tmp, path) = create_test_repo();
+
+        std::fs::write(path.join("README.md"), "# Stashed change\n").unwrap();
+        let mut repo = git::open_repo(&path).unwrap();
+        git::create_stash(&mut repo, None, &git::StashOptions::default()).unwrap();
+
+        let result = git::stash_branch(path.to_str().unwrap(), 0, "main");
+        assert!(matches!(result, Err(git::GitError::Validation(_))));
+    }
+
+    #[test]
+    fn test_stash_staged_only() {
+        let (_tmp, path) = create_test_repo();
+
+        std::fs::write(path.join("staged.txt"), "staged content\n").unwrap();
+        run_git(&path, &["add", "staged.txt"]);
+        std::fs::write(path.join("README.md"), "# Modified\n").unwrap();
+
+        let mut repo = git::open_repo(&path).unwrap();
+        let options = git::StashOptions {
+            staged_only: true,
+            ..Default::default()
+        };
+        git::create_stash(&mut repo, Some("staged only"), &options).expect("should create stash");
+
+        let repo = git::open_repo(&path).unwrap();
+        let status = git::get_status(&repo).unwrap();
+        assert!(status.staged.is_empty(), "staged changes should be stashed");
+        assert_eq!(status.unstaged.len(), 1, "unstaged changes should remain");
+        assert_eq!(status.unstaged[0].path, "README.md");
+        assert!(!path.join("staged.txt").exists());
+    }
+
+    #[test]
+    fn test_stash_staged_only_rejects_include_untracked() {
+        let (_tmp, path) = create_test_repo();
+
+        std::fs::write(path.join("staged.txt"), "staged content\n").unwrap();
+        run_git(&path, &["add", "staged.txt"]);
+
+        let mut repo = git::open_repo(&path).unwrap();
+        let options = git::StashOptions {
+            staged_only: true,
+            include_untracked: true,
+            ..Default::default()
+        };
+        let result = git::create_stash(&mut repo, None, &options);
+        assert!(matches!(result, Err(git::GitError::Validation(_))));
+    }
+
+    #[test]
+    fn test_stash_specific_paths() {
+        let (_tmp, path) = create_test_repo();
+
+        std::fs::write(path.join("a.txt"), "a\n").unwrap();
+        std::fs::write(path.join("b.txt"), "b\n").unwrap();
+        run_git(&path, &["add", "a.txt", "b.txt"]);
+        run_git(&path, &["commit", "-m", "Add a and b"]);
+        std::fs::write(path.join("a.txt"), "a modified\n").unwrap();
+        std::fs::write(path.join("b.txt"), "b modified\n").unwrap();
+
+        let mut repo = git::open_repo(&path).unwrap();
+        let options = git::StashOptions {
+            paths: Some(vec!["a.txt".to_string()]),
+            ..Default::default()
+        };
+        git::create_stash(&mut repo, Some("only a"), &options).expect("should create stash");
+
+        let repo = git::open_repo(&path).unwrap();
+        let status = git::get_status(&repo).unwrap();
+        assert_eq!(status.unstaged.len(), 1);
+        assert_eq!(status.unstaged[0].path, "b.txt");
+
+        let mut repo = git::open_repo(&path).unwrap();
+        let stashes = git::list_stashes(&mut repo).unwrap();
+        assert_eq!(stashes.len(), 1);
+        assert!(stashes[0].message.contains("only a"));
+    }
 }
 
 // =============================================================================
@@ -1422,7 +3956,7 @@ mod edge_cases {
         assert!(status.staged.is_empty());
 
         // Getting commits should return empty
-        let commits = git::get_commits(&repo, None, 10, 0);
+        let commits = git::get_commits(&repo, None, 10, 0, None, None);
         // This might fail or return empty depending on implementation
         match commits {
             Ok(c) => assert!(c.is_empty()),
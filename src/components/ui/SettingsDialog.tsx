@@ -219,7 +219,7 @@ export function SettingsDialog() {
                         </select>
                         <div className="text-xs text-text-muted leading-relaxed flex-1">
                           {aiReviewReviewerId === "coderabbit-cli" ? (
-                            <span>Working changes only (v1).</span>
+                            <span>Supports commit and compare review.</span>
                           ) : (
                             <span>Supports skills and commit review.</span>
                           )}
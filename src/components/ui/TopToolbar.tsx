@@ -53,10 +53,17 @@ export function TopToolbar() {
     setIsPulling(true);
     try {
       const result = await gitPull(repository.path);
-      toast.success(
-        "Pull complete",
-        result || "Successfully pulled from remote",
-      );
+      if (result.hasConflicts) {
+        toast.warning(
+          "Pull has conflicts",
+          `${result.conflictingFiles.length} file(s) need to be resolved`,
+        );
+      } else {
+        toast.success(
+          "Pull complete",
+          result.output || "Successfully pulled from remote",
+        );
+      }
       queryClient.invalidateQueries({ queryKey: ["branches"] });
       queryClient.invalidateQueries({ queryKey: ["commits"] });
       queryClient.invalidateQueries({ queryKey: ["status"] });
@@ -25,6 +25,7 @@ import {
 } from "../../../lib/tauri";
 import {
   getErrorMessage,
+  isNoChangesError,
   isValidationError,
   normalizeError,
 } from "../../../lib/errors";
@@ -645,8 +646,8 @@ const CodeRabbitIssueCard = memo(function CodeRabbitIssueCard({
         aiAgentPrompt: issue.aiAgentPrompt,
       });
 
-      setFixResult({ success: true, message: result });
-      toast.success("Fix applied", result);
+      setFixResult({ success: true, message: result.message });
+      toast.success("Fix applied", result.message);
 
       // Invalidate queries to refresh file state
       queryClient.invalidateQueries({ queryKey: ["working-diff-staged"] });
@@ -1039,9 +1040,6 @@ export function AIReviewContent() {
 
   // For CodeRabbit, skills are not supported
   const supportsSkills = aiReviewReviewerId === "claude-cli";
-  // For CodeRabbit, commit review and compare review are not supported
-  const supportsCommitReview = aiReviewReviewerId === "claude-cli";
-  const supportsCompareReview = aiReviewReviewerId === "claude-cli";
 
   // Reset to initial state (to select different skills)
   const handleNewReview = useCallback(() => {
@@ -1214,7 +1212,7 @@ export function AIReviewContent() {
       // For compare mode, pass the base/head refs instead of commit
       const commitId = isCompareMode
         ? undefined
-        : viewMode === "commit" && supportsCommitReview
+        : viewMode === "commit"
           ? (selectedCommit ?? undefined)
           : undefined;
       // Only pass skills for Claude CLI
@@ -1245,7 +1243,7 @@ export function AIReviewContent() {
       }
     } catch (error) {
       const normalized = normalizeError(error);
-      if (isValidationError(error)) {
+      if (isValidationError(error) || isNoChangesError(error)) {
         // This is a "not running" state (e.g., no changes to review)
         setNotRunningReason(normalized.message);
         setAIReviewError(null);
@@ -1262,7 +1260,6 @@ export function AIReviewContent() {
     selectedCommit,
     selectedSkillIds,
     aiReviewReviewerId,
-    supportsCommitReview,
     aiReviewLoading,
     setAIReview,
     setAIReviewLoading,
@@ -1291,7 +1288,7 @@ export function AIReviewContent() {
           }));
 
         const result = await fixAIReviewIssues(repository.path, issues);
-        setFixResult({ success: true, message: result });
+        setFixResult({ success: true, message: result.message });
 
         // Show success toast
         toast.success(
@@ -1363,12 +1360,8 @@ export function AIReviewContent() {
     !aiReviewError &&
     !notRunningReason
   ) {
-    // Claude can review commits and compare diffs, CodeRabbit always reviews working changes
-    const isCommitMode =
-      viewMode === "commit" && selectedCommit && supportsCommitReview;
-    
-    // Check if compare review is unavailable (CodeRabbit doesn't support it)
-    const compareUnavailable = isCompareMode && !supportsCompareReview;
+    // Review the selected commit in commit mode, otherwise working changes
+    const isCommitMode = viewMode === "commit" && selectedCommit;
 
     return (
       <div className="flex flex-col items-center justify-center h-full p-6 text-center">
@@ -1402,27 +1395,6 @@ export function AIReviewContent() {
           </div>
         )}
         
-        {/* Compare mode not supported by CodeRabbit */}
-        {compareUnavailable && (
-          <div className="w-full max-w-sm mb-4 p-3 bg-accent-yellow/10 border border-accent-yellow/30 rounded-lg">
-            <div className="flex items-start gap-2">
-              <Warning
-                size={16}
-                weight="fill"
-                className="text-accent-yellow shrink-0 mt-0.5"
-              />
-              <div className="text-left">
-                <p className="text-sm font-medium text-text-primary">
-                  Compare review not supported
-                </p>
-                <p className="text-xs text-text-muted mt-1">
-                  CodeRabbit only supports working changes. Select Claude CLI in Settings to review compare diffs.
-                </p>
-              </div>
-            </div>
-          </div>
-        )}
-
         {/* What's being reviewed - prominent indicator */}
         <div className="w-full max-w-sm mb-4">
           {isCompareMode ? (
@@ -1483,14 +1455,12 @@ export function AIReviewContent() {
 
         <button
           onClick={handleGenerateReview}
-          disabled={!repository || !selectedCLIAvailable || compareUnavailable}
+          disabled={!repository || !selectedCLIAvailable}
           className="px-4 py-2 bg-accent-purple text-white rounded-lg font-medium text-sm hover:bg-accent-purple/90 disabled:bg-bg-tertiary disabled:text-text-muted disabled:cursor-not-allowed transition-colors flex items-center gap-2"
           title={
             !selectedCLIAvailable
               ? `${reviewerDisplayName} is not installed`
-              : compareUnavailable
-                ? "Select Claude CLI to review compare diffs"
-                : undefined
+              : undefined
           }
         >
           <Sparkle size={16} weight="bold" />
@@ -3,6 +3,7 @@ import {
   CheckCircle,
   GitBranch,
   ArrowsClockwise,
+  LinkBreak,
 } from '@phosphor-icons/react';
 import type { RefInfo, BranchInfo } from '../../../types/git';
 
@@ -16,8 +17,13 @@ function getBranchInfo(refName: string, branches: BranchInfo[]): BranchInfo | un
   return branches.find((b) => b.name === refName);
 }
 
-function getRefColor(ref: RefInfo): string {
+function isUpstreamGone(ref: RefInfo, branch: BranchInfo | undefined): boolean {
+  return ref.type === 'branch' && branch?.upstreamState === 'gone';
+}
+
+function getRefColor(ref: RefInfo, branch: BranchInfo | undefined): string {
   if (ref.isHead) return 'bg-accent-green/20 text-accent-green border-accent-green/40';
+  if (isUpstreamGone(ref, branch)) return 'bg-accent-red/10 text-accent-red border-accent-red/40 border-dashed';
   if (ref.type === 'remote') return 'bg-accent-purple/20 text-accent-purple border-accent-purple/40';
   if (ref.type === 'tag') return 'bg-accent-yellow/20 text-accent-yellow border-accent-yellow/40';
   return 'bg-accent-blue/20 text-accent-blue border-accent-blue/40';
@@ -27,7 +33,10 @@ function getRefIcon(ref: RefInfo, branch: BranchInfo | undefined) {
   if (ref.isHead) {
     return <CheckCircle size={12} weight="fill" className="shrink-0" />;
   }
-  if (ref.type === 'branch' && branch?.upstream) {
+  if (isUpstreamGone(ref, branch)) {
+    return <LinkBreak size={12} weight="bold" className="shrink-0" />;
+  }
+  if (ref.type === 'branch' && branch?.upstreamState === 'tracking') {
     return <ArrowsClockwise size={12} weight="bold" className="shrink-0" />;
   }
   if (ref.type === 'branch') {
@@ -53,7 +62,7 @@ const RefBadge = memo(function RefBadge({
   branch: BranchInfo | undefined;
   maxWidth: number;
 }) {
-  const colorClass = getRefColor(ref);
+  const colorClass = getRefColor(ref, branch);
   const icon = getRefIcon(ref, branch);
   const displayName = getDisplayName(ref);
 
@@ -61,7 +70,11 @@ const RefBadge = memo(function RefBadge({
     <div
       className={`inline-flex items-center gap-1 px-1.5 py-0.5 rounded-sm text-xs font-medium border ${colorClass}`}
       style={{ maxWidth }}
-      title={ref.name}
+      title={
+        isUpstreamGone(ref, branch)
+          ? `${ref.name} (upstream ${branch?.upstream} is gone)`
+          : ref.name
+      }
     >
       {icon}
       <span className="truncate">{displayName}</span>
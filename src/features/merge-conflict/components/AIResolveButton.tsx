@@ -1,9 +1,11 @@
 import { Sparkle, Warning } from '@phosphor-icons/react';
 import { useMergeConflictStore } from '../../../stores/merge-conflict-store';
-import { aiResolveConflict } from '../../../lib/tauri';
+import { useTabsStore } from '../../../stores/tabs-store';
+import { aiResolveConflict, getConflictVersions } from '../../../lib/tauri';
 import { Button } from '../../../components/ui/Button';
 
 export function AIResolveButton() {
+  const { repository } = useTabsStore();
   const {
     currentFile,
     notes,
@@ -22,11 +24,22 @@ export function AIResolveButton() {
     setAIError(null);
 
     try {
+      // The index's base stage gives the model the common ancestor; 2-way without it
+      const base = repository
+        ? await getConflictVersions(repository.path, currentFile.filePath)
+            .then((versions) => versions.base ?? undefined)
+            .catch(() => undefined)
+        : undefined;
+
       const response = await aiResolveConflict(
         currentFile.filePath,
         currentFile.oursFull,
         currentFile.theirsFull,
-        notes || undefined
+        notes || undefined,
+        undefined,
+        repository?.path,
+        false,
+        base
       );
 
       // Set the resolved content (user can still edit before saving)
@@ -15,6 +15,8 @@ export interface ConflictRegion {
   endLine: number;
   oursContent: string;
   theirsContent: string;
+  /** Common ancestor section; only present for diff3/zdiff3 conflict markers */
+  baseContent: string | null;
 }
 
 export interface FileConflictInfo {
@@ -25,9 +27,24 @@ export interface FileConflictInfo {
   originalContent: string;
 }
 
+/** Side that wins when a conflicted file is resolved wholesale */
+export type ConflictSide = "ours" | "theirs";
+
+/** Index stages of a conflicted file; null when a side doesn't exist */
+export interface ConflictVersions {
+  filePath: string;
+  base: string | null;
+  ours: string | null;
+  theirs: string | null;
+}
+
 export interface AIResolveConflictResponse {
   resolved: string;
   explanation: string;
+  /** True when the resolution was written to disk and staged (autoApply) */
+  applied: boolean;
+  /** Conflict regions in the file on disk that the resolution replaces; empty without repoPath */
+  resolvedRegions: ConflictRegion[];
 }
 
 // Interactive rebase types
@@ -53,6 +70,8 @@ export interface InteractiveRebasePlanEntry {
   commitId: string;
   action: RebaseTodoAction;
   newMessage?: string;
+  /** Shell command to run (as an `exec` line) after this entry is applied */
+  execCommand?: string;
 }
 
 export type RebaseStopReason =
@@ -73,4 +92,6 @@ export interface InteractiveRebaseState {
   conflictingFiles: string[];
   ontoRef: string | null;
   currentMessage: string | null;
+  remaining: InteractiveRebasePlanEntry[];
+  done: InteractiveRebasePlanEntry[];
 }
@@ -25,12 +25,13 @@ import {
   generateCommitMessage,
   listStashes,
   createStash,
-  applyStash,
-  popStash,
-  dropStash,
+  applyStashByOid,
+  popStashByOid,
+  dropStashByOid,
   normalizeError,
   getErrorMessage,
 } from "../../../lib/tauri";
+import { isNoStagedChangesError } from "../../../lib/errors";
 import { FileContextMenu, StatusIcon, Input } from "../../../components/ui";
 import { useTabsStore, useActiveTabState } from "../../../stores/tabs-store";
 import { useUIStore } from "../../../stores/ui-store";
@@ -273,6 +274,7 @@ export function StagingSidebar() {
   const [stashesExpanded, setStashesExpanded] = useState(true);
   const [isCommitting, setIsCommitting] = useState(false);
   const [isGenerating, setIsGenerating] = useState(false);
+  const [showStageFirstHint, setShowStageFirstHint] = useState(false);
   const [isStashing, setIsStashing] = useState(false);
   const [showStashInput, setShowStashInput] = useState(false);
   const [stashMessage, setStashMessage] = useState("");
@@ -414,8 +416,8 @@ export function StagingSidebar() {
   });
 
   const applyStashMutation = useMutation({
-    mutationFn: (stashIndex: number) =>
-      applyStash(repository!.path, stashIndex),
+    mutationFn: (stashOid: string) =>
+      applyStashByOid(repository!.path, stashOid),
     onSuccess: invalidateAfterStash,
     onError: (error) => {
       alert(getErrorMessage(normalizeError(error)));
@@ -423,7 +425,7 @@ export function StagingSidebar() {
   });
 
   const popStashMutation = useMutation({
-    mutationFn: (stashIndex: number) => popStash(repository!.path, stashIndex),
+    mutationFn: (stashOid: string) => popStashByOid(repository!.path, stashOid),
     onSuccess: invalidateAfterStash,
     onError: (error) => {
       alert(getErrorMessage(normalizeError(error)));
@@ -431,7 +433,8 @@ export function StagingSidebar() {
   });
 
   const dropStashMutation = useMutation({
-    mutationFn: (stashIndex: number) => dropStash(repository!.path, stashIndex),
+    mutationFn: (stashOid: string) =>
+      dropStashByOid(repository!.path, stashOid),
     onSuccess: () => {
       queryClient.invalidateQueries({
         queryKey: ["stashes", repoPath],
@@ -509,6 +512,7 @@ export function StagingSidebar() {
     if (!repository || stagedFiles.length === 0 || isGenerating) return;
 
     setIsGenerating(true);
+    setShowStageFirstHint(false);
     try {
       const message = await generateCommitMessage(repository.path);
       // Split on first newline to separate title from body
@@ -520,7 +524,12 @@ export function StagingSidebar() {
         setCommitDescription(body);
       }
     } catch (error) {
-      console.error("Failed to generate commit message:", error);
+      if (isNoStagedChangesError(error)) {
+        // The staged list was stale; prompt to stage instead of failing silently
+        setShowStageFirstHint(true);
+      } else {
+        console.error("Failed to generate commit message:", error);
+      }
     } finally {
       setIsGenerating(false);
     }
@@ -632,13 +641,9 @@ export function StagingSidebar() {
                       <StashRow
                         key={stash.oid}
                         stash={stash}
-                        onApply={() =>
-                          applyStashMutation.mutate(stash.stashIndex)
-                        }
-                        onPop={() => popStashMutation.mutate(stash.stashIndex)}
-                        onDrop={() =>
-                          dropStashMutation.mutate(stash.stashIndex)
-                        }
+                        onApply={() => applyStashMutation.mutate(stash.oid)}
+                        onPop={() => popStashMutation.mutate(stash.oid)}
+                        onDrop={() => dropStashMutation.mutate(stash.oid)}
                         fontSize={panelFontSize}
                         isLoading={isStashMutating}
                       />
@@ -777,6 +782,35 @@ export function StagingSidebar() {
               </button>
             </div>
 
+            {showStageFirstHint && (
+              <div
+                className="flex items-center justify-between gap-2 px-2 py-1.5 bg-bg-tertiary border border-border-primary rounded-sm text-text-muted shrink-0"
+                style={{ fontSize: `${panelFontSize}px` }}
+              >
+                <span>Stage some files first to generate a message</span>
+                <div className="flex items-center gap-1">
+                  {unstagedFiles.length > 0 && (
+                    <button
+                      onClick={() => {
+                        handleStageAll();
+                        setShowStageFirstHint(false);
+                      }}
+                      className="px-2 py-0.5 bg-accent-blue text-white rounded-sm text-xs hover:bg-accent-blue/90 cursor-pointer transition-colors"
+                    >
+                      Stage all
+                    </button>
+                  )}
+                  <button
+                    onClick={() => setShowStageFirstHint(false)}
+                    className="p-0.5 text-text-muted hover:text-text-primary cursor-pointer"
+                    title="Dismiss"
+                  >
+                    <X size={12} weight="bold" />
+                  </button>
+                </div>
+              </div>
+            )}
+
             {/* Description */}
             <textarea
               placeholder="Description (optional)"
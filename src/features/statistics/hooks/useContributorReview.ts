@@ -5,7 +5,6 @@ import {
   generateContributorReview,
   type ContributorReviewData,
 } from "../../../lib/tauri";
-import type { CommitInfo } from "../../../types/git";
 
 interface UseContributorReviewOptions {
   repoPath: string;
@@ -79,7 +78,7 @@ export function useContributorReview({
   });
 
   const generateReview = useCallback(async () => {
-    if (!allCommits) {
+    if (!contributorEmail && !allCommits) {
       setError(new Error("No commit data available"));
       return;
     }
@@ -88,47 +87,34 @@ export function useContributorReview({
     setError(null);
 
     try {
-      let filteredCommits: CommitInfo[];
-      let reviewName: string;
-      let reviewEmail: string;
-
       if (contributorEmail) {
-        // Individual contributor review
-        filteredCommits = allCommits.filter(
-          (c) => c.authorEmail === contributorEmail && c.time >= since,
-        );
-
-        if (filteredCommits.length === 0) {
-          setError(
-            new Error(
-              "No commits found for this contributor in the selected time range",
-            ),
-          );
-          setIsGenerating(false);
-          return;
-        }
-
-        reviewName = filteredCommits[0].authorName;
-        reviewEmail = contributorEmail;
-      } else {
-        // Team review - all contributors
-        filteredCommits = allCommits.filter((c) => c.time >= since);
-
-        if (filteredCommits.length === 0) {
-          setError(new Error("No commits found in the selected time range"));
-          setIsGenerating(false);
-          return;
-        }
-
-        // Create a team name based on the number of contributors
-        const uniqueContributors = new Set(
-          filteredCommits.map((c) => c.authorEmail),
-        );
-        const contributorCount = uniqueContributors.size;
-        reviewName = `Team (${contributorCount} contributor${contributorCount !== 1 ? "s" : ""})`;
-        reviewEmail = "team@contributors";
+        // Individual contributor review - stats are aggregated by the backend
+        const result = await generateContributorReview({
+          contributorEmail,
+          timeRangeLabel: getTimeRangeLabel(timeRangeMonths),
+          repoPath,
+          since,
+        });
+        setReview(result);
+        return;
+      }
+
+      // Team review - all contributors
+      const filteredCommits = (allCommits ?? []).filter((c) => c.time >= since);
+
+      if (filteredCommits.length === 0) {
+        setError(new Error("No commits found in the selected time range"));
+        return;
       }
 
+      // Create a team name based on the number of contributors
+      const uniqueContributors = new Set(
+        filteredCommits.map((c) => c.authorEmail),
+      );
+      const contributorCount = uniqueContributors.size;
+      const reviewName = `Team (${contributorCount} contributor${contributorCount !== 1 ? "s" : ""})`;
+      const reviewEmail = "team@contributors";
+
       // Aggregate stats
       const totalCommits = filteredCommits.length;
       const totalFilesChanged = filteredCommits.reduce(
@@ -145,13 +131,10 @@ export function useContributorReview({
       );
 
       // Get commit summaries (first line of commit message)
-      // For team reviews, limit to most recent commits to avoid overwhelming the AI
-      const maxSummaries = contributorEmail ? 100 : 50;
+      // Limit to most recent commits to avoid overwhelming the AI
       const commitSummaries = filteredCommits
-        .slice(0, maxSummaries)
-        .map((c) =>
-          contributorEmail ? c.summary : `[${c.authorName}] ${c.summary}`,
-        );
+        .slice(0, 50)
+        .map((c) => `[${c.authorName}] ${c.summary}`);
 
       // Call AI
       const result = await generateContributorReview({
@@ -171,7 +154,7 @@ export function useContributorReview({
     } finally {
       setIsGenerating(false);
     }
-  }, [contributorEmail, allCommits, since, timeRangeMonths]);
+  }, [contributorEmail, allCommits, since, timeRangeMonths, repoPath]);
 
   const clearReview = useCallback(() => {
     setReview(null);
@@ -37,9 +37,38 @@ export function isAuthError(error: unknown): boolean {
   return hasErrorCode(error, 'errors.git_auth');
 }
 
+/**
+ * Check if an error was caused by conflicts (merge, stash apply, checkout).
+ */
+export function isConflictError(error: unknown): boolean {
+  return isAppError(error) && error.kind === 'conflict';
+}
+
 /**
  * Check if an error is a validation error.
  */
 export function isValidationError(error: unknown): boolean {
   return hasErrorCode(error, 'errors.validation');
 }
+
+/**
+ * Check if an error means there is nothing staged (e.g. for commit message generation).
+ */
+export function isNoStagedChangesError(error: unknown): boolean {
+  return hasErrorCode(error, 'errors.no_staged_changes');
+}
+
+/**
+ * Check if an error means there are no changes to operate on (e.g. nothing to review).
+ */
+export function isNoChangesError(error: unknown): boolean {
+  return hasErrorCode(error, 'errors.no_changes');
+}
+
+/**
+ * Check if an error means local modifications block the operation (offer to stash and retry).
+ * The modified files are in `error.paths`.
+ */
+export function isDirtyWorkingTreeError(error: unknown): boolean {
+  return hasErrorCode(error, 'errors.dirty_working_tree');
+}
@@ -8,6 +8,7 @@ import type {
   ChangelogCommit,
   StatusInfo,
   UnifiedDiff,
+  DiffStats,
   FileDiff,
   AIReviewData,
   AIReviewReviewerId,
@@ -22,6 +23,8 @@ import type { SkillMetadata, RemoteSkill } from "../types/skills";
 import type {
   MergeStatus,
   FileConflictInfo,
+  ConflictVersions,
+  ConflictSide,
   AIResolveConflictResponse,
   RebaseStatus,
   InteractiveRebaseCommit,
@@ -58,6 +61,8 @@ async function tracedInvoke<T>(
 export interface CLIAvailability {
   available: boolean;
   path: string | null;
+  /** First line of `--version` output, if the CLI printed one */
+  version: string | null;
   installInstructions: string;
 }
 
@@ -90,14 +95,37 @@ export type ErrorCode =
   | "errors.parse"
   | "errors.git"
   | "errors.ai"
-  | "errors.skill";
+  | "errors.skill"
+  | "errors.no_staged_changes"
+  | "errors.no_changes"
+  | "errors.cancelled"
+  | "errors.dirty_working_tree";
+
+/**
+ * Coarse error category returned alongside the code.
+ */
+export type ErrorKind =
+  | "unknown"
+  | "io"
+  | "git"
+  | "ai"
+  | "validation"
+  | "conflict"
+  | "auth"
+  | "network";
 
 /**
  * Structured error shape from the backend.
  */
 export interface AppError {
   code: ErrorCode;
+  /** Present on errors from the backend; absent on normalized frontend errors */
+  kind?: ErrorKind;
   message: string;
+  /** Files left conflicted, on conflicts from merge/rebase */
+  conflictingFiles?: string[];
+  /** Locally modified files, on errors.dirty_working_tree */
+  paths?: string[];
 }
 
 /**
@@ -180,11 +208,19 @@ export async function listBranches(repoPath: string): Promise<BranchInfo[]> {
   return tracedInvoke<BranchInfo[]>("list_branches", { repoPath });
 }
 
+export interface BranchCheckoutOptions {
+  /** Overwrite conflicting local changes */
+  force?: boolean;
+  /** Stash local changes first (left in the stash list) */
+  stashBefore?: boolean;
+}
+
 export async function checkoutBranch(
   repoPath: string,
   branchName: string,
+  options?: BranchCheckoutOptions,
 ): Promise<void> {
-  return invoke<void>("checkout_branch", { repoPath, branchName });
+  return invoke<void>("checkout_branch", { repoPath, branchName, options });
 }
 
 export async function createBranch(
@@ -201,12 +237,18 @@ export async function getCommitHistory(
   branch?: string,
   limit: number = 100,
   offset: number = 0,
+  since?: number,
+  until?: number,
+  verifySignatures?: boolean,
 ): Promise<CommitInfo[]> {
   return tracedInvoke<CommitInfo[]>("get_commit_history", {
     repoPath,
     branch,
     limit,
     offset,
+    since,
+    until,
+    verifySignatures,
   });
 }
 
@@ -268,35 +310,89 @@ export async function getCommitGraph(
 }
 
 // Diff
+
+/** Which whitespace differences to hide; files with only hidden changes are omitted. */
+export type WhitespaceMode = "none" | "ignoreAll" | "ignoreChange" | "ignoreEol";
+
 export async function getCommitDiff(
   repoPath: string,
   commitId: string,
+  ignoreWhitespace?: WhitespaceMode,
+  functionContext?: boolean,
 ): Promise<UnifiedDiff> {
-  return tracedInvoke<UnifiedDiff>("get_commit_diff", { repoPath, commitId });
+  return tracedInvoke<UnifiedDiff>("get_commit_diff", {
+    repoPath,
+    commitId,
+    ignoreWhitespace,
+    functionContext,
+  });
 }
 
 export async function getFileDiff(
   repoPath: string,
   commitId: string,
   filePath: string,
+  ignoreWhitespace?: WhitespaceMode,
 ): Promise<FileDiff> {
-  return invoke<FileDiff>("get_file_diff", { repoPath, commitId, filePath });
+  return invoke<FileDiff>("get_file_diff", { repoPath, commitId, filePath, ignoreWhitespace });
 }
 
 export async function getWorkingDiff(
   repoPath: string,
   staged: boolean,
+  ignoreWhitespace?: WhitespaceMode,
+): Promise<UnifiedDiff> {
+  return tracedInvoke<UnifiedDiff>("get_working_diff", { repoPath, staged, ignoreWhitespace });
+}
+
+// Everything that differs between refName and the working tree, committed or not
+export async function getWorkdirVsRefDiff(
+  repoPath: string,
+  refName: string,
+  ignoreWhitespace?: WhitespaceMode,
 ): Promise<UnifiedDiff> {
-  return tracedInvoke<UnifiedDiff>("get_working_diff", { repoPath, staged });
+  return tracedInvoke<UnifiedDiff>("get_workdir_vs_ref_diff", {
+    repoPath,
+    refName,
+    ignoreWhitespace,
+  });
+}
+
+export async function getDiffStats(
+  repoPath: string,
+  commitId: string,
+): Promise<DiffStats> {
+  return invoke<DiffStats>("get_diff_stats", { repoPath, commitId });
 }
 
+export async function getWorkingDiffStats(
+  repoPath: string,
+  staged: boolean,
+): Promise<DiffStats> {
+  return invoke<DiffStats>("get_working_diff_stats", { repoPath, staged });
+}
+
+/**
+ * "twoDot" diffs base directly against head; "threeDot" diffs from the
+ * merge-base of base and head (only head's side of the changes).
+ */
+export type CompareMode = "twoDot" | "threeDot";
+
 // Compare diff (between two refs)
 export async function getCompareDiff(
   repoPath: string,
   baseRef: string,
   headRef: string,
+  mode?: CompareMode,
+  ignoreWhitespace?: WhitespaceMode,
 ): Promise<UnifiedDiff> {
-  return tracedInvoke<UnifiedDiff>("get_compare_diff", { repoPath, baseRef, headRef });
+  return tracedInvoke<UnifiedDiff>("get_compare_diff", {
+    repoPath,
+    baseRef,
+    headRef,
+    mode,
+    ignoreWhitespace,
+  });
 }
 
 export async function getCompareFileDiff(
@@ -304,8 +400,17 @@ export async function getCompareFileDiff(
   baseRef: string,
   headRef: string,
   filePath: string,
+  mode?: CompareMode,
+  ignoreWhitespace?: WhitespaceMode,
 ): Promise<FileDiff> {
-  return invoke<FileDiff>("get_compare_file_diff", { repoPath, baseRef, headRef, filePath });
+  return invoke<FileDiff>("get_compare_file_diff", {
+    repoPath,
+    baseRef,
+    headRef,
+    filePath,
+    mode,
+    ignoreWhitespace,
+  });
 }
 
 // Commit range (commits between two refs)
@@ -345,13 +450,53 @@ export async function discardChanges(
   return invoke<void>("discard_changes", { repoPath, paths });
 }
 
+/** Restore one file to its version at `sourceRef`; `staged` also updates the index */
+export async function checkoutFile(
+  repoPath: string,
+  sourceRef: string,
+  filePath: string,
+  staged: boolean,
+): Promise<void> {
+  return invoke<void>("checkout_file", { repoPath, sourceRef, filePath, staged });
+}
+
 // Remote operations
 export async function gitFetch(repoPath: string): Promise<string> {
   return invoke<string>("git_fetch", { repoPath });
 }
 
-export async function gitPull(repoPath: string): Promise<string> {
-  return invoke<string>("git_pull", { repoPath });
+export interface FetchedRef {
+  refName: string;
+  /** null when the fetch created the ref */
+  oldId: string | null;
+  /** null when the fetch pruned the ref */
+  newId: string | null;
+}
+
+export async function gitFetchRemote(
+  repoPath: string,
+  remote: string,
+  refspec?: string,
+  prune?: boolean,
+): Promise<FetchedRef[]> {
+  return invoke<FetchedRef[]>("git_fetch_remote", { repoPath, remote, refspec, prune });
+}
+
+export type PullStrategy = "merge" | "ffOnly" | "rebase";
+
+export interface PullResult {
+  output: string;
+  /** The pull stopped with conflicts and left a merge or rebase in progress */
+  hasConflicts: boolean;
+  conflictingFiles: string[];
+}
+
+export async function gitPull(
+  repoPath: string,
+  strategy?: PullStrategy,
+  autostash?: boolean,
+): Promise<PullResult> {
+  return invoke<PullResult>("git_pull", { repoPath, strategy, autostash });
 }
 
 export async function gitPush(repoPath: string): Promise<string> {
@@ -386,6 +531,22 @@ export async function resetHard(
   return invoke<string>("reset_hard", { repoPath, commitId });
 }
 
+export type ResetMode = "soft" | "mixed" | "hard";
+
+export async function resetCommit(
+  repoPath: string,
+  commitId: string,
+  mode: ResetMode,
+  confirm?: boolean,
+): Promise<string> {
+  return invoke<string>("reset_commit", { repoPath, commitId, mode, confirm });
+}
+
+/** Undo the last commit, keeping its changes staged */
+export async function uncommit(repoPath: string): Promise<void> {
+  return invoke<void>("uncommit", { repoPath });
+}
+
 // Squash result type
 export interface SquashResult {
   newCommitId: string;
@@ -405,16 +566,57 @@ export async function squashCommits(
 }
 
 // Commit
+export interface AuthorOverride {
+  name?: string;
+  email?: string;
+  /** Unix seconds */
+  time?: number;
+}
+
 export async function createCommit(
   repoPath: string,
   message: string,
+  author?: AuthorOverride,
 ): Promise<string> {
-  return invoke<string>("create_commit", { repoPath, message });
+  return invoke<string>("create_commit", {
+    repoPath,
+    message,
+    authorName: author?.name,
+    authorEmail: author?.email,
+    authorTime: author?.time,
+  });
+}
+
+export async function getGitConfig(
+  repoPath: string,
+  keys: string[],
+): Promise<Record<string, string | null>> {
+  return invoke<Record<string, string | null>>("get_git_config", { repoPath, keys });
+}
+
+export async function setGitConfig(
+  repoPath: string,
+  key: string,
+  value: string,
+  global?: boolean,
+): Promise<void> {
+  return invoke<void>("set_git_config", { repoPath, key, value, global });
 }
 
 // AI
-export async function generateCommitMessage(repoPath: string): Promise<string> {
-  return invoke<string>("generate_commit_message", { repoPath });
+// CLI that runs generation prompts; the backend defaults to "claude"
+export type AiBackendKind = "claude" | "codex";
+
+export async function generateCommitMessage(
+  repoPath: string,
+  commitId?: string,
+  backend?: AiBackendKind,
+): Promise<string> {
+  return invoke<string>("generate_commit_message", {
+    repoPath,
+    commitId,
+    backend,
+  });
 }
 
 export interface IssueToFix {
@@ -428,14 +630,75 @@ export async function generateAIReview(
   repoPath: string,
   commitId?: string,
   skillIds?: string[],
+  filePaths?: string[],
+  forceRefresh?: boolean,
+  baseRef?: string,
+  headRef?: string,
+  backend?: AiBackendKind,
+  requestId?: string,
 ): Promise<AIReviewData> {
   return invoke<AIReviewData>("generate_ai_review", {
     repoPath,
     commitId,
     skillIds,
+    filePaths,
+    forceRefresh,
+    baseRef,
+    headRef,
+    backend,
+    requestId,
+  });
+}
+
+/**
+ * Stream an AI review (Claude only). Progress arrives as `ai_review_chunk`
+ * events, then one `ai_review_complete` or `ai_review_error`, all tagged
+ * with `streamId`. Resolves once the review has finished.
+ */
+export async function generateAIReviewStreaming(
+  streamId: string,
+  repoPath: string,
+  commitId?: string,
+  skillIds?: string[],
+  filePaths?: string[],
+  forceRefresh?: boolean,
+  baseRef?: string,
+  headRef?: string,
+): Promise<void> {
+  return invoke<void>("generate_ai_review_streaming", {
+    streamId,
+    repoPath,
+    commitId,
+    skillIds,
+    filePaths,
+    forceRefresh,
+    baseRef,
+    headRef,
   });
 }
 
+/** Payload of ai_review_chunk events: raw model output as it arrives. */
+export interface AIReviewChunkEvent {
+  streamId: string;
+  text: string;
+}
+
+/** Payload of ai_review_complete events. */
+export interface AIReviewCompleteEvent {
+  streamId: string;
+  review: AIReviewData;
+}
+
+/** Payload of ai_review_error events. */
+export interface AIReviewErrorEvent {
+  streamId: string;
+  error: AppError;
+}
+
+export async function clearReviewCache(): Promise<void> {
+  return invoke<void>("clear_review_cache");
+}
+
 export async function generateReview(
   repoPath: string,
   reviewerId: AIReviewReviewerId,
@@ -454,11 +717,36 @@ export async function generateReview(
   });
 }
 
+export interface AIFixResult {
+  message: string;
+  summary: string;
+  /** [path, new content] per changed file */
+  files: [string, string][];
+  /** Paths from the response that pointed outside the repo; never written */
+  skipped: string[];
+  /** False for a dry run: `files` are proposals and nothing was written */
+  applied: boolean;
+  /** Pass to `undoAIFix` to restore the original files; null for dry runs */
+  backupId: string | null;
+}
+
 export async function fixAIReviewIssues(
   repoPath: string,
   issues: IssueToFix[],
-): Promise<string> {
-  return invoke<string>("fix_ai_review_issues", { repoPath, issues });
+  requestId?: string,
+  dryRun?: boolean,
+): Promise<AIFixResult> {
+  return invoke<AIFixResult>("fix_ai_review_issues", {
+    repoPath,
+    issues,
+    requestId,
+    dryRun,
+  });
+}
+
+// Kill the AI process started with `requestId`; false if it already finished
+export async function cancelAIRequest(requestId: string): Promise<boolean> {
+  return invoke<boolean>("cancel_ai_request", { requestId });
 }
 
 export interface CodeRabbitIssueFix {
@@ -471,25 +759,138 @@ export interface CodeRabbitIssueFix {
 export async function fixCodeRabbitIssue(
   repoPath: string,
   issue: CodeRabbitIssueFix,
-): Promise<string> {
-  return invoke<string>("fix_coderabbit_issue", { repoPath, issue });
+): Promise<AIFixResult> {
+  return invoke<AIFixResult>("fix_coderabbit_issue", { repoPath, issue });
 }
 
-// Generate Mermaid diagram from working changes using Claude
-export async function generateDiagram(repoPath: string): Promise<string> {
-  return invoke<string>("generate_diagram", { repoPath });
+// Restore the files an applied AI fix overwrote; returns the number restored
+export async function undoAIFix(backupId: string): Promise<number> {
+  return invoke<number>("undo_ai_fix", { backupId });
+}
+
+// Mermaid diagram kind to generate; "auto" lets the model choose
+export type DiagramType =
+  | "auto"
+  | "sequence"
+  | "flowchart"
+  | "classDiagram"
+  | "stateDiagram"
+  | "entityRelationship";
+
+// Generate Mermaid diagram using the selected AI backend. Describes commitId or
+// the baseRef..headRef range when given, else working changes.
+export async function generateDiagram(
+  repoPath: string,
+  backend?: AiBackendKind,
+  commitId?: string,
+  baseRef?: string,
+  headRef?: string,
+  diagramType?: DiagramType,
+): Promise<string> {
+  return invoke<string>("generate_diagram", {
+    repoPath,
+    backend,
+    commitId,
+    baseRef,
+    headRef,
+    diagramType,
+  });
 }
 
 // Contributor Review
-export interface ContributorReviewRequest {
-  contributorName: string;
-  contributorEmail: string;
-  timeRangeLabel: string;
-  commitSummaries: string[];
+export interface ContributorStats {
+  authorName: string;
+  authorEmail: string;
   totalCommits: number;
   totalFilesChanged: number;
   totalAdditions: number;
   totalDeletions: number;
+  /** Newest first; empty in getContributorLeaderboard results */
+  commitSummaries: string[];
+  /** Unix seconds of the oldest and newest commit in range */
+  firstCommitTime: number | null;
+  lastCommitTime: number | null;
+}
+
+// Aggregate one author's commits across local branches (unix seconds range)
+export async function getContributorStats(
+  repoPath: string,
+  email: string,
+  since: number,
+  until?: number,
+): Promise<ContributorStats> {
+  return invoke<ContributorStats>("get_contributor_stats", {
+    repoPath,
+    email,
+    since,
+    until,
+  });
+}
+
+// Stats for every author across local branches, most commits first
+export async function getContributorLeaderboard(
+  repoPath: string,
+  since: number,
+  until?: number,
+): Promise<ContributorStats[]> {
+  return invoke<ContributorStats[]>("get_contributor_leaderboard", {
+    repoPath,
+    since,
+    until,
+  });
+}
+
+export interface FileHotspot {
+  path: string;
+  changeCount: number;
+  totalAdditions: number;
+  totalDeletions: number;
+  /** Unix seconds of the most recent change */
+  lastChanged: number;
+}
+
+// Most frequently changed files across local branches, most changes first
+export async function getFileHotspots(
+  repoPath: string,
+  since: number,
+  until?: number,
+  limit?: number,
+): Promise<FileHotspot[]> {
+  return invoke<FileHotspot[]>("get_file_hotspots", {
+    repoPath,
+    since,
+    until,
+    limit,
+  });
+}
+
+export interface RepoSummary {
+  /** Commits reachable from HEAD */
+  totalCommits: number;
+  branchCount: number;
+  tagCount: number;
+  contributorCount: number;
+  headCommit: CommitInfo | null;
+}
+
+export async function getRepoSummary(repoPath: string): Promise<RepoSummary> {
+  return invoke<RepoSummary>("get_repo_summary", { repoPath });
+}
+
+// With repoPath and since set, stats are computed by the backend and the
+// aggregate fields can be omitted
+export interface ContributorReviewRequest {
+  contributorName?: string;
+  contributorEmail: string;
+  timeRangeLabel: string;
+  commitSummaries?: string[];
+  totalCommits?: number;
+  totalFilesChanged?: number;
+  totalAdditions?: number;
+  totalDeletions?: number;
+  repoPath?: string;
+  since?: number;
+  until?: number;
 }
 
 export interface ContributorReviewData {
@@ -577,8 +978,32 @@ export async function popStash(
 export async function dropStash(
   repoPath: string,
   stashIndex: number,
+  expectedOid?: string,
 ): Promise<void> {
-  return invoke<void>("drop_stash", { repoPath, stashIndex });
+  return invoke<void>("drop_stash", { repoPath, stashIndex, expectedOid });
+}
+
+// Oid-based stash operations resolve the stash's current index at call time,
+// so they stay correct if the stash list changed since it was fetched
+export async function applyStashByOid(
+  repoPath: string,
+  stashOid: string,
+): Promise<void> {
+  return invoke<void>("apply_stash_by_oid", { repoPath, stashOid });
+}
+
+export async function popStashByOid(
+  repoPath: string,
+  stashOid: string,
+): Promise<void> {
+  return invoke<void>("pop_stash_by_oid", { repoPath, stashOid });
+}
+
+export async function dropStashByOid(
+  repoPath: string,
+  stashOid: string,
+): Promise<void> {
+  return invoke<void>("drop_stash_by_oid", { repoPath, stashOid });
 }
 
 // Reflog
@@ -596,6 +1021,15 @@ export async function getAheadBehind(
   return invoke<AheadBehind | null>("get_ahead_behind", { repoPath });
 }
 
+/** Best common ancestor of two refs; null for unrelated histories */
+export async function getMergeBase(
+  repoPath: string,
+  refA: string,
+  refB: string,
+): Promise<CommitInfo | null> {
+  return invoke<CommitInfo | null>("get_merge_base", { repoPath, refA, refB });
+}
+
 // Skills
 export async function getSkillsDir(): Promise<string> {
   return invoke<string>("get_skills_dir");
@@ -613,6 +1047,17 @@ export async function installSkillFromUrl(url: string): Promise<SkillMetadata> {
   return invoke<SkillMetadata>("install_skill_from_url", { url });
 }
 
+export async function installSkillFromFile(path: string): Promise<SkillMetadata> {
+  return invoke<SkillMetadata>("install_skill_from_file", { path });
+}
+
+export async function installSkillFromContent(
+  content: string,
+  sourceLabel?: string,
+): Promise<SkillMetadata> {
+  return invoke<SkillMetadata>("install_skill_from_content", { content, sourceLabel });
+}
+
 export async function deleteSkill(skillId: string): Promise<void> {
   return invoke<void>("delete_skill", { skillId });
 }
@@ -633,6 +1078,11 @@ export async function updateSkill(
   return invoke<SkillMetadata>("update_skill", { skillId, content, newId });
 }
 
+// Re-download a URL-installed skill from its original source
+export async function refreshSkill(skillId: string): Promise<SkillMetadata> {
+  return invoke<SkillMetadata>("refresh_skill", { skillId });
+}
+
 // Merge conflict operations
 export async function getMergeStatus(repoPath: string): Promise<MergeStatus> {
   return invoke<MergeStatus>("get_merge_status", { repoPath });
@@ -648,6 +1098,39 @@ export async function parseFileConflicts(
   });
 }
 
+// Base/ours/theirs straight from the index, unaffected by edits to the markers
+export async function getConflictVersions(
+  repoPath: string,
+  filePath: string,
+): Promise<ConflictVersions> {
+  return invoke<ConflictVersions>("get_conflict_versions", {
+    repoPath,
+    filePath,
+  });
+}
+
+export async function resolveConflictOurs(
+  repoPath: string,
+  filePath: string,
+): Promise<void> {
+  return invoke<void>("resolve_conflict_ours", { repoPath, filePath });
+}
+
+export async function resolveConflictTheirs(
+  repoPath: string,
+  filePath: string,
+): Promise<void> {
+  return invoke<void>("resolve_conflict_theirs", { repoPath, filePath });
+}
+
+// Resolve every conflicting file in favor of one side; returns the resolved paths
+export async function resolveAllConflicts(
+  repoPath: string,
+  strategy: ConflictSide,
+): Promise<string[]> {
+  return invoke<string[]>("resolve_all_conflicts", { repoPath, strategy });
+}
+
 export async function saveResolvedFile(
   repoPath: string,
   filePath: string,
@@ -671,11 +1154,35 @@ export async function continueMerge(repoPath: string): Promise<string> {
   return invoke<string>("continue_merge", { repoPath });
 }
 
+export interface MergeOptions {
+  noFf?: boolean;
+  ffOnly?: boolean;
+  squash?: boolean;
+  /** Passed as `-X <option>`, e.g. "ours", "theirs" or "patience" */
+  strategyOption?: string;
+}
+
 export async function mergeBranch(
   repoPath: string,
   branchName: string,
+  options?: MergeOptions,
 ): Promise<string> {
-  return invoke<string>("merge_branch", { repoPath, branchName });
+  return invoke<string>("merge_branch", { repoPath, branchName, options });
+}
+
+export interface MergePreview {
+  upToDate: boolean;
+  changedFiles: string[];
+  conflictingFiles: string[];
+  /** false when the trial merge couldn't be aborted and is still in progress */
+  aborted: boolean;
+}
+
+export async function previewMerge(
+  repoPath: string,
+  branchName: string,
+): Promise<MergePreview> {
+  return invoke<MergePreview>("preview_merge", { repoPath, branchName });
 }
 
 // Rebase operations
@@ -686,16 +1193,17 @@ export async function getRebaseStatus(repoPath: string): Promise<RebaseStatus> {
 export async function rebaseOnto(
   repoPath: string,
   ontoRef: string,
+  autostash?: boolean,
 ): Promise<string> {
-  return invoke<string>("rebase_onto", { repoPath, ontoRef });
+  return invoke<string>("rebase_onto", { repoPath, ontoRef, autostash });
 }
 
 export async function continueRebase(repoPath: string): Promise<string> {
   return invoke<string>("continue_rebase", { repoPath });
 }
 
-export async function abortRebase(repoPath: string): Promise<string> {
-  return invoke<string>("abort_rebase", { repoPath });
+export async function abortRebase(repoPath: string, force?: boolean): Promise<string> {
+  return invoke<string>("abort_rebase", { repoPath, force });
 }
 
 export async function skipRebase(repoPath: string): Promise<string> {
@@ -743,17 +1251,31 @@ export async function continueInteractiveRebase(
   });
 }
 
+export async function splitCommitReset(repoPath: string): Promise<string> {
+  return invoke<string>("split_commit_reset", { repoPath });
+}
+
 export async function aiResolveConflict(
   filePath: string,
   oursContent: string,
   theirsContent: string,
   instructions?: string,
+  backend?: AiBackendKind,
+  // With autoApply, the resolution is written to the file in repoPath and staged
+  repoPath?: string,
+  autoApply?: boolean,
+  // Common ancestor version; omit for 2-way conflicts
+  baseContent?: string,
 ): Promise<AIResolveConflictResponse> {
   return invoke<AIResolveConflictResponse>("ai_resolve_conflict", {
     filePath,
     oursContent,
     theirsContent,
+    baseContent,
     instructions,
+    backend,
+    repoPath,
+    autoApply,
   });
 }
 
@@ -800,3 +1322,19 @@ export async function readRepoFile(
 ): Promise<string> {
   return invoke<string>("read_repo_file", { repoPath, filePath, commitId });
 }
+
+export interface TreeEntry {
+  name: string;
+  path: string;
+  isDir: boolean;
+  mode: number;
+  size: number | null;
+}
+
+export async function listTree(
+  repoPath: string,
+  dirPath: string,
+  commitId?: string,
+): Promise<TreeEntry[]> {
+  return invoke<TreeEntry[]>("list_tree", { repoPath, commitId, dirPath });
+}
@@ -10,10 +10,16 @@ export interface BranchInfo {
   isHead: boolean;
   isRemote: boolean;
   upstream: string | null;
+  /** "gone" when the configured upstream was deleted on the remote and pruned */
+  upstreamState: UpstreamState;
   commitId: string;
   commitMessage: string;
 }
 
+export type UpstreamState = "tracking" | "gone" | "none";
+
+export type SignatureStatus = "good" | "bad" | "unknown" | "unsigned";
+
 export interface CommitInfo {
   id: string;
   shortId: string;
@@ -22,16 +28,32 @@ export interface CommitInfo {
   authorName: string;
   authorEmail: string;
   time: number;
+  committerName: string;
+  committerEmail: string;
+  committerTime: number;
+  /** Only set when history was requested with `verifySignatures` */
+  signatureStatus: SignatureStatus | null;
+  /** [key, value] pairs from the message's trailer block, e.g. Co-authored-by */
+  trailers: [string, string][];
   parentIds: string[];
   filesChanged: number;
   additions: number;
   deletions: number;
 }
 
+export type RefKind = "localBranch" | "remoteBranch" | "tag";
+
+export interface RefDecoration {
+  name: string;
+  kind: RefKind;
+}
+
 export interface GraphNode {
   commitId: string;
   column: number;
   connections: GraphConnection[];
+  /** Branches, remote-tracking refs and tags pointing at this commit */
+  decorations: RefDecoration[];
 }
 
 export interface GraphConnection {
@@ -67,6 +89,10 @@ export interface DiffFile {
   // Extended metadata (additive fields for richer diff info)
   /** Whether the file is binary */
   isBinary?: boolean;
+  /** Size in bytes before the change (binary files only) */
+  oldSize?: number;
+  /** Size in bytes after the change (binary files only) */
+  newSize?: number;
   /** Old file mode (e.g., 0o100644 for regular file, 0o120000 for symlink) */
   oldMode?: number;
   /** New file mode */
@@ -77,6 +103,8 @@ export interface DiffFile {
   isSymlink?: boolean;
   /** Whether file is a submodule */
   isSubmodule?: boolean;
+  /** Whether the patch body was replaced by a placeholder for exceeding maxPatchBytes */
+  truncated?: boolean;
 }
 
 export interface UnifiedDiff {
@@ -84,6 +112,13 @@ export interface UnifiedDiff {
   patch: string;
 }
 
+/** Totals for a diff, computed without transferring the patch */
+export interface DiffStats {
+  filesChanged: number;
+  insertions: number;
+  deletions: number;
+}
+
 export interface FileDiff {
   path: string;
   patch: string;
@@ -137,6 +172,8 @@ export interface AIReviewData {
   overview: string;
   issues: AIReviewIssue[];
   generatedAt: number;
+  /** True when served from the local review cache */
+  cached?: boolean;
 }
 
 export type AIReviewReviewerId = "claude-cli" | "coderabbit-cli";
@@ -200,6 +237,8 @@ export interface StashEntry {
   message: string;
   oid: string;
   time: number;
+  branch: string | null;
+  baseCommitId: string;
 }
 
 export interface AheadBehind {